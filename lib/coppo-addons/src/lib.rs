@@ -13,10 +13,11 @@ pub type AddonResult = Result<(), Box<dyn std::error::Error>>;
 /// The `Addon` trait provides an interface for Coppo add-ons.
 /// You can create a new add-on by implementing the `Addon` trait.
 ///
-/// The `Addon` trait has four methods:
+/// The `Addon` trait has five methods:
 /// - `name`: The name of the add-on.(required)
 /// - `version`: The version of the add-on.
 /// - `description`: The description of the add-on.
+/// - `needs_project`: Whether the add-on works on the project, defaults to `true`.
 /// - `run`: The entry point of the add-on.(required)
 ///
 /// # Example
@@ -55,6 +56,14 @@ pub trait Addon {
         None
     }
 
+    /// Whether the add-on works on the project.
+    /// If it does, the nearest `Coppo.toml` in the current directory or its ancestors is loaded,
+    /// and the current directory is changed to the project root before it runs.
+    /// Otherwise, only the `Coppo.toml` in the current directory is loaded.
+    fn needs_project(&self) -> bool {
+        true
+    }
+
     /// The arguments of the add-on.
     fn args(&self) -> Vec<Arg> {
         vec![]
@@ -73,6 +82,7 @@ pub trait Addon {
 /// And the following fields are optional:
 /// - `version`: The version of the add-on.
 /// - `description`: The description of the add-on.
+/// - `project`: Whether the add-on works on the project, see [`Addon::needs_project`].
 /// - `args`: The arguments of the add-on.
 ///
 /// You can not need to specify the `version` field,
@@ -112,6 +122,7 @@ macro_rules! impl_addon {
     (
         $addon:ty,
         name => $name:expr,
+        $(project => $project:expr,)?
         $(args => [$($args:expr),*$(,)?],)?
         run => |$config:ident, $matches:ident| $run:block$(,)?
     ) => {
@@ -128,6 +139,10 @@ macro_rules! impl_addon {
                 Some(env!("CARGO_PKG_DESCRIPTION"))
             }
 
+            $(fn needs_project(&self) -> bool {
+                $project
+            })?
+
             $(fn args(&self) -> Vec<Arg> {
                 vec![$($args),*]
             })?
@@ -142,6 +157,7 @@ macro_rules! impl_addon {
         $addon:ty,
         name => $name:expr,
         version => $version:expr,
+        $(project => $project:expr,)?
         $(args => [$($args:expr),*$(,)?],)?
         run => |$config:ident, $matches:ident| $run:block$(,)?
     ) => {
@@ -158,6 +174,10 @@ macro_rules! impl_addon {
                 Some(env!("CARGO_PKG_DESCRIPTION"))
             }
 
+            $(fn needs_project(&self) -> bool {
+                $project
+            })?
+
             $(fn args(&self) -> Vec<Arg> {
                 vec![$($args),*]
             })?
@@ -173,6 +193,7 @@ macro_rules! impl_addon {
         name => $name:expr,
         $(version => $version:expr,)?
         description => $description:expr,
+        $(project => $project:expr,)?
         $(args => [$($args:expr),*$(,)?],)?
         run => |$config:ident, $matches:ident| $run:block$(,)?
     ) => {
@@ -189,6 +210,10 @@ macro_rules! impl_addon {
                 Some($description)
             }
 
+            $(fn needs_project(&self) -> bool {
+                $project
+            })?
+
             $(fn args(&self) -> Vec<Arg> {
                 vec![$($args),*]
             })?
@@ -204,6 +229,7 @@ macro_rules! impl_addon {
         name => $name:expr,
         version => $version:expr,
         description => $description:expr,
+        $(project => $project:expr,)?
         $(args => [$($args:expr),*$(,)?],)?
         run => |$config:ident, $matches:ident| $run:block$(,)?
     ) => {
@@ -220,6 +246,10 @@ macro_rules! impl_addon {
                 Some($description)
             }
 
+            $(fn needs_project(&self) -> bool {
+                $project
+            })?
+
             $(fn args(&self) -> Vec<Arg> {
                 vec![$($args),*]
             })?
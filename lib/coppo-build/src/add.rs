@@ -0,0 +1,223 @@
+//! The `coppo add` and `coppo remove` add-ons, they edit the dependencies in `Coppo.toml`.
+//!
+//! `coppo add fmt@10.1` adds `fmt = "^10.1"` to `[dependencies]`,
+//! and without a version the newest one is required, like `fmt = "^10.2.1"`:
+//! the one of the registry with `--registry`, or the installed one of a system package.
+//! The manifest is edited in place, so its comments and formatting are kept,
+//! and `Coppo.lock` is updated, see [`crate::lock`].
+//!
+//! # Usage
+//! ```sh
+//! coppo add <DEPENDENCY>... [--dev] [--registry <NAME>] [--path <DIR>] [--optional]
+//! coppo remove <NAME>... [--dev]
+//! ```
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use coppo_addons::prelude::*;
+use coppo_config::lock::{Lockfile, LOCK_FILE};
+use coppo_config::prelude::*;
+use coppo_config::DetailedDependency;
+use coppo_logger::prelude::*;
+use semver::Version;
+
+use crate::lock::{changes, resolve};
+use crate::script::ScriptDependency;
+use crate::Result;
+
+/// The `Coppo add` add-on.
+/// Add the dependencies to `Coppo.toml`, and update `Coppo.lock`.
+pub struct CoppoAddAddon;
+
+impl_addon! {
+    CoppoAddAddon,
+    name => "add",
+    description => "Add dependencies to the manifest",
+    args => [
+        arg!(<DEPENDENCY>... "The dependencies, like `fmt` or `fmt@10.1`")
+            .value_parser(value_parser!(String)),
+        arg!(--dev "Add them to `[dev-dependencies]`").action(ArgAction::SetTrue),
+        arg!(--registry <NAME> "The registry to download them from, see `[registries]`")
+            .value_parser(value_parser!(String))
+            .conflicts_with("path"),
+        arg!(--path <DIR> "The directory of the dependency, a Coppo project")
+            .value_parser(value_parser!(PathBuf)),
+        arg!(--optional "Only use them if a feature enables them").action(ArgAction::SetTrue),
+    ],
+    run => |config, matches| {
+        let specs = matches
+            .get_many::<String>("DEPENDENCY")
+            .unwrap_or_default()
+            .cloned()
+            .collect::<Vec<_>>();
+        let source = Source {
+            registry: matches.get_one::<String>("registry").cloned(),
+            path: matches.get_one::<PathBuf>("path").cloned(),
+            optional: matches.get_flag("optional"),
+        };
+        add(config, &specs, matches.get_flag("dev"), &source)?;
+    }
+}
+
+/// The `Coppo remove` add-on.
+/// Remove the dependencies from `Coppo.toml`, and update `Coppo.lock`.
+pub struct CoppoRemoveAddon;
+
+impl_addon! {
+    CoppoRemoveAddon,
+    name => "remove",
+    description => "Remove dependencies from the manifest",
+    args => [
+        arg!(<NAME>... "The names of the dependencies").value_parser(value_parser!(String)),
+        arg!(--dev "Remove them from `[dev-dependencies]`").action(ArgAction::SetTrue),
+    ],
+    run => |config, matches| {
+        let names = matches
+            .get_many::<String>("NAME")
+            .unwrap_or_default()
+            .cloned()
+            .collect::<Vec<_>>();
+        remove(config, &names, matches.get_flag("dev"))?;
+    }
+}
+
+/// Where the added dependencies come from, and how they are used.
+#[derive(Debug, Default, Clone)]
+pub struct Source {
+    /// The registry of the dependencies.
+    pub registry: Option<String>,
+    /// The directory of the dependency.
+    pub path: Option<PathBuf>,
+    /// Whether the dependencies are only used if a feature enables them.
+    pub optional: bool,
+}
+
+impl Source {
+    /// The dependency with the version requirement.
+    pub fn dependency(&self, version: Option<VersionReq>) -> Dependency {
+        if self.registry.is_none() && self.path.is_none() && !self.optional {
+            return Dependency::Simple(version.unwrap_or(VersionReq::STAR));
+        }
+
+        Dependency::Detailed(DetailedDependency {
+            version,
+            registry: self.registry.clone(),
+            path: self.path.clone(),
+            optional: self.optional,
+            ..Default::default()
+        })
+    }
+}
+
+/// The table of the dependencies, with its name.
+fn table(config: &mut Config, dev: bool) -> (&'static str, &mut HashMap<String, Dependency>) {
+    match dev {
+        true => ("dev-dependencies", &mut config.dev_dependencies),
+        false => ("dependencies", &mut config.dependencies),
+    }
+}
+
+/// Add the dependencies like `fmt@10.1` to the manifest, and update the lockfile.
+/// The ones without a version require the resolved one, except the path dependencies.
+pub fn add(config: &mut Config, specs: &[String], dev: bool, source: &Source) -> Result<()> {
+    if !Config::exists() {
+        return Err("The project does not have a `Coppo.toml` file.".into());
+    }
+    if source.path.is_some() && specs.len() > 1 {
+        return Err("Only one dependency can be added with `--path`.".into());
+    }
+
+    let mut unversioned = vec![];
+    for spec in specs {
+        let dependency = spec.parse::<ScriptDependency>()?;
+        let version = spec.contains('@').then_some(dependency.version);
+        if version.is_none() && source.path.is_none() {
+            unversioned.push(dependency.name.clone());
+        }
+        let (name, table) = table(config, dev);
+        match table.insert(dependency.name.clone(), source.dependency(version)) {
+            Some(_) => info!("Updating `{}` in `[{}]`.", dependency.name, name),
+            None => info!("Adding `{}` to `[{}]`.", dependency.name, name),
+        }
+    }
+
+    let locked = Lockfile::from_file(Path::new("."))?;
+    let resolved = resolve(config, locked.as_ref())?;
+    for name in unversioned {
+        let version = resolved
+            .get(&name)
+            .and_then(|package| Version::parse(&package.version).ok());
+        if let Some(version) = version {
+            let requirement = VersionReq::parse(&version.to_string())?;
+            let (_, table) = table(config, dev);
+            table.insert(name, source.dependency(Some(requirement)));
+        }
+    }
+
+    config.save()?;
+    update(locked, resolved)
+}
+
+/// Remove the dependencies from the manifest, and update the lockfile.
+pub fn remove(config: &mut Config, names: &[String], dev: bool) -> Result<()> {
+    if !Config::exists() {
+        return Err("The project does not have a `Coppo.toml` file.".into());
+    }
+
+    for name in names {
+        let (table_name, table) = table(config, dev);
+        if table.remove(name).is_none() {
+            return Err(format!(
+                "The dependency `{}` is not found in `[{}]`.",
+                name, table_name
+            )
+            .into());
+        }
+        info!("Removing `{}` from `[{}]`.", name, table_name);
+    }
+
+    let locked = Lockfile::from_file(Path::new("."))?;
+    let resolved = resolve(config, locked.as_ref())?;
+    config.save()?;
+    update(locked, resolved)
+}
+
+/// Write the resolved lockfile, and print the changes of the locked one.
+fn update(locked: Option<Lockfile>, resolved: Lockfile) -> Result<()> {
+    resolved.to_file(Path::new("."))?;
+    for change in changes(&locked.unwrap_or_default(), &resolved) {
+        info!("{}", change);
+    }
+
+    success!("Updated `{}` and `{}`.", CONFIG_FILE, LOCK_FILE);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dependency() {
+        let version = VersionReq::parse("10.1").ok();
+        assert_eq!(
+            Source::default().dependency(version.clone()),
+            Dependency::Simple(VersionReq::parse("^10.1").unwrap())
+        );
+
+        let source = Source {
+            registry: Some("company".to_string()),
+            ..Default::default()
+        };
+        let dependency = source.dependency(version);
+        assert_eq!(dependency.registry(), Some("company"));
+        assert_eq!(dependency.version().to_string(), "^10.1");
+
+        let source = Source {
+            path: Some(PathBuf::from("../util")),
+            ..Default::default()
+        };
+        assert_eq!(source.dependency(None).version(), VersionReq::STAR);
+    }
+}
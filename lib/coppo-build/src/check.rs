@@ -0,0 +1,166 @@
+//! The `coppo check` add-on, it checks the sources of the project without building it.
+//!
+//! Every source of the library, the binaries and the examples is compiled with `-fsyntax-only`,
+//! or `/Zs` for MSVC, with the same flags as `coppo build`,
+//! so the errors are reported without generating any object or binary.
+//!
+//! # Usage
+//! ```sh
+//! coppo check [--release] [--profile <NAME>] [-j <N>]
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::process;
+
+use coppo_addons::prelude::*;
+use coppo_config::prelude::*;
+use coppo_logger::prelude::*;
+
+use crate::parallel::{default_jobs, run_parallel};
+use crate::{
+    all_features_arg, bins, example_source, examples, features_arg, find_compiler, flags,
+    lib_sources, log_command, msvc, no_default_features_arg, profile_arg, profile_flags,
+    release_arg, select_features, select_target, selected_profile, standard, target_arg,
+    verbose_arg, workspace, Result,
+};
+
+/// The `Coppo check` add-on.
+/// Check all the sources of the current project, or of the members of the workspace
+/// selected by `-p` and `--workspace`, without producing any binary.
+pub struct CoppoCheckAddon;
+
+impl_addon! {
+    CoppoCheckAddon,
+    name => "check",
+    description => "Check the current project for errors without building it",
+    args => [
+        release_arg(),
+        profile_arg(),
+        features_arg(),
+        all_features_arg(),
+        no_default_features_arg(),
+        arg!(-j --jobs <N> "The number of sources checked in parallel, defaults to the number of CPUs")
+            .value_parser(value_parser!(usize)),
+        target_arg(),
+        verbose_arg(),
+        workspace::package_arg(),
+        workspace::workspace_arg(),
+    ],
+    run => |config, matches| {
+        let profile = selected_profile(matches);
+        let jobs = matches.get_one::<usize>("jobs").copied().unwrap_or_else(default_jobs);
+        set_verbose(matches.get_flag("verbose"));
+        workspace::for_each(config, matches, "Checking", |config| {
+            select_features(config, matches)?;
+            select_target(config, matches)?;
+            check(config, profile, jobs)
+        })?;
+    }
+}
+
+/// The sources of the project, the ones of the library, the binaries and the examples.
+pub fn sources(config: &Config) -> Result<Vec<PathBuf>> {
+    let mut sources = match &config.lib {
+        Some(lib) => lib_sources(lib)?,
+        None => vec![],
+    };
+    sources.extend(
+        bins(config)
+            .iter()
+            .map(BinTarget::path)
+            .filter(|path| path.exists()),
+    );
+    sources.extend(examples()?.iter().filter_map(|name| example_source(name)));
+
+    Ok(sources)
+}
+
+/// The command to check the source with the flags, without generating any output.
+pub fn check_command(compiler: &str, source: &Path, flags: &[String]) -> process::Command {
+    let mut command = process::Command::new(compiler);
+    match msvc::is_msvc(compiler) {
+        true => {
+            msvc::configure(&mut command);
+            command
+                .args(["/nologo", "/EHsc", "/Zs"])
+                .args(msvc::translate(flags).0)
+                .arg(source);
+        }
+        false => {
+            command.arg("-fsyntax-only").args(flags).arg(source);
+        }
+    }
+
+    command
+}
+
+/// Check all the sources of the project with the profile, `jobs` at a time.
+/// The diagnostics of the compiler are printed for every source in order.
+pub fn check(config: &Config, profile: &str, jobs: usize) -> Result<()> {
+    if config.is_empty() {
+        return Err("The project name and version is needed".into());
+    }
+    let compiler = find_compiler(config)?;
+    let mut flags = flags(config);
+    flags.extend(workspace::include_flags(config)?);
+    flags.extend(profile_flags(&config.profile(profile)?));
+    standard::check(&compiler, config.project.language, &flags)?;
+
+    let sources = sources(config)?;
+    info!("Checking {} source(s)...", sources.len());
+    let outputs = run_parallel(&sources, jobs, |source| {
+        let mut command = check_command(&compiler, source, &flags);
+        log_command(&command);
+        command.output()
+    });
+
+    let mut failed = 0;
+    for (source, output) in sources.iter().zip(outputs) {
+        let output = output?;
+        let diagnostics = String::from_utf8_lossy(&output.stderr);
+        let diagnostics = diagnostics.trim();
+        if output.status.success() {
+            if !diagnostics.is_empty() {
+                warn!("{}", diagnostics);
+            }
+        } else {
+            failed += 1;
+            error!("{}", diagnostics);
+            error!("Failed to check `{}`.", source.display());
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{} of {} source(s) have errors.", failed, sources.len()).into());
+    }
+
+    success!("The project has been checked.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check_command() {
+        let flags = ["-std=c++20".to_owned(), "-Iinclude".to_owned()];
+        let command = check_command("g++", Path::new("src/main.cpp"), &flags);
+        assert_eq!(
+            command.get_args().collect::<Vec<_>>(),
+            ["-fsyntax-only", "-std=c++20", "-Iinclude", "src/main.cpp"]
+        );
+
+        let command = check_command("cl.exe", Path::new("src/main.cpp"), &flags);
+        assert_eq!(
+            command.get_args().collect::<Vec<_>>(),
+            [
+                "/nologo",
+                "/EHsc",
+                "/Zs",
+                "/std:c++20",
+                "/Iinclude",
+                "src/main.cpp"
+            ]
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! The `coppo clean` add-on, it removes the build artifacts.
+//!
+//! # Usage
+//! ```sh
+//! coppo clean [--release | --profile <name>] [-p <package> | --workspace] [--cache]
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+use coppo_addons::prelude::*;
+use coppo_config::prelude::*;
+use coppo_config::profile::RELEASE_PROFILE;
+use coppo_logger::prelude::*;
+
+use crate::{bin_path, bins, target_dir, workspace};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The `Coppo clean` add-on.
+/// Remove the whole `target` directory by default,
+/// or only the directories of a profile with `--release` or `--profile`,
+/// `target/<profile>` and the cross-compiled `target/<triple>/<profile>`.
+/// With `--cache`, the entries of the project in the global artifact cache are also removed,
+/// or the ones of the selected members in a workspace.
+/// In a workspace, `-p` only removes the binaries and the library artifacts of the members
+/// from the shared `target` directory, or only their binaries of the profile.
+pub struct CoppoCleanAddon;
+
+impl_addon! {
+    CoppoCleanAddon,
+    name => "clean",
+    description => "Remove the build artifacts",
+    args => [
+        arg!(--release "Only remove the artifacts of the release profile")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("profile"),
+        arg!(--profile <NAME> "Only remove the artifacts of the profile")
+            .value_parser(value_parser!(String)),
+        workspace::package_arg(),
+        workspace::workspace_arg(),
+        arg!(--cache "Also remove the entries of the project in the global artifact cache")
+            .action(ArgAction::SetTrue),
+    ],
+    run => |config, matches| {
+        clean(config, matches)?;
+    }
+}
+
+fn clean(config: &mut Config, matches: &ArgMatches) -> Result<()> {
+    let profile = if matches.get_flag("release") {
+        Some(RELEASE_PROFILE)
+    } else {
+        matches.get_one::<String>("profile").map(String::as_str)
+    };
+    if let Some(profile) = profile {
+        check_profile(config, profile)?;
+    }
+
+    // The members share the `target` directory,
+    // so only the binaries and the library artifacts of the selected members are removed.
+    if matches.contains_id("package") && config.workspace.is_some() {
+        let mut removed = 0;
+        workspace::for_each(config, matches, "Cleaning", |config| {
+            for bin in bins(config) {
+                let profiles = match profile {
+                    Some(profile) => vec![profile.to_owned()],
+                    None => profiles(),
+                };
+                for profile in profiles {
+                    let binary = Path::new(&bin_path(config, &bin.name, &profile)).to_owned();
+                    if binary.is_file() {
+                        fs::remove_file(&binary)?;
+                        info!("Removed `{}`", binary.display());
+                        removed += 1;
+                    }
+                }
+            }
+            // The library artifacts are shared by the profiles with the same flags.
+            if let (Some(lib), None) = (&config.lib, profile) {
+                let deps = target_dir().join(workspace::DEPS_DIR);
+                for entry in fs::read_dir(deps).into_iter().flatten().flatten() {
+                    let name = entry.file_name();
+                    let is_artifact = name
+                        .to_string_lossy()
+                        .rsplit_once('-')
+                        .is_some_and(|(name, _)| name == lib.name);
+                    if is_artifact {
+                        removed += remove(&entry.path())?;
+                    }
+                }
+            }
+            if matches.get_flag("cache") {
+                removed += remove_cache(&config.project.name)?;
+            }
+            Ok(())
+        })?;
+
+        success!("Removed {} files", removed);
+        return Ok(());
+    }
+    let members = workspace::select(config, matches)?;
+
+    let mut removed = match profile {
+        Some(profile) => {
+            let mut removed = remove(&target_dir().join(profile))?;
+            for triple in triples() {
+                removed += remove(&target_dir().join(triple).join(profile))?;
+            }
+            removed
+        }
+        None => remove(&target_dir())?,
+    };
+    if matches.get_flag("cache") {
+        match members {
+            Some(members) => {
+                for member in members {
+                    removed += remove_cache(&member.name)?;
+                }
+            }
+            None if config.is_empty() => {
+                return Err("The project name is needed to find its cache entries.".into())
+            }
+            None => removed += remove_cache(&config.project.name)?,
+        }
+    }
+
+    success!("Removed {} files", removed);
+    Ok(())
+}
+
+/// Check if the profile is defined, and its name is a single directory,
+/// so nothing outside its output directory is removed.
+fn check_profile(config: &Config, profile: &str) -> Result<()> {
+    if profile.is_empty() || profile.contains(['/', '\\']) || profile.contains("..") {
+        return Err(format!("Invalid profile name `{}`.", profile).into());
+    }
+    config.profile(profile)?;
+
+    Ok(())
+}
+
+/// The target triples cross-compiled into the `target` directory, like `target/<triple>`.
+fn triples() -> Vec<String> {
+    fs::read_dir(target_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.split('-').filter(|part| !part.is_empty()).count() >= 2)
+        .collect()
+}
+
+/// The names of the profiles built into the `target` directory, like `debug`.
+fn profiles() -> Vec<String> {
+    fs::read_dir(target_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name != workspace::DEPS_DIR)
+        .collect()
+}
+
+/// Remove the entries of the project in the global artifact cache.
+/// The entries are not split by profile, so all of them are removed.
+fn remove_cache(project: &str) -> Result<usize> {
+    let cache = artifact_cache(project).ok_or("Failed to get the Coppo home directory.")?;
+
+    remove(&cache)
+}
+
+/// Remove the directory, and return the number of removed files.
+fn remove(dir: &Path) -> Result<usize> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+
+    let count = count_files(dir)?;
+    fs::remove_dir_all(dir).map_err(|e| format!("Failed to remove `{}`: {}", dir.display(), e))?;
+    info!("Removed `{}`", dir.display());
+
+    Ok(count)
+}
+
+/// Count the files in the directory recursively.
+fn count_files(dir: &Path) -> Result<usize> {
+    let mut count = 0;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            count += count_files(&entry.path())?;
+        } else {
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check_profile() {
+        let config = Config::from_str(
+            r#"
+            [project]
+            name = "app"
+            version = "0.1.0"
+            authors = []
+
+            [profile.bench]
+            inherits = "release"
+            "#,
+        )
+        .unwrap();
+
+        assert!(check_profile(&config, "release").is_ok());
+        assert!(check_profile(&config, "bench").is_ok());
+        assert!(check_profile(&config, "..").is_err());
+        assert!(check_profile(&config, "../app").is_err());
+        assert!(check_profile(&config, "").is_err());
+        assert!(check_profile(&config, "unknown").is_err());
+    }
+
+    #[test]
+    fn test_remove_cache() {
+        let home = std::env::temp_dir().join(format!("coppo-clean-{}", std::process::id()));
+        std::env::set_var("COPPO_HOME", &home);
+        let entry = artifact_cache("app").unwrap().join("app-0123456789abcdef");
+        fs::create_dir_all(&entry).unwrap();
+        fs::write(entry.join("libapp.a"), "archive").unwrap();
+        let other = artifact_cache("other").unwrap();
+        fs::create_dir_all(&other).unwrap();
+
+        assert_eq!(remove_cache("app").unwrap(), 1);
+        assert!(!artifact_cache("app").unwrap().exists());
+        assert!(other.is_dir());
+        assert_eq!(remove_cache("app").unwrap(), 0);
+
+        fs::remove_dir_all(&home).unwrap();
+    }
+}
@@ -0,0 +1,78 @@
+//! The compilation database `target/compile_commands.json`,
+//! it is used by the Clang tools like `clang-tidy` and `clangd`.
+//!
+//! See <https://clang.llvm.org/docs/JSONCompilationDatabase.html>.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use coppo_config::prelude::*;
+use serde::Serialize;
+
+use crate::{bins, compiler, flags, lib_sources, target_dir, workspace};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The file name of the compilation database.
+pub const COMPILE_COMMANDS: &str = "compile_commands.json";
+
+/// An entry of the compilation database, the command to compile a single source file.
+#[derive(Debug, Clone, Serialize)]
+pub struct CompileCommand {
+    /// The working directory of the command.
+    pub directory: PathBuf,
+    /// The source file, relative to the working directory.
+    pub file: PathBuf,
+    /// The arguments of the command, the first one is the compiler.
+    pub arguments: Vec<String>,
+}
+
+/// The compile commands of all the source files of the project.
+pub fn compile_commands(config: &Config) -> Result<Vec<CompileCommand>> {
+    let directory = env::current_dir()?;
+
+    let mut sources = match &config.lib {
+        Some(lib) => lib_sources(lib)?,
+        None => vec![],
+    };
+    sources.extend(
+        bins(config)
+            .iter()
+            .map(BinTarget::path)
+            .filter(|path| path.exists()),
+    );
+
+    let compiler = compiler(config);
+    let mut flags = flags(config);
+    flags.extend(workspace::include_flags(config)?);
+    let commands = sources
+        .into_iter()
+        .map(|file| {
+            let mut arguments = vec![compiler.clone()];
+            arguments.extend(flags.iter().cloned());
+            arguments.extend(["-c".to_owned(), file.display().to_string()]);
+
+            CompileCommand {
+                directory: directory.clone(),
+                file,
+                arguments,
+            }
+        })
+        .collect();
+
+    Ok(commands)
+}
+
+/// Write the compilation database into the `target` directory,
+/// and return its path.
+pub fn write(config: &Config) -> Result<PathBuf> {
+    let path = target_dir().join(COMPILE_COMMANDS);
+    fs::create_dir_all(target_dir())?;
+    fs::write(
+        &path,
+        serde_json::to_string_pretty(&compile_commands(config)?)? + "\n",
+    )?;
+
+    Ok(path)
+}
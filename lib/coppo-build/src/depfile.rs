@@ -0,0 +1,163 @@
+//! The dependency files of the compiled sources, like `target/debug/obj/core/src/core.d`.
+//!
+//! The compiler writes the headers included by a source into a Makefile rule with `-MMD -MF`,
+//! the system headers are left out:
+//!
+//! ```make
+//! target/debug/obj/core/src/core.o: src/core.cpp include/core.h \
+//!   include/detail/util.h
+//! ```
+//!
+//! MSVC writes them into a JSON file with `/sourceDependencies` instead, see [`crate::msvc`].
+//!
+//! The source and the headers are the inputs of its fingerprint, see [`crate::fingerprint`],
+//! so editing a header only compiles the sources including it again.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The flags to write the dependency file of the source.
+pub fn flags(depfile: &Path) -> Vec<String> {
+    vec![
+        "-MMD".to_string(),
+        "-MF".to_string(),
+        depfile.display().to_string(),
+    ]
+}
+
+/// The dependencies of the dependency file, resolved from the directory the compiler runs in.
+/// It is empty if the file does not exist yet.
+pub fn read(depfile: &Path, dir: &Path) -> Vec<PathBuf> {
+    let Ok(content) = fs::read_to_string(depfile) else {
+        return vec![];
+    };
+
+    let dependencies = match content.trim_start().starts_with('{') {
+        true => parse_json(&content),
+        false => parse(&content),
+    };
+    dependencies
+        .into_iter()
+        .map(|dependency| dir.join(dependency))
+        .collect()
+}
+
+/// The prerequisites of the rules of the dependency file, in order and without duplicates.
+///
+/// # Example
+/// ```rust
+/// use coppo_build::depfile::parse;
+/// use std::path::PathBuf;
+///
+/// let deps = parse("core.o: src/core.cpp include/my\\ header.h \\\n  include/util.h\n");
+/// assert_eq!(deps, ["src/core.cpp", "include/my header.h", "include/util.h"].map(PathBuf::from));
+/// ```
+pub fn parse(content: &str) -> Vec<PathBuf> {
+    let mut dependencies: Vec<PathBuf> = vec![];
+    let mut in_prerequisites = false;
+    let mut word = String::new();
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.peek() {
+                // A line continuation.
+                Some('\n') => {
+                    chars.next();
+                }
+                Some('\r') => {
+                    chars.next();
+                    chars.next_if_eq(&'\n');
+                }
+                Some(' ') | Some('#') | Some('\\') => {
+                    word.push(chars.next().unwrap_or(c));
+                }
+                _ => word.push(c),
+            },
+            '$' if chars.peek() == Some(&'$') => {
+                chars.next();
+                word.push('$');
+            }
+            // The colon of a rule is followed by a space, unlike the one of `C:\`.
+            ':' if matches!(chars.peek(), None | Some(' ' | '\t' | '\n' | '\r')) => {
+                word.clear();
+                in_prerequisites = true;
+            }
+            ' ' | '\t' => {
+                if in_prerequisites {
+                    push(&mut word, &mut dependencies);
+                }
+                word.clear();
+            }
+            '\n' | '\r' => {
+                if in_prerequisites {
+                    push(&mut word, &mut dependencies);
+                }
+                word.clear();
+                in_prerequisites = false;
+            }
+            c => word.push(c),
+        }
+    }
+    if in_prerequisites {
+        push(&mut word, &mut dependencies);
+    }
+
+    dependencies
+}
+
+/// The source and the headers of the JSON dependency file of MSVC.
+///
+/// ```json
+/// { "Version": "1.1", "Data": { "Source": "c:\\app\\src\\main.cpp", "Includes": ["c:\\app\\include\\app.h"] } }
+/// ```
+fn parse_json(content: &str) -> Vec<PathBuf> {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(content) else {
+        return vec![];
+    };
+    let data = &json["Data"];
+    let includes = data["Includes"].as_array().into_iter().flatten();
+
+    std::iter::once(&data["Source"])
+        .chain(includes)
+        .filter_map(|path| path.as_str())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Add the word as a dependency if it is not empty or added yet.
+fn push(word: &mut String, dependencies: &mut Vec<PathBuf>) {
+    let path = PathBuf::from(std::mem::take(word));
+    if !path.as_os_str().is_empty() && !dependencies.contains(&path) {
+        dependencies.push(path);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let content = "obj/a.o: src/a.cpp include/a.h \\\n include/b.h\ninclude/a.h:\n\nobj/b.o: src/b.cpp include/a.h C:\\sdk\\x.h\n";
+        assert_eq!(
+            parse(content),
+            [
+                "src/a.cpp",
+                "include/a.h",
+                "include/b.h",
+                "src/b.cpp",
+                "C:\\sdk\\x.h"
+            ]
+            .map(PathBuf::from)
+        );
+        assert_eq!(parse("a.o: a$$b.cpp"), [PathBuf::from("a$b.cpp")]);
+        assert!(parse("").is_empty());
+
+        let json =
+            r#"{"Version": "1.1", "Data": {"Source": "c:\\a.cpp", "Includes": ["c:\\a.h"]}}"#;
+        assert_eq!(
+            parse_json(json),
+            ["c:\\a.cpp", "c:\\a.h"].map(PathBuf::from)
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! The `coppo env` add-on, it prints the resolved environment Coppo will use,
+//! the compiler, the flags of each profile, the directories and the configuration layers.
+//!
+//! # Usage
+//! ```sh
+//! coppo env [--json]
+//! ```
+
+use std::env;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use coppo_addons::prelude::*;
+use coppo_config::prelude::*;
+use coppo_config::profile::{self, DEBUG_PROFILE, RELEASE_PROFILE};
+use serde::Serialize;
+
+use crate::{compiler, flags, profile_flags, target_dir};
+
+/// The `Coppo env` add-on.
+/// Print the environment of the current project.
+pub struct CoppoEnvAddon;
+
+impl_addon! {
+    CoppoEnvAddon,
+    name => "env",
+    description => "Print the environment Coppo will use",
+    args => [
+        arg!(--json "Print the environment as JSON")
+            .action(ArgAction::SetTrue),
+    ],
+    run => |config, matches| {
+        let environment = Environment::resolve(config)?;
+        if matches.get_flag("json") {
+            println!("{}", serde_json::to_string_pretty(&environment)?);
+        } else {
+            print!("{}", environment.render());
+        }
+    }
+}
+
+/// The resolved environment.
+#[derive(Debug, Serialize)]
+pub struct Environment {
+    /// The compiler.
+    pub compiler: Compiler,
+    /// The build profiles.
+    pub profiles: Vec<Profile>,
+    /// The Coppo home directory.
+    pub coppo_home: Option<PathBuf>,
+    /// The output directory of the project.
+    pub target_dir: PathBuf,
+    /// The cache directories.
+    pub caches: Vec<Cache>,
+    /// The configuration layers, the later ones take precedence.
+    pub layers: Vec<Layer>,
+}
+
+/// The compiler.
+#[derive(Debug, Serialize)]
+pub struct Compiler {
+    /// The name of the compiler.
+    pub name: String,
+    /// The full path of the compiler, it is `None` if the compiler is not found.
+    pub path: Option<PathBuf>,
+    /// The first line of `--version`.
+    pub version: Option<String>,
+}
+
+/// A build profile.
+#[derive(Debug, Serialize)]
+pub struct Profile {
+    /// The name of the profile.
+    pub name: String,
+    /// The compile flags of the profile.
+    pub flags: Vec<String>,
+}
+
+/// A cache directory.
+#[derive(Debug, Serialize)]
+pub struct Cache {
+    /// What is cached.
+    pub name: &'static str,
+    /// The directory.
+    pub path: PathBuf,
+}
+
+/// A configuration layer.
+#[derive(Debug, Serialize)]
+pub struct Layer {
+    /// The name of the layer.
+    pub name: &'static str,
+    /// Where the layer comes from, a file or an environment variable.
+    pub source: String,
+    /// Whether the layer is present.
+    pub active: bool,
+}
+
+impl Environment {
+    /// Resolve the environment of the project in the current directory.
+    pub fn resolve(config: &Config) -> std::io::Result<Self> {
+        let current_dir = env::current_dir()?;
+
+        let name = compiler(config);
+        let path = which(&name);
+        let version = path.as_ref().and_then(|path| version(path));
+
+        // The artifact cache is per project, it is unknown outside a project.
+        let artifacts = match config.project.name.is_empty() {
+            true => None,
+            false => artifact_cache(&config.project.name),
+        };
+        let caches = [("artifacts", artifacts), ("scripts", script_cache())]
+            .into_iter()
+            .filter_map(|(name, path)| Some(Cache { name, path: path? }))
+            .collect();
+
+        // The built-in profiles come first, the invalid ones are reported by the builds.
+        let mut names = vec![DEBUG_PROFILE.to_owned(), RELEASE_PROFILE.to_owned()];
+        names.extend(
+            config
+                .profile
+                .keys()
+                .filter(|name| profile::Profile::builtin(name).is_none())
+                .cloned(),
+        );
+        let profiles = names
+            .into_iter()
+            .filter_map(|name| {
+                let mut flags = flags(config);
+                flags.extend(profile_flags(&config.profile(&name).ok()?));
+                Some(Profile { name, flags })
+            })
+            .collect();
+
+        let global = GlobalConfig::path();
+        let layers = vec![
+            Layer {
+                name: "defaults",
+                source: "built-in".to_owned(),
+                active: true,
+            },
+            Layer {
+                name: "global",
+                source: global
+                    .as_ref()
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_default(),
+                active: global.is_some_and(|path| path.is_file()),
+            },
+            Layer {
+                name: "project",
+                source: current_dir.join(CONFIG_FILE).display().to_string(),
+                active: Config::exists(),
+            },
+            Layer {
+                name: "environment",
+                source: "COPPO_HOME".to_owned(),
+                active: env::var_os("COPPO_HOME").is_some_and(|home| !home.is_empty()),
+            },
+        ];
+
+        Ok(Self {
+            compiler: Compiler {
+                name,
+                path,
+                version,
+            },
+            profiles,
+            coppo_home: coppo_home(),
+            target_dir: current_dir.join(target_dir()),
+            caches,
+            layers,
+        })
+    }
+
+    /// Render the environment as text.
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+        let path = |path: &Option<PathBuf>| match path {
+            Some(path) => path.display().to_string(),
+            None => "not found".to_owned(),
+        };
+
+        let _ = writeln!(
+            text,
+            "compiler: {} ({})",
+            self.compiler.name,
+            path(&self.compiler.path)
+        );
+        if let Some(version) = &self.compiler.version {
+            let _ = writeln!(text, "  version: {}", version);
+        }
+
+        let _ = writeln!(text, "profiles:");
+        for profile in &self.profiles {
+            let _ = writeln!(text, "  {}: {}", profile.name, profile.flags.join(" "));
+        }
+
+        let _ = writeln!(text, "coppo home: {}", path(&self.coppo_home));
+        let _ = writeln!(text, "target dir: {}", self.target_dir.display());
+
+        let _ = writeln!(text, "caches:");
+        for cache in &self.caches {
+            let _ = writeln!(text, "  {}: {}", cache.name, cache.path.display());
+        }
+
+        let _ = writeln!(text, "config layers:");
+        for layer in &self.layers {
+            let state = match layer.active {
+                true => "",
+                false => " (inactive)",
+            };
+            let _ = writeln!(text, "  {}: {}{}", layer.name, layer.source, state);
+        }
+
+        text
+    }
+}
+
+/// Find the program in the `PATH`.
+pub fn which(program: &str) -> Option<PathBuf> {
+    let program = Path::new(program).with_extension(env::consts::EXE_EXTENSION);
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(&program))
+        .find(|path| path.is_file())
+}
+
+/// The first line of `<compiler> --version`.
+fn version(compiler: &Path) -> Option<String> {
+    let output = process::Command::new(compiler)
+        .arg("--version")
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(str::to_owned)
+}
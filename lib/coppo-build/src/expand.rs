@@ -0,0 +1,205 @@
+//! The `coppo expand` add-on, it prints the preprocessed source file.
+//!
+//! The file is preprocessed with the same arguments as `coppo build` compiles it,
+//! so the include paths and the macros are exactly the ones of the build.
+//!
+//! # Usage
+//! ```sh
+//! coppo expand <FILE> [--lines <START:END>]
+//! ```
+
+use std::env;
+use std::fs;
+use std::io::{IsTerminal, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::process::{self, Stdio};
+
+use coppo_addons::prelude::*;
+
+use crate::compdb::compile_commands;
+use crate::compiler;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The `Coppo expand` add-on.
+/// Preprocess a source file of the current project, and print the expanded output.
+/// The output is paged if it is printed to a terminal.
+pub struct CoppoExpandAddon;
+
+impl_addon! {
+    CoppoExpandAddon,
+    name => "expand",
+    description => "Print the preprocessed source file",
+    args => [
+        arg!(<FILE> "The source file to preprocess")
+            .value_parser(value_parser!(PathBuf)),
+        arg!(--lines <RANGE> "Only print the expansion of the source lines, like `10:20`")
+            .value_parser(parse_range),
+        arg!(--"no-pager" "Do not page the output")
+            .action(ArgAction::SetTrue),
+    ],
+    run => |config, matches| {
+        let file = matches.get_one::<PathBuf>("FILE").unwrap();
+        let expanded = expand(config, file, matches.get_one::<RangeInclusive<usize>>("lines"))?;
+        print(&expanded, !matches.get_flag("no-pager"))?;
+    }
+}
+
+/// Parse a line range like `10:20`, `10:` or `:20`.
+fn parse_range(range: &str) -> std::result::Result<RangeInclusive<usize>, String> {
+    let invalid = || format!("Invalid line range `{}`, expected `START:END`", range);
+    let (start, end) = range.split_once(':').ok_or_else(invalid)?;
+    let parse = |line: &str, default| match line.trim() {
+        "" => Ok(default),
+        line => line.parse::<usize>().map_err(|_| invalid()),
+    };
+
+    let (start, end) = (parse(start, 1)?, parse(end, usize::MAX)?);
+    if start > end {
+        return Err(invalid());
+    }
+
+    Ok(start..=end)
+}
+
+/// The preprocessor arguments of the file,
+/// the compile arguments without `-c` and the file itself.
+fn arguments(config: &Config, file: &Path) -> Result<Vec<String>> {
+    let canonical = fs::canonicalize(file)?;
+    let command = compile_commands(config)?.into_iter().find(|command| {
+        fs::canonicalize(command.directory.join(&command.file)).is_ok_and(|path| path == canonical)
+    });
+
+    let mut arguments = match command {
+        Some(command) => command
+            .arguments
+            .into_iter()
+            .filter(|argument| argument != "-c" && Path::new(argument) != command.file)
+            .collect(),
+        // The file is not a part of any target, like a header, use the arguments of the project.
+        None => {
+            let mut arguments = vec![compiler(config)];
+            if let Some(lib) = &config.lib {
+                arguments.push(format!("-I{}", lib.include().display()));
+            }
+            arguments
+        }
+    };
+    arguments.push("-E".to_owned());
+    arguments.push(file.display().to_string());
+
+    Ok(arguments)
+}
+
+/// Preprocess the file, and return the expanded output.
+fn expand(config: &Config, file: &Path, lines: Option<&RangeInclusive<usize>>) -> Result<String> {
+    if !file.is_file() {
+        return Err(format!("The file `{}` does not exist.", file.display()).into());
+    }
+
+    let arguments = arguments(config, file)?;
+    let output = process::Command::new(&arguments[0])
+        .args(&arguments[1..])
+        .output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to preprocess `{}`: {}",
+            file.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    let expanded = String::from_utf8_lossy(&output.stdout);
+    Ok(filter(&expanded, &file.display().to_string(), lines))
+}
+
+/// Remove the line markers like `# 12 "src/main.cpp"` from the expanded output,
+/// and only keep the expansion of the lines of the file if the range is given.
+fn filter(expanded: &str, file: &str, lines: Option<&RangeInclusive<usize>>) -> String {
+    let mut filtered = String::new();
+    // The origin of the current line, the file and the line number.
+    let mut origin: (&str, usize) = ("", 0);
+
+    for line in expanded.lines() {
+        if let Some(marker) = parse_marker(line) {
+            origin = marker;
+            continue;
+        }
+
+        if lines.is_none_or(|lines| origin.0 == file && lines.contains(&origin.1)) {
+            filtered += line;
+            filtered += "\n";
+        }
+        origin.1 += 1;
+    }
+
+    filtered
+}
+
+/// Parse a line marker like `# 12 "src/main.cpp" 2` into the file and the line number.
+fn parse_marker(line: &str) -> Option<(&str, usize)> {
+    let marker = line
+        .strip_prefix("# ")
+        .or_else(|| line.strip_prefix("#line "))?;
+    let (number, rest) = marker.split_once(' ')?;
+    let file = rest.strip_prefix('"')?.split('"').next()?;
+
+    Some((file, number.parse().ok()?))
+}
+
+/// Print the output, it is paged by `$PAGER` or `less` if it is printed to a terminal.
+fn print(output: &str, page: bool) -> Result<()> {
+    if page && std::io::stdout().is_terminal() {
+        let pager = env::var("PAGER").unwrap_or_else(|_| "less".to_owned());
+        let mut parts = pager.split_whitespace();
+        if let Some(program) = parts.next() {
+            if let Ok(mut child) = process::Command::new(program)
+                .args(parts)
+                .stdin(Stdio::piped())
+                .spawn()
+            {
+                if let Some(mut stdin) = child.stdin.take() {
+                    // The pager may be closed before reading the whole output.
+                    let _ = stdin.write_all(output.as_bytes());
+                }
+                child.wait()?;
+                return Ok(());
+            }
+        }
+    }
+
+    print!("{}", output);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_filter() {
+        let expanded = "# 1 \"src/main.cpp\"\n\
+            # 1 \"include/add.hpp\" 1\n\
+            int add(int a, int b);\n\
+            # 2 \"src/main.cpp\" 2\n\
+            \n\
+            int main() {\n\
+            \x20   return add(1, 2);\n\
+            }\n";
+
+        assert_eq!(
+            filter(expanded, "src/main.cpp", None),
+            "int add(int a, int b);\n\nint main() {\n    return add(1, 2);\n}\n"
+        );
+        assert_eq!(
+            filter(expanded, "src/main.cpp", Some(&(3..=4))),
+            "int main() {\n    return add(1, 2);\n"
+        );
+
+        assert_eq!(parse_range("10:20"), Ok(10..=20));
+        assert_eq!(parse_range(":20"), Ok(1..=20));
+        assert!(parse_range("20:10").is_err());
+    }
+}
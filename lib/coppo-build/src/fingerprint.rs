@@ -0,0 +1,202 @@
+//! The fingerprints of the build outputs, kept in `target/.fingerprint`.
+//!
+//! A fingerprint records the modification time and the content hash of each input of an output,
+//! like the source of an object and the headers it includes, see [`crate::depfile`],
+//! and the hash of the settings it is built with,
+//! like the compiler and the flags.
+//! The output is not built again if it exists and its fingerprint still matches:
+//! an input whose modification time changed is only considered changed if its content did,
+//! so touching a file does not trigger a rebuild.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::target_dir;
+
+/// The directory of the fingerprints in the `target` directory.
+pub const FINGERPRINT_DIR: &str = ".fingerprint";
+
+/// The fingerprint of an output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    /// The hash of the settings.
+    pub settings: String,
+    /// The inputs, in order.
+    pub inputs: Vec<Input>,
+}
+
+/// An input of an output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Input {
+    /// The path of the input.
+    pub path: PathBuf,
+    /// The modification time, in nanoseconds since the Unix epoch.
+    pub mtime: u64,
+    /// The SHA-256 hash of the content.
+    pub hash: String,
+}
+
+impl Input {
+    fn new(path: &Path) -> io::Result<Self> {
+        Ok(Input {
+            path: path.to_owned(),
+            mtime: mtime(path)?,
+            hash: hash_file(path)?,
+        })
+    }
+}
+
+/// The file of the fingerprint of the output, named after the output and the hash of its path,
+/// like `target/.fingerprint/main-1a2b3c4d5e6f7a8b.json`.
+pub fn path(output: &Path) -> PathBuf {
+    let stem = output
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .replace('.', "_");
+    let id = hash(output.to_string_lossy().as_bytes());
+
+    target_dir()
+        .join(FINGERPRINT_DIR)
+        .join(format!("{}-{}.json", stem, &id[..16]))
+}
+
+/// Check if the output is up to date with its inputs and settings.
+/// The recorded modification times are refreshed if only the times of the inputs changed.
+pub fn is_fresh(output: &Path, inputs: &[PathBuf], settings: &[String]) -> bool {
+    if !output.exists() {
+        return false;
+    }
+    let Some(recorded) = load(output) else {
+        return false;
+    };
+    if recorded.settings != hash_settings(settings)
+        || recorded.inputs.len() != inputs.len()
+        || recorded
+            .inputs
+            .iter()
+            .zip(inputs)
+            .any(|(i, p)| i.path != *p)
+    {
+        return false;
+    }
+
+    let mut touched = false;
+    for input in &recorded.inputs {
+        match mtime(&input.path) {
+            Ok(mtime) if mtime == input.mtime => {}
+            Ok(_) => match hash_file(&input.path) {
+                Ok(hash) if hash == input.hash => touched = true,
+                _ => return false,
+            },
+            Err(_) => return false,
+        }
+    }
+    if touched {
+        // A failure only costs a hash of the inputs next time.
+        let _ = save(output, inputs, settings);
+    }
+
+    true
+}
+
+/// Record the fingerprint of the output after it is built.
+pub fn save(output: &Path, inputs: &[PathBuf], settings: &[String]) -> io::Result<()> {
+    let fingerprint = Fingerprint {
+        settings: hash_settings(settings),
+        inputs: inputs
+            .iter()
+            .map(|input| Input::new(input))
+            .collect::<io::Result<_>>()?,
+    };
+    let file = path(output);
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    fs::write(file, serde_json::to_string(&fingerprint)?)
+}
+
+/// Load the recorded fingerprint of the output, it is `None` if it is missing or invalid.
+fn load(output: &Path) -> Option<Fingerprint> {
+    let content = fs::read_to_string(path(output)).ok()?;
+
+    serde_json::from_str(&content).ok()
+}
+
+/// The hash of the settings, each one is separated so `["-a", "b"]` and `["-ab"]` differ.
+fn hash_settings(settings: &[String]) -> String {
+    let mut hasher = Sha256::new();
+    for setting in settings {
+        hasher.update(setting);
+        hasher.update([0]);
+    }
+
+    format!("{:x}", hasher.finalize())
+}
+
+fn hash(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+fn hash_file(path: &Path) -> io::Result<String> {
+    Ok(hash(&fs::read(path)?))
+}
+
+fn mtime(path: &Path) -> io::Result<u64> {
+    let modified = fs::metadata(path)?.modified()?;
+    let since = modified.duration_since(UNIX_EPOCH).unwrap_or_default();
+
+    Ok(since.as_nanos() as u64)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Set the modification time, the writes in a row can have the same one.
+    fn touch(path: &Path, secs: u64) -> io::Result<()> {
+        let time = UNIX_EPOCH + std::time::Duration::from_secs(secs);
+        fs::File::options()
+            .write(true)
+            .open(path)?
+            .set_modified(time)
+    }
+
+    #[test]
+    fn test_fingerprint() -> io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("coppo-fingerprint-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let source = dir.join("main.cpp");
+        let output = dir.join("main.o");
+        let inputs = vec![source.clone()];
+        let settings = vec!["g++".to_string(), "-O2".to_string()];
+        fs::write(&source, "int main() {}")?;
+        fs::write(&output, "")?;
+
+        let result = (|| {
+            assert!(!is_fresh(&output, &inputs, &settings));
+            save(&output, &inputs, &settings)?;
+            assert!(is_fresh(&output, &inputs, &settings));
+            assert!(!is_fresh(&output, &inputs, &["g++".to_string()]));
+
+            // The same content is still fresh, a different one is not.
+            touch(&source, 1)?;
+            assert!(is_fresh(&output, &inputs, &settings));
+            fs::write(&source, "int main() { return 1; }")?;
+            touch(&source, 2)?;
+            assert!(!is_fresh(&output, &inputs, &settings));
+
+            Ok(())
+        })();
+        let _ = fs::remove_file(path(&output));
+        fs::remove_dir_all(&dir)?;
+
+        result
+    }
+}
@@ -0,0 +1,173 @@
+//! The GNU make jobserver, so Coppo shares the job slots of a parent build.
+//!
+//! When Coppo runs from a make (or ninja) recipe with a jobserver,
+//! `MAKEFLAGS` has `--jobserver-auth=fifo:<PATH>` or `--jobserver-auth=<R>,<W>`.
+//! Coppo then takes a token from the jobserver for every job it runs in parallel,
+//! besides the implicit one every process owns, instead of oversubscribing the machine.
+//!
+//! The processes spawned by Coppo get a jobserver too:
+//! the one of the parent build is inherited through the environment,
+//! otherwise Coppo serves its own with a token per CPU.
+//! The compilers use it for their parallel jobs, like `-flto=jobserver` of GCC.
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::process;
+use std::sync::OnceLock;
+
+use crate::parallel::default_jobs;
+
+/// The `--jobserver-auth` argument of `MAKEFLAGS`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Auth {
+    /// A named pipe, used by GNU make 4.4 and later.
+    Fifo(PathBuf),
+    /// The read and write ends of an inherited pipe.
+    Pipe(u32, u32),
+}
+
+/// Parse the jobserver of `MAKEFLAGS`, the last `--jobserver-auth` wins like in make.
+/// The older `--jobserver-fds` is accepted too.
+pub fn parse_makeflags(flags: &str) -> Option<Auth> {
+    let auth = flags
+        .split_whitespace()
+        // The variable definitions of the command line come after `--`.
+        .take_while(|arg| *arg != "--")
+        .filter_map(|arg| {
+            arg.strip_prefix("--jobserver-auth=")
+                .or_else(|| arg.strip_prefix("--jobserver-fds="))
+        })
+        .last()?;
+
+    if let Some(path) = auth.strip_prefix("fifo:") {
+        return Some(Auth::Fifo(PathBuf::from(path)));
+    }
+    let (read, write) = auth.split_once(',')?;
+    Some(Auth::Pipe(read.parse().ok()?, write.parse().ok()?))
+}
+
+/// A client of the jobserver of the parent build.
+#[derive(Debug)]
+pub struct Client {
+    read: File,
+    write: File,
+}
+
+impl Client {
+    /// Connect to the jobserver.
+    /// The inherited pipe is opened through `/dev/fd`, and it must still be a pipe,
+    /// since make does not pass it to the recipes not marked with `+`.
+    #[cfg(unix)]
+    pub fn open(auth: &Auth) -> io::Result<Client> {
+        use std::fs::OpenOptions;
+        use std::os::unix::fs::FileTypeExt;
+
+        let (read, write) = match auth {
+            Auth::Fifo(path) => (path.clone(), path.clone()),
+            Auth::Pipe(read, write) => (
+                PathBuf::from(format!("/dev/fd/{}", read)),
+                PathBuf::from(format!("/dev/fd/{}", write)),
+            ),
+        };
+        for path in [&read, &write] {
+            if !path.metadata()?.file_type().is_fifo() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("`{}` is not a pipe", path.display()),
+                ));
+            }
+        }
+
+        Ok(Client {
+            read: File::open(read)?,
+            write: OpenOptions::new().write(true).open(write)?,
+        })
+    }
+
+    /// Connect to the jobserver, it is only supported on Unix.
+    #[cfg(not(unix))]
+    pub fn open(_auth: &Auth) -> io::Result<Client> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    /// Take a token, it blocks until one is available.
+    /// The token is given back when it is dropped.
+    pub fn acquire(&self) -> io::Result<Token<'_>> {
+        let mut byte = [0];
+        loop {
+            match (&self.read).read(&mut byte) {
+                Ok(1) => return Ok(Token { client: self, byte }),
+                Ok(_) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// A token of the jobserver, the permission to run one more job.
+#[derive(Debug)]
+pub struct Token<'a> {
+    client: &'a Client,
+    byte: [u8; 1],
+}
+
+impl Drop for Token<'_> {
+    fn drop(&mut self) {
+        // The same byte is written back, make gives a meaning to some of them.
+        let _ = (&self.client.write).write_all(&self.byte);
+    }
+}
+
+/// The jobserver of the parent build, if Coppo runs under one.
+pub fn inherited() -> Option<&'static Client> {
+    static CLIENT: OnceLock<Option<Client>> = OnceLock::new();
+
+    CLIENT
+        .get_or_init(|| {
+            let flags = std::env::var("MAKEFLAGS").ok()?;
+            Client::open(&parse_makeflags(&flags)?).ok()
+        })
+        .as_ref()
+}
+
+/// Give the child process access to a jobserver,
+/// the one of the parent build or the one served by Coppo.
+pub fn configure(command: &mut process::Command) {
+    static SERVER: OnceLock<Option<jobserver::Client>> = OnceLock::new();
+
+    // The environment, and so the jobserver of the parent build, is inherited.
+    if inherited().is_some() {
+        return;
+    }
+    if let Some(server) = SERVER
+        .get_or_init(|| jobserver::Client::new(default_jobs()).ok())
+        .as_ref()
+    {
+        server.configure_make(command);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_makeflags() {
+        assert_eq!(
+            parse_makeflags("-j4 --jobserver-auth=fifo:/tmp/GMfifo42"),
+            Some(Auth::Fifo(PathBuf::from("/tmp/GMfifo42")))
+        );
+        assert_eq!(
+            parse_makeflags(" -j --jobserver-fds=3,4 --jobserver-auth=5,6"),
+            Some(Auth::Pipe(5, 6))
+        );
+        assert_eq!(
+            parse_makeflags("-j4 -- --jobserver-auth=3,4 CC=clang"),
+            None
+        );
+        assert_eq!(parse_makeflags("-j4"), None);
+        assert_eq!(parse_makeflags("--jobserver-auth=a,b"), None);
+    }
+}
@@ -1,4 +1,11 @@
 //! The `coppo-build` crate is a Coppo addon that allows you to compile the current project.
+//! It also provides the `run`, `check`, `clean`, `env`, `expand`, `verify-project`,
+//! `locate-project`, `pkgid`, `metadata`, `add`, `remove`, `update`, `vendor` and `watch` add-ons.
+//! `coppo run` can run a binary target or an example, like `coppo run --example hello`,
+//! or a standalone C++ file outside any project, like `coppo run hello.cpp`,
+//! and the arguments after `--` are passed to the program, like `coppo run -- --verbose`.
+//! `coppo build --target <TRIPLE>` cross-compiles the project into `target/<triple>/<profile>`,
+//! with the compiler, sysroot and linker of `[target.<triple>]` in the global configuration.
 //!
 //! # Usage
 //! ```sh
@@ -8,24 +15,307 @@
 #![forbid(unsafe_code)]
 
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::OnceLock;
 
 use coppo_addons::prelude::*;
+use coppo_config::files::SOURCE_EXTENSIONS;
+use coppo_config::global::TargetSettings;
+use coppo_config::prelude::*;
+use coppo_config::profile::{Lto, DEBUG_PROFILE, RELEASE_PROFILE};
+use coppo_config::toolchain;
+use coppo_config::{FeatureSelection, Language};
 use coppo_logger::prelude::*;
 
+pub mod add;
+pub mod check;
+pub mod clean;
+pub mod compdb;
+pub mod depfile;
+pub mod env;
+pub mod expand;
+pub mod fingerprint;
+pub mod jobserver;
+pub mod locate;
+pub mod lock;
+pub mod lto;
+pub mod metadata;
+pub mod msvc;
+pub mod parallel;
+pub mod probe;
+pub mod script;
+pub mod standard;
+pub mod vendor;
+pub mod verify;
+pub mod watch;
+pub mod workspace;
+
+pub use add::{CoppoAddAddon, CoppoRemoveAddon};
+pub use check::CoppoCheckAddon;
+pub use clean::CoppoCleanAddon;
+pub use env::CoppoEnvAddon;
+pub use expand::CoppoExpandAddon;
+pub use locate::{CoppoLocateProjectAddon, CoppoPkgidAddon};
+pub use lock::CoppoUpdateAddon;
+pub use metadata::CoppoMetadataAddon;
+pub use vendor::CoppoVendorAddon;
+pub use verify::CoppoVerifyProjectAddon;
+pub use watch::CoppoWatchAddon;
+
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 /// The compile output will be stored in the `target` directory.
 pub const COMPILE_OUTPUT: &str = "target";
 
-/// The default compile backend.
+/// The environment variable which overrides the output directory,
+/// the members of a workspace share the output directory of the workspace root with it.
+pub const TARGET_DIR_ENV: &str = "COPPO_TARGET_DIR";
+
+/// The output directory, it is [`COMPILE_OUTPUT`] unless `COPPO_TARGET_DIR` is set.
+pub fn target_dir() -> PathBuf {
+    match std::env::var_os(TARGET_DIR_ENV) {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(COMPILE_OUTPUT),
+    }
+}
+
+/// The default compile backend, used if no compiler is configured or detected.
 /// It defaults to `clang++` with `llvm`.
 pub const COMPILER: &str = "clang++";
 
+/// The compilers looked up in the `PATH` if none is configured, in order.
+pub const COMPILERS: &[&str] = &["clang++", "g++", "cl.exe"];
+
+/// The default compiler of the C projects, see [`COMPILER`].
+pub const C_COMPILER: &str = "clang";
+
+/// The compilers of the C projects looked up in the `PATH`, see [`COMPILERS`].
+pub const C_COMPILERS: &[&str] = &["clang", "gcc", "cl.exe"];
+
+/// The compiler to use, it is the first one of:
+/// 1. The `compiler` of the target of `--target` in the global configuration, see [`target_arg`].
+/// 2. The compiler of the toolchain selected by `coppo toolchain override`.
+/// 3. The `build.compiler` of the project.
+/// 4. The `build.compiler` of the global configuration.
+/// 5. The first one of [`COMPILERS`] in the `PATH`, or `cl.exe` of Visual Studio,
+///    see [`detect_compiler`].
+/// 6. [`COMPILER`].
+///
+/// The C projects use [`C_COMPILERS`] and [`C_COMPILER`] instead,
+/// and the C compiler of the same family as the others but the one of the project,
+/// like `gcc` for `g++`, see [`c_compiler`].
+pub fn compiler(config: &Config) -> String {
+    compiler_source(config).0
+}
+
+/// The compiler to use, with where it comes from,
+/// `target`, `toolchain`, `project`, `global`, `detected` or `default`, see [`compiler`].
+pub fn compiler_source(config: &Config) -> (String, &'static str) {
+    let language = config.project.language;
+    if let Some(compiler) = target_settings(config).and_then(|target| target.compiler.as_ref()) {
+        return (c_compiler(compiler, language), "target");
+    }
+    if let Some(compiler) = toolchain::active_compiler() {
+        return (
+            c_compiler(&compiler.display().to_string(), language),
+            "toolchain",
+        );
+    }
+
+    match (&config.build.compiler, &config.global.build.compiler) {
+        (Some(compiler), _) => (compiler.clone(), "project"),
+        (None, Some(compiler)) => (c_compiler(compiler, language), "global"),
+        (None, None) => match detect_compiler(language) {
+            Some(compiler) => (compiler, "detected"),
+            None => match language {
+                Language::Cpp => (COMPILER.to_owned(), "default"),
+                Language::C => (C_COMPILER.to_owned(), "default"),
+            },
+        },
+    }
+}
+
+/// The C compiler of the same family as the C++ compiler, for the C projects,
+/// like `gcc` for `g++` or `aarch64-linux-gnu-gcc` for `aarch64-linux-gnu-g++`.
+/// The compiler of a C++ project, and the ones which are not C++ compilers, are kept.
+///
+/// # Example
+/// ```rust
+/// use coppo_build::c_compiler;
+/// use coppo_config::Language;
+///
+/// assert_eq!(c_compiler("/usr/bin/clang++-18", Language::C), "/usr/bin/clang-18");
+/// assert_eq!(c_compiler("aarch64-linux-gnu-g++", Language::C), "aarch64-linux-gnu-gcc");
+/// assert_eq!(c_compiler("cl.exe", Language::C), "cl.exe");
+/// assert_eq!(c_compiler("g++", Language::Cpp), "g++");
+/// ```
+pub fn c_compiler(compiler: &str, language: Language) -> String {
+    if language == Language::Cpp {
+        return compiler.to_owned();
+    }
+
+    let path = Path::new(compiler);
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return compiler.to_owned();
+    };
+    let c_name = [("clang++", "clang"), ("g++", "gcc"), ("c++", "cc")]
+        .iter()
+        .find(|(cxx, _)| name.contains(cxx))
+        .map(|(cxx, c)| name.replacen(cxx, c, 1));
+    match c_name {
+        Some(c_name) => path.with_file_name(c_name).display().to_string(),
+        None => compiler.to_owned(),
+    }
+}
+
+/// The first one of [`COMPILERS`], or of [`C_COMPILERS`] for C, in the `PATH`,
+/// or `cl.exe` of the Visual Studio located by [`msvc::locate`].
+/// It is only looked up once per run.
+pub fn detect_compiler(language: Language) -> Option<String> {
+    static DETECTED: OnceLock<Option<String>> = OnceLock::new();
+    static DETECTED_C: OnceLock<Option<String>> = OnceLock::new();
+
+    let (detected, compilers) = match language {
+        Language::Cpp => (&DETECTED, COMPILERS),
+        Language::C => (&DETECTED_C, C_COMPILERS),
+    };
+    detected
+        .get_or_init(|| {
+            compilers
+                .iter()
+                .find(|compiler| env::which(compiler).is_some())
+                .map(|compiler| compiler.to_string())
+                .or_else(|| msvc::locate().map(|path| path.display().to_string()))
+        })
+        .clone()
+}
+
+/// The compiler to use, see [`compiler`].
+/// It fails with the hints to install or configure one if the compiler is not found.
+pub fn find_compiler(config: &Config) -> Result<String> {
+    let (compiler, source) = compiler_source(config);
+    let path = Path::new(&compiler);
+    let found = match path.components().count() > 1 {
+        true => path.is_file(),
+        false => {
+            env::which(&compiler).is_some()
+                || (msvc::is_msvc(&compiler) && msvc::locate().is_some())
+        }
+    };
+    if found {
+        return Ok(compiler);
+    }
+
+    let message = match source {
+        "default" => format!(
+            "No {} compiler is found in the `PATH`, looked for {}.",
+            config.project.language,
+            match config.project.language {
+                Language::Cpp => COMPILERS,
+                Language::C => C_COMPILERS,
+            }
+            .join(", ")
+        ),
+        "toolchain" => format!("The compiler `{}` of the toolchain is not found.", compiler),
+        "target" => format!(
+            "The compiler `{}` of the target `{}` is not found.",
+            compiler,
+            config.build_target.as_deref().unwrap_or_default()
+        ),
+        source => format!(
+            "The compiler `{}` of the {} configuration is not found.",
+            compiler, source
+        ),
+    };
+    Err(format!(
+        "{}\n\
+        help: {}\n\
+        help: or use another one with `compiler` in `[build]` of `Coppo.toml`, \
+        or `coppo config set build.compiler <compiler>`",
+        message,
+        install_hint()
+    )
+    .into())
+}
+
+/// How to install a compiler on the host.
+fn install_hint() -> &'static str {
+    if cfg!(windows) {
+        "install the Build Tools for Visual Studio, or LLVM with `winget install LLVM.LLVM`"
+    } else if cfg!(target_os = "macos") {
+        "install the Command Line Tools with `xcode-select --install`"
+    } else {
+        "install Clang or GCC with the package manager, like `sudo apt install clang` or `sudo dnf install gcc-c++`"
+    }
+}
+
+/// The compiler wrappers looked for in the `PATH`, see [`wrapper`].
+pub const WRAPPERS: &[&str] = &["sccache", "ccache"];
+
+/// The compiler wrapper, like `ccache`, the compiler is run through it to cache the objects.
+/// It is the first one of:
+/// 1. The `build.wrapper` of the project.
+/// 2. The `build.wrapper` of the global configuration.
+/// 3. The first one of [`WRAPPERS`] in the `PATH`, see [`detect_wrapper`].
+///
+/// An empty `build.wrapper` disables it.
+pub fn wrapper(config: &Config) -> Option<String> {
+    match config
+        .build
+        .wrapper
+        .as_ref()
+        .or(config.global.build.wrapper.as_ref())
+    {
+        Some(wrapper) if wrapper.is_empty() => None,
+        Some(wrapper) => Some(wrapper.clone()),
+        None => detect_wrapper(),
+    }
+}
+
+/// The first one of [`WRAPPERS`] in the `PATH`, it is only looked up once per run.
+pub fn detect_wrapper() -> Option<String> {
+    static DETECTED: OnceLock<Option<String>> = OnceLock::new();
+
+    DETECTED
+        .get_or_init(|| {
+            WRAPPERS
+                .iter()
+                .find(|wrapper| env::which(wrapper).is_some())
+                .map(|wrapper| wrapper.to_string())
+        })
+        .clone()
+}
+
+/// The compiler wrapper to use, see [`wrapper`].
+/// It fails if the configured wrapper is not found.
+pub fn find_wrapper(config: &Config) -> Result<Option<String>> {
+    let wrapper = wrapper(config);
+    if let Some(wrapper) = &wrapper {
+        let path = Path::new(wrapper);
+        let found = match path.components().count() > 1 {
+            true => path.is_file(),
+            false => env::which(wrapper).is_some(),
+        };
+        if !found {
+            return Err(format!(
+                "The compiler wrapper `{}` is not found.\n\
+                help: install it, or disable it with `wrapper = \"\"` in `[build]` of `Coppo.toml`",
+                wrapper
+            )
+            .into());
+        }
+    }
+
+    Ok(wrapper)
+}
+
 /// The `Coppo build` add-on.
 /// Compile the current project.
-/// It will compile the current project.
+/// It will compile the current project,
+/// or the members of the workspace selected by `-p` and `--workspace` in the dependency order.
+/// The binaries are placed in `target/<profile>`, see [`profile_arg`],
+/// or `target/<triple>/<profile>` when cross-compiling with `--target`, see [`target_arg`].
 /// The project must have a `Coppo.toml` file.
 /// The `Coppo.toml` file must have the following fields:
 /// - `name`: The name of the project.
@@ -36,38 +326,542 @@ impl_addon! {
     CoppoBuildAddon,
     name => "build",
     description => "Compile the current project",
+    args => [
+        release_arg(),
+        profile_arg(),
+        features_arg(),
+        all_features_arg(),
+        no_default_features_arg(),
+        target_arg(),
+        verbose_arg(),
+        workspace::package_arg(),
+        workspace::workspace_arg(),
+    ],
     run => |config, matches| {
-        build(config, matches)?;
+        let profile = selected_profile(matches);
+        set_verbose(matches.get_flag("verbose"));
+        workspace::for_each(config, matches, "Building", |config| {
+            select_features(config, matches)?;
+            select_target(config, matches)?;
+            build(config, profile)
+        })?;
     }
 }
 
+/// The `Coppo run` add-on.
+/// Compile and run a binary target or an example of the current project.
+/// If the project has multiple runnable targets, `--bin` or `--example` is needed to choose one.
+/// A standalone C++ file can be run as a script, see [`script`].
+/// In a workspace, `-p` chooses the member to run.
+/// The arguments after `--` are passed to the program, like `coppo run -- --port 8080`,
+/// and Coppo exits with the exit code of the program.
 pub struct CoppoRunAddon;
 
 impl_addon! {
     CoppoRunAddon,
     name => "run",
     description => "Compile and run the current project",
+    args => [
+        arg!([SCRIPT] "A standalone C++ file to run as a script")
+            .value_parser(value_parser!(PathBuf))
+            .conflicts_with_all(["bin", "example"]),
+        arg!(--bin <NAME> "The binary target to run")
+            .value_parser(value_parser!(String))
+            .conflicts_with("example"),
+        arg!(--example <NAME> "The example to run")
+            .value_parser(value_parser!(String)),
+        release_arg(),
+        profile_arg(),
+        features_arg(),
+        all_features_arg(),
+        no_default_features_arg(),
+        arg!(--cwd <DIR> "The working directory of the program, defaults to the current directory")
+            .value_parser(value_parser!(PathBuf)),
+        verbose_arg(),
+        workspace::package_arg(),
+        arg!([ARGS]... "The arguments of the program, after `--`")
+            .value_parser(value_parser!(String))
+            .last(true),
+    ],
     run => |config, matches| {
-        let bin_name = if cfg!(windows) {
-            format!("{}/{}.exe", COMPILE_OUTPUT, config.project.name)
-        } else {
-            format!("{}/{}", COMPILE_OUTPUT, config.project.name)
-        };
+        set_verbose(matches.get_flag("verbose"));
+        workspace::for_one(config, matches, "Running", |config| run(config, matches))?;
+    }
+}
+
+/// The profile used if no profile is given.
+pub const DEFAULT_PROFILE: &str = DEBUG_PROFILE;
+
+/// The `--release` argument, use the release profile.
+pub fn release_arg() -> Arg {
+    arg!(--release "Use the release profile")
+        .action(ArgAction::SetTrue)
+        .conflicts_with("profile")
+}
+
+/// The `--profile` argument, use the profile of the name.
+pub fn profile_arg() -> Arg {
+    arg!(--profile <NAME> "The build profile, defaults to `debug`")
+        .value_parser(value_parser!(String))
+}
 
-        // Check if the output binary exists.
-        if !Path::new(&bin_name).exists() {
-            build(config, matches)?;
+/// The profile selected by `--release` or `--profile`, see [`release_arg`] and [`profile_arg`].
+pub fn selected_profile(matches: &ArgMatches) -> &str {
+    if matches.get_flag("release") {
+        return RELEASE_PROFILE;
+    }
+    matches
+        .get_one::<String>("profile")
+        .map_or(DEFAULT_PROFILE, String::as_str)
+}
+
+/// The `--target` argument, cross-compile for the target triple.
+pub fn target_arg() -> Arg {
+    arg!(--target <TRIPLE> "Build for the target triple, like `x86_64-pc-windows-gnu`")
+        .value_parser(value_parser!(String))
+}
+
+/// Select the target given by [`target_arg`], the outputs are then placed in `target/<triple>`.
+pub fn select_target(config: &mut Config, matches: &ArgMatches) -> Result<()> {
+    let Some(triple) = matches.get_one::<String>("target") else {
+        return Ok(());
+    };
+    if triple.split('-').filter(|part| !part.is_empty()).count() < 2 {
+        return Err(format!(
+            "The target `{}` is not a target triple, like `x86_64-unknown-linux-gnu`.",
+            triple
+        )
+        .into());
+    }
+    config.build_target = Some(triple.clone());
+
+    Ok(())
+}
+
+/// The settings of the target of `--target`, the `[target.<triple>]` of the global configuration.
+pub fn target_settings(config: &Config) -> Option<&TargetSettings> {
+    config
+        .build_target
+        .as_ref()
+        .and_then(|triple| config.global.target.get(triple))
+}
+
+/// The compile flags of the target of `--target`, the sysroot and the extra flags of its settings,
+/// and `--target=<triple>` unless the target has its own compiler.
+pub fn target_flags(config: &Config) -> Vec<String> {
+    let Some(triple) = &config.build_target else {
+        return vec![];
+    };
+    let settings = target_settings(config).cloned().unwrap_or_default();
+
+    let mut flags = vec![];
+    if settings.compiler.is_none() {
+        flags.push(format!("--target={}", triple));
+    }
+    if let Some(sysroot) = &settings.sysroot {
+        flags.push(format!("--sysroot={}", sysroot.display()));
+    }
+    flags.extend(settings.flags);
+
+    flags
+}
+
+/// The link flags, the `ldflags` of `[build]`, and the linker of the target of `--target`.
+pub fn ldflags(config: &Config) -> Vec<String> {
+    let mut flags = config.build.ldflags.clone();
+    if let Some(linker) = target_settings(config).and_then(|target| target.linker.as_ref()) {
+        flags.push(format!("-fuse-ld={}", linker));
+    }
+
+    flags
+}
+
+/// The `-v/--verbose` argument, print the commands run by the build.
+pub fn verbose_arg() -> Arg {
+    arg!(-v --verbose "Print the full compiler and linker commands").action(ArgAction::SetTrue)
+}
+
+/// The command line of the command, with its working directory and environment if they are set,
+/// the arguments with spaces are quoted.
+///
+/// # Example
+/// ```rust
+/// use coppo_build::command_line;
+///
+/// let mut command = std::process::Command::new("g++");
+/// command.args(["-DNAME=a b", "main.cpp"]).current_dir("app").env("LANG", "C");
+/// assert_eq!(command_line(&command), "cd app && LANG=C g++ '-DNAME=a b' main.cpp");
+/// ```
+pub fn command_line(command: &process::Command) -> String {
+    let quote = |arg: &std::ffi::OsStr| {
+        let arg = arg.to_string_lossy();
+        match arg.is_empty() || arg.contains(char::is_whitespace) {
+            true => format!("'{}'", arg),
+            false => arg.into_owned(),
+        }
+    };
+    let mut line = vec![];
+    if let Some(dir) = command.get_current_dir() {
+        line.push(format!("cd {} &&", quote(dir.as_os_str())));
+    }
+    for (key, value) in command.get_envs() {
+        if let Some(value) = value {
+            line.push(format!("{}={}", key.to_string_lossy(), quote(value)));
+        }
+    }
+    line.push(quote(command.get_program()));
+    line.extend(command.get_args().map(quote));
+
+    line.join(" ")
+}
+
+/// Print the command line of the command about to run, with `-v/--verbose`.
+pub fn log_command(command: &process::Command) {
+    verbose!("Running `{}`", command_line(command));
+}
+
+/// The `--features` argument, the features to enable, separated by commas or spaces.
+pub fn features_arg() -> Arg {
+    arg!(-F --features <FEATURES> "The features to enable, separated by commas or spaces")
+        .value_parser(value_parser!(String))
+        .action(ArgAction::Append)
+}
+
+/// The `--all-features` argument, enable all the features.
+pub fn all_features_arg() -> Arg {
+    arg!(--"all-features" "Enable all the features").action(ArgAction::SetTrue)
+}
+
+/// The `--no-default-features` argument, do not enable the `default` feature.
+pub fn no_default_features_arg() -> Arg {
+    arg!(--"no-default-features" "Do not enable the `default` feature").action(ArgAction::SetTrue)
+}
+
+/// Select the features given by [`features_arg`], [`all_features_arg`] and [`no_default_features_arg`],
+/// and check them against the `[features]` table.
+pub fn select_features(config: &mut Config, matches: &ArgMatches) -> Result<()> {
+    config.feature_selection = FeatureSelection {
+        features: matches
+            .get_many::<String>("features")
+            .unwrap_or_default()
+            .flat_map(|features| features.split([',', ' ']))
+            .filter(|feature| !feature.is_empty())
+            .map(str::to_owned)
+            .collect(),
+        no_default_features: matches.get_flag("no-default-features"),
+        all_features: matches.get_flag("all-features"),
+    };
+    config.check_features()?;
+    config.enabled_features()?;
+
+    Ok(())
+}
+
+/// The compile flags of the profile, like `-O3 -DNDEBUG`,
+/// they are passed to the link step too for the link-time optimization, see [`lto`].
+pub fn profile_flags(profile: &Profile) -> Vec<String> {
+    let mut flags = vec![];
+    if let Some(opt_level) = &profile.opt_level {
+        flags.push(format!("-O{}", opt_level));
+    }
+    if profile.debug == Some(true) {
+        flags.push("-g".to_owned());
+    }
+    if profile.debug_assertions == Some(false) {
+        flags.push("-DNDEBUG".to_owned());
+    }
+    if let Some(lto) = profile.lto.as_ref().and_then(Lto::flag) {
+        flags.push(lto);
+    }
+    flags.extend(profile.flags.iter().cloned());
+
+    flags
+}
+
+/// The output directory of the profile, like `target/debug`,
+/// or `target/<triple>/debug` for the target of `--target`.
+pub fn profile_dir(config: &Config, profile: &str) -> PathBuf {
+    match &config.build_target {
+        Some(triple) => target_dir().join(triple).join(profile),
+        None => target_dir().join(profile),
+    }
+}
+
+/// The examples are placed in the `examples` directory.
+pub const EXAMPLES_DIR: &str = "examples";
+
+/// A target which can be run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Runnable {
+    /// A binary target.
+    Bin(String),
+    /// An example in the `examples` directory.
+    Example(String),
+}
+
+fn run(config: &mut Config, matches: &ArgMatches) -> Result<()> {
+    let profile = selected_profile(matches);
+    select_features(config, matches)?;
+
+    let binary = match matches.get_one::<PathBuf>("SCRIPT") {
+        // The paths of the arguments are relative to the directory Coppo is run in.
+        Some(script) => script::build(config, &invocation_dir(config).join(script))?,
+        None => {
+            let runnable = select(
+                config,
+                matches.get_one::<String>("bin").map(String::as_str),
+                matches.get_one::<String>("example").map(String::as_str),
+            )?;
+
+            // The project is built first, only the outputs which are out of date
+            // with their sources are built again, see [`fingerprint`].
+            match &runnable {
+                Runnable::Bin(name) => {
+                    build(config, profile)?;
+                    PathBuf::from(bin_path(config, name, profile))
+                }
+                Runnable::Example(name) => build_example(config, name, profile)?,
+            }
+        }
+    };
+
+    let mut command = process::Command::new(fs::canonicalize(&binary)?);
+    command
+        .args(matches.get_many::<String>("ARGS").unwrap_or_default())
+        .stdin(process::Stdio::inherit());
+    if let Some(cwd) = matches.get_one::<PathBuf>("cwd") {
+        let cwd = invocation_dir(config).join(cwd);
+        if !cwd.is_dir() {
+            return Err(format!("The directory `{}` does not exist.", cwd.display()).into());
+        }
+        command.current_dir(cwd);
+    } else if let Some(dir) = &config.invocation_dir {
+        command.current_dir(dir);
+    }
+
+    info!("Running `{}`...", binary.display());
+
+    let mut subprocess = command.spawn()?;
+    let status = subprocess.wait()?;
+    // The exit code of the program is the one of Coppo.
+    if !status.success() {
+        process::exit(exit_code(status));
+    }
+
+    Ok(())
+}
+
+/// The exit code of the program, `128 + <signal>` if it is killed by a signal like a shell.
+fn exit_code(status: process::ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
         }
+    }
+
+    status.code().unwrap_or(1)
+}
+
+/// The directory Coppo is run in, the current directory is the project root when it runs.
+fn invocation_dir(config: &Config) -> PathBuf {
+    config.invocation_dir.clone().unwrap_or_default()
+}
 
-        info!("Running the project...");
+/// Select the target to run.
+/// Without `--bin` and `--example`, the project must have exactly one binary target.
+pub fn select(config: &Config, bin: Option<&str>, example: Option<&str>) -> Result<Runnable> {
+    let bins = bins(config)
+        .into_iter()
+        .map(|bin| bin.name)
+        .collect::<Vec<_>>();
+    let examples = examples()?;
 
-        let mut subprocess = process::Command::new(&bin_name)
-            .spawn()?;
-        subprocess.wait()?;
+    let available = || {
+        let mut available = vec![];
+        if !bins.is_empty() {
+            available.push(format!("available binaries: {}", bins.join(", ")));
+        }
+        if !examples.is_empty() {
+            available.push(format!("available examples: {}", examples.join(", ")));
+        }
+        match available.is_empty() {
+            true => "the project does not have a runnable target".to_owned(),
+            false => available.join("; "),
+        }
+    };
+
+    match (bin, example) {
+        (Some(bin), _) if bins.iter().any(|name| name == bin) => Ok(Runnable::Bin(bin.to_owned())),
+        (Some(bin), _) => {
+            Err(format!("The binary `{}` is not found, {}.", bin, available()).into())
+        }
+        (None, Some(example)) if examples.iter().any(|name| name == example) => {
+            Ok(Runnable::Example(example.to_owned()))
+        }
+        (None, Some(example)) => {
+            Err(format!("The example `{}` is not found, {}.", example, available()).into())
+        }
+        (None, None) => match bins.as_slice() {
+            [bin] => Ok(Runnable::Bin(bin.clone())),
+            [] => Err(format!(
+                "The project does not have a binary target to run, {}.",
+                available()
+            )
+            .into()),
+            _ => Err(format!(
+                "Could not determine which binary to run, use `--bin` or `--example` to choose one, {}.",
+                available()
+            )
+            .into()),
+        },
+    }
+}
+
+/// The names of the examples, the file stems of the sources in the `examples` directory.
+pub fn examples() -> Result<Vec<String>> {
+    let dir = Path::new(EXAMPLES_DIR);
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let mut examples = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if is_source(&path) {
+            if let Some(name) = path.file_stem() {
+                examples.push(name.to_string_lossy().into_owned());
+            }
+        }
+    }
+    examples.sort();
+
+    Ok(examples)
+}
+
+/// Find the source of the example.
+fn example_source(name: &str) -> Option<PathBuf> {
+    SOURCE_EXTENSIONS
+        .iter()
+        .map(|ext| Path::new(EXAMPLES_DIR).join(format!("{}.{}", name, ext)))
+        .find(|path| path.is_file())
+}
+
+/// The output path of the example in the directory of the profile,
+/// like `target/debug/examples/<name>`, with the `.exe` extension for Windows.
+pub fn example_path(config: &Config, name: &str, profile: &str) -> PathBuf {
+    profile_dir(config, profile)
+        .join(EXAMPLES_DIR)
+        .join(match config.platform().is_windows() {
+            true => format!("{}.exe", name),
+            false => name.to_owned(),
+        })
+}
+
+/// Compile the example linked with the library of the profile, and return the path of its binary,
+/// like `target/debug/examples/<name>`.
+pub fn build_example(config: &Config, name: &str, profile: &str) -> Result<PathBuf> {
+    let source =
+        example_source(name).ok_or_else(|| format!("The example `{}` is not found.", name))?;
+    let flags = profile_flags(&config.profile(profile)?);
+    let binary = example_path(config, name, profile);
+    fs::create_dir_all(binary.parent().unwrap_or(&target_dir()))?;
+
+    let library = build_library(config, profile, &flags)?;
+    let artifacts = workspace::artifacts(config, &flags)?;
+    info!("Building the example `{}`...", name);
+    link(
+        config,
+        &source,
+        &binary,
+        library.as_deref(),
+        &artifacts,
+        &flags,
+    )?;
+
+    Ok(binary)
+}
+
+/// The binary targets of the project.
+/// If no `[[bin]]` is declared, `src/main.cpp` is the binary named after the project,
+/// unless the project is a library without `src/main.cpp`.
+/// The sources of a C project are `src/main.c` and `src/bin/<name>.c` instead.
+pub fn bins(config: &Config) -> Vec<BinTarget> {
+    let extension = config.project.language.source_extension();
+    if !config.bin.is_empty() {
+        let mut bins = config.bin.clone();
+        for bin in &mut bins {
+            bin.path.get_or_insert_with(|| {
+                PathBuf::from(format!("src/bin/{}.{}", bin.name, extension))
+            });
+        }
+        return bins;
+    }
+
+    let main = PathBuf::from(format!("src/main.{}", extension));
+    if config.lib.is_none() || main.exists() {
+        vec![BinTarget {
+            name: config.project.name.clone(),
+            path: Some(main),
+        }]
+    } else {
+        vec![]
     }
 }
 
-fn build(config: &mut Config, _matches: &ArgMatches) -> Result<()> {
+/// The output path of the binary in the directory of the profile, like `target/debug/<name>`,
+/// with the `.exe` extension for Windows.
+pub fn bin_path(config: &Config, name: &str, profile: &str) -> String {
+    match config.platform().is_windows() {
+        true => format!("{}/{}.exe", profile_dir(config, profile).display(), name),
+        false => format!("{}/{}", profile_dir(config, profile).display(), name),
+    }
+}
+
+/// The source files of the library target.
+/// The path of the library can be a directory or a single file.
+pub fn lib_sources(lib: &LibTarget) -> Result<Vec<PathBuf>> {
+    let path = lib.path();
+    if path.is_file() {
+        return Ok(vec![path]);
+    }
+    if !path.is_dir() {
+        return Err(format!(
+            "The source directory `{}` of the library `{}` does not exist.",
+            path.display(),
+            lib.name
+        )
+        .into());
+    }
+
+    let mut sources = vec![];
+    let mut dirs = vec![path];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if is_source(&path) {
+                sources.push(path);
+            }
+        }
+    }
+    sources.sort();
+
+    Ok(sources)
+}
+
+/// Check if the file is a C++ source file.
+fn is_source(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| SOURCE_EXTENSIONS.iter().any(|source| ext == *source))
+}
+
+/// Compile all the targets of the current project with the profile,
+/// the library first, then the binaries linked with it.
+/// The outputs whose sources and flags did not change are not built again, see [`fingerprint`].
+pub fn build(config: &Config, profile: &str) -> Result<()> {
     info!("Building the project...");
 
     // Check if the project has a `Coppo.toml` file.
@@ -80,32 +874,768 @@ fn build(config: &mut Config, _matches: &ArgMatches) -> Result<()> {
         return Err("The project name and version is needed".into());
     }
 
-    // Check if the `src/main.cpp` file exists.
-    if !Path::new("src/main.cpp").exists() {
-        return Err("The `src/main.cpp` file does not exist.".into());
+    let settings = config.profile(profile)?;
+    let flags = profile_flags(&settings);
+    let compiler = find_compiler(config)?;
+    find_wrapper(config)?;
+    // The flags of the profile come last, and can give the standard too.
+    let mut all_flags = self::flags(config);
+    all_flags.extend(flags.iter().cloned());
+    standard::check(&compiler, config.project.language, &all_flags)?;
+    let mut probe_flags = target_flags(config);
+    probe_flags.extend(ldflags(config));
+    lto::check(&compiler, config.project.language, &settings, &probe_flags)?;
+
+    // The dependencies must match the lockfile.
+    lock::ensure(config)?;
+
+    // Create the output directory of the profile if it does not exist.
+    fs::create_dir_all(profile_dir(config, profile))?;
+
+    // Keep the compilation database in sync for the Clang tools.
+    compdb::write(config)?;
+
+    let library = build_library(config, profile, &flags)?;
+
+    // The libraries of the workspace members it depends on.
+    let artifacts = workspace::artifacts(config, &flags)?;
+
+    for bin in bins(config) {
+        // Check if the source file of the binary exists.
+        let source = bin.path();
+        if !source.exists() {
+            return Err(format!("The `{}` file does not exist.", source.display()).into());
+        }
+
+        // Compile the binary with the library built above,
+        // And store the output in the directory of the profile.
+        link(
+            config,
+            &source,
+            Path::new(&bin_path(config, &bin.name, profile)),
+            library.as_deref(),
+            &artifacts,
+            &flags,
+        )?;
     }
 
-    // Create the `target` directory if it does not exist.
-    if !Path::new(COMPILE_OUTPUT).exists() {
-        fs::create_dir(COMPILE_OUTPUT)?;
+    success!("The project has been built.");
+    Ok(())
+}
+
+/// Build the library target of the project with the profile and its flags,
+/// and return the library the binaries are linked with, it is `None` without a library.
+/// The library of a workspace member is built for the members depending on it,
+/// otherwise it is built into the directory of the profile.
+pub fn build_library(config: &Config, profile: &str, extra: &[String]) -> Result<Option<PathBuf>> {
+    if let Some(artifact) = workspace::lib_artifact(config, extra)? {
+        info!(
+            "The library has been built into `{}`.",
+            artifact.archive.display()
+        );
+        return Ok(Some(artifact.archive));
     }
 
-    // Compile the project,
-    // And store the output in the `target` directory.
-    let bin_name = if cfg!(windows) {
-        format!("{}/{}.exe", COMPILE_OUTPUT, config.project.name)
-    } else {
-        format!("{}/{}", COMPILE_OUTPUT, config.project.name)
+    match &config.lib {
+        Some(lib) => Ok(Some(build_profile_lib(config, lib, profile, extra)?)),
+        None => Ok(None),
+    }
+}
+
+/// The link flags to find the shared library from the directory of the binary at run time,
+/// like `-Wl,-rpath,$ORIGIN/../debug`, it is empty for a static library or with MSVC.
+pub fn run_path_flags(compiler: &str, binary: &Path, library: &Path) -> Vec<String> {
+    let shared = library
+        .extension()
+        .is_some_and(|extension| extension == "so" || extension == "dylib");
+    if !shared || msvc::is_msvc(compiler) {
+        return vec![];
+    }
+
+    let origin = match library
+        .extension()
+        .is_some_and(|extension| extension == "dylib")
+    {
+        true => "@loader_path",
+        false => "$ORIGIN",
     };
-    let output = process::Command::new(COMPILER)
-        .args(&["src/main.cpp", "-o", &bin_name])
-        .output()?;
+    let from = binary.parent().unwrap_or(Path::new(""));
+    let to = library.parent().unwrap_or(Path::new(""));
+    let common = from
+        .components()
+        .zip(to.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut path = PathBuf::from(origin);
+    path.extend(from.components().skip(common).map(|_| ".."));
+    path.extend(to.components().skip(common));
+
+    vec![format!("-Wl,-rpath,{}", path.display())]
+}
+
+/// Compile the binary target into `binary` with the extra flags,
+/// like the release flags of `coppo dist`, the libraries it depends on are built with them too.
+pub fn build_bin(config: &Config, bin: &BinTarget, binary: &Path, extra: &[String]) -> Result<()> {
+    let source = bin.path();
+    if !source.exists() {
+        return Err(format!("The `{}` file does not exist.", source.display()).into());
+    }
+    if let Some(dir) = binary.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let artifacts = workspace::artifacts(config, extra)?;
+    compile(config, &source, binary, &artifacts, extra)
+}
+
+/// The output path of the static library in the directory of the profile,
+/// like `target/debug/lib<name>.a`, or `<name>.lib` for MSVC.
+pub fn profile_lib_path(config: &Config, name: &str, profile: &str) -> PathBuf {
+    profile_dir(config, profile).join(static_lib_name(config, name))
+}
+
+/// The file name of the static library, like `lib<name>.a`, or `<name>.lib` for MSVC.
+pub fn static_lib_name(config: &Config, name: &str) -> String {
+    match config.platform().env == "msvc" {
+        true => format!("{}.lib", name),
+        false => format!("lib{}.a", name),
+    }
+}
 
-    if output.status.success() {
-        success!("The project has been built.");
-        Ok(())
+/// The output path of the shared library in the directory of the profile,
+/// like `target/debug/lib<name>.so`, `lib<name>.dylib` for macOS or `<name>.dll` for Windows.
+pub fn shared_lib_path(config: &Config, name: &str, profile: &str) -> PathBuf {
+    let platform = config.platform();
+    let file = if platform.is_windows() {
+        format!("{}.dll", name)
+    } else if platform.os == "macos" {
+        format!("lib{}.dylib", name)
     } else {
+        format!("lib{}.so", name)
+    };
+
+    profile_dir(config, profile).join(file)
+}
+
+/// The directory the public headers of the library are installed into, `target/include/<name>`.
+pub fn installed_include_dir(name: &str) -> PathBuf {
+    target_dir().join("include").join(name)
+}
+
+/// Compile the library target with the profile flags into the directory of the profile,
+/// as the kinds of `[lib] kind`, and install its public headers, see [`installed_include_dir`].
+/// The objects are stored in `target/<profile>/obj/<name>`.
+/// The returned library is the one the binaries are linked with, the static one if it is built.
+fn build_profile_lib(
+    config: &Config,
+    lib: &LibTarget,
+    profile: &str,
+    extra: &[String],
+) -> Result<PathBuf> {
+    let compiler = compiler(config);
+    let wrapper = wrapper(config);
+    let objects_dir = profile_dir(config, profile).join("obj").join(&lib.name);
+    let mut flags = flags(config);
+    flags.extend(workspace::include_flags(config)?);
+    flags.extend(extra.iter().cloned());
+    // The objects of a shared library must be position independent,
+    // the static library is archived from the same objects.
+    let shared = lib.builds(LibKind::Shared);
+    if shared && !msvc::is_msvc(&compiler) {
+        flags.push("-fPIC".to_owned());
+    }
+
+    info!("Building the library `{}`...", lib.name);
+    let dir = Path::new(".");
+    let objects = compile_objects(
+        dir,
+        wrapper.as_deref(),
+        &compiler,
+        lib,
+        &flags,
+        &objects_dir,
+    )?;
+    let mut linked = None;
+    if lib.builds(LibKind::Static) {
+        let archive = profile_lib_path(config, &lib.name, profile);
+        archive_objects(dir, &compiler, lib, &objects, &archive)?;
+        info!(
+            "The static library has been built into `{}`.",
+            archive.display()
+        );
+        linked = Some(archive);
+    }
+    if shared {
+        let output = shared_lib_path(config, &lib.name, profile);
+        // The profile flags are needed by the link-time optimization.
+        let mut ldflags = extra.to_vec();
+        ldflags.extend(self::ldflags(config));
+        link_shared(
+            wrapper.as_deref(),
+            &compiler,
+            lib,
+            &objects,
+            &ldflags,
+            &output,
+        )?;
+        info!(
+            "The shared library has been built into `{}`.",
+            output.display()
+        );
+        // The binaries are linked with the import library of the DLL.
+        let output = match msvc::is_msvc(&compiler) {
+            true => msvc::import_lib(&output),
+            false => output,
+        };
+        linked.get_or_insert(output);
+    }
+    install_headers(lib)?;
+
+    linked.ok_or_else(|| format!("The library `{}` does not have any kind.", lib.name).into())
+}
+
+/// Link the objects of the library into the shared library.
+fn link_shared(
+    wrapper: Option<&str>,
+    compiler: &str,
+    lib: &LibTarget,
+    objects: &[PathBuf],
+    ldflags: &[String],
+    output: &Path,
+) -> Result<()> {
+    let mut settings = vec![compiler.to_owned(), "shared".to_owned()];
+    settings.extend(ldflags.iter().cloned());
+    if fingerprint::is_fresh(output, objects, &settings) {
+        return Ok(());
+    }
+
+    let mut command = compiler_command(wrapper, compiler);
+    match msvc::is_msvc(compiler) {
+        true => {
+            msvc::configure(&mut command);
+            command.args(msvc::shared_args(objects, ldflags, output));
+        }
+        false => {
+            command.arg("-shared").args(objects).args(ldflags);
+            // The binaries find the library by its name in their run path.
+            let name = output.file_name().unwrap_or_default().to_string_lossy();
+            match output.extension().and_then(|extension| extension.to_str()) {
+                Some("dylib") => {
+                    command.arg(format!("-Wl,-install_name,@rpath/{}", name));
+                }
+                Some("so") => {
+                    command.arg(format!("-Wl,-soname,{}", name));
+                }
+                _ => {}
+            }
+            command.arg("-o").arg(output);
+        }
+    }
+    log_command(&command);
+    let output_status = command.output()?;
+    if !output_status.status.success() {
+        return Err(format!(
+            "Failed to link the shared library `{}`: {}",
+            lib.name,
+            String::from_utf8_lossy(&output_status.stderr).trim()
+        )
+        .into());
+    }
+    fingerprint::save(output, objects, &settings)?;
+
+    Ok(())
+}
+
+/// Install the public headers of the library into `target/include/<name>`,
+/// only the changed ones are copied so the others keep their modification times.
+fn install_headers(lib: &LibTarget) -> Result<()> {
+    let include = lib.include();
+    if !include.is_dir() {
+        return Ok(());
+    }
+
+    let installed = installed_include_dir(&lib.name);
+    let mut dirs = vec![include.clone()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let target = installed.join(path.strip_prefix(&include).unwrap_or(&path));
+            let content = fs::read(&path)?;
+            if fs::read(&target).ok().as_ref() != Some(&content) {
+                fs::create_dir_all(target.parent().unwrap_or(&installed))?;
+                fs::write(&target, content)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The archiver of the static libraries.
+pub const ARCHIVER: &str = "ar";
+
+/// Compile the sources of the library in the project directory `dir` with the flags,
+/// and archive the objects into `archive`.
+/// The paths of the objects and the archive must be absolute if `dir` is not the current directory.
+pub(crate) fn archive_lib(
+    dir: &Path,
+    wrapper: Option<&str>,
+    compiler: &str,
+    lib: &LibTarget,
+    flags: &[String],
+    objects_dir: &Path,
+    archive: &Path,
+) -> Result<()> {
+    info!("Building the library `{}`...", lib.name);
+    let objects = compile_objects(dir, wrapper, compiler, lib, flags, objects_dir)?;
+
+    archive_objects(dir, compiler, lib, &objects, archive)
+}
+
+/// Compile the sources of the library in the project directory `dir` with the flags,
+/// and return the objects, the unchanged ones are not compiled again.
+fn compile_objects(
+    dir: &Path,
+    wrapper: Option<&str>,
+    compiler: &str,
+    lib: &LibTarget,
+    flags: &[String],
+    objects_dir: &Path,
+) -> Result<Vec<PathBuf>> {
+    // The sources are listed in `dir`, and compiled with the paths relative to it.
+    let located = LibTarget {
+        path: Some(dir.join(lib.path())),
+        ..lib.clone()
+    };
+    let mut settings = vec![compiler.to_owned()];
+    settings.extend(flags.iter().cloned());
+    // The sources listed by the depfile, or the source itself before it is compiled.
+    let inputs = |source: &Path, depfile: &Path| match depfile::read(depfile, dir) {
+        dependencies if dependencies.is_empty() => vec![dir.join(source)],
+        dependencies => dependencies,
+    };
+
+    let mut objects = vec![];
+    let mut stale = vec![];
+    for source in lib_sources(&located)? {
+        let source = source.strip_prefix(dir).unwrap_or(&source).to_owned();
+        let object = objects_dir
+            .join(&source)
+            .with_extension(object_extension(compiler));
+        let depfile = object.with_extension(depfile_extension(compiler));
+        objects.push(object.clone());
+        // The sources whose headers did not change either are not compiled again.
+        if !fingerprint::is_fresh(&object, &inputs(&source, &depfile), &settings) {
+            stale.push((source, object, depfile));
+        }
+    }
+
+    // The stale sources are compiled in parallel, within the jobserver of a parent build.
+    let failures = parallel::run_parallel(
+        &stale,
+        parallel::default_jobs(),
+        |(source, object, depfile)| -> std::result::Result<(), String> {
+            fs::create_dir_all(object.parent().unwrap_or(objects_dir))
+                .map_err(|e| e.to_string())?;
+            let mut command = compile_command(wrapper, compiler, source, object, flags, depfile);
+            command.current_dir(dir);
+            log_command(&command);
+            let output = command.output().map_err(|e| e.to_string())?;
+            if !output.status.success() {
+                return Err(String::from_utf8_lossy(&output.stderr).into_owned());
+            }
+            fingerprint::save(object, &inputs(source, depfile), &settings)
+                .map_err(|e| e.to_string())
+        },
+    )
+    .into_iter()
+    .filter_map(|result| result.err())
+    .collect::<Vec<_>>();
+    if !failures.is_empty() {
+        error!("The library `{}` failed to build.", lib.name);
+        return Err(failures.join("\n").into());
+    }
+
+    // The objects are resolved from the current directory.
+    Ok(objects.iter().map(|object| dir.join(object)).collect())
+}
+
+/// Archive the objects of the library into `archive`.
+fn archive_objects(
+    dir: &Path,
+    compiler: &str,
+    lib: &LibTarget,
+    objects: &[PathBuf],
+    archive: &Path,
+) -> Result<()> {
+    let mut command = archive_command(compiler, archive, objects);
+    let archiver = vec![command.get_program().to_string_lossy().into_owned()];
+    if fingerprint::is_fresh(archive, objects, &archiver) {
+        return Ok(());
+    }
+
+    // `ar` appends to an existing archive, so it is created from scratch.
+    if archive.exists() {
+        fs::remove_file(archive)?;
+    }
+    command.current_dir(dir);
+    log_command(&command);
+    let output = command.output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to archive the library `{}`: {}",
+            lib.name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    fingerprint::save(archive, objects, &archiver)?;
+
+    Ok(())
+}
+
+/// The extension of the objects of the compiler, `obj` for MSVC and `o` otherwise.
+pub fn object_extension(compiler: &str) -> &'static str {
+    match msvc::is_msvc(compiler) {
+        true => msvc::OBJECT_EXTENSION,
+        false => "o",
+    }
+}
+
+/// The extension of the dependency files of the compiler, see [`depfile`].
+fn depfile_extension(compiler: &str) -> &'static str {
+    match msvc::is_msvc(compiler) {
+        true => "json",
+        false => "d",
+    }
+}
+
+/// The command to run the compiler, through the wrapper if it is given, see [`wrapper`].
+pub fn compiler_command(wrapper: Option<&str>, compiler: &str) -> process::Command {
+    match wrapper {
+        Some(wrapper) => {
+            let mut command = process::Command::new(wrapper);
+            command.arg(compiler);
+            command
+        }
+        None => process::Command::new(compiler),
+    }
+}
+
+/// The command to compile the source into the object,
+/// with the headers it includes written into the dependency file.
+pub fn compile_command(
+    wrapper: Option<&str>,
+    compiler: &str,
+    source: &Path,
+    object: &Path,
+    flags: &[String],
+    depfile: &Path,
+) -> process::Command {
+    let mut command = compiler_command(wrapper, compiler);
+    jobserver::configure(&mut command);
+    match msvc::is_msvc(compiler) {
+        true => {
+            msvc::configure(&mut command);
+            command.args(msvc::compile_args(source, object, flags, depfile));
+        }
+        false => {
+            command
+                .arg("-c")
+                .arg(source)
+                .args(flags)
+                .args(depfile::flags(depfile))
+                .arg("-o")
+                .arg(object);
+        }
+    }
+
+    command
+}
+
+/// The command to compile and link the inputs, the sources and the libraries, into the binary.
+/// The headers included by the sources are written into the dependency file if it is given.
+pub fn link_command(
+    wrapper: Option<&str>,
+    compiler: &str,
+    inputs: &[PathBuf],
+    flags: &[String],
+    binary: &Path,
+    depfile: Option<&Path>,
+) -> process::Command {
+    let mut command = compiler_command(wrapper, compiler);
+    jobserver::configure(&mut command);
+    match msvc::is_msvc(compiler) {
+        true => {
+            msvc::configure(&mut command);
+            command.args(msvc::link_args(inputs, flags, binary, depfile));
+        }
+        false => {
+            command.args(inputs).args(flags);
+            if let Some(depfile) = depfile {
+                command.args(depfile::flags(depfile));
+            }
+            command.arg("-o").arg(binary);
+        }
+    }
+
+    command
+}
+
+/// The command to archive the objects into the static library,
+/// with [`ARCHIVER`], or `lib.exe` for MSVC.
+pub fn archive_command(compiler: &str, archive: &Path, objects: &[PathBuf]) -> process::Command {
+    if msvc::is_msvc(compiler) {
+        return msvc::archive_command(compiler, archive, objects);
+    }
+
+    let mut command = process::Command::new(archiver(compiler));
+    command.arg("rcs").arg(archive).args(objects);
+
+    command
+}
+
+/// The archiver of the compiler, the one with the same target prefix for a cross compiler,
+/// like `x86_64-w64-mingw32-ar` for `x86_64-w64-mingw32-g++`, if it is in the `PATH`,
+/// otherwise [`ARCHIVER`].
+pub fn archiver(compiler: &str) -> String {
+    let path = Path::new(compiler);
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let prefix = ["-g++", "-c++", "-clang++", "-gcc", "-clang"]
+        .into_iter()
+        .find_map(|suffix| name.strip_suffix(suffix))
+        .filter(|prefix| prefix.contains('-'));
+
+    prefix
+        .map(|prefix| path.with_file_name(format!("{}-{}", prefix, ARCHIVER)))
+        .filter(|archiver| match archiver.components().count() > 1 {
+            true => archiver.is_file(),
+            false => env::which(&archiver.display().to_string()).is_some(),
+        })
+        .map_or_else(
+            || ARCHIVER.to_owned(),
+            |archiver| archiver.display().to_string(),
+        )
+}
+
+/// The compile flags shared by all the sources of the project,
+/// the `build.flags` of the global configuration come first,
+/// then the flags of the target of `--target`, see [`target_flags`],
+/// then the `[build]` settings of the project, see [`BuildSettings::compile_flags`],
+/// and the macros of the enabled features come last.
+/// The standard is the default one of the language if none of them gives one,
+/// see [`coppo_config::standard::default_std`].
+pub fn flags(config: &Config) -> Vec<String> {
+    let language = config.project.language;
+    let mut flags = config.global.build.flags.clone();
+    flags.extend(target_flags(config));
+    flags.extend(config.build.compile_flags(language));
+    if standard::selected(&flags).is_none() {
+        let std = coppo_config::standard::default_std(language);
+        flags.push(coppo_config::standard::flag(std, language));
+    }
+    if let Some(lib) = &config.lib {
+        flags.push(format!("-I{}", lib.include().display()));
+    }
+    flags.extend(config.feature_defines());
+
+    flags
+}
+
+/// Compile the source into the binary, with the library sources of the project
+/// and the libraries of the workspace members it depends on.
+fn compile(
+    config: &Config,
+    source: &Path,
+    binary: &Path,
+    artifacts: &[workspace::Artifact],
+    extra: &[String],
+) -> Result<()> {
+    link(config, source, binary, None, artifacts, extra)
+}
+
+/// Compile the source into the binary, linked with the built library of the project,
+/// or with its sources if it is not built.
+fn link(
+    config: &Config,
+    source: &Path,
+    binary: &Path,
+    library: Option<&Path>,
+    artifacts: &[workspace::Artifact],
+    extra: &[String],
+) -> Result<()> {
+    let mut sources = vec![source.to_owned()];
+    let mut archives = vec![];
+    match (library, &config.lib) {
+        (Some(archive), _) => archives.push(archive.to_owned()),
+        // The library sources are compiled into the binary.
+        (None, Some(lib)) => sources.extend(lib_sources(lib)?),
+        (None, None) => {}
+    }
+
+    let compiler = compiler(config);
+    let mut args = flags(config);
+    args.extend(workspace::link_flags(artifacts));
+    args.extend(extra.iter().cloned());
+    args.extend(ldflags(config));
+    if let Some(library) = library {
+        args.extend(run_path_flags(&compiler, binary, library));
+    }
+    let mut settings = vec![compiler.clone()];
+    settings.extend(args.iter().cloned());
+
+    // The headers are only tracked if a single source is compiled,
+    // otherwise the binary is always built again.
+    let depfile = (sources.len() == 1).then(|| binary.with_extension(depfile_extension(&compiler)));
+    let inputs = |depfile: &Path| {
+        let mut inputs = match depfile::read(depfile, Path::new("")) {
+            dependencies if dependencies.is_empty() => vec![source.to_owned()],
+            dependencies => dependencies,
+        };
+        inputs.extend(archives.iter().cloned());
+        inputs.extend(artifacts.iter().map(|artifact| artifact.archive.clone()));
+        inputs
+    };
+    if let Some(depfile) = &depfile {
+        if fingerprint::is_fresh(binary, &inputs(depfile), &settings) {
+            return Ok(());
+        }
+    }
+
+    let mut linked = sources;
+    linked.extend(archives.iter().cloned());
+    let mut command = link_command(
+        wrapper(config).as_deref(),
+        &compiler,
+        &linked,
+        &args,
+        binary,
+        depfile.as_deref(),
+    );
+    log_command(&command);
+    let output = command.output()?;
+
+    if !output.status.success() {
         error!("The project failed to build.");
-        Err(String::from_utf8_lossy(&output.stderr).into())
+        return Err(String::from_utf8_lossy(&output.stderr).into());
+    }
+    if let Some(depfile) = &depfile {
+        fingerprint::save(binary, &inputs(depfile), &settings)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_select() {
+        let config = Config {
+            bin: ["server", "client"]
+                .iter()
+                .map(|name| BinTarget {
+                    name: name.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            select(&config, Some("client"), None).ok(),
+            Some(Runnable::Bin("client".to_string()))
+        );
+
+        let error = select(&config, None, None).unwrap_err().to_string();
+        assert!(error.contains("available binaries: server, client"));
+
+        let error = select(&config, Some("proxy"), None)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("`proxy` is not found"));
+
+        assert!(select(&config, None, Some("hello")).is_err());
+    }
+
+    #[test]
+    fn test_run_path_flags() {
+        let library = Path::new("target/debug/libcore.so");
+        let flags = run_path_flags("g++", Path::new("target/debug/app"), library);
+        let tests = run_path_flags("g++", Path::new("target/tests/app"), library);
+        if cfg!(target_os = "linux") {
+            assert_eq!(flags, ["-Wl,-rpath,$ORIGIN"]);
+            assert_eq!(tests, ["-Wl,-rpath,$ORIGIN/../debug"]);
+        }
+        assert!(run_path_flags("g++", Path::new("app"), Path::new("libcore.a")).is_empty());
+        assert!(run_path_flags("cl.exe", Path::new("app"), library).is_empty());
+    }
+
+    #[test]
+    fn test_wrapper() {
+        let mut config = Config::default();
+        config.global.build.wrapper = Some("sccache".to_string());
+        assert_eq!(wrapper(&config).as_deref(), Some("sccache"));
+        config.build.wrapper = Some(String::new());
+        assert_eq!(wrapper(&config), None);
+
+        let command = compiler_command(Some("ccache"), "g++");
+        assert_eq!(command.get_program(), "ccache");
+        assert_eq!(command.get_args().collect::<Vec<_>>(), ["g++"]);
+    }
+
+    #[test]
+    fn test_find_compiler() {
+        let mut config = Config::default();
+        config.build.compiler = Some("coppo-no-such-compiler".to_string());
+        if toolchain::active_compiler().is_some() {
+            return;
+        }
+
+        let error = find_compiler(&config).unwrap_err().to_string();
+        assert!(error.starts_with(
+            "The compiler `coppo-no-such-compiler` of the project configuration is not found."
+        ));
+        assert!(error.contains("help: or use another one with `compiler` in `[build]`"));
+    }
+
+    #[test]
+    fn test_target() {
+        let mut config = Config {
+            build_target: Some("x86_64-w64-mingw32".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(target_flags(&config), ["--target=x86_64-w64-mingw32"]);
+        assert_eq!(
+            profile_dir(&config, DEFAULT_PROFILE),
+            target_dir()
+                .join("x86_64-w64-mingw32")
+                .join(DEFAULT_PROFILE)
+        );
+        assert!(bin_path(&config, "app", DEFAULT_PROFILE).ends_with("app.exe"));
+
+        config.global.target.insert(
+            "x86_64-w64-mingw32".to_string(),
+            TargetSettings {
+                compiler: Some("x86_64-w64-mingw32-g++".to_string()),
+                sysroot: Some(PathBuf::from("/usr/x86_64-w64-mingw32")),
+                linker: Some("lld".to_string()),
+                flags: vec!["-static".to_string()],
+            },
+        );
+        assert_eq!(
+            target_flags(&config),
+            ["--sysroot=/usr/x86_64-w64-mingw32", "-static"]
+        );
+        assert_eq!(ldflags(&config), ["-fuse-ld=lld"]);
+        assert_eq!(archiver("g++"), ARCHIVER);
     }
 }
@@ -0,0 +1,112 @@
+//! The `coppo locate-project` and `coppo pkgid` add-ons,
+//! the small commands used by the editors and the scripts.
+//!
+//! # Usage
+//! ```sh
+//! coppo locate-project [--json]
+//! coppo pkgid
+//! ```
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+use coppo_addons::prelude::*;
+use coppo_config::prelude::*;
+use serde::Serialize;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The `Coppo locate-project` add-on.
+/// Print the path of the nearest `Coppo.toml` in the current directory or its ancestors.
+pub struct CoppoLocateProjectAddon;
+
+impl_addon! {
+    CoppoLocateProjectAddon,
+    name => "locate-project",
+    description => "Print the path of the manifest of the current project",
+    args => [
+        arg!(--json "Print the path as JSON, like `{\"root\": \"/path/to/Coppo.toml\"}`")
+            .action(ArgAction::SetTrue),
+    ],
+    run => |_config, matches| {
+        let root = locate()?;
+        if matches.get_flag("json") {
+            println!("{}", serde_json::to_string(&Location { root })?);
+        } else {
+            println!("{}", root.display());
+        }
+    }
+}
+
+/// The `Coppo pkgid` add-on.
+/// Print the package identifier of the current project, like `path+file:///path/to/app#app@0.1.0`.
+pub struct CoppoPkgidAddon;
+
+impl_addon! {
+    CoppoPkgidAddon,
+    name => "pkgid",
+    description => "Print the package identifier of the current project",
+    run => |_config, _matches| {
+        let manifest = locate()?;
+        let config = Config::from_path(&manifest)?;
+        if config.is_empty() {
+            return Err(format!("`{}` does not have a project name and version.", manifest.display()).into());
+        }
+
+        let dir = manifest.parent().unwrap_or(Path::new("/"));
+        println!("{}", pkgid(dir, &config.project));
+    }
+}
+
+/// The output of `coppo locate-project --json`.
+#[derive(Serialize)]
+struct Location {
+    root: PathBuf,
+}
+
+/// Find the manifest of the current project.
+fn locate() -> Result<PathBuf> {
+    let dir = env::current_dir()?;
+    Config::locate(&dir).ok_or_else(|| {
+        format!(
+            "Could not find `{}` in `{}` or any parent directory.",
+            CONFIG_FILE,
+            dir.display()
+        )
+        .into()
+    })
+}
+
+/// The package identifier of the project in the directory, like `path+file:///app#app@0.1.0`.
+pub fn pkgid(dir: &Path, project: &Project) -> String {
+    let mut path = dir.display().to_string().replace('\\', "/");
+    // The Windows paths like `C:/app` need a leading slash in the URL.
+    if !path.starts_with('/') {
+        path.insert(0, '/');
+    }
+
+    format!("path+file://{}#{}@{}", path, project.name, project.version)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pkgid() {
+        let project = Project {
+            name: "app".to_string(),
+            version: Version::new(0, 1, 0),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            pkgid(Path::new("/home/me/app"), &project),
+            "path+file:///home/me/app#app@0.1.0"
+        );
+        assert_eq!(
+            pkgid(Path::new(r"C:\projects\app"), &project),
+            "path+file:///C:/projects/app#app@0.1.0"
+        );
+    }
+}
@@ -0,0 +1,497 @@
+//! The resolution of the dependencies into the lockfile `Coppo.lock`, and the `coppo update` add-on.
+//!
+//! A dependency with `registry` resolves to the newest matching version of the registry,
+//! see [`coppo_registry`], and keeps its locked version while it still matches.
+//! A dependency named after a member of the workspace or a path dependency resolves to it,
+//! otherwise it is a system package found with `pkg-config`,
+//! and its checksum is the one of its `.pc` file.
+//! The dev-dependencies of the project and the dependencies of the members are resolved too.
+//!
+//! `coppo build` writes the lockfile if there is none,
+//! and refuses to build if the resolved dependencies differ from the locked ones,
+//! like after editing `[dependencies]` or upgrading a system package.
+//! `coppo update` resolves the dependencies again and rewrites the lockfile,
+//! or only the given packages, the others keep their locked versions,
+//! and `--precise` selects the exact version of a package of a registry.
+//! With `coppo --offline`, the packages of the registries must be locked,
+//! and vendored or downloaded already, the ones which are not are listed in the error.
+//!
+//! # Usage
+//! ```sh
+//! coppo update [PACKAGE]... [--precise <VERSION>]
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use coppo_addons::prelude::*;
+use coppo_config::lock::{LockedPackage, Lockfile, LOCK_FILE, LOCK_VERSION};
+use coppo_config::{Dependency, Platform};
+use coppo_logger::prelude::*;
+use coppo_registry::Index;
+use semver::{Version, VersionReq};
+use sha2::{Digest, Sha256};
+
+use crate::script::{pkg_config, system_version};
+use crate::workspace;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The source of the system packages.
+pub const SYSTEM_SOURCE: &str = "system";
+
+/// The `Coppo update` add-on.
+/// Resolve the dependencies again and rewrite `Coppo.lock`.
+pub struct CoppoUpdateAddon;
+
+impl_addon! {
+    CoppoUpdateAddon,
+    name => "update",
+    description => "Update the dependencies locked in `Coppo.lock`",
+    args => [
+        arg!([PACKAGE]... "The packages to update, all of them by default")
+            .value_parser(value_parser!(String)),
+        arg!(--precise <VERSION> "The exact version of the package of a registry")
+            .value_parser(value_parser!(Version))
+            .requires("PACKAGE"),
+    ],
+    run => |config, matches| {
+        if !Config::exists() {
+            return Err("The project does not have a `Coppo.toml` file.".into());
+        }
+
+        let names = matches
+            .get_many::<String>("PACKAGE")
+            .unwrap_or_default()
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut pins = Pins::new();
+        if let Some(version) = matches.get_one::<Version>("precise") {
+            if names.len() != 1 {
+                return Err("`--precise` can only be used with one package.".into());
+            }
+            pins.insert(names[0].clone(), version.clone());
+        }
+
+        let locked = Lockfile::from_file(Path::new("."))?.unwrap_or_default();
+        let kept = kept(&locked, &names)?;
+        let resolved = resolve_pinned(config, kept.as_ref(), &pins)?;
+        resolved.to_file(Path::new("."))?;
+
+        let changes = changes(&locked, &resolved);
+        for change in &changes {
+            info!("{}", change);
+        }
+        match changes.is_empty() {
+            true => success!("The dependencies are up to date."),
+            false => success!("Updated `{}`.", LOCK_FILE),
+        }
+    }
+}
+
+/// The versions of the packages of the registries selected by `coppo update --precise`.
+pub type Pins = BTreeMap<String, Version>;
+
+/// The locked packages kept by `coppo update` with the packages to update,
+/// it is `None` if all of them are updated.
+fn kept(locked: &Lockfile, names: &[String]) -> Result<Option<Lockfile>> {
+    if names.is_empty() {
+        return Ok(None);
+    }
+    if let Some(name) = names.iter().find(|name| locked.get(name).is_none()) {
+        return Err(format!("The package `{}` is not found in `{}`.", name, LOCK_FILE).into());
+    }
+
+    Ok(Some(Lockfile {
+        packages: locked
+            .packages
+            .iter()
+            .filter(|package| !names.contains(&package.name))
+            .cloned()
+            .collect(),
+        ..locked.clone()
+    }))
+}
+
+/// Resolve the dependencies and dev-dependencies of the project,
+/// and the dependencies of the members it depends on.
+/// The optional dependencies are resolved too, whether a feature enables them or not,
+/// but the target-specific dependencies only if they apply to the host.
+/// The packages of the registries keep their version in the `locked` lockfile if it still matches.
+pub fn resolve(config: &Config, locked: Option<&Lockfile>) -> Result<Lockfile> {
+    resolve_pinned(config, locked, &Pins::new())
+}
+
+/// Like [`resolve`], with the exact versions of some packages of the registries.
+pub fn resolve_pinned(config: &Config, locked: Option<&Lockfile>, pins: &Pins) -> Result<Lockfile> {
+    let (root, members) = workspace::all_members(config)?.unwrap_or_default();
+    let platform = Platform::host();
+    let index_of = |dependency: &Dependency| {
+        dependency
+            .registry()
+            .map(|registry| Index::of(&config.global, registry))
+            .transpose()
+    };
+
+    let mut packages = BTreeMap::new();
+    let mut offline = vec![];
+    let mut pending = vec![];
+    for (name, dependency) in config
+        .dependencies_for(&platform)
+        .into_iter()
+        .chain(config.dev_dependencies_for(&platform))
+    {
+        pending.push((name.to_owned(), dependency.version(), index_of(dependency)?));
+    }
+    while let Some((name, requirement, index)) = pending.pop() {
+        if packages.contains_key(&name) {
+            continue;
+        }
+        if name == config.project.name {
+            return Err(format!(
+                "The project `{}` depends on itself through its dependencies.",
+                name
+            )
+            .into());
+        }
+
+        if let Some(index) = index {
+            if coppo_registry::is_offline()
+                && (pins.contains_key(&name)
+                    || kept_package(&name, &requirement, &index, locked).is_none())
+            {
+                offline.push(format!(
+                    "{} {}, it is not locked in `{}`",
+                    name, requirement, LOCK_FILE
+                ));
+                continue;
+            }
+            let (package, dependencies) =
+                registry(&name, &requirement, &index, locked, pins.get(&name))?;
+            pending.extend(
+                dependencies
+                    .into_iter()
+                    .map(|(name, requirement)| (name, requirement, Some(index.clone()))),
+            );
+            packages.insert(name, package);
+            continue;
+        }
+
+        let package = match members.iter().find(|member| member.name == name) {
+            Some(member) => {
+                let version = &member.config.project.version;
+                check(&name, &requirement, version)?;
+
+                let mut dependencies = vec![];
+                for (name, dependency) in member.config.dependencies_for(&platform) {
+                    dependencies.push(name.to_owned());
+                    pending.push((name.to_owned(), dependency.version(), index_of(dependency)?));
+                }
+
+                LockedPackage {
+                    name: name.clone(),
+                    version: version.to_string(),
+                    source: format!("path+{}", slash(&root.join(&member.dir), &root)),
+                    checksum: None,
+                    dependencies,
+                }
+            }
+            None => system(&name, &requirement)?,
+        };
+        packages.insert(name, package);
+    }
+    if coppo_registry::is_offline() {
+        let vendor = Path::new(coppo_registry::VENDOR_DIR);
+        offline.extend(
+            packages
+                .values()
+                .filter(|package| {
+                    package.source.starts_with(coppo_registry::SOURCE_PREFIX)
+                        && !coppo_registry::is_available(package, vendor)
+                })
+                .map(|package| {
+                    format!(
+                        "{} {}, it is neither downloaded nor vendored",
+                        package.name, package.version
+                    )
+                }),
+        );
+    }
+    if !offline.is_empty() {
+        offline.sort();
+        return Err(format!(
+            "The dependencies need the network, but `--offline` is given:\n    {}\n\
+            help: build once without `--offline`, or run `coppo vendor`",
+            offline.join("\n    ")
+        )
+        .into());
+    }
+    let unpinned = pins.keys().find(|name| {
+        !packages
+            .get(*name)
+            .is_some_and(|package| package.source.starts_with(coppo_registry::SOURCE_PREFIX))
+    });
+    if let Some(name) = unpinned {
+        return Err(format!(
+            "`--precise` only applies to the packages of the registries, `{}` is not one.",
+            name
+        )
+        .into());
+    }
+
+    Ok(Lockfile {
+        version: LOCK_VERSION,
+        packages: packages.into_values().collect(),
+    })
+}
+
+/// Check the dependencies against the lockfile, or write the lockfile if there is none.
+pub fn ensure(config: &Config) -> Result<()> {
+    let locked = Lockfile::from_file(Path::new("."))?;
+    if locked.is_none()
+        && config.dependencies.is_empty()
+        && config.dev_dependencies.is_empty()
+        && config.target.is_empty()
+    {
+        return Ok(());
+    }
+
+    let resolved = resolve(config, locked.as_ref())?;
+    let Some(locked) = locked else {
+        resolved.to_file(Path::new("."))?;
+        info!("Created `{}`", LOCK_FILE);
+        return Ok(());
+    };
+
+    let changes = changes(&locked, &resolved);
+    if changes.is_empty() {
+        return Ok(());
+    }
+    for change in &changes {
+        warn!("{}", change);
+    }
+    Err(format!(
+        "The dependencies do not match `{}`, run `coppo update` to update it.",
+        LOCK_FILE
+    )
+    .into())
+}
+
+/// The compile and link flags of the system packages the project depends on, from `pkg-config`.
+/// The dev-dependencies are included if `dev` is `true`,
+/// and the optional dependencies if a feature enables them.
+/// The target-specific dependencies are included if they apply to the platform built for,
+/// like `ws2_32` on Windows.
+pub fn system_flags(config: &Config, dev: bool) -> Result<Vec<String>> {
+    let (_, members) = workspace::dependencies(config, false, dev)?.unwrap_or_default();
+    let platform = config.platform();
+
+    let mut names = config
+        .dependencies_for(&platform)
+        .into_keys()
+        .filter(|name| config.is_dependency_enabled(name))
+        .collect::<Vec<_>>();
+    if dev {
+        names.extend(config.dev_dependencies_for(&platform).into_keys());
+    }
+    names.retain(|name| !members.iter().any(|member| member.name == *name));
+    if names.is_empty() {
+        return Ok(vec![]);
+    }
+    names.sort();
+
+    let mut args = vec!["--cflags", "--libs"];
+    args.extend(names);
+    Ok(pkg_config(&args)?
+        .split_whitespace()
+        .map(str::to_owned)
+        .collect())
+}
+
+/// The differences between the lockfiles, like `Updating fmt 10.1.0 -> 10.2.1`.
+pub fn changes(old: &Lockfile, new: &Lockfile) -> Vec<String> {
+    let mut changes = vec![];
+    for package in &new.packages {
+        match old.get(&package.name) {
+            None => changes.push(format!("Adding {} {}", package.name, package.version)),
+            Some(locked) if locked.version != package.version => changes.push(format!(
+                "Updating {} {} -> {}",
+                package.name, locked.version, package.version
+            )),
+            Some(locked) if locked != package => {
+                changes.push(format!("Changing {} {}", package.name, package.version))
+            }
+            Some(_) => {}
+        }
+    }
+    for package in &old.packages {
+        if new.get(&package.name).is_none() {
+            changes.push(format!("Removing {} {}", package.name, package.version));
+        }
+    }
+
+    changes
+}
+
+/// Check the resolved version against the requirement.
+fn check(name: &str, requirement: &VersionReq, version: &Version) -> Result<()> {
+    if !requirement.matches(version) {
+        return Err(format!(
+            "The dependency `{}` requires `{}`, but `{}` is found.",
+            name, requirement, version
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// The locked package of the registry, if it still matches the requirement.
+fn kept_package<'a>(
+    name: &str,
+    requirement: &VersionReq,
+    index: &Index,
+    locked: Option<&'a Lockfile>,
+) -> Option<&'a LockedPackage> {
+    locked
+        .and_then(|locked| locked.get(name))
+        .filter(|package| {
+            package.source == index.source()
+                && Version::parse(&package.version)
+                    .is_ok_and(|version| requirement.matches(&version))
+        })
+}
+
+/// Resolve the package of the registry, with the requirements of its dependencies.
+/// The pinned version is used if there is one, otherwise the locked version is kept
+/// if it still matches, with the locked versions of its dependencies.
+fn registry(
+    name: &str,
+    requirement: &VersionReq,
+    index: &Index,
+    locked: Option<&Lockfile>,
+    pinned: Option<&Version>,
+) -> Result<(LockedPackage, Vec<(String, VersionReq)>)> {
+    if let Some(version) = pinned {
+        check(name, requirement, version)?;
+    }
+    if let Some(package) = kept_package(name, requirement, index, locked) {
+        let dependencies = package
+            .dependencies
+            .iter()
+            .map(|name| (name.clone(), VersionReq::STAR))
+            .collect();
+        return Ok((package.clone(), dependencies));
+    }
+
+    let release = match pinned {
+        Some(version) => index.release(name, version)?,
+        None => index.select(name, requirement)?,
+    };
+    let package = LockedPackage {
+        name: name.to_owned(),
+        version: release.version.to_string(),
+        source: index.source(),
+        checksum: Some(release.checksum),
+        dependencies: release.dependencies.keys().cloned().collect(),
+    };
+    Ok((package, release.dependencies.into_iter().collect()))
+}
+
+/// Resolve the system package with `pkg-config`.
+fn system(name: &str, requirement: &VersionReq) -> Result<LockedPackage> {
+    let installed = pkg_config(&["--modversion", name]).map_err(|_| {
+        format!(
+            "The dependency `{}` is neither a workspace member nor found by `pkg-config`.",
+            name
+        )
+    })?;
+    match system_version(&installed) {
+        Some(version) => check(name, requirement, &version)?,
+        None => warn!(
+            "The version `{}` of `{}` can not be checked.",
+            installed, name
+        ),
+    }
+
+    let pc_file = pkg_config(&["--path", name])?;
+    let checksum = Sha256::digest(fs::read(&pc_file)?);
+
+    Ok(LockedPackage {
+        name: name.to_owned(),
+        version: installed,
+        source: SYSTEM_SOURCE.to_owned(),
+        checksum: Some(format!("sha256:{:x}", checksum)),
+        dependencies: vec![],
+    })
+}
+
+/// The path relative to the base, with `/` as the separator on all platforms.
+fn slash(path: &Path, base: &Path) -> String {
+    let path = path.strip_prefix(base).unwrap_or(path);
+    let components = path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>();
+
+    components.join("/")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn package(name: &str, version: &str) -> LockedPackage {
+        LockedPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            source: SYSTEM_SOURCE.to_string(),
+            checksum: None,
+            dependencies: vec![],
+        }
+    }
+
+    #[test]
+    fn test_changes() {
+        let old = Lockfile {
+            version: LOCK_VERSION,
+            packages: vec![package("fmt", "10.1.0"), package("zlib", "1.3.0")],
+        };
+        let mut new = Lockfile {
+            version: LOCK_VERSION,
+            packages: vec![package("fmt", "10.2.1"), package("zlib", "1.3.0")],
+        };
+        new.packages[1].checksum = Some("sha256:00".to_string());
+        new.packages.push(package("zstd", "1.5.5"));
+
+        assert_eq!(
+            changes(&old, &new),
+            [
+                "Updating fmt 10.1.0 -> 10.2.1",
+                "Changing zlib 1.3.0",
+                "Adding zstd 1.5.5"
+            ]
+        );
+        assert_eq!(changes(&new, &old).last().unwrap(), "Removing zstd 1.5.5");
+        assert!(changes(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_kept() {
+        let locked = Lockfile {
+            version: LOCK_VERSION,
+            packages: vec![package("fmt", "10.1.0"), package("zlib", "1.3.0")],
+        };
+        assert_eq!(kept(&locked, &[]).unwrap(), None);
+
+        let kept_packages = kept(&locked, &["fmt".to_string()]).unwrap().unwrap();
+        assert_eq!(kept_packages.packages, [package("zlib", "1.3.0")]);
+        assert_eq!(
+            kept(&locked, &["json".to_string()])
+                .unwrap_err()
+                .to_string(),
+            "The package `json` is not found in `Coppo.lock`."
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! The link-time optimization of a profile, `lto = true` or `"thin"` in `[profile.<name>]`.
+//!
+//! The flag, `-flto` or `-flto=thin`, is one of the profile flags, see [`crate::profile_flags`],
+//! so it is passed to both the compile and the link steps,
+//! and MSVC translates it into `/GL` and `/LTCG`, see [`crate::msvc::translate`].
+//! The objects then contain the intermediate code of the compiler instead of machine code,
+//! which only a linker with the plugin of the compiler can read,
+//! so the support is checked by building a trivial program before the project, see [`crate::probe`].
+
+use coppo_config::profile::{Lto, Profile};
+use coppo_config::Language;
+
+use crate::probe::probe;
+use crate::{msvc, Result};
+
+/// Check if the compiler of the language and its linker support the link-time optimization
+/// of the profile, with the link flags like `-fuse-ld=lld`.
+/// It always passes without `lto`.
+pub fn check(
+    compiler: &str,
+    language: Language,
+    profile: &Profile,
+    ldflags: &[String],
+) -> Result<()> {
+    let Some(flag) = profile.lto.as_ref().and_then(Lto::flag) else {
+        return Ok(());
+    };
+    // MSVC always supports `/GL` and `/LTCG`.
+    if msvc::is_msvc(compiler) {
+        return Ok(());
+    }
+    let mut flags = vec![flag.clone()];
+    flags.extend(ldflags.iter().cloned());
+    if let Err(output) = probe(compiler, language, &flags) {
+        return Err(format!(
+            "The link-time optimization `{}` is not supported by `{}` or its linker:\n{}\n\
+            help: {}\n\
+            help: or disable it with `lto = false` in the profile",
+            flag,
+            compiler,
+            output,
+            hint(compiler, &flag)
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// How to get a linker supporting the link-time optimization.
+fn hint(compiler: &str, flag: &str) -> String {
+    let clang = compiler.contains("clang");
+    if flag == "-flto=thin" && !clang {
+        format!(
+            "`lto = \"thin\"` needs Clang, use `lto = true` with `{}`, \
+            or `compiler = \"clang++\"` in `[build]`",
+            compiler
+        )
+    } else if clang {
+        "use the LLVM linker with `ldflags = [\"-fuse-ld=lld\"]` in `[build]`, \
+        or install the LLVM gold plugin"
+            .to_owned()
+    } else {
+        "install the linker plugin of GCC, it comes with binutils 2.21 or newer".to_owned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        // Nothing is checked without `lto`.
+        assert!(check("no-such-compiler", Language::Cpp, &Profile::release(), &[]).is_ok());
+
+        let profile = Profile {
+            lto: Some(Lto::Mode("thin".to_string())),
+            ..Profile::release()
+        };
+        let error = check("no-such-compiler", Language::Cpp, &profile, &[])
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Failed to run `no-such-compiler`"));
+
+        assert!(hint("g++", "-flto=thin").contains("needs Clang"));
+        assert!(hint("clang++", "-flto=thin").contains("-fuse-ld=lld"));
+    }
+}
@@ -0,0 +1,280 @@
+//! The `coppo metadata` add-on, it prints a JSON description of the current project,
+//! so the editors and the other tools do not need to parse the manifest themselves.
+//!
+//! The document has the following fields:
+//! - `version`: The version of the format, [`METADATA_VERSION`].
+//! - `project`: The name, the version, the root directory and the manifest of the project.
+//! - `targets`: The library, the binaries and the examples, with their sources
+//!   and their outputs with the selected profile.
+//! - `dependencies`: The resolved packages, like in `Coppo.lock`, see [`crate::lock`].
+//! - `profiles`: The flags of every profile.
+//! - `target-directory`: The output directory.
+//! - `compiler`: The compiler, its wrapper, the standard and the compile flags.
+//!
+//! The fields are only added in the same version, a removed or changed field changes the version.
+//! The paths are absolute, and the dependencies are read from `Coppo.lock`,
+//! or resolved without writing it if there is none, unless `--no-deps` is given.
+//!
+//! # Usage
+//! ```sh
+//! coppo metadata [--format-version <N>] [--no-deps] [--release] [--profile <NAME>] [--target <TRIPLE>]
+//! ```
+
+use std::collections::BTreeMap;
+use std::env;
+use std::path::{Path, PathBuf};
+
+use coppo_addons::prelude::*;
+use coppo_config::lock::{LockedPackage, Lockfile};
+use coppo_config::prelude::*;
+use coppo_config::profile::{DEBUG_PROFILE, RELEASE_PROFILE};
+use coppo_config::LibKind;
+use serde::Serialize;
+
+use crate::{
+    all_features_arg, bin_path, bins, compiler_source, example_path, example_source, examples,
+    features_arg, flags, lib_sources, lock, no_default_features_arg, profile_arg, profile_flags,
+    profile_lib_path, release_arg, select_features, select_target, selected_profile,
+    shared_lib_path, standard, target_arg, target_dir, workspace, wrapper, Result,
+};
+
+/// The version of the format of `coppo metadata`.
+pub const METADATA_VERSION: u32 = 1;
+
+/// The `Coppo metadata` add-on.
+/// Print the description of the current project as JSON.
+pub struct CoppoMetadataAddon;
+
+impl_addon! {
+    CoppoMetadataAddon,
+    name => "metadata",
+    description => "Print the description of the current project as JSON",
+    args => [
+        arg!(--"format-version" <N> "The version of the format, only `1` is supported")
+            .value_parser(value_parser!(u32).range(1..=1))
+            .default_value("1"),
+        arg!(--"no-deps" "Do not resolve the dependencies").action(ArgAction::SetTrue),
+        release_arg(),
+        profile_arg(),
+        features_arg(),
+        all_features_arg(),
+        no_default_features_arg(),
+        target_arg(),
+    ],
+    run => |config, matches| {
+        if config.is_empty() {
+            return Err("The project name and version is needed".into());
+        }
+        select_features(config, matches)?;
+        select_target(config, matches)?;
+
+        let metadata = metadata(config, selected_profile(matches), !matches.get_flag("no-deps"))?;
+        println!("{}", serde_json::to_string_pretty(&metadata)?);
+    }
+}
+
+/// The description of the project printed by `coppo metadata`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Metadata {
+    /// The version of the format.
+    pub version: u32,
+    /// The project.
+    pub project: ProjectMetadata,
+    /// The targets of the project.
+    pub targets: Vec<TargetMetadata>,
+    /// The resolved dependencies, they are empty with `--no-deps`.
+    pub dependencies: Vec<LockedPackage>,
+    /// The compile flags of the profiles, by their names.
+    pub profiles: BTreeMap<String, Vec<String>>,
+    /// The output directory.
+    pub target_directory: PathBuf,
+    /// The compiler settings.
+    pub compiler: CompilerMetadata,
+}
+
+/// The project in the metadata.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ProjectMetadata {
+    /// The name.
+    pub name: String,
+    /// The version.
+    pub version: String,
+    /// The root directory.
+    pub root: PathBuf,
+    /// The path of `Coppo.toml`.
+    pub manifest_path: PathBuf,
+    /// The enabled features.
+    pub features: Vec<String>,
+}
+
+/// A target in the metadata.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TargetMetadata {
+    /// The kind, `lib`, `bin` or `example`.
+    pub kind: &'static str,
+    /// The name.
+    pub name: String,
+    /// The sources.
+    pub sources: Vec<PathBuf>,
+    /// The files built with the selected profile.
+    pub outputs: Vec<PathBuf>,
+}
+
+/// The compiler settings in the metadata.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CompilerMetadata {
+    /// The compiler.
+    pub path: String,
+    /// Where the compiler comes from, see [`compiler_source`].
+    pub source: &'static str,
+    /// The compiler wrapper, like `ccache`.
+    pub wrapper: Option<String>,
+    /// The target of `--target`.
+    pub target: Option<String>,
+    /// The selected profile.
+    pub profile: String,
+    /// The C++ standard, like `c++17`.
+    pub standard: Option<String>,
+    /// The compile flags of the sources with the selected profile.
+    pub flags: Vec<String>,
+}
+
+/// Describe the project in the current directory, with the outputs of the profile.
+/// The dependencies are resolved if `deps` is `true`.
+pub fn metadata(config: &Config, profile: &str, deps: bool) -> Result<Metadata> {
+    let root = env::current_dir()?;
+    let absolute = |path: &Path| root.join(path);
+
+    let mut targets = vec![];
+    if let Some(lib) = &config.lib {
+        let mut outputs = vec![];
+        if lib.builds(LibKind::Static) {
+            outputs.push(absolute(&profile_lib_path(config, &lib.name, profile)));
+        }
+        if lib.builds(LibKind::Shared) {
+            outputs.push(absolute(&shared_lib_path(config, &lib.name, profile)));
+        }
+        targets.push(TargetMetadata {
+            kind: "lib",
+            name: lib.name.clone(),
+            sources: lib_sources(lib)
+                .unwrap_or_default()
+                .iter()
+                .map(|source| absolute(source))
+                .collect(),
+            outputs,
+        });
+    }
+    for bin in bins(config) {
+        targets.push(TargetMetadata {
+            kind: "bin",
+            sources: vec![absolute(&bin.path())],
+            outputs: vec![absolute(Path::new(&bin_path(config, &bin.name, profile)))],
+            name: bin.name,
+        });
+    }
+    for name in examples()? {
+        targets.push(TargetMetadata {
+            kind: "example",
+            sources: example_source(&name)
+                .iter()
+                .map(|source| absolute(source))
+                .collect(),
+            outputs: vec![absolute(&example_path(config, &name, profile))],
+            name,
+        });
+    }
+
+    let mut profiles = BTreeMap::new();
+    for name in [DEBUG_PROFILE, RELEASE_PROFILE]
+        .into_iter()
+        .chain(config.profile.keys().map(String::as_str))
+    {
+        profiles.insert(name.to_owned(), profile_flags(&config.profile(name)?));
+    }
+
+    let mut dependencies = vec![];
+    let mut compile_flags = flags(config);
+    if deps {
+        let locked = match Lockfile::from_file(Path::new("."))? {
+            Some(locked) => locked,
+            None => lock::resolve(config, None)?,
+        };
+        dependencies = locked.packages;
+        compile_flags.extend(workspace::include_flags(config)?);
+        compile_flags.extend(lock::system_flags(config, false)?);
+    }
+    compile_flags.extend(profile_flags(&config.profile(profile)?));
+
+    let (compiler, source) = compiler_source(config);
+    Ok(Metadata {
+        version: METADATA_VERSION,
+        project: ProjectMetadata {
+            name: config.project.name.clone(),
+            version: config.project.version.to_string(),
+            manifest_path: root.join(CONFIG_FILE),
+            features: config.enabled_features()?.into_iter().collect(),
+            root: root.clone(),
+        },
+        targets,
+        dependencies,
+        profiles,
+        target_directory: absolute(&target_dir()),
+        compiler: CompilerMetadata {
+            path: compiler,
+            source,
+            wrapper: wrapper(config),
+            target: config.build_target.clone(),
+            profile: profile.to_owned(),
+            standard: standard::selected(&compile_flags).map(str::to_owned),
+            flags: compile_flags,
+        },
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_metadata() {
+        let config = Config::from_str(
+            r#"
+            [project]
+            name = "app"
+            version = "0.1.0"
+            authors = []
+
+            [[bin]]
+            name = "tool"
+            path = "src/tool.cpp"
+
+            [profile.profiling]
+            inherits = "release"
+            debug = true
+            "#,
+        )
+        .unwrap();
+
+        let metadata = metadata(&config, "release", false).unwrap();
+        assert_eq!(metadata.version, METADATA_VERSION);
+        assert_eq!(metadata.project.name, "app");
+        assert_eq!(metadata.targets[0].kind, "bin");
+        assert!(metadata.targets[0].outputs[0].ends_with("release/tool"));
+        assert!(metadata.dependencies.is_empty());
+        assert_eq!(
+            metadata.profiles.keys().collect::<Vec<_>>(),
+            ["debug", "profiling", "release"]
+        );
+        assert_eq!(metadata.compiler.profile, "release");
+        assert!(metadata.compiler.flags.contains(&"-O3".to_string()));
+
+        let json = serde_json::to_value(&metadata).unwrap();
+        assert!(json["target-directory"].is_string());
+        assert_eq!(json["targets"][0]["name"], "tool");
+    }
+}
@@ -0,0 +1,354 @@
+//! The MSVC backend, used if the compiler is `cl.exe` or `clang-cl`.
+//!
+//! The flags of the project are written in the GCC style, like `-O2` or `-std=c++20`,
+//! and translated into the MSVC ones, like `/O2` or `/std:c++20`, see [`translate`].
+//! The objects are `.obj` files, the libraries are archived by `lib.exe`,
+//! and the debug information of a binary is written into a `.pdb` file next to it.
+//!
+//! If `cl.exe` is not in the `PATH`, the latest Visual Studio with the C++ tools
+//! is located by `vswhere.exe`, and the environment of its `vcvars` script is used,
+//! so Coppo does not need to be run from a developer prompt.
+
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::OnceLock;
+
+/// The extension of the objects of MSVC.
+pub const OBJECT_EXTENSION: &str = "obj";
+
+/// The archiver of MSVC.
+pub const ARCHIVER: &str = "lib.exe";
+
+/// Check if the compiler is MSVC or takes the same flags, like `cl.exe` or `clang-cl`.
+pub fn is_msvc(compiler: &str) -> bool {
+    Path::new(compiler)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| {
+            stem.eq_ignore_ascii_case("cl") || stem.eq_ignore_ascii_case("clang-cl")
+        })
+}
+
+/// Translate the GCC style flags into the ones of MSVC,
+/// the flags for the compiler come first, then the ones for the linker.
+///
+/// The flags which are already in the MSVC style and the unknown ones are kept as they are,
+/// and the ones without an equivalent are dropped.
+///
+/// # Example
+/// ```rust
+/// use coppo_build::msvc::translate;
+///
+/// let flags = ["-O2", "-std=c++20", "-Iinclude", "-DNDEBUG", "-lws2_32"].map(String::from);
+/// let (compile, link) = translate(&flags);
+/// assert_eq!(compile, ["/O2", "/std:c++20", "/Iinclude", "/DNDEBUG"]);
+/// assert_eq!(link, ["ws2_32.lib"]);
+///
+/// let (compile, _) = translate(&["-std=c17".to_owned()]);
+/// assert_eq!(compile, ["/std:c17"]);
+/// ```
+pub fn translate(flags: &[String]) -> (Vec<String>, Vec<String>) {
+    let mut compile = vec![];
+    let mut link = vec![];
+    let mut flags = flags.iter();
+    while let Some(flag) = flags.next() {
+        match flag.as_str() {
+            "-O0" | "-Og" => compile.push("/Od".to_owned()),
+            "-O1" | "-Os" | "-Oz" => compile.push("/O1".to_owned()),
+            "-O2" | "-O3" | "-Ofast" => compile.push("/O2".to_owned()),
+            "-g" => {
+                compile.push("/Z7".to_owned());
+                link.push("/DEBUG".to_owned());
+            }
+            // MSVC does not have the thin link-time optimization.
+            "-flto" | "-flto=thin" => {
+                compile.push("/GL".to_owned());
+                link.push("/LTCG".to_owned());
+            }
+            "-Wall" => compile.push("/W4".to_owned()),
+            "-Werror" => compile.push("/WX".to_owned()),
+            "-w" => compile.push("/w".to_owned()),
+            // The options with a separate value.
+            "-I" | "-D" | "-U" => {
+                if let Some(value) = flags.next() {
+                    compile.push(format!("/{}{}", &flag[1..], value));
+                }
+            }
+            "-L" => {
+                if let Some(dir) = flags.next() {
+                    link.push(format!("/LIBPATH:{}", dir));
+                }
+            }
+            "-fPIC" | "-fpic" | "-pthread" | "-pedantic" | "-Wextra" | "-Wpedantic" => {}
+            _ => {
+                if let Some(std) = flag
+                    .strip_prefix("-std=c++")
+                    .or_else(|| flag.strip_prefix("-std=gnu++"))
+                {
+                    compile.push(format!("/std:{}", standard(std)));
+                } else if let Some(std) = flag
+                    .strip_prefix("-std=c")
+                    .or_else(|| flag.strip_prefix("-std=gnu"))
+                {
+                    // The C standards before C11 are the default of MSVC.
+                    if let Some(std) = c_standard(std) {
+                        compile.push(format!("/std:{}", std));
+                    }
+                } else if let Some(define) = flag.strip_prefix("-D") {
+                    compile.push(format!("/D{}", define));
+                } else if let Some(undefine) = flag.strip_prefix("-U") {
+                    compile.push(format!("/U{}", undefine));
+                } else if let Some(dir) = flag.strip_prefix("-I") {
+                    compile.push(format!("/I{}", dir));
+                } else if let Some(dir) = flag.strip_prefix("-L") {
+                    link.push(format!("/LIBPATH:{}", dir));
+                } else if let Some(name) = flag.strip_prefix("-l") {
+                    link.push(format!("{}.lib", name));
+                } else if flag.starts_with("-W") {
+                    // The warnings of GCC do not have the same names.
+                } else {
+                    compile.push(flag.clone());
+                }
+            }
+        }
+    }
+
+    (compile, link)
+}
+
+/// The MSVC standard of the C++ standard, like `c++20` for `20`.
+/// MSVC starts from C++14, and the newer ones than C++20 are `c++latest`.
+fn standard(std: &str) -> &'static str {
+    match std {
+        "98" | "03" | "11" | "14" | "1y" => "c++14",
+        "17" | "1z" => "c++17",
+        "20" | "2a" => "c++20",
+        _ => "c++latest",
+    }
+}
+
+/// The MSVC C standard of the version, like `c11` for `11` or `1x`.
+fn c_standard(std: &str) -> Option<&'static str> {
+    match std {
+        "89" | "90" | "99" | "9x" => None,
+        "11" | "1x" => Some("c11"),
+        "17" | "18" => Some("c17"),
+        _ => Some("clatest"),
+    }
+}
+
+/// The arguments to compile the source into the object,
+/// with the included headers written into the dependency file, see [`crate::depfile`].
+pub fn compile_args(source: &Path, object: &Path, flags: &[String], depfile: &Path) -> Vec<String> {
+    let (flags, _) = translate(flags);
+    let mut args = vec![
+        "/nologo".to_owned(),
+        "/EHsc".to_owned(),
+        "/c".to_owned(),
+        source.display().to_string(),
+    ];
+    args.extend(flags);
+    args.push("/sourceDependencies".to_owned());
+    args.push(depfile.display().to_string());
+    args.push(format!("/Fo{}", object.display()));
+
+    args
+}
+
+/// The arguments to compile and link the inputs into the binary,
+/// the debug information is written into `<binary>.pdb`.
+pub fn link_args(
+    inputs: &[PathBuf],
+    flags: &[String],
+    binary: &Path,
+    depfile: Option<&Path>,
+) -> Vec<String> {
+    let (flags, link) = translate(flags);
+    let mut args = vec!["/nologo".to_owned(), "/EHsc".to_owned()];
+    args.extend(inputs.iter().map(|input| input.display().to_string()));
+    args.extend(flags);
+    if let Some(depfile) = depfile {
+        args.push("/sourceDependencies".to_owned());
+        args.push(depfile.display().to_string());
+    }
+    args.push(format!("/Fe{}", binary.display()));
+    // The objects of the sources are written beside the binary instead of the current directory.
+    let dir = binary.parent().unwrap_or(Path::new("."));
+    args.push(format!("/Fo{}\\", dir.display()));
+    args.push("/link".to_owned());
+    if link.iter().any(|flag| flag == "/DEBUG") {
+        args.push(format!("/PDB:{}", binary.with_extension("pdb").display()));
+    }
+    args.extend(link);
+
+    args
+}
+
+/// The arguments to link the objects into the DLL, with its import library, see [`import_lib`].
+/// The symbols must be exported with `__declspec(dllexport)`.
+pub fn shared_args(objects: &[PathBuf], flags: &[String], dll: &Path) -> Vec<String> {
+    let (_, link) = translate(flags);
+    let mut args = vec!["/nologo".to_owned(), "/LD".to_owned()];
+    args.extend(objects.iter().map(|object| object.display().to_string()));
+    args.push(format!("/Fe{}", dll.display()));
+    args.push("/link".to_owned());
+    args.push(format!("/IMPLIB:{}", import_lib(dll).display()));
+    args.extend(link);
+
+    args
+}
+
+/// The import library of the DLL, like `core.dll.lib` for `core.dll`,
+/// so it does not replace the static library `core.lib`.
+pub fn import_lib(dll: &Path) -> PathBuf {
+    dll.with_extension("dll.lib")
+}
+
+/// The command to archive the objects into the static library,
+/// `lib.exe` is the one beside the compiler if the compiler is a path.
+pub fn archive_command(compiler: &str, archive: &Path, objects: &[PathBuf]) -> process::Command {
+    let archiver = match Path::new(compiler).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.join(ARCHIVER),
+        _ => PathBuf::from(ARCHIVER),
+    };
+    let mut command = process::Command::new(archiver);
+    configure(&mut command);
+    command
+        .arg("/nologo")
+        .arg(format!("/OUT:{}", archive.display()))
+        .args(objects);
+
+    command
+}
+
+/// Use the environment of Visual Studio for the command, if it is not set already.
+pub fn configure(command: &mut process::Command) {
+    if let Some(environment) = environment() {
+        command.envs(environment);
+    }
+}
+
+/// Locate `cl.exe` of the latest Visual Studio with the C++ tools.
+pub fn locate() -> Option<PathBuf> {
+    let path = environment()?.get("PATH")?;
+
+    env::split_paths(path)
+        .map(|dir| dir.join("cl.exe"))
+        .find(|path| path.is_file())
+}
+
+/// The environment of the `vcvars` script of Visual Studio, it is loaded once per run.
+/// It is `None` in a developer prompt, where `INCLUDE` is set, or if Visual Studio is not found.
+fn environment() -> Option<&'static HashMap<String, String>> {
+    static ENVIRONMENT: OnceLock<Option<HashMap<String, String>>> = OnceLock::new();
+
+    ENVIRONMENT
+        .get_or_init(|| {
+            if env::var_os("INCLUDE").is_some() {
+                return None;
+            }
+            let script = installation()?
+                .join("VC")
+                .join("Auxiliary")
+                .join("Build")
+                .join(vcvars());
+            let output = process::Command::new("cmd")
+                .arg("/C")
+                .arg(script)
+                .arg(">nul")
+                .arg("&&")
+                .arg("set")
+                .output()
+                .ok()?;
+            if !output.status.success() {
+                return None;
+            }
+
+            Some(
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .filter_map(|line| line.split_once('='))
+                    .map(|(key, value)| (key.to_uppercase(), value.to_owned()))
+                    .collect(),
+            )
+        })
+        .as_ref()
+}
+
+/// The `vcvars` script of the host architecture.
+fn vcvars() -> &'static str {
+    match env::consts::ARCH {
+        "x86" => "vcvars32.bat",
+        "aarch64" => "vcvarsarm64.bat",
+        _ => "vcvars64.bat",
+    }
+}
+
+/// The installation directory of the latest Visual Studio with the C++ tools, from `vswhere.exe`.
+fn installation() -> Option<PathBuf> {
+    let vswhere = PathBuf::from(env::var_os("ProgramFiles(x86)")?)
+        .join("Microsoft Visual Studio")
+        .join("Installer")
+        .join("vswhere.exe");
+    let output = process::Command::new(vswhere)
+        .args(["-latest", "-products", "*"])
+        .args([
+            "-requires",
+            "Microsoft.VisualStudio.Component.VC.Tools.x86.x64",
+        ])
+        .args(["-property", "installationPath"])
+        .output()
+        .ok()?;
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+
+    match output.status.success() && !path.is_empty() {
+        true => Some(PathBuf::from(path)),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_translate() {
+        let flags = [
+            "-g",
+            "-O0",
+            "-std=c++23",
+            "-I",
+            "include",
+            "-Wall",
+            "-Wshadow",
+            "-fPIC",
+            "-flto=thin",
+            "/MD",
+            "-L",
+            "lib",
+            "libcore.lib",
+        ]
+        .map(String::from);
+        let (compile, link) = translate(&flags);
+        assert_eq!(
+            compile,
+            [
+                "/Z7",
+                "/Od",
+                "/std:c++latest",
+                "/Iinclude",
+                "/W4",
+                "/GL",
+                "/MD",
+                "libcore.lib"
+            ]
+        );
+        assert_eq!(link, ["/DEBUG", "/LTCG", "/LIBPATH:lib"]);
+
+        assert!(is_msvc("cl.exe"));
+        assert!(is_msvc("C:/VS/bin/CL.EXE"));
+        assert!(!is_msvc("clang++"));
+    }
+}
@@ -0,0 +1,90 @@
+//! Run the tasks in parallel with a limited number of threads.
+//!
+//! Under the jobserver of a parent build, every thread but the first one
+//! takes a token from it for each task, see [`crate::jobserver`].
+//! If a token can not be taken, the thread stops, and its task runs after the others,
+//! so the tasks never run beyond the tokens.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use coppo_logger::prelude::*;
+
+use crate::jobserver;
+
+/// The default number of parallel jobs, the number of CPUs.
+pub fn default_jobs() -> usize {
+    thread::available_parallelism().map_or(1, Into::into)
+}
+
+/// Run the task for every item with at most `jobs` threads,
+/// the results are in the same order as the items.
+/// The tasks are also limited by the tokens of the jobserver of the parent build, if any.
+pub fn run_parallel<I, T, F>(items: &[I], jobs: usize, task: F) -> Vec<T>
+where
+    I: Sync,
+    T: Send,
+    F: Fn(&I) -> T + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(items.len()));
+    let jobserver = jobserver::inherited();
+    // The items whose threads failed to take a token.
+    let leftover = Mutex::new(vec![]);
+    let warned = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+        for worker in 0..jobs.clamp(1, items.len().max(1)) {
+            let (next, results, task) = (&next, &results, &task);
+            let (leftover, warned) = (&leftover, &warned);
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(index) else {
+                    break;
+                };
+                // The first thread runs with the implicit token of the process.
+                // The token is taken after the item, so no thread waits for a token it does not need.
+                let _token = match jobserver {
+                    Some(client) if worker > 0 => match client.acquire() {
+                        Ok(token) => Some(token),
+                        Err(e) => {
+                            if !warned.swap(true, Ordering::Relaxed) {
+                                warn!("Failed to take a token of the jobserver, running the rest serially: {}", e);
+                            }
+                            leftover.lock().unwrap().push(index);
+                            break;
+                        }
+                    },
+                    _ => None,
+                };
+                let result = task(item);
+                results.lock().unwrap().push((index, result));
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    for index in leftover.into_inner().unwrap() {
+        results.push((index, task(&items[index])));
+    }
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_run_parallel() {
+        let items = (0..20).collect::<Vec<_>>();
+
+        assert_eq!(
+            run_parallel(&items, 4, |i| i * 2),
+            (0..40).step_by(2).collect::<Vec<_>>()
+        );
+        assert_eq!(run_parallel(&items, 0, |i| *i), items);
+        assert!(run_parallel(&[] as &[i32], 4, |i| *i).is_empty());
+    }
+}
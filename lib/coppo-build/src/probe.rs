@@ -0,0 +1,78 @@
+//! The probes of what the compiler supports, like a C++ standard or the link-time optimization.
+//!
+//! A probe builds a trivial program with the flags in a temporary directory,
+//! the flags are supported if it succeeds.
+//! The outcomes are kept for the run, so the members of a workspace only probe once.
+
+use std::collections::HashMap;
+use std::fs;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use coppo_config::Language;
+
+/// The source of the trivial program.
+const PROGRAM: &str = "int main() { return 0; }\n";
+
+/// The outcomes of the probes, by the compiler and the flags.
+type Outcomes = HashMap<Vec<String>, Result<(), String>>;
+
+/// Build the trivial program of the language with the compiler and the flags,
+/// the error is the output of the compiler, or why it could not run.
+/// With `-fsyntax-only` among the flags, the program is only checked.
+pub fn probe(compiler: &str, language: Language, flags: &[String]) -> Result<(), String> {
+    static OUTCOMES: Mutex<Option<Outcomes>> = Mutex::new(None);
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let mut key = vec![compiler.to_owned(), language.source_extension().to_owned()];
+    key.extend(flags.iter().cloned());
+    if let Some(outcome) = OUTCOMES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_insert_with(HashMap::new)
+        .get(&key)
+    {
+        return outcome.clone();
+    }
+
+    // The probes of the tests run in parallel in the same process.
+    let dir = std::env::temp_dir().join(format!(
+        "coppo-probe-{}-{}",
+        process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let outcome = run(compiler, language, flags, &dir);
+    let _ = fs::remove_dir_all(&dir);
+    OUTCOMES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_insert_with(HashMap::new)
+        .insert(key, outcome.clone());
+
+    outcome
+}
+
+fn run(
+    compiler: &str,
+    language: Language,
+    flags: &[String],
+    dir: &std::path::Path,
+) -> Result<(), String> {
+    let source = dir.join(format!("main.{}", language.source_extension()));
+    fs::create_dir_all(dir)
+        .and_then(|_| fs::write(&source, PROGRAM))
+        .map_err(|e| format!("Failed to write `{}`: {}", source.display(), e))?;
+    let output = process::Command::new(compiler)
+        .arg(&source)
+        .args(flags)
+        .arg("-o")
+        .arg(dir.join("main"))
+        .output()
+        .map_err(|e| format!("Failed to run `{}`: {}", compiler, e))?;
+
+    match output.status.success() {
+        true => Ok(()),
+        false => Err(String::from_utf8_lossy(&output.stderr).trim().to_owned()),
+    }
+}
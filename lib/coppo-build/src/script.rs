@@ -0,0 +1,229 @@
+//! The single-file script mode, like `coppo run hello.cpp`.
+//!
+//! A standalone C++ file can be run outside any project,
+//! it is compiled to `~/.coppo/script-cache/<hash>/<name>`,
+//! where the hash is computed from the content of the file, the compiler and the flags,
+//! so the script is only compiled again after it changes.
+//!
+//! The dependencies of the script are declared in its leading comments:
+//! ```cpp
+//! // coppo: deps = ["fmt@10"]
+//! #include <fmt/core.h>
+//!
+//! int main() { fmt::print("Hello!\n"); }
+//! ```
+//! They are resolved from the system with `pkg-config`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use coppo_config::prelude::*;
+use coppo_logger::prelude::*;
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::{find_compiler, link_command, log_command, wrapper};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The prefix of the header lines of the script.
+const HEADER_PREFIX: &str = "coppo:";
+
+/// The header of the script.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Header {
+    #[serde(default)]
+    deps: Vec<String>,
+}
+
+/// A dependency of the script, like `fmt@10`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptDependency {
+    /// The `pkg-config` name of the dependency.
+    pub name: String,
+    /// The version requirement, it defaults to `*`.
+    pub version: VersionReq,
+}
+
+impl std::str::FromStr for ScriptDependency {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (name, version) = s.split_once('@').unwrap_or((s, "*"));
+        if name.is_empty() {
+            return Err(format!("The dependency `{}` does not have a name", s));
+        }
+        let version = VersionReq::parse(version)
+            .map_err(|e| format!("Invalid version requirement of `{}`: {}", name, e))?;
+
+        Ok(Self {
+            name: name.to_owned(),
+            version,
+        })
+    }
+}
+
+/// Parse the dependencies declared in the leading comments of the script,
+/// the lines like `// coppo: deps = ["fmt@10"]`.
+pub fn dependencies(source: &str) -> std::result::Result<Vec<ScriptDependency>, String> {
+    let header = source
+        .lines()
+        .map(str::trim)
+        .take_while(|line| line.is_empty() || line.starts_with("//"))
+        .filter_map(|line| {
+            line.trim_start_matches('/')
+                .trim()
+                .strip_prefix(HEADER_PREFIX)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let header = toml::from_str::<Header>(&header)
+        .map_err(|e| format!("Failed to parse the `// coppo:` header: {}", e))?;
+    header.deps.iter().map(|dep| dep.parse()).collect()
+}
+
+/// Run `pkg-config` and return its output.
+pub(crate) fn pkg_config(args: &[&str]) -> Result<String> {
+    let output = process::Command::new("pkg-config")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run `pkg-config`: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr)
+            .trim()
+            .to_owned()
+            .into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+/// Parse the version of a system package,
+/// versions like `10.2` are padded to be compared with the requirements.
+pub(crate) fn system_version(installed: &str) -> Option<Version> {
+    let mut padded = installed.to_owned();
+    while padded.matches('.').count() < 2 {
+        padded += ".0";
+    }
+
+    Version::parse(&padded).ok()
+}
+
+/// Get the compile flags of the dependency from the system.
+fn resolve(dependency: &ScriptDependency) -> Result<Vec<String>> {
+    let installed = pkg_config(&["--modversion", &dependency.name]).map_err(|_| {
+        format!(
+            "The dependency `{}` is not found by `pkg-config`.",
+            dependency.name
+        )
+    })?;
+    match system_version(&installed) {
+        Some(version) if !dependency.version.matches(&version) => {
+            return Err(format!(
+                "The dependency `{}` requires `{}`, but `{}` is installed.",
+                dependency.name, dependency.version, installed
+            )
+            .into());
+        }
+        Some(_) => {}
+        None => warn!(
+            "The version `{}` of `{}` can not be checked.",
+            installed, dependency.name
+        ),
+    }
+
+    let flags = pkg_config(&["--cflags", "--libs", &dependency.name])?;
+    Ok(flags.split_whitespace().map(str::to_owned).collect())
+}
+
+/// The cached binary of the script, keyed by the content and the flags.
+fn cached_binary(script: &Path, source: &str, compiler: &str, flags: &[String]) -> Result<PathBuf> {
+    let cache = script_cache().ok_or("Could not find the Coppo home directory.")?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(compiler);
+    for flag in flags {
+        hasher.update([0]);
+        hasher.update(flag);
+    }
+    hasher.update([0]);
+    hasher.update(source);
+    let hash = format!("{:x}", hasher.finalize());
+
+    let name = script.file_stem().unwrap_or(script.as_os_str());
+    Ok(cache
+        .join(&hash[..16])
+        .join(name)
+        .with_extension(std::env::consts::EXE_EXTENSION))
+}
+
+/// Compile the script if it is not cached, and return the path of the binary.
+/// The compiler is the one of the project in the current directory, if there is one.
+pub fn build(config: &Config, script: &Path) -> Result<PathBuf> {
+    let source = fs::read_to_string(script)
+        .map_err(|e| format!("Failed to read the script `{}`: {}", script.display(), e))?;
+
+    let mut flags = vec![];
+    for dependency in dependencies(&source)? {
+        flags.extend(resolve(&dependency)?);
+    }
+
+    let compiler = find_compiler(config)?;
+    let binary = cached_binary(script, &source, &compiler, &flags)?;
+    if binary.is_file() {
+        return Ok(binary);
+    }
+    fs::create_dir_all(binary.parent().unwrap_or(Path::new(".")))?;
+
+    info!("Compiling the script `{}`...", script.display());
+    let mut command = link_command(
+        wrapper(config).as_deref(),
+        &compiler,
+        &[script.to_owned()],
+        &flags,
+        &binary,
+        None,
+    );
+    log_command(&command);
+    let output = command.output()?;
+    if !output.status.success() {
+        error!("The script failed to compile.");
+        return Err(String::from_utf8_lossy(&output.stderr).into());
+    }
+
+    Ok(binary)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dependencies() {
+        let source = "// A script.\n\
+            // coppo: deps = [\"fmt@10\", \"zlib\"]\n\
+            #include <fmt/core.h>\n\
+            // coppo: deps = [\"ignored\"]\n";
+        let deps = dependencies(source).unwrap();
+        assert_eq!(
+            deps,
+            vec![
+                ScriptDependency {
+                    name: "fmt".to_string(),
+                    version: VersionReq::parse("10").unwrap(),
+                },
+                ScriptDependency {
+                    name: "zlib".to_string(),
+                    version: VersionReq::STAR,
+                },
+            ]
+        );
+
+        assert!(dependencies("// coppo: deps = [\"fmt@>=a\"]").is_err());
+        assert!(dependencies("// coppo: dependencies = []").is_err());
+    }
+}
@@ -0,0 +1,73 @@
+//! The check of the C++ or C standard the project is built with, see [`coppo_config::standard`].
+//!
+//! The standard is the last `-std=` of the compile flags, the one the compiler uses,
+//! so it can come from `std` in `[build]`, the extra flags, or [`standard::default_std`].
+//! It must be known, and the compiler must support it, which is checked by a probe,
+//! see [`crate::probe`], instead of a table of the compiler versions.
+
+use coppo_config::standard;
+use coppo_config::Language;
+
+use crate::probe::probe;
+use crate::{msvc, Result};
+
+/// The standard of the flags, it is the last one given.
+pub fn selected(flags: &[String]) -> Option<&str> {
+    flags
+        .iter()
+        .rev()
+        .find_map(|flag| flag.strip_prefix("-std="))
+}
+
+/// Check if the standard of the flags is known for the language and supported by the compiler.
+pub fn check(compiler: &str, language: Language, flags: &[String]) -> Result<()> {
+    let default = standard::default_std(language);
+    let std = selected(flags).unwrap_or(default);
+    standard::validate(std, language).map_err(|e| {
+        format!(
+            "{}\nhelp: set the standard with `std` in `[build]` of `Coppo.toml`, like `std = \"{}\"`",
+            e, default
+        )
+    })?;
+    // MSVC only has some of the standards, the others are mapped to the closest one.
+    if msvc::is_msvc(compiler) {
+        return Ok(());
+    }
+
+    let flags = [standard::flag(std, language), "-fsyntax-only".to_owned()];
+    if let Err(output) = probe(compiler, language, &flags) {
+        return Err(format!(
+            "The {} standard `{}` is not supported by `{}`:\n{}\n\
+            help: use an older one with `std` in `[build]` of `Coppo.toml`, like `std = \"{}\"`\n\
+            help: or upgrade the compiler",
+            language, std, compiler, output, default
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let flags = ["-std=c++20", "-Wall", "-std=gnu++23"].map(String::from);
+        assert_eq!(selected(&flags), Some("gnu++23"));
+        assert_eq!(selected(&[]), None);
+
+        let error = check("cl.exe", Language::Cpp, &["-std=c++21".to_owned()])
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("The C++ standard `c++21` is invalid"));
+        assert!(check("cl.exe", Language::Cpp, &["-std=c++20".to_owned()]).is_ok());
+
+        let error = check("cl.exe", Language::C, &["-std=c++20".to_owned()])
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("The C standard `c++20` is invalid"));
+        assert!(error.ends_with("like `std = \"c17\"`"));
+    }
+}
@@ -0,0 +1,137 @@
+//! The `coppo vendor` add-on, it copies the packages of the registries into `vendor/`,
+//! so the project builds without the network, like in an airgapped CI.
+//!
+//! Every package locked in `Coppo.lock` is copied into `vendor/<name>-<version>`
+//! with the checksum of its archive, and it replaces the registry as long as the checksum
+//! matches the lockfile, see [`coppo_registry::locate`], so there is nothing to configure.
+//! The path dependencies and the workspace members are local already,
+//! and the system packages can not be vendored.
+//!
+//! # Usage
+//! ```sh
+//! coppo vendor
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+use coppo_addons::prelude::*;
+use coppo_config::lock::{Lockfile, LOCK_FILE};
+use coppo_logger::prelude::*;
+use coppo_registry::{VENDOR_CHECKSUM_FILE, VENDOR_DIR};
+
+use crate::lock::{self, SYSTEM_SOURCE};
+use crate::Result;
+
+/// The `Coppo vendor` add-on.
+/// Copy the locked packages of the registries into `vendor/`.
+pub struct CoppoVendorAddon;
+
+impl_addon! {
+    CoppoVendorAddon,
+    name => "vendor",
+    description => "Copy the packages of the registries into `vendor/` to build offline",
+    args => [],
+    run => |config, _matches| {
+        vendor(config, Path::new(VENDOR_DIR))?;
+    }
+}
+
+/// Copy the locked packages of the registries into the directory,
+/// and remove the vendored ones which are not locked anymore.
+pub fn vendor(config: &Config, vendor: &Path) -> Result<()> {
+    if !Config::exists() {
+        return Err("The project does not have a `Coppo.toml` file.".into());
+    }
+    // The stale copies would fail the resolution, they are vendored again.
+    if let Some(locked) = Lockfile::from_file(Path::new("."))? {
+        for package in &locked.packages {
+            if coppo_registry::vendored(vendor, package).is_err() {
+                fs::remove_dir_all(coppo_registry::vendor_dir(vendor, package))?;
+            }
+        }
+    }
+    lock::ensure(config)?;
+    let locked = Lockfile::from_file(Path::new("."))?.unwrap_or_default();
+
+    let mut dirs = vec![];
+    for package in &locked.packages {
+        if package.source == SYSTEM_SOURCE {
+            warn!(
+                "The system package `{}` is not vendored, it must be installed.",
+                package.name
+            );
+            continue;
+        }
+        if !package.source.starts_with(coppo_registry::SOURCE_PREFIX) {
+            continue;
+        }
+
+        let dir = coppo_registry::vendor_dir(vendor, package);
+        if coppo_registry::vendored(vendor, package)?.is_none() {
+            info!("Vendoring `{}` {}...", package.name, package.version);
+            let source = coppo_registry::fetch_locked(package, &config.global)?;
+            copy_dir(&source, &dir)?;
+            let checksum = package.checksum.clone().unwrap_or_default();
+            fs::write(dir.join(VENDOR_CHECKSUM_FILE), checksum + "\n")?;
+        }
+        dirs.push(dir);
+    }
+
+    if vendor.is_dir() {
+        for entry in fs::read_dir(vendor)? {
+            let path = entry?.path();
+            if path.join(VENDOR_CHECKSUM_FILE).is_file() && !dirs.contains(&path) {
+                info!("Removing `{}`, it is not locked anymore.", path.display());
+                fs::remove_dir_all(&path)?;
+            }
+        }
+    }
+
+    success!(
+        "Vendored {} package(s) of `{}` into `{}`.",
+        dirs.len(),
+        LOCK_FILE,
+        vendor.display()
+    );
+    Ok(())
+}
+
+/// Copy the directory recursively.
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        match entry.file_type()?.is_dir() {
+            true => copy_dir(&entry.path(), &target)?,
+            false => {
+                fs::copy(entry.path(), &target)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_copy_dir() {
+        let dir = std::env::temp_dir().join(format!("coppo-vendor-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("from/include")).unwrap();
+        fs::write(dir.join("from/Coppo.toml"), "[package]\n").unwrap();
+        fs::write(dir.join("from/include/fmt.h"), "#pragma once\n").unwrap();
+
+        copy_dir(&dir.join("from"), &dir.join("to")).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("to/include/fmt.h")).unwrap(),
+            "#pragma once\n"
+        );
+        assert!(dir.join("to/Coppo.toml").is_file());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,334 @@
+//! The `coppo verify-project` add-on, it validates the manifest and the project layout
+//! without building the project.
+//!
+//! # Usage
+//! ```sh
+//! coppo verify-project [--json]
+//! ```
+
+use std::path::Path;
+
+use coppo_addons::prelude::*;
+use coppo_config::files::FileFilter;
+use coppo_config::prelude::*;
+use coppo_config::{name, standard};
+use coppo_logger::prelude::*;
+use serde::Serialize;
+
+/// The `Coppo verify-project` add-on.
+/// Check the manifest and the declared files of the current project,
+/// it fails if any error is found.
+pub struct CoppoVerifyProjectAddon;
+
+impl_addon! {
+    CoppoVerifyProjectAddon,
+    name => "verify-project",
+    description => "Check the manifest and the layout of the current project",
+    args => [
+        arg!(--json "Print the results as JSON")
+            .action(ArgAction::SetTrue),
+    ],
+    run => |_config, matches| {
+        let problems = verify(Path::new(CONFIG_FILE));
+        let errors = problems.iter().filter(|p| p.level == Severity::Error).count();
+
+        if matches.get_flag("json") {
+            let report = Report {
+                success: errors == 0,
+                problems: &problems,
+            };
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            for problem in &problems {
+                match problem.level {
+                    Severity::Error => error!("error: {}", problem),
+                    Severity::Warning => warn!("warning: {}", problem),
+                }
+            }
+        }
+
+        if errors > 0 {
+            return Err(format!("The project is invalid, {} error(s) found.", errors).into());
+        }
+        if !matches.get_flag("json") {
+            success!("The project is valid.");
+        }
+    }
+}
+
+/// The severity of a problem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The project can not be built.
+    Error,
+    /// The project can be built, but it is likely a mistake.
+    Warning,
+}
+
+/// A problem of the project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Problem {
+    /// The severity of the problem.
+    pub level: Severity,
+    /// The manifest key of the problem, like `bin.server.path`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// The description of the problem.
+    pub message: String,
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.key {
+            Some(key) => write!(f, "`{}`: {}", key, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// The machine-readable results.
+#[derive(Serialize)]
+struct Report<'a> {
+    success: bool,
+    problems: &'a [Problem],
+}
+
+/// The collected problems.
+#[derive(Default)]
+struct Problems(Vec<Problem>);
+
+impl Problems {
+    fn error(&mut self, key: impl Into<Option<String>>, message: impl Into<String>) {
+        self.push(Severity::Error, key.into(), message.into());
+    }
+
+    fn warning(&mut self, key: impl Into<Option<String>>, message: impl Into<String>) {
+        self.push(Severity::Warning, key.into(), message.into());
+    }
+
+    fn push(&mut self, level: Severity, key: Option<String>, message: String) {
+        self.0.push(Problem {
+            level,
+            key,
+            message,
+        });
+    }
+}
+
+/// Check the manifest and the project layout, the paths are relative to the current directory.
+pub fn verify(manifest: &Path) -> Vec<Problem> {
+    let mut problems = Problems::default();
+
+    // The fields inherited from the workspace are resolved with the manifest path.
+    let config = match Config::from_path(manifest) {
+        Ok(config) => config,
+        Err(ConfigError::Read { error, .. }) => {
+            problems.error(
+                None,
+                format!("Failed to read `{}`: {}", manifest.display(), error),
+            );
+            return problems.0;
+        }
+        Err(ConfigError::Parse(mut e)) => {
+            // The manifest is already named in the message.
+            e.file = None;
+            problems.error(
+                None,
+                format!(
+                    "Failed to parse `{}`: {}",
+                    manifest.display(),
+                    ConfigError::Parse(e)
+                ),
+            );
+            return problems.0;
+        }
+    };
+
+    verify_project(&config.project, &mut problems);
+    verify_dependencies(&config, &mut problems);
+    if let Err(e) = config.check_features() {
+        problems.error(Some("features".to_owned()), e);
+    }
+    if let Err(e) = config.check_targets() {
+        problems.error(Some("target".to_owned()), e);
+    }
+    if let Some(std) = &config.build.std {
+        if let Err(e) = standard::validate(std, config.project.language) {
+            problems.error(Some("build.std".to_owned()), e);
+        }
+    }
+    verify_targets(&config, &mut problems);
+
+    problems.0
+}
+
+fn verify_project(project: &Project, problems: &mut Problems) {
+    let key = |field: &str| Some(format!("project.{}", field));
+
+    if let Err(e) = name::validate(&project.name) {
+        problems.error(key("name"), e);
+    }
+    if project.authors.is_empty() {
+        problems.warning(key("authors"), "No author is specified");
+    }
+    if let Err(e) = FileFilter::new(project) {
+        problems.error(key("include"), e.to_string());
+    }
+}
+
+fn verify_dependencies(config: &Config, problems: &mut Problems) {
+    let mut dependencies = config
+        .dependencies
+        .iter()
+        .map(|(name, dependency)| ("dependencies".to_owned(), name, dependency))
+        .chain(
+            config
+                .dev_dependencies
+                .iter()
+                .map(|(name, dependency)| ("dev-dependencies".to_owned(), name, dependency)),
+        )
+        .collect::<Vec<_>>();
+    for (spec, target) in &config.target {
+        let table = |table: &str| format!("target.{}.{}", spec, table);
+        dependencies.extend(
+            target
+                .dependencies
+                .iter()
+                .map(|(name, dependency)| (table("dependencies"), name, dependency)),
+        );
+        dependencies.extend(
+            target
+                .dev_dependencies
+                .iter()
+                .map(|(name, dependency)| (table("dev-dependencies"), name, dependency)),
+        );
+    }
+    dependencies.sort_by(|a, b| (&a.0, a.1).cmp(&(&b.0, b.1)));
+
+    for (table, name, dependency) in dependencies {
+        let key = Some(format!("{}.{}", table, name));
+        if let Err(e) = dependency.validate() {
+            problems.error(key.clone(), e);
+        }
+        if let Some(registry) = dependency
+            .registry()
+            .filter(|registry| !config.global.registries.contains_key(*registry))
+        {
+            problems.error(
+                key.clone(),
+                format!(
+                    "The registry `{}` is not in the global configuration",
+                    registry
+                ),
+            );
+        }
+        if let Some(package) = dependency.name().filter(|package| *package != name) {
+            problems.warning(key, format!("The name `{}` differs from the key", package));
+        }
+    }
+}
+
+fn verify_targets(config: &Config, problems: &mut Problems) {
+    if let Some(lib) = &config.lib {
+        if let Err(e) = name::validate(&lib.name) {
+            problems.error(Some("lib.name".to_owned()), e);
+        }
+        if !lib.path().exists() {
+            problems.error(
+                Some("lib.path".to_owned()),
+                format!(
+                    "The library sources `{}` do not exist",
+                    lib.path().display()
+                ),
+            );
+        }
+        if !lib.include().is_dir() {
+            problems.warning(
+                Some("lib.include".to_owned()),
+                format!(
+                    "The include directory `{}` does not exist",
+                    lib.include().display()
+                ),
+            );
+        }
+    }
+
+    for (i, bin) in config.bin.iter().enumerate() {
+        let key = |field: &str| Some(format!("bin.{}.{}", bin.name, field));
+        if let Err(e) = name::validate(&bin.name) {
+            problems.error(key("name"), e);
+        }
+        if config.bin[..i].iter().any(|other| other.name == bin.name) {
+            problems.error(key("name"), "The binary target is declared twice");
+        }
+        if !bin.path().is_file() {
+            problems.error(
+                key("path"),
+                format!("The source file `{}` does not exist", bin.path().display()),
+            );
+        }
+    }
+
+    if config.bin.is_empty() && config.lib.is_none() && !Path::new("src/main.cpp").is_file() {
+        problems.error(
+            None,
+            "The project has no target, add `src/main.cpp`, a `[lib]` or a `[[bin]]`",
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_verify() {
+        let dir = std::env::temp_dir().join(format!("coppo-verify-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let manifest = dir.join(CONFIG_FILE);
+
+        for manifest_str in [
+            "[project]\nname = \"app\"",
+            "[project]\nname = \"app\"\nversion = \"1.0\"",
+        ] {
+            fs::write(&manifest, manifest_str).unwrap();
+            let problems = verify(&manifest);
+            assert_eq!(problems.len(), 1);
+            assert!(problems[0].message.starts_with("Failed to parse"));
+        }
+
+        fs::write(
+            &manifest,
+            r#"
+            [project]
+            name = "app"
+            version = "1.0.0"
+            authors = []
+
+            [dependencies]
+            fmt = { name = "fmt", branch = "master" }
+
+            [[bin]]
+            name = "server"
+            path = "missing/server.cpp"
+            "#,
+        )
+        .unwrap();
+        let problems = verify(&manifest)
+            .into_iter()
+            .map(|problem| (problem.level, problem.key.unwrap_or_default()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            problems,
+            vec![
+                (Severity::Warning, "project.authors".to_string()),
+                (Severity::Error, "dependencies.fmt".to_string()),
+                (Severity::Error, "bin.server.path".to_string()),
+            ]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
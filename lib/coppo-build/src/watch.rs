@@ -0,0 +1,257 @@
+//! The `coppo watch` add-on, it runs a command again whenever the project changes.
+//!
+//! The project and its path dependencies are watched with filesystem notifications,
+//! and a change of a source, a header or `Coppo.toml` runs the command again,
+//! `coppo build` unless another one is given, like `coppo watch run --release`.
+//! The changes are debounced, so saving several files at once runs the command once,
+//! and the previous run is cancelled with the compilers it started if it has not finished.
+//! The output directory and `.git` are not watched.
+//!
+//! # Usage
+//! ```sh
+//! coppo watch [--delay <MS>] [COMMAND]...
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use coppo_addons::prelude::*;
+use coppo_config::files::{HEADER_EXTENSIONS, SOURCE_EXTENSIONS};
+use coppo_config::prelude::*;
+use coppo_logger::prelude::*;
+use notify::{RecursiveMode, Watcher};
+
+use crate::{target_dir, Result, COMPILE_OUTPUT};
+
+/// The command run without any given, `coppo build`.
+pub const DEFAULT_COMMAND: &str = "build";
+
+/// The default time in milliseconds to wait for more changes before running the command.
+pub const DEFAULT_DELAY: u64 = 300;
+
+/// The interval to check if the run has finished or `Ctrl-C` is pressed.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The time given to the cancelled run to exit before it is killed.
+const CANCEL_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The `Coppo watch` add-on.
+/// Run a command of Coppo again whenever a source, a header or `Coppo.toml` changes.
+pub struct CoppoWatchAddon;
+
+impl_addon! {
+    CoppoWatchAddon,
+    name => "watch",
+    description => "Run a command again whenever the project changes",
+    args => [
+        arg!(--delay <MS> "The milliseconds to wait for more changes before running, defaults to 300")
+            .value_parser(value_parser!(u64)),
+        arg!([COMMAND]... "The command with its arguments, like `run --release`, defaults to `build`")
+            .value_parser(value_parser!(String))
+            .trailing_var_arg(true)
+            .allow_hyphen_values(true),
+    ],
+    run => |config, matches| {
+        let mut command = matches
+            .get_many::<String>("COMMAND")
+            .unwrap_or_default()
+            .cloned()
+            .collect::<Vec<_>>();
+        if command.is_empty() {
+            command.push(DEFAULT_COMMAND.to_string());
+        }
+        let delay = matches.get_one::<u64>("delay").copied().unwrap_or(DEFAULT_DELAY);
+        watch(config, &command, Duration::from_millis(delay))?;
+    }
+}
+
+/// Whether a change of the file runs the command again,
+/// it is a source, a header or `Coppo.toml` outside the ignored directories and `.git`.
+pub fn is_watched(path: &Path, ignored: &[PathBuf]) -> bool {
+    if ignored.iter().any(|dir| path.starts_with(dir))
+        || path
+            .components()
+            .any(|component| component.as_os_str() == ".git")
+    {
+        return false;
+    }
+    if path.file_name().is_some_and(|name| name == CONFIG_FILE) {
+        return true;
+    }
+
+    path.extension().is_some_and(|ext| {
+        SOURCE_EXTENSIONS
+            .iter()
+            .chain(HEADER_EXTENSIONS)
+            .any(|watched| ext == *watched)
+    })
+}
+
+/// The directories to watch, the project and its path dependencies.
+fn watched_dirs(config: &Config) -> Result<Vec<PathBuf>> {
+    let mut dirs = vec![Path::new(".").canonicalize()?];
+    let paths = config
+        .dependencies
+        .values()
+        .chain(config.dev_dependencies.values())
+        .filter_map(Dependency::path);
+    for path in paths {
+        match path.canonicalize() {
+            Ok(dir) if !dirs.iter().any(|watched| dir.starts_with(watched)) => dirs.push(dir),
+            Ok(_) => {}
+            Err(_) => warn!("The path dependency `{}` is not found.", path.display()),
+        }
+    }
+
+    Ok(dirs)
+}
+
+/// Start the command as `coppo <COMMAND>...`.
+/// It is in its own process group on Unix, so it can be cancelled with the compilers it started.
+fn spawn(command: &[String]) -> Result<process::Child> {
+    let mut child = process::Command::new(std::env::current_exe()?);
+    if coppo_registry::is_offline() {
+        child.arg("--offline");
+    }
+    child.args(command);
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut child, 0);
+
+    child
+        .spawn()
+        .map_err(|e| format!("Failed to run `coppo {}`: {}", command.join(" "), e).into())
+}
+
+/// Cancel the run, with the processes it started, and wait for it to exit.
+fn cancel(child: &mut process::Child) -> Result<()> {
+    let pid = child.id().to_string();
+    #[cfg(unix)]
+    let stopped = process::Command::new("kill")
+        .args(["-TERM", &format!("-{}", pid)])
+        .status();
+    #[cfg(windows)]
+    let stopped = process::Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &pid])
+        .output()
+        .map(|output| output.status);
+    #[cfg(not(any(unix, windows)))]
+    let stopped: std::io::Result<process::ExitStatus> = Err(std::io::ErrorKind::Unsupported.into());
+
+    if stopped.is_ok_and(|status| status.success()) {
+        let start = Instant::now();
+        while start.elapsed() < CANCEL_TIMEOUT {
+            if child.try_wait()?.is_some() {
+                return Ok(());
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+    let _ = child.kill();
+    child.wait()?;
+    Ok(())
+}
+
+/// Run the command, and run it again whenever the project changes, until `Ctrl-C` is pressed.
+/// The changes within `delay` of each other run it once.
+pub fn watch(config: &Config, command: &[String], delay: Duration) -> Result<()> {
+    if !Config::exists() {
+        return Err("The project does not have a `Coppo.toml` file.".into());
+    }
+
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let flag = interrupted.clone();
+    ctrlc::set_handler(move || flag.store(true, Ordering::SeqCst))?;
+
+    let dirs = watched_dirs(config)?;
+    let mut ignored = dirs
+        .iter()
+        .map(|dir| dir.join(COMPILE_OUTPUT))
+        .collect::<Vec<_>>();
+    ignored.push(std::env::current_dir()?.join(target_dir()));
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    for dir in &dirs {
+        watcher
+            .watch(dir, RecursiveMode::Recursive)
+            .map_err(|e| format!("Failed to watch `{}`: {}", dir.display(), e))?;
+    }
+    let changed = |event: &notify::Result<notify::Event>| match event {
+        Ok(event) => {
+            !event.kind.is_access() && event.paths.iter().any(|path| is_watched(path, &ignored))
+        }
+        Err(e) => {
+            warn!("Failed to watch the project: {}", e);
+            false
+        }
+    };
+
+    let name = format!("coppo {}", command.join(" "));
+    info!("Watching the project, running `{}` on changes.", name);
+    let mut running = Some(spawn(command)?);
+    loop {
+        if interrupted.load(Ordering::SeqCst) {
+            if let Some(mut child) = running.take() {
+                cancel(&mut child)?;
+            }
+            return Ok(());
+        }
+        if let Some(child) = running.as_mut() {
+            if let Some(status) = child.try_wait()? {
+                running = None;
+                match status.success() {
+                    true => info!("Waiting for changes..."),
+                    false => warn!("`{}` failed, waiting for changes...", name),
+                }
+            }
+        }
+
+        match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(event) if changed(&event) => {}
+            Ok(_) | Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => return Err("The watcher has stopped.".into()),
+        }
+        // Wait until nothing changes within the delay.
+        loop {
+            match receiver.recv_timeout(delay) {
+                Ok(_) => {}
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err("The watcher has stopped.".into())
+                }
+            }
+        }
+
+        if let Some(mut child) = running.take() {
+            info!("Cancelling the previous run...");
+            cancel(&mut child)?;
+        }
+        info!("The project has changed, running `{}`.", name);
+        running = Some(spawn(command)?);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_is_watched() {
+        let ignored = [PathBuf::from("/app/target")];
+        assert!(is_watched(Path::new("/app/src/main.cpp"), &ignored));
+        assert!(is_watched(Path::new("/app/include/app/util.hpp"), &ignored));
+        assert!(is_watched(Path::new("/app/Coppo.toml"), &ignored));
+        assert!(!is_watched(Path::new("/app/Coppo.lock"), &ignored));
+        assert!(!is_watched(Path::new("/app/README.md"), &ignored));
+        assert!(!is_watched(
+            Path::new("/app/target/debug/gen.cpp"),
+            &ignored
+        ));
+        assert!(!is_watched(Path::new("/app/.git/main.cpp"), &ignored));
+    }
+}
@@ -0,0 +1,855 @@
+//! The workspace-aware execution of the commands, like `coppo build -p core` or `coppo test --workspace`.
+//!
+//! The selected members are run one by one in their own directories, in the dependency order,
+//! a member depends on another one if it has a dependency named after it.
+//! All the members share the `target` directory of the workspace root.
+//!
+//! Without `-p` and `--workspace`, only the root project is used,
+//! or all the members if the workspace does not have a root project.
+//!
+//! The path dependencies, like `util = { path = "../util" }`, are used like members too,
+//! whether the project is in a workspace or not, see [`path_members`].
+//! Their paths are relative to the project declaring them, and they are loaded recursively.
+//! So are the packages of the registries locked in `Coppo.lock`,
+//! from `vendor/` or the download cache, see [`coppo_registry::locate`].
+//!
+//! The libraries of the members are built once into `target/deps/<name>-<hash>`,
+//! and reused by every member depending on them, see [`artifacts`].
+//! The hash is of the compiler and the flags, including the ones of the profile,
+//! and a library is rebuilt only if its sources, headers or manifest are newer.
+//! They are also published into the global artifact cache, in `~/.coppo/cache/artifacts/<member>`,
+//! so they are restored from it instead of rebuilt once `target` is cleaned.
+
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use coppo_addons::prelude::*;
+use coppo_config::lock::Lockfile;
+use coppo_config::prelude::*;
+use coppo_logger::prelude::*;
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    archive_lib, compiler, flags, static_lib_name, target_dir, wrapper, COMPILE_OUTPUT,
+    TARGET_DIR_ENV,
+};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The artifacts are stored in `target/deps`.
+pub const DEPS_DIR: &str = "deps";
+
+/// A member of the workspace.
+#[derive(Debug)]
+pub struct Member {
+    /// The project name of the member.
+    pub name: String,
+    /// The directory of the member, relative to the workspace root,
+    /// or to the project directory if it is a path dependency outside of any workspace.
+    pub dir: PathBuf,
+    /// The configuration of the member.
+    pub config: Config,
+}
+
+/// The `-p` argument, the members to use.
+pub fn package_arg() -> Arg {
+    arg!(-p --package <NAME> "The package to use, it can be specified multiple times")
+        .action(ArgAction::Append)
+        .value_parser(value_parser!(String))
+}
+
+/// The `--workspace` argument, use all the members.
+pub fn workspace_arg() -> Arg {
+    arg!(--workspace "Use all the members of the workspace")
+        .action(ArgAction::SetTrue)
+        .conflicts_with("package")
+}
+
+/// A library of a member, built for the members depending on it.
+#[derive(Debug, Clone)]
+pub struct Artifact {
+    /// The name of the member.
+    pub member: String,
+    /// The name of the library.
+    pub name: String,
+    /// The static library.
+    pub archive: PathBuf,
+    /// The absolute directory of the public headers.
+    pub include: PathBuf,
+}
+
+/// Load all the members of the workspace, the root project is also a member if there is one.
+pub fn members(config: &Config) -> Result<Vec<Member>> {
+    load(Path::new("."), config)
+}
+
+/// Load the members of the workspace in the root directory.
+fn load(root: &Path, config: &Config) -> Result<Vec<Member>> {
+    let Some(workspace) = &config.workspace else {
+        return Ok(vec![]);
+    };
+
+    let mut members = vec![];
+    if !config.is_empty() {
+        members.push(Member {
+            name: config.project.name.clone(),
+            dir: PathBuf::from("."),
+            config: read(&root.join(CONFIG_FILE), &config.global)?,
+        });
+    }
+    for dir in workspace.members(root)? {
+        let dir = dir.strip_prefix(root).unwrap_or(&dir).to_owned();
+        let config = read(&root.join(&dir).join(CONFIG_FILE), &config.global)?;
+        if config.project.name.is_empty() {
+            return Err(format!(
+                "The member `{}` does not have a project name.",
+                dir.display()
+            )
+            .into());
+        }
+        if let Some(other) = members
+            .iter()
+            .find(|member| member.name == config.project.name)
+        {
+            return Err(format!(
+                "The members `{}` and `{}` have the same name `{}`.",
+                other.dir.display(),
+                dir.display(),
+                config.project.name
+            )
+            .into());
+        }
+
+        members.push(Member {
+            name: config.project.name.clone(),
+            dir,
+            config,
+        });
+    }
+
+    Ok(members)
+}
+
+/// Parse the manifest, with the global configuration.
+fn read(manifest: &Path, global: &GlobalConfig) -> Result<Config> {
+    let mut config = Config::from_path(manifest)?;
+    config.global = global.clone();
+
+    Ok(config)
+}
+
+/// Load the projects of the path dependencies of the current project and of the members,
+/// like `util = { path = "../util" }`, and of their own path dependencies recursively.
+/// The packages of the registries are downloaded if they are locked, and skipped otherwise.
+/// The dev-dependencies are only followed for the current project,
+/// and the projects already loaded, like the members of the workspace, are skipped.
+/// The directories are relative to the root.
+pub fn path_members(root: &Path, config: &Config, members: &[Member]) -> Result<Vec<Member>> {
+    let locked = Lockfile::from_file(Path::new("."))?.unwrap_or_default();
+    let mut loaded = vec![fs::canonicalize(".")?];
+    for member in members {
+        loaded.push(fs::canonicalize(root.join(&member.dir))?);
+    }
+
+    let mut pending = path_dependencies(&loaded[0], config, true, &locked)?;
+    for (member, dir) in members.iter().zip(&loaded[1..]) {
+        pending.extend(path_dependencies(dir, &member.config, false, &locked)?);
+    }
+    let mut found: Vec<Member> = vec![];
+    while let Some((name, dir)) = pending.pop() {
+        let manifest = dir.join(CONFIG_FILE);
+        if !manifest.is_file() {
+            return Err(format!(
+                "The path dependency `{}` is not found, `{}` does not exist.",
+                name,
+                manifest.display()
+            )
+            .into());
+        }
+        let dir = fs::canonicalize(&dir)?;
+        if loaded.contains(&dir) {
+            continue;
+        }
+        let config = read(&manifest, &config.global)?;
+        if config.project.name != name {
+            return Err(format!(
+                "The path dependency `{}` is the project `{}` in `{}`.
+                help: rename the dependency to `{}`",
+                name,
+                config.project.name,
+                dir.display(),
+                config.project.name
+            )
+            .into());
+        }
+
+        pending.extend(path_dependencies(&dir, &config, false, &locked)?);
+        loaded.push(dir.clone());
+        found.push(Member {
+            name,
+            dir: relative(&dir, root),
+            config,
+        });
+    }
+
+    Ok(found)
+}
+
+/// The names and the directories of the path dependencies of the project in `dir`,
+/// and of the packages of the registries locked in `locked`,
+/// with the dev-dependencies if `dev` is `true`.
+fn path_dependencies(
+    dir: &Path,
+    config: &Config,
+    dev: bool,
+    locked: &Lockfile,
+) -> Result<Vec<(String, PathBuf)>> {
+    let platform = config.platform();
+    let mut dependencies = config.dependencies_for(&platform);
+    if dev {
+        dependencies.extend(config.dev_dependencies_for(&platform));
+    }
+
+    let mut paths = vec![];
+    for (name, dependency) in dependencies {
+        if let Some(path) = dependency.path() {
+            paths.push((name.to_owned(), dir.join(path)));
+        } else if let Some(package) = locked
+            .get(name)
+            .filter(|package| package.source.starts_with(coppo_registry::SOURCE_PREFIX))
+        {
+            // The packages of a registry may not name it in their own dependencies.
+            let vendor = Path::new(coppo_registry::VENDOR_DIR);
+            // Offline, the resolution lists the ones which can not be used.
+            if coppo_registry::is_offline() && !coppo_registry::is_available(package, vendor) {
+                continue;
+            }
+            paths.push((
+                name.to_owned(),
+                coppo_registry::locate(package, vendor, &config.global)?,
+            ));
+        }
+    }
+
+    Ok(paths)
+}
+
+/// The path relative to the base, both are absolute.
+fn relative(path: &Path, base: &Path) -> PathBuf {
+    let common = path
+        .components()
+        .zip(base.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut relative = PathBuf::new();
+    for _ in base.components().skip(common) {
+        relative.push("..");
+    }
+    relative.extend(path.components().skip(common));
+
+    match relative.as_os_str().is_empty() {
+        true => PathBuf::from("."),
+        false => relative,
+    }
+}
+
+/// Sort the members in the dependency order, the dependencies come first.
+pub fn order(mut members: Vec<Member>) -> Result<Vec<Member>> {
+    let mut ordered: Vec<Member> = vec![];
+    while !members.is_empty() {
+        let ready = members.iter().position(|member| {
+            let dependencies = member.config.dependencies_for(&member.config.platform());
+            dependencies.keys().all(|dependency| {
+                *dependency == member.name
+                    || ordered.iter().any(|done| done.name == *dependency)
+                    || !members.iter().any(|other| other.name == *dependency)
+            })
+        });
+        match ready {
+            Some(i) => ordered.push(members.remove(i)),
+            None => {
+                let names = members
+                    .iter()
+                    .map(|member| member.name.as_str())
+                    .collect::<Vec<_>>();
+                return Err(
+                    format!("The members have a dependency cycle: {}.", names.join(", ")).into(),
+                );
+            }
+        }
+    }
+
+    Ok(ordered)
+}
+
+/// The members the project depends on directly or indirectly, in the dependency order.
+/// The dev-dependencies of the project are included if `dev` is `true`,
+/// but never the ones of the members.
+/// The optional dependencies of the project are only included if a feature enables them,
+/// and the target-specific dependencies if they apply to the platform built for.
+pub fn required(members: Vec<Member>, config: &Config, dev: bool) -> Vec<Member> {
+    let platform = config.platform();
+    let mut names = HashSet::new();
+    let mut pending = config
+        .dependencies_for(&platform)
+        .into_keys()
+        .filter(|name| config.is_dependency_enabled(name))
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    if dev {
+        pending.extend(
+            config
+                .dev_dependencies_for(&platform)
+                .into_keys()
+                .map(str::to_owned),
+        );
+    }
+    while let Some(name) = pending.pop() {
+        if name == config.project.name || !names.insert(name.clone()) {
+            continue;
+        }
+        if let Some(member) = members.iter().find(|member| member.name == name) {
+            pending.extend(
+                member
+                    .config
+                    .dependencies_for(&platform)
+                    .into_keys()
+                    .map(str::to_owned),
+            );
+        }
+    }
+
+    members
+        .into_iter()
+        .filter(|member| names.contains(&member.name))
+        .collect()
+}
+
+/// Find the workspace root of the directory,
+/// the nearest ancestor with a `[workspace]` which has the directory as a member or the root project.
+/// The global configuration is attached to the manifest of the root.
+pub fn root(dir: &Path, global: &GlobalConfig) -> Result<Option<(PathBuf, Config)>> {
+    let dir = fs::canonicalize(dir)?;
+    for ancestor in dir.ancestors() {
+        let manifest = ancestor.join(CONFIG_FILE);
+        if !manifest.is_file() {
+            continue;
+        }
+        let config = read(&manifest, global)?;
+        let Some(workspace) = &config.workspace else {
+            continue;
+        };
+
+        let is_member = ancestor == dir
+            || workspace
+                .members(ancestor)?
+                .iter()
+                .any(|member| fs::canonicalize(member).is_ok_and(|member| member == dir));
+        if is_member {
+            return Ok(Some((ancestor.to_owned(), config)));
+        }
+    }
+
+    Ok(None)
+}
+
+/// The members of the workspace and the path dependencies the current project depends on,
+/// with the workspace root, or the project directory outside of any workspace.
+/// The current project itself is included if `itself` is `true`,
+/// and the dev-dependencies if `dev` is `true`.
+pub fn dependencies(
+    config: &Config,
+    itself: bool,
+    dev: bool,
+) -> Result<Option<(PathBuf, Vec<Member>)>> {
+    if config.dependencies.is_empty()
+        && (!dev || config.dev_dependencies.is_empty())
+        && config.target.is_empty()
+        && !itself
+    {
+        return Ok(None);
+    }
+    let Some((root, mut members)) = all_members(config)? else {
+        return Ok(None);
+    };
+
+    // The members are built for the same target.
+    for member in &mut members {
+        member.config.build_target = config.build_target.clone();
+    }
+    let current = members
+        .iter()
+        .position(|member| member.name == config.project.name)
+        .map(|i| members.remove(i));
+    let mut members = required(members, config, dev);
+    // The project is removed from the members, so it would not be found in a cycle.
+    if let Some(member) = members.iter().find(|member| {
+        member
+            .config
+            .dependencies_for(&member.config.platform())
+            .contains_key(config.project.name.as_str())
+    }) {
+        return Err(format!(
+            "The project `{}` depends on itself through `{}`.",
+            config.project.name, member.name
+        )
+        .into());
+    }
+    if itself {
+        members.extend(current);
+    }
+
+    Ok(Some((root, order(members)?)))
+}
+
+/// All the members of the workspace of the current project and the path dependencies,
+/// with the workspace root, or the project directory outside of any workspace.
+pub(crate) fn all_members(config: &Config) -> Result<Option<(PathBuf, Vec<Member>)>> {
+    let workspace = root(Path::new("."), &config.global)?;
+    let in_workspace = workspace.is_some();
+    let (root, mut members) = match workspace {
+        Some((root, workspace)) => {
+            let members = load(&root, &workspace)?;
+            (root, members)
+        }
+        None => (fs::canonicalize(".")?, vec![]),
+    };
+    let paths = path_members(&root, config, &members)?;
+    if !in_workspace && paths.is_empty() {
+        return Ok(None);
+    }
+    members.extend(paths);
+
+    Ok(Some((root, members)))
+}
+
+/// The include flags of the libraries of the members the current project depends on.
+pub fn include_flags(config: &Config) -> Result<Vec<String>> {
+    let Some((root, members)) = dependencies(config, false, false)? else {
+        return Ok(vec![]);
+    };
+
+    Ok(members
+        .iter()
+        .filter_map(|member| member.config.lib.as_ref().map(|lib| (member, lib)))
+        .map(|(member, lib)| format!("-I{}", root.join(&member.dir).join(lib.include()).display()))
+        .collect())
+}
+
+/// Build the libraries of the members the current project depends on, in the dependency order,
+/// or reuse them if they are up to date.
+/// The extra flags are the ones of the profile, like `-O3`.
+pub fn artifacts(config: &Config, extra: &[String]) -> Result<Vec<Artifact>> {
+    match dependencies(config, false, false)? {
+        Some((root, members)) => build_artifacts(&root, members, extra),
+        None => Ok(vec![]),
+    }
+}
+
+/// Like [`artifacts`], with the libraries of the dev-dependencies, for the tests.
+pub fn dev_artifacts(config: &Config, extra: &[String]) -> Result<Vec<Artifact>> {
+    match dependencies(config, false, true)? {
+        Some((root, members)) => build_artifacts(&root, members, extra),
+        None => Ok(vec![]),
+    }
+}
+
+/// Build the library of the current project as an artifact, if it is a member of a workspace,
+/// so the members depending on it reuse it.
+pub fn lib_artifact(config: &Config, extra: &[String]) -> Result<Option<Artifact>> {
+    if config.lib.is_none() {
+        return Ok(None);
+    }
+    let Some((root, members)) = dependencies(config, true, false)? else {
+        return Ok(None);
+    };
+
+    let artifacts = build_artifacts(&root, members, extra)?;
+    Ok(artifacts
+        .into_iter()
+        .find(|artifact| artifact.member == config.project.name))
+}
+
+/// Build the libraries of the members in order, or reuse them if they are up to date.
+fn build_artifacts(root: &Path, members: Vec<Member>, extra: &[String]) -> Result<Vec<Artifact>> {
+    // The shared `target` directory, even if a member is built in its own directory.
+    let target = match env::var_os(TARGET_DIR_ENV) {
+        Some(_) => env::current_dir()?.join(target_dir()),
+        None => root.join(COMPILE_OUTPUT),
+    };
+
+    let mut artifacts: Vec<Artifact> = vec![];
+    for member in members {
+        let Some(lib) = &member.config.lib else {
+            continue;
+        };
+        let dir = root.join(&member.dir);
+
+        // The headers of the libraries it depends on are visible to it.
+        let compiler = compiler(&member.config);
+        let mut flags = flags(&member.config);
+        flags.extend(extra.iter().cloned());
+        flags.extend(
+            artifacts
+                .iter()
+                .map(|artifact| format!("-I{}", artifact.include.display())),
+        );
+
+        let name = format!("{}-{}", lib.name, fingerprint(&compiler, &flags));
+        let out = target.join(DEPS_DIR).join(&name);
+        let file = static_lib_name(&member.config, &lib.name);
+        let archive = out.join(&file);
+        let cached = artifact_cache(&member.name).map(|cache| cache.join(&name).join(&file));
+
+        // It is rebuilt if a library it depends on is rebuilt.
+        let mut inputs = vec![
+            dir.join(lib.path()),
+            dir.join(lib.include()),
+            dir.join(CONFIG_FILE),
+        ];
+        inputs.extend(artifacts.iter().map(|artifact| artifact.archive.clone()));
+        let changed = newest(&inputs);
+        match (modified(&archive), changed) {
+            (Some(built), Some(changed)) if built >= changed => {
+                info!("The library `{}` is up to date.", lib.name);
+            }
+            _ if cached
+                .as_deref()
+                .is_some_and(|cached| restore(cached, &archive, changed)) =>
+            {
+                info!(
+                    "The library `{}` is restored from the artifact cache.",
+                    lib.name
+                );
+            }
+            _ => archive_lib(
+                &dir,
+                wrapper(&member.config).as_deref(),
+                &compiler,
+                lib,
+                &flags,
+                &out.join("obj"),
+                &archive,
+            )?,
+        }
+        if let Some(cached) = &cached {
+            publish(&archive, cached);
+        }
+
+        artifacts.push(Artifact {
+            member: member.name.clone(),
+            name: lib.name.clone(),
+            archive,
+            include: dir.join(lib.include()),
+        });
+    }
+
+    Ok(artifacts)
+}
+
+/// Copy the cached library to the archive if it is newer than the inputs.
+fn restore(cached: &Path, archive: &Path, changed: Option<SystemTime>) -> bool {
+    match (modified(cached), changed) {
+        (Some(built), Some(changed)) if built >= changed => {}
+        _ => return false,
+    }
+
+    archive
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::copy(cached, archive))
+        .is_ok()
+}
+
+/// Copy the library to the artifact cache if the cached one is older.
+/// A failure only skips the cache, the library is still used from `target`.
+fn publish(archive: &Path, cached: &Path) {
+    if modified(cached) >= modified(archive) {
+        return;
+    }
+
+    let result = cached
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::copy(archive, cached));
+    if let Err(e) = result {
+        warn!(
+            "Failed to copy `{}` to the artifact cache: {}",
+            archive.display(),
+            e
+        );
+    }
+}
+
+/// The flags to compile and link with the artifacts,
+/// the libraries come after the ones depending on them for the linker.
+pub fn link_flags(artifacts: &[Artifact]) -> Vec<String> {
+    let includes = artifacts
+        .iter()
+        .map(|artifact| format!("-I{}", artifact.include.display()));
+    let archives = artifacts
+        .iter()
+        .rev()
+        .map(|artifact| artifact.archive.display().to_string());
+
+    includes.chain(archives).collect()
+}
+
+/// The hash of the build settings, the artifacts built with different settings are kept apart.
+fn fingerprint(compiler: &str, flags: &[String]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(compiler);
+    for flag in flags {
+        hasher.update([0]);
+        hasher.update(flag);
+    }
+    let hash = format!("{:x}", hasher.finalize());
+
+    hash[..16].to_owned()
+}
+
+/// The modification time of the file.
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// The newest modification time of the files, the directories are walked recursively.
+fn newest(paths: &[PathBuf]) -> Option<SystemTime> {
+    let mut newest = None;
+    let mut pending = paths.to_vec();
+    while let Some(path) = pending.pop() {
+        if path.is_dir() {
+            for entry in fs::read_dir(&path).into_iter().flatten().flatten() {
+                pending.push(entry.path());
+            }
+        } else if let Some(time) = modified(&path) {
+            newest = newest.max(Some(time));
+        }
+    }
+
+    newest
+}
+
+/// Get the flag if the command has it.
+fn flag(matches: &ArgMatches, id: &str) -> bool {
+    matches
+        .try_get_one::<bool>(id)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false)
+}
+
+/// Select the members by `-p` and `--workspace`, in the dependency order.
+/// It returns `None` if the command only runs on the current project.
+pub fn select(config: &Config, matches: &ArgMatches) -> Result<Option<Vec<Member>>> {
+    let packages = matches
+        .try_get_many::<String>("package")
+        .ok()
+        .flatten()
+        .map(|packages| packages.cloned().collect::<Vec<_>>());
+
+    if config.workspace.is_none() {
+        for package in packages.iter().flatten() {
+            if *package != config.project.name {
+                return Err(format!(
+                    "The package `{}` is not found, the current package is `{}`.",
+                    package, config.project.name
+                )
+                .into());
+            }
+        }
+        return Ok(None);
+    }
+
+    let mut members = members(config)?;
+    match packages {
+        Some(packages) => {
+            for package in &packages {
+                if !members.iter().any(|member| member.name == *package) {
+                    let names = members
+                        .iter()
+                        .map(|member| member.name.as_str())
+                        .collect::<Vec<_>>();
+                    return Err(format!(
+                        "The package `{}` is not a member of the workspace, the members are: {}.",
+                        package,
+                        names.join(", ")
+                    )
+                    .into());
+                }
+            }
+            members.retain(|member| packages.contains(&member.name));
+        }
+        None if !flag(matches, "workspace") && !config.is_empty() => return Ok(None),
+        None => {}
+    }
+
+    order(members).map(Some)
+}
+
+/// Run the task on the current project, or on each of the selected members in its directory.
+/// The members share the `target` directory of the workspace root.
+pub fn for_each(
+    config: &mut Config,
+    matches: &ArgMatches,
+    verb: &str,
+    mut task: impl FnMut(&mut Config) -> Result<()>,
+) -> Result<()> {
+    match select(config, matches)? {
+        Some(members) => execute(members, verb, task),
+        None => task(config),
+    }
+}
+
+/// Like [`for_each`], but only one member can be selected.
+pub fn for_one(
+    config: &mut Config,
+    matches: &ArgMatches,
+    verb: &str,
+    mut task: impl FnMut(&mut Config) -> Result<()>,
+) -> Result<()> {
+    match select(config, matches)? {
+        Some(members) if members.len() != 1 => {
+            let names = members
+                .iter()
+                .map(|member| member.name.as_str())
+                .collect::<Vec<_>>();
+            Err(format!(
+                "Could not determine which package to use, use `-p` to choose one of: {}.",
+                names.join(", ")
+            )
+            .into())
+        }
+        Some(members) => execute(members, verb, task),
+        None => task(config),
+    }
+}
+
+/// Run the task on the members one by one, and stop at the first failure.
+fn execute(
+    members: Vec<Member>,
+    verb: &str,
+    mut task: impl FnMut(&mut Config) -> Result<()>,
+) -> Result<()> {
+    let root = env::current_dir()?;
+    if env::var_os(TARGET_DIR_ENV).is_none() {
+        env::set_var(TARGET_DIR_ENV, root.join(target_dir()));
+    }
+
+    let total = members.len();
+    for (i, mut member) in members.into_iter().enumerate() {
+        info!(
+            "[{}/{}] {} `{}` ({})",
+            i + 1,
+            total,
+            verb,
+            member.name,
+            member.dir.display()
+        );
+
+        env::set_current_dir(&member.dir)?;
+        let result = task(&mut member.config);
+        env::set_current_dir(&root)?;
+
+        if let Err(e) = result {
+            error!("[{}/{}] `{}` failed.", i + 1, total, member.name);
+            return Err(e);
+        }
+    }
+
+    success!("Finished {} package(s).", total);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn member(name: &str, dependencies: &[&str]) -> Member {
+        let mut config = Config::default();
+        config.project.name = name.to_string();
+        for dependency in dependencies {
+            config
+                .dependencies
+                .insert(dependency.to_string(), Dependency::default());
+        }
+
+        Member {
+            name: name.to_string(),
+            dir: PathBuf::from(name),
+            config,
+        }
+    }
+
+    #[test]
+    fn test_order() {
+        let members = vec![
+            member("app", &["net", "core"]),
+            member("net", &["core", "fmt"]),
+            member("core", &[]),
+        ];
+        let names = order(members)
+            .unwrap()
+            .into_iter()
+            .map(|member| member.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["core", "net", "app"]);
+
+        let error = order(vec![member("a", &["b"]), member("b", &["a"])]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The members have a dependency cycle: a, b."
+        );
+    }
+
+    #[test]
+    fn test_required() {
+        let members = vec![
+            member("app", &["net"]),
+            member("net", &["core", "fmt"]),
+            member("core", &[]),
+            member("tool", &["core"]),
+        ];
+        let mut app = member("app", &["net"]);
+        let names = required(members, &app.config, false)
+            .into_iter()
+            .map(|member| member.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["net", "core"]);
+
+        app.config
+            .dev_dependencies
+            .insert("tool".to_string(), Dependency::default());
+        let members = vec![
+            member("net", &[]),
+            member("core", &[]),
+            member("tool", &["core"]),
+        ];
+        let names = required(members, &app.config, true)
+            .into_iter()
+            .map(|member| member.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["net", "core", "tool"]);
+    }
+
+    #[test]
+    fn test_relative() {
+        let root = Path::new("/home/me/app");
+        assert_eq!(
+            relative(Path::new("/home/me/util"), root),
+            Path::new("../util")
+        );
+        assert_eq!(
+            relative(Path::new("/home/me/app/libs/net"), root),
+            Path::new("libs/net")
+        );
+        assert_eq!(relative(root, root), Path::new("."));
+    }
+}
@@ -0,0 +1,338 @@
+//! The `coppo-cache` crate is a Coppo addon that inspects and prunes the global caches:
+//! - `downloads`, the downloaded packages in `~/.coppo/cache/downloads/<package>`.
+//! - `artifacts`, the build artifacts in `~/.coppo/cache/artifacts/<project>`.
+//! - `scripts`, the compiled scripts in `~/.coppo/script-cache/<hash>`.
+//!
+//! # Usage
+//! ```sh
+//! coppo cache stats [--cache <CACHE>]
+//! coppo cache clean [--cache <CACHE>]
+//! coppo cache prune --older-than 30d [--cache <CACHE>]
+//! ```
+
+#![forbid(unsafe_code)]
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use coppo_addons::prelude::*;
+use coppo_config::prelude::*;
+use coppo_config::ARTIFACT_CACHE_DIR;
+use coppo_logger::prelude::*;
+use coppo_size::format_size;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The `Coppo cache` add-on.
+/// Report the disk usage of the global caches, and remove their entries.
+/// The entries used by the current project are never pruned.
+pub struct CoppoCacheAddon;
+
+impl_addon! {
+    CoppoCacheAddon,
+    name => "cache",
+    description => "Inspect and prune the global caches",
+    args => [
+        arg!(<ACTION> "The action to do")
+            .value_parser(["stats", "clean", "prune"]),
+        arg!(--cache <CACHE> "Only use the cache")
+            .value_parser(Cache::NAMES),
+        arg!(--"older-than" <AGE> "Prune the entries unused for the age, like `30d`")
+            .value_parser(parse_age),
+    ],
+    run => |config, matches| {
+        let caches = match matches.get_one::<String>("cache") {
+            Some(cache) => vec![cache.parse::<Cache>()?],
+            None => Cache::ALL.to_vec(),
+        };
+        let mut entries = vec![];
+        for cache in caches {
+            entries.extend(cache.entries()?);
+        }
+
+        match matches.get_one::<String>("ACTION").map(String::as_str) {
+            Some("stats") => print!("{}", stats(&entries)),
+            Some("clean") => remove(&entries)?,
+            Some("prune") => {
+                let age = matches
+                    .get_one::<Duration>("older-than")
+                    .ok_or("`--older-than` is needed to prune the caches, like `--older-than 30d`.")?;
+                let now = SystemTime::now();
+                let stale = entries
+                    .into_iter()
+                    .filter(|entry| !entry.is_referenced(config))
+                    .filter(|entry| {
+                        now.duration_since(entry.modified).unwrap_or_default() > *age
+                    })
+                    .collect::<Vec<_>>();
+                remove(&stale)?;
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// The global caches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cache {
+    /// The downloaded packages.
+    Downloads,
+    /// The build artifacts of the projects.
+    Artifacts,
+    /// The compiled scripts.
+    Scripts,
+}
+
+impl Cache {
+    /// All the caches.
+    pub const ALL: [Cache; 3] = [Cache::Downloads, Cache::Artifacts, Cache::Scripts];
+
+    /// All the names of the caches.
+    pub const NAMES: [&'static str; 3] = ["downloads", "artifacts", "scripts"];
+
+    /// The directory of the cache.
+    pub fn path(&self) -> Option<PathBuf> {
+        match self {
+            Cache::Downloads => download_cache(),
+            Cache::Artifacts => coppo_home().map(|home| home.join(ARTIFACT_CACHE_DIR)),
+            Cache::Scripts => script_cache(),
+        }
+    }
+
+    /// The entries of the cache, one for each sub-directory.
+    pub fn entries(&self) -> io::Result<Vec<Entry>> {
+        match self.path().filter(|dir| dir.is_dir()) {
+            Some(dir) => self.entries_in(&dir),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// The entries of the cache in the directory.
+    fn entries_in(&self, dir: &Path) -> io::Result<Vec<Entry>> {
+        let mut entries = vec![];
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            // The symbolic links are not followed.
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let (size, modified) = usage(&entry.path())?;
+            entries.push(Entry {
+                cache: *self,
+                name: entry.file_name().to_string_lossy().into_owned(),
+                path: entry.path(),
+                size,
+                modified,
+            });
+        }
+        entries.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+
+        Ok(entries)
+    }
+}
+
+impl FromStr for Cache {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "downloads" => Ok(Cache::Downloads),
+            "artifacts" => Ok(Cache::Artifacts),
+            "scripts" => Ok(Cache::Scripts),
+            _ => Err(format!(
+                "Unknown cache `{}`, the caches are: {}",
+                s,
+                Cache::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Cache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cache::Downloads => write!(f, "downloads"),
+            Cache::Artifacts => write!(f, "artifacts"),
+            Cache::Scripts => write!(f, "scripts"),
+        }
+    }
+}
+
+/// An entry of a cache, a package, a project or a script.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    /// The cache of the entry.
+    pub cache: Cache,
+    /// The name of the entry.
+    pub name: String,
+    /// The directory of the entry.
+    pub path: PathBuf,
+    /// The disk usage of the entry.
+    pub size: u64,
+    /// The last modification time of the files of the entry.
+    pub modified: SystemTime,
+}
+
+impl Entry {
+    /// Check if the entry is used by the current project,
+    /// the downloads of its dependencies and its own artifacts.
+    pub fn is_referenced(&self, config: &Config) -> bool {
+        match self.cache {
+            Cache::Downloads => config.dependencies.contains_key(&self.name),
+            Cache::Artifacts => !config.is_empty() && config.project.name == self.name,
+            Cache::Scripts => false,
+        }
+    }
+}
+
+/// The total size and the last modification time of the files in the directory.
+fn usage(dir: &Path) -> io::Result<(u64, SystemTime)> {
+    let mut size = 0;
+    let mut modified = fs::symlink_metadata(dir)?.modified()?;
+    let mut dirs = vec![dir.to_owned()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_dir() {
+                dirs.push(entry.path());
+            } else {
+                size += metadata.len();
+            }
+            modified = modified.max(metadata.modified()?);
+        }
+    }
+
+    Ok((size, modified))
+}
+
+/// Parse the age like `30d`, the units are `s`, `m`, `h`, `d` and `w`.
+pub fn parse_age(age: &str) -> std::result::Result<Duration, String> {
+    let unit = age
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("The age `{}` needs a unit, like `30d`", age))?;
+    let (value, unit) = age.split_at(unit);
+    let value = value
+        .parse::<u64>()
+        .map_err(|_| format!("Invalid age `{}`", age))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => {
+            return Err(format!(
+                "Unknown unit `{}`, the units are: s, m, h, d, w",
+                unit
+            ))
+        }
+    };
+
+    Ok(Duration::from_secs(value * seconds))
+}
+
+/// The disk usage of each cache and each entry.
+fn stats(entries: &[Entry]) -> String {
+    let mut text = String::new();
+    for cache in Cache::ALL {
+        let entries = entries
+            .iter()
+            .filter(|entry| entry.cache == cache)
+            .collect::<Vec<_>>();
+        if entries.is_empty() {
+            continue;
+        }
+
+        let total = entries.iter().map(|entry| entry.size).sum::<u64>();
+        text += &format!(
+            "{} ({} entries, {})\n",
+            cache,
+            entries.len(),
+            format_size(total)
+        );
+        for entry in entries {
+            text += &format!("  {:>10}  {}\n", format_size(entry.size), entry.name);
+        }
+    }
+
+    match text.is_empty() {
+        true => "The caches are empty.\n".to_owned(),
+        false => text,
+    }
+}
+
+/// Remove the entries, and report the freed disk space.
+fn remove(entries: &[Entry]) -> Result<()> {
+    let mut freed = 0;
+    for entry in entries {
+        fs::remove_dir_all(&entry.path)?;
+        freed += entry.size;
+    }
+
+    success!(
+        "Removed {} entries, {} freed.",
+        entries.len(),
+        format_size(freed)
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_entries() {
+        let dir = std::env::temp_dir().join(format!("coppo-cache-{}", std::process::id()));
+        fs::create_dir_all(dir.join("app/core-0123456789abcdef")).unwrap();
+        fs::write(dir.join("app/core-0123456789abcdef/libcore.a"), [0; 64]).unwrap();
+        fs::create_dir_all(dir.join("tool")).unwrap();
+        fs::write(dir.join("stray.txt"), "not an entry").unwrap();
+
+        let entries = Cache::Artifacts.entries_in(&dir).unwrap();
+        let names = entries
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.size))
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![("app", 64), ("tool", 0)]);
+
+        let config = Config::from_str(
+            r#"
+            [project]
+            name = "app"
+            version = "0.1.0"
+            authors = []
+            "#,
+        )
+        .unwrap();
+        assert!(entries[0].is_referenced(&config));
+        assert!(!entries[1].is_referenced(&config));
+        assert!(!entries[0].is_referenced(&Config::default()));
+
+        let mut download = entries[1].clone();
+        download.cache = Cache::Downloads;
+        assert!(!download.is_referenced(&config));
+        let mut config = config;
+        config
+            .dependencies
+            .insert("tool".to_string(), Dependency::default());
+        assert!(download.is_referenced(&config));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("30d"), Ok(Duration::from_secs(30 * 24 * 60 * 60)));
+        assert_eq!(parse_age("2w"), Ok(Duration::from_secs(14 * 24 * 60 * 60)));
+        assert_eq!(parse_age("90s"), Ok(Duration::from_secs(90)));
+        assert!(parse_age("30").is_err());
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("1y").is_err());
+    }
+}
@@ -3,10 +3,12 @@
 //! And it will run the add-on which is specified by the user.
 
 #![forbid(unsafe_code)]
-#![feature(type_alias_impl_trait)]
 #![allow(clippy::new_without_default)]
 
 pub use coppo_addons::prelude::*;
+use coppo_config::error::{ConfigError, ParseError};
+use coppo_config::global::ColorChoice;
+use coppo_config::GlobalConfig;
 use coppo_logger::prelude::*;
 
 /// The packings of the add-ons.
@@ -69,6 +71,7 @@ impl CoppoCli {
 
     /// Run the `CoppoCli`.
     /// The `run` method will run the add-on which is specified by the user.
+    /// If the add-on fails, the process exits with code `1`.
     /// the `command` arg is the main command of the CLI.
     /// you can use the `command!` macro to create the main command.
     /// # Example
@@ -83,9 +86,18 @@ impl CoppoCli {
         self.command = self
             .command
             .clone()
-            .args(&[arg!(-q --quiet "Do not print Coppo log messages")
-                .action(ArgAction::SetTrue)
-                .value_parser(value_parser!(bool))])
+            .args(&[
+                arg!(-q --quiet "Do not print Coppo log messages")
+                    .action(ArgAction::SetTrue)
+                    .value_parser(value_parser!(bool)),
+                arg!(--strict "Treat the unused keys of the manifest as errors")
+                    .action(ArgAction::SetTrue)
+                    .value_parser(value_parser!(bool)),
+                arg!(--offline "Do not access the network, only use the locked, downloaded and vendored packages")
+                    .action(ArgAction::SetTrue)
+                    .value_parser(value_parser!(bool))
+                    .global(true),
+            ])
             .about("Cpp package manager")
             .help_template(
                 "{before-help}{about-with-newline}\n\
@@ -105,16 +117,30 @@ impl CoppoCli {
             }));
 
         let matches = self.command.clone().get_matches();
-        let mut config = Config::from_file().unwrap_or_default();
 
         // If the user specifies the `--quiet` flag, the logger will not output messages.
         init_logger(*matches.get_one::<bool>("quiet").unwrap_or(&false));
 
+        let global = GlobalConfig::from_file().unwrap_or_else(|e| {
+            warn!("{}", e);
+            GlobalConfig::default()
+        });
+        match global.term.color {
+            ColorChoice::Auto => {}
+            ColorChoice::Always => set_colored(true),
+            ColorChoice::Never => set_colored(false),
+        }
+
+        coppo_registry::set_offline(*matches.get_one::<bool>("offline").unwrap_or(&false));
+        let strict = *matches.get_one::<bool>("strict").unwrap_or(&false);
         if let Some((name, matches)) = matches.subcommand() {
             for addon in self.addons.iter() {
                 if name == addon.name() {
+                    let mut config = load(addon.needs_project(), strict);
+                    config.global = global.clone();
                     if let Err(e) = addon.run(&mut config, matches) {
                         error!("{}", e);
+                        std::process::exit(1);
                     }
                 }
             }
@@ -122,6 +148,87 @@ impl CoppoCli {
     }
 }
 
+/// Load the configuration of the project, the process exits if the manifest is invalid.
+/// With `project`, the manifest is looked up in the ancestors too,
+/// and the current directory is changed to the project root.
+/// The unused keys of the manifest are warned, or fail the command with `strict`.
+fn load(project: bool, strict: bool) -> Config {
+    let loaded = match project {
+        true => Config::find_and_load(),
+        false if Config::exists() => Config::from_file().map(Some),
+        false => Ok(None),
+    };
+    let config = loaded.unwrap_or_else(|e| {
+        LOGGER
+            .get_or_init(|| Logger::new(false))
+            .diagnostic(&diagnostic(&e));
+        std::process::exit(1);
+    });
+
+    let Some(config) = config else {
+        return Config::default();
+    };
+    let level = match strict {
+        true => Level::Error,
+        false => Level::Warning,
+    };
+    for key in &config.unused_keys {
+        LOGGER
+            .get_or_init(|| Logger::new(false))
+            .diagnostic(&Diagnostic {
+                notes: key
+                    .suggestion
+                    .iter()
+                    .map(|known| Diagnostic::new(Level::Help, format!("did you mean `{}`?", known)))
+                    .collect(),
+                ..Diagnostic::new(level, format!("unused manifest key: {}", key.key))
+            });
+    }
+    if strict && !config.unused_keys.is_empty() {
+        std::process::exit(1);
+    }
+    if let Some(root) = &config.root {
+        if let Err(e) = std::env::set_current_dir(root) {
+            error!("Failed to enter `{}`: {}", root.display(), e);
+            std::process::exit(1);
+        }
+    }
+
+    config
+}
+
+/// The diagnostic of the error of the manifest, pointing at the offending part of the file.
+fn diagnostic(e: &ConfigError) -> Diagnostic {
+    let ConfigError::Parse(error) = e else {
+        return Diagnostic::new(Level::Error, e.to_string());
+    };
+    let ParseError {
+        message,
+        file,
+        span,
+        key,
+        suggestion,
+    } = error.as_ref();
+
+    let message = match key {
+        Some(key) => format!("{} (at `{}`)", message, key),
+        None => message.clone(),
+    };
+    Diagnostic {
+        location: file.as_ref().zip(*span).map(|(file, span)| Location {
+            file: file.clone(),
+            line: span.line,
+            column: span.column,
+            length: span.length,
+        }),
+        notes: suggestion
+            .iter()
+            .map(|suggestion| Diagnostic::new(Level::Help, suggestion.as_str()))
+            .collect(),
+        ..Diagnostic::new(Level::Error, message)
+    }
+}
+
 /// The `addons!` macro is used to add multiple add-ons to the `CoppoCli`.
 /// You can use this macro like `addons![Addon1, Addon2]`.
 #[macro_export]
@@ -0,0 +1,233 @@
+//! The downloads of the archives, like the packages and the templates of the registries,
+//! and the toolchains.
+//!
+//! The files are downloaded with `curl`, with the proxy, the timeout and the retries of `[http]`,
+//! see [`HttpSettings::curl_args`].
+//! An archive is verified against its checksum, like `sha256:<hex>`, before it is extracted,
+//! and the `.tar.gz`, `.tar.xz` and `.zip` archives are extracted without any external tool.
+
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::process::{self, Stdio};
+
+use flate2::read::GzDecoder;
+use sha2::{Digest, Sha256};
+use xz2::read::XzDecoder;
+
+use crate::global::HttpSettings;
+use crate::E;
+
+/// Download the file with `curl`, and return the content, or write it into `output`.
+/// The progress bar of `curl` is shown if `progress` is `true`, for the large files,
+/// unless the errors are not printed to a terminal.
+pub fn download(
+    url: &str,
+    output: Option<&Path>,
+    http: &HttpSettings,
+    progress: bool,
+) -> Result<Vec<u8>, String> {
+    let mut command = process::Command::new("curl");
+    match progress && io::stderr().is_terminal() {
+        true => command
+            .args(["-fL", "--progress-bar"])
+            .stderr(Stdio::inherit()),
+        false => command.arg("-fsSL"),
+    };
+    command.args(http.curl_args());
+    if let Some(output) = output {
+        command.arg("-o").arg(output);
+    }
+
+    let result = command
+        .arg(url)
+        .output()
+        .map_err(|e| format!("Failed to run `curl`: {}", e))?;
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        return Err(match stderr.trim() {
+            "" => format!("Failed to download `{}`.", url),
+            stderr => format!("Failed to download `{}`: {}", url, stderr),
+        });
+    }
+
+    Ok(result.stdout)
+}
+
+/// The checksum of the file, like `sha256:<hex>`.
+pub fn checksum(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+
+    Ok(format!("sha256:{:x}", hasher.finalize()))
+}
+
+/// Extract the archive into the directory, its format is given by its extension,
+/// `.tar.gz` (or `.tgz`), `.tar.xz` (or `.txz`) and `.zip`.
+/// The entries outside of the directory, like `../file`, are skipped.
+pub fn extract(archive: &Path, dir: &Path) -> Result<(), E> {
+    let name = archive
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_ascii_lowercase();
+
+    if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        unpack_tar(GzDecoder::new(fs::File::open(archive)?), dir)?;
+    } else if name.ends_with(".tar.xz") || name.ends_with(".txz") {
+        unpack_tar(XzDecoder::new(fs::File::open(archive)?), dir)?;
+    } else if name.ends_with(".zip") {
+        zip::ZipArchive::new(fs::File::open(archive)?)?.extract(dir)?;
+    } else {
+        return Err(format!(
+            "Unsupported archive `{}`, the archives are `.tar.gz`, `.tar.xz` and `.zip`.",
+            archive.display()
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Extract the entries of the tar archive into the directory.
+fn unpack_tar(reader: impl io::Read, dir: &Path) -> io::Result<()> {
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries()? {
+        entry?.unpack_in(dir)?;
+    }
+
+    Ok(())
+}
+
+/// Download the archive named `archive` with `fetch`, verify its checksum, extract it,
+/// and move it into the directory once it is checked by `check`.
+/// The archives have a single top-level directory, or the content itself.
+///
+/// It all happens in `.download-<name>` next to the directory, which is removed afterwards,
+/// so the directory is only replaced by a verified and checked archive.
+pub fn unpack(
+    dir: &Path,
+    archive: &str,
+    checksum: &str,
+    label: &str,
+    fetch: impl FnOnce(&Path) -> Result<(), E>,
+    check: impl FnOnce(&Path) -> Result<(), E>,
+) -> Result<(), E> {
+    let file_name = dir
+        .file_name()
+        .ok_or_else(|| format!("Invalid directory `{}`.", dir.display()))?;
+    let download = dir.with_file_name(format!(".download-{}", file_name.to_string_lossy()));
+    if download.exists() {
+        fs::remove_dir_all(&download)?;
+    }
+    let extract = download.join("extract");
+    fs::create_dir_all(&extract)?;
+
+    let result = (|| -> Result<(), E> {
+        // The name comes from a URL, only its last component is used.
+        let archive = download.join(Path::new(archive).file_name().unwrap_or("archive".as_ref()));
+        fetch(&archive)?;
+
+        let actual = self::checksum(&archive)?;
+        if actual != checksum {
+            return Err(format!(
+                "The checksum of {} is `{}`, but `{}` is expected.\n\
+                help: the archive may be corrupted or changed after it is published",
+                label, actual, checksum
+            )
+            .into());
+        }
+
+        self::extract(&archive, &extract)
+            .map_err(|e| format!("Failed to extract {}: {}", label, e))?;
+        let entries = fs::read_dir(&extract)?.flatten().collect::<Vec<_>>();
+        let root = match entries.as_slice() {
+            [entry] if entry.path().is_dir() => entry.path(),
+            _ => extract.clone(),
+        };
+        check(&root)?;
+        if dir.exists() {
+            fs::remove_dir_all(dir)?;
+        }
+        fs::rename(root, dir)?;
+
+        Ok(())
+    })();
+    fs::remove_dir_all(&download)?;
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Write a `.tar.gz` archive with the files, the paths are relative to the archive root.
+    fn tar_gz(path: &Path, files: &[(&str, &str)]) {
+        let encoder = flate2::write::GzEncoder::new(
+            fs::File::create(path).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+        for (name, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn test_unpack() {
+        let root = std::env::temp_dir().join(format!("coppo-download-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let source = root.join("pkg-1.0.0.tar.gz");
+        tar_gz(
+            &source,
+            &[
+                ("pkg-1.0.0/Coppo.toml", "[project]\n"),
+                ("pkg-1.0.0/src/lib.cpp", ""),
+            ],
+        );
+        let copy = |archive: &Path| -> Result<(), E> {
+            fs::copy(&source, archive)?;
+            Ok(())
+        };
+        let sum = checksum(&source).unwrap();
+        assert!(sum.starts_with("sha256:"));
+
+        let dir = root.join("pkg");
+        unpack(
+            &dir,
+            "pkg-1.0.0.tar.gz",
+            &sum,
+            "`pkg`",
+            copy,
+            |root| match root.join("Coppo.toml").is_file() {
+                true => Ok(()),
+                false => Err("no manifest".into()),
+            },
+        )
+        .unwrap();
+        assert!(dir.join("Coppo.toml").is_file());
+        assert!(dir.join("src/lib.cpp").is_file());
+        assert!(!root.join(".download-pkg").exists());
+
+        // A mismatching archive is never extracted, and the directory is kept.
+        let error = unpack(&dir, "pkg-1.0.0.tar.gz", "sha256:00", "`pkg`", copy, |_| {
+            Ok(())
+        })
+        .unwrap_err();
+        assert!(error.to_string().contains("The checksum of `pkg`"));
+        assert!(dir.join("Coppo.toml").is_file());
+        assert!(!root.join(".download-pkg").exists());
+
+        let error = extract(&root.join("pkg.rar"), &root).unwrap_err();
+        assert!(error.to_string().starts_with("Unsupported archive"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
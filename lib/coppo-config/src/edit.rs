@@ -0,0 +1,236 @@
+//! Write the configuration back to `Coppo.toml`.
+//!
+//! The configuration is merged into the existing file with `toml_edit`,
+//! so only the changed keys are rewritten,
+//! and the order of the keys, the comments and the formatting of the rest are kept.
+
+use std::fs;
+use std::path::Path;
+
+use toml_edit::{DocumentMut, Item, TableLike, Value};
+
+use crate::{Config, VersionReq, CONFIG_FILE, E};
+
+#[cfg(test)]
+use crate::Dependency;
+
+impl Config {
+    /// Write the configuration into the file, the existing file is edited in place.
+    pub fn to_file(&self, path: &Path) -> Result<(), E> {
+        let manifest = match path.exists() {
+            true => fs::read_to_string(path)?,
+            false => String::new(),
+        };
+        let manifest = self
+            .edit_str(&manifest)
+            .map_err(|e| format!("Failed to write `{}`: {}", path.display(), e))?;
+        fs::write(path, manifest)?;
+
+        Ok(())
+    }
+
+    /// Write the configuration into `Coppo.toml` in the current directory.
+    pub fn save(&self) -> Result<(), E> {
+        self.to_file(Path::new(CONFIG_FILE))
+    }
+
+    /// Merge the configuration into the content of a manifest,
+    /// the keys which are not changed are kept as they are.
+    ///
+    /// # Example
+    /// ```rust
+    /// use coppo_config::Config;
+    /// let manifest = r#"
+    /// [project]
+    /// name = "app" # Keep it short.
+    /// version = "0.1.0"
+    /// authors = []
+    /// "#;
+    ///
+    /// let mut config = Config::from_str(manifest).unwrap();
+    /// config.project.version = "0.2.0".parse().unwrap();
+    ///
+    /// let manifest = config.edit_str(manifest).unwrap();
+    /// assert!(manifest.contains("name = \"app\" # Keep it short.\nversion = \"0.2.0\""));
+    /// ```
+    pub fn edit_str(&self, manifest: &str) -> Result<String, E> {
+        let mut document = manifest.parse::<DocumentMut>()?;
+        let original = document.clone();
+        let mut config = toml::to_string(self)?.parse::<DocumentMut>()?;
+        inline_dependencies(config.as_item_mut());
+        merge(document.as_item_mut(), config.as_item());
+
+        // The inherited fields are checked with the values of the workspace.
+        Config::from_str(&document.to_string())
+            .map_err(|e| format!("Invalid configuration: {}", e))?;
+        self.keep_inherited(&original, &mut document);
+
+        Ok(document.to_string())
+    }
+}
+
+/// The tables of the dependencies.
+const DEPENDENCY_TABLES: [&str; 2] = ["dependencies", "dev-dependencies"];
+
+/// Write the table form of the dependencies inline, like `fmt = { version = "10.2" }`,
+/// including the ones of the `[target.<spec>]` tables.
+/// The ones written as `[dependencies.fmt]` in the manifest are kept by [`merge`].
+fn inline_dependencies(item: &mut Item) {
+    let Some(table) = item.as_table_like_mut() else {
+        return;
+    };
+    for name in DEPENDENCY_TABLES {
+        let Some(dependencies) = table.get_mut(name).and_then(Item::as_table_like_mut) else {
+            continue;
+        };
+        for (_, dependency) in dependencies.iter_mut() {
+            if let Item::Table(detail) = dependency {
+                *dependency = Item::Value(Value::InlineTable(detail.clone().into_inline_table()));
+            }
+        }
+    }
+    if let Some(targets) = table.get_mut("target").and_then(Item::as_table_like_mut) {
+        for (_, target) in targets.iter_mut() {
+            inline_dependencies(target);
+        }
+    }
+}
+
+/// Merge the new item into the old one.
+fn merge(old: &mut Item, new: &Item) {
+    if let (Some(old), Some(new)) = (old.as_table_like_mut(), new.as_table_like()) {
+        return merge_table(old, new);
+    }
+    if let (Some(old), Some(new)) = (old.as_array_of_tables_mut(), new.as_array_of_tables()) {
+        for (i, table) in new.iter().enumerate() {
+            match old.get_mut(i) {
+                Some(old) => merge_table(old, table),
+                None => old.push(table.clone()),
+            }
+        }
+        while old.len() > new.len() {
+            old.remove(old.len() - 1);
+        }
+        return;
+    }
+    if let (Some(old), Some(new)) = (old.as_value_mut(), new.as_value()) {
+        return merge_value(old, new);
+    }
+
+    // The type of the item is changed.
+    *old = new.clone();
+}
+
+/// Merge the keys of the new table into the old one, the removed keys are removed.
+fn merge_table(old: &mut dyn TableLike, new: &dyn TableLike) {
+    let removed = old
+        .iter()
+        .map(|(key, _)| key.to_owned())
+        .filter(|key| !new.contains_key(key))
+        .collect::<Vec<_>>();
+    for key in removed {
+        old.remove(&key);
+    }
+
+    for (key, item) in new.iter() {
+        match old.get_mut(key) {
+            Some(old) => merge(old, item),
+            None => {
+                old.insert(key, item.clone());
+            }
+        }
+    }
+}
+
+/// Replace the old value if it is changed, its comments are kept.
+fn merge_value(old: &mut Value, new: &Value) {
+    match (old, new) {
+        (Value::Array(old), Value::Array(new)) if old.len() == new.len() => {
+            for (old, new) in old.iter_mut().zip(new.iter()) {
+                merge_value(old, new);
+            }
+        }
+        (Value::InlineTable(old), Value::InlineTable(new)) => merge_table(old, new),
+        (old, new) if !same(old, new) => {
+            let decor = old.decor().clone();
+            *old = new.clone();
+            *old.decor_mut() = decor;
+        }
+        _ => {}
+    }
+}
+
+/// Check if the scalar values are the same, regardless of how they are written.
+/// The version requirements are compared by their meaning, `1.2` is the same as `^1.2`.
+fn same(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::String(a), Value::String(b)) => {
+            a.value() == b.value()
+                || matches!(
+                    (VersionReq::parse(a.value()), VersionReq::parse(b.value())),
+                    (Ok(a), Ok(b)) if a == b
+                )
+        }
+        (Value::Integer(a), Value::Integer(b)) => a.value() == b.value(),
+        (Value::Float(a), Value::Float(b)) => a.value() == b.value(),
+        (Value::Boolean(a), Value::Boolean(b)) => a.value() == b.value(),
+        (Value::Datetime(a), Value::Datetime(b)) => a.value() == b.value(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_edit_str() -> Result<(), E> {
+        let manifest = r#"# The manifest of the app.
+[project]
+name = 'app' # The name.
+version = "0.1.0"
+authors = [
+    "A",
+]
+
+[dependencies]
+fmt = { version = "10.2", features = ["os"] }
+zlib = "1.3"
+
+[[bin]]
+name = "server"
+
+[[bin]]
+name = "client"
+"#;
+        let mut config = Config::from_str(manifest)?;
+        assert_eq!(config.edit_str(manifest)?, manifest);
+
+        config.project.version = "0.2.0".parse()?;
+        config.dependencies.remove("zlib");
+        config.dependencies.insert(
+            "gtest".to_string(),
+            Dependency::Simple(VersionReq::parse("1.14")?),
+        );
+        config.bin.pop();
+
+        let edited = config.edit_str(manifest)?;
+        assert!(edited.starts_with("# The manifest of the app.\n[project]\nname = 'app' # The name.\nversion = \"0.2.0\"\nauthors = [\n    \"A\",\n]\n"));
+        assert!(edited
+            .contains("fmt = { version = \"10.2\", features = [\"os\"] }\ngtest = \"^1.14\"\n"));
+        assert!(!edited.contains("zlib") && !edited.contains("client"));
+
+        config.dependencies.insert(
+            "util".to_string(),
+            Dependency::Detailed(crate::DetailedDependency {
+                path: Some("../util".into()),
+                ..Default::default()
+            }),
+        );
+        assert!(config
+            .edit_str(manifest)?
+            .contains("\nutil = { path = \"../util\" }\n"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,305 @@
+//! The errors of parsing the manifest.
+//!
+//! A [`ConfigError::Parse`] error points at the offending part of `Coppo.toml`,
+//! with the dotted key it belongs to and a suggestion to fix it if there is one,
+//! so it can be rendered like a compiler diagnostic.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The position of the offending part of the manifest, the line and column start from `1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    /// The line number.
+    pub line: usize,
+    /// The column number, counted in characters.
+    pub column: usize,
+    /// The number of the characters in the span, on the same line.
+    pub length: usize,
+}
+
+/// An error of reading or parsing the manifest.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The manifest can not be read.
+    Read {
+        /// The manifest.
+        file: PathBuf,
+        /// The reason.
+        error: io::Error,
+    },
+    /// The manifest is not valid.
+    Parse(Box<ParseError>),
+}
+
+/// The details of [`ConfigError::Parse`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The reason, like ``missing field `version` ``.
+    pub message: String,
+    /// The manifest, it is `None` if the manifest is parsed from a string.
+    pub file: Option<PathBuf>,
+    /// The offending part of the manifest.
+    pub span: Option<Span>,
+    /// The dotted key of the offending part, like `dependencies.fmt`.
+    pub key: Option<String>,
+    /// How to fix the error.
+    pub suggestion: Option<String>,
+}
+
+impl ConfigError {
+    /// Create the error from the TOML error, with the source to locate it.
+    pub(crate) fn from_toml(error: toml::de::Error, source: &str) -> Self {
+        let message = error.message().trim().to_owned();
+        let span = error
+            .span()
+            .map(|range| span(source, range.start, range.end));
+        let key = error.span().and_then(|range| key_at(source, range.start));
+        let suggestion = suggest(&message, key.as_deref());
+
+        ConfigError::Parse(Box::new(ParseError {
+            message,
+            file: None,
+            span,
+            key,
+            suggestion,
+        }))
+    }
+
+    /// Attach the manifest to the error.
+    pub fn with_file(mut self, path: &Path) -> Self {
+        if let ConfigError::Parse(error) = &mut self {
+            error.file = Some(path.to_owned());
+        }
+
+        self
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read { file, error } => {
+                write!(f, "Failed to read `{}`: {}", file.display(), error)
+            }
+            ConfigError::Parse(error) => {
+                let ParseError {
+                    message,
+                    file,
+                    span,
+                    key,
+                    suggestion,
+                } = error.as_ref();
+                match (file, span) {
+                    (Some(file), Some(span)) => {
+                        write!(f, "{}:{}:{}: ", file.display(), span.line, span.column)?
+                    }
+                    (Some(file), None) => write!(f, "{}: ", file.display())?,
+                    (None, Some(span)) => {
+                        write!(f, "line {}, column {}: ", span.line, span.column)?
+                    }
+                    (None, None) => {}
+                }
+                write!(f, "{}", message)?;
+                if let Some(key) = key {
+                    write!(f, " (at `{}`)", key)?;
+                }
+                if let Some(suggestion) = suggestion {
+                    write!(f, "\nhelp: {}", suggestion)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigError::Read { error, .. } => Some(error),
+            ConfigError::Parse(_) => None,
+        }
+    }
+}
+
+/// The span of the byte range, cut at the end of its first line.
+fn span(source: &str, start: usize, end: usize) -> Span {
+    let start = start.min(source.len());
+    let before = &source[..start];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let rest = &source[start..end.clamp(start, source.len())];
+
+    Span {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+        length: rest
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .count()
+            .max(1),
+    }
+}
+
+/// The dotted key of the position, from the table header above it and the keys before it on its line.
+fn key_at(source: &str, position: usize) -> Option<String> {
+    let position = position.min(source.len());
+    let line_start = source[..position].rfind('\n').map_or(0, |i| i + 1);
+
+    let line = &source[line_start..position];
+    if line.trim().is_empty() && source[position..].starts_with('[') {
+        let header = source[line_start..].lines().next().unwrap_or_default();
+        return Some(header.trim().trim_matches(['[', ']']).trim().to_owned());
+    }
+
+    let mut keys = source[..line_start]
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| line.starts_with('['))
+        .map(|header| vec![header.trim_matches(['[', ']']).trim().to_owned()])
+        .unwrap_or_default();
+    keys.extend(inline_keys(line));
+
+    match keys.is_empty() {
+        true => None,
+        false => Some(keys.join(".")),
+    }
+}
+
+/// The keys of the nested inline tables which are open at the end of the line,
+/// like `fmt` and `version` of `fmt = { version = `.
+fn inline_keys(line: &str) -> Vec<String> {
+    let mut keys: Vec<String> = vec![];
+    let mut depth = 0;
+    let mut token = String::new();
+    let mut quote = None;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') if token.trim().is_empty() && !keys.is_empty() => quote = Some(c),
+            (None, '{') => {
+                depth += 1;
+                token.clear();
+            }
+            (None, '}') => {
+                depth -= 1;
+                keys.truncate(depth);
+                token.clear();
+            }
+            (None, ',') => token.clear(),
+            (None, '=') => {
+                keys.truncate(depth);
+                keys.push(token.trim().trim_matches(['"', '\'']).to_owned());
+                token.clear();
+            }
+            (None, c) => token.push(c),
+        }
+    }
+
+    keys
+}
+
+/// The suggestion to fix the error, from the message and the key.
+fn suggest(message: &str, key: Option<&str>) -> Option<String> {
+    if let Some(field) = between(message, "missing field `", "`") {
+        return Some(match key {
+            Some(key) => format!("add `{} = ...` to `[{}]`", field, key),
+            None => format!("add `{} = ...`", field),
+        });
+    }
+    if let Some(unknown) = between(message, "unknown field `", "`")
+        .or_else(|| between(message, "unknown variant `", "`"))
+    {
+        let expected = message.split("expected ").nth(1).unwrap_or_default();
+        let closest = expected
+            .split('`')
+            .skip(1)
+            .step_by(2)
+            .map(|candidate| (distance(unknown, candidate), candidate))
+            .filter(|(distance, _)| *distance <= 2)
+            .min();
+        return closest.map(|(_, candidate)| format!("did you mean `{}`?", candidate));
+    }
+
+    let key = key.unwrap_or_default();
+    let version = key.ends_with("version") && message.contains("while parsing");
+    if message.starts_with("invalid version requirement")
+        || (version && key.contains("dependencies"))
+    {
+        Some("use a version requirement like `1.2`, `~1.2.3` or `>=1.2, <2`".to_owned())
+    } else if version {
+        Some("use a semantic version like `1.0.0` or `1.0.0-alpha`".to_owned())
+    } else {
+        None
+    }
+}
+
+/// The text between the prefix and the suffix.
+fn between<'a>(message: &'a str, prefix: &str, suffix: &str) -> Option<&'a str> {
+    let start = message.find(prefix)? + prefix.len();
+    let end = message[start..].find(suffix)? + start;
+
+    Some(&message[start..end])
+}
+
+/// The edit distance between the words.
+pub(crate) fn distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = match ca == *cb {
+                true => previous,
+                false => 1 + previous.min(row[j]).min(row[j + 1]),
+            };
+            previous = current;
+        }
+    }
+
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_key_at() {
+        let source = "[project]\nname = \"app\"\n\n[dependencies]\nfmt = { version = \"x\" }\n";
+        assert_eq!(
+            key_at(source, source.find("\"app\"").unwrap()).as_deref(),
+            Some("project.name")
+        );
+        assert_eq!(
+            key_at(source, source.find("\"x\"").unwrap()).as_deref(),
+            Some("dependencies.fmt.version")
+        );
+        assert_eq!(key_at(source, 0).as_deref(), Some("project"));
+    }
+
+    #[test]
+    fn test_suggest() {
+        assert_eq!(
+            suggest("missing field `version`", Some("project")).as_deref(),
+            Some("add `version = ...` to `[project]`")
+        );
+        assert_eq!(
+            suggest(
+                "unknown variant `doxgen`, expected `doxygen` or `builtin`",
+                None
+            )
+            .as_deref(),
+            Some("did you mean `doxygen`?")
+        );
+        assert_eq!(distance("kitten", "sitting"), 3);
+    }
+}
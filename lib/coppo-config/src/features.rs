@@ -0,0 +1,201 @@
+//! The features of the project, the `[features]` table.
+//!
+//! A feature enables other features and the optional dependencies given as `dep:<name>`,
+//! and each enabled feature defines the macro `COPPO_FEATURE_<NAME>` for the sources.
+//! The `default` feature is enabled unless `--no-default-features` is given:
+//!
+//! ```toml
+//! [features]
+//! default = ["logging"]
+//! logging = []
+//! json = ["dep:nlohmann_json"]
+//! full = ["logging", "json"]
+//!
+//! [dependencies]
+//! nlohmann_json = { version = "3.11", optional = true }
+//! ```
+
+use std::collections::BTreeSet;
+
+use crate::{Config, E};
+
+/// The name of the feature enabled by default.
+pub const DEFAULT_FEATURE: &str = "default";
+
+/// The prefix of the entries which enable an optional dependency.
+pub const DEP_PREFIX: &str = "dep:";
+
+/// The features selected on the command line.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FeatureSelection {
+    /// The features to enable, besides the default ones.
+    pub features: Vec<String>,
+    /// Do not enable the `default` feature.
+    pub no_default_features: bool,
+    /// Enable all the features.
+    pub all_features: bool,
+}
+
+impl Config {
+    /// Check if the entries of the features refer to the features or the optional dependencies.
+    pub fn check_features(&self) -> Result<(), String> {
+        for (feature, entries) in &self.features {
+            for entry in entries {
+                self.check_entry(entry)
+                    .map_err(|e| format!("The feature `{}` is invalid: {}", feature, e))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_entry(&self, entry: &str) -> Result<(), String> {
+        match entry.strip_prefix(DEP_PREFIX) {
+            Some(name) => match self.find_dependency(name) {
+                Some(dependency) if dependency.optional() => Ok(()),
+                Some(_) => Err(format!("the dependency `{}` is not optional", name)),
+                None => Err(format!("the dependency `{}` is not found", name)),
+            },
+            None if self.features.contains_key(entry) => Ok(()),
+            None => Err(format!("the feature `{}` is not found", entry)),
+        }
+    }
+
+    /// The enabled features, from the selection of [`Config::feature_selection`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use coppo_config::Config;
+    /// let mut config = Config::from_str(r#"
+    ///     [features]
+    ///     default = ["logging"]
+    ///     logging = []
+    ///     json = ["dep:json"]
+    ///
+    ///     [dependencies]
+    ///     json = { version = "3.11", optional = true }
+    /// "#).expect("Failed to parse config file.");
+    ///
+    /// assert_eq!(config.enabled_features().unwrap(), ["default", "logging"].map(String::from).into());
+    /// assert!(!config.is_dependency_enabled("json"));
+    ///
+    /// config.feature_selection.features = vec!["json".to_string()];
+    /// assert!(config.is_dependency_enabled("json"));
+    /// ```
+    pub fn enabled_features(&self) -> Result<BTreeSet<String>, E> {
+        let selection = &self.feature_selection;
+        let mut pending = match selection.all_features {
+            true => self.features.keys().cloned().collect(),
+            false => selection.features.clone(),
+        };
+        if !selection.no_default_features && self.features.contains_key(DEFAULT_FEATURE) {
+            pending.push(DEFAULT_FEATURE.to_owned());
+        }
+
+        let mut enabled = BTreeSet::new();
+        while let Some(feature) = pending.pop() {
+            let Some(entries) = self.features.get(&feature) else {
+                return Err(format!(
+                    "The feature `{}` is not found in `[features]` of `{}`",
+                    feature, self.project.name
+                )
+                .into());
+            };
+            if enabled.insert(feature) {
+                pending.extend(
+                    entries
+                        .iter()
+                        .filter(|entry| !entry.starts_with(DEP_PREFIX))
+                        .cloned(),
+                );
+            }
+        }
+
+        Ok(enabled)
+    }
+
+    /// Check if the dependency is used, an optional one must be enabled by a feature.
+    pub fn is_dependency_enabled(&self, name: &str) -> bool {
+        match self.find_dependency(name) {
+            Some(dependency) if dependency.optional() => {
+                let entry = format!("{}{}", DEP_PREFIX, name);
+                self.enabled_features()
+                    .unwrap_or_default()
+                    .iter()
+                    .any(|feature| self.features[feature].contains(&entry))
+            }
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    /// The `-D` flags of the enabled features, like `-DCOPPO_FEATURE_LOGGING`.
+    pub fn feature_defines(&self) -> Vec<String> {
+        self.enabled_features()
+            .unwrap_or_default()
+            .iter()
+            .filter(|feature| *feature != DEFAULT_FEATURE)
+            .map(|feature| format!("-D{}", define(feature)))
+            .collect()
+    }
+}
+
+/// The macro defined by the feature, like `COPPO_FEATURE_LOGGING` for `logging`.
+pub fn define(feature: &str) -> String {
+    let name = feature
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c.to_ascii_uppercase(),
+            false => '_',
+        })
+        .collect::<String>();
+
+    format!("COPPO_FEATURE_{}", name)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_features() -> Result<(), E> {
+        let mut config = Config::from_str(
+            r#"
+            [features]
+            default = ["std-io"]
+            std-io = []
+            json = ["dep:json"]
+            full = ["std-io", "json"]
+
+            [dependencies]
+            json = { version = "3.11", optional = true }
+            fmt = "10"
+            "#,
+        )?;
+        config.check_features()?;
+        assert_eq!(config.feature_defines(), vec!["-DCOPPO_FEATURE_STD_IO"]);
+        assert!(config.is_dependency_enabled("fmt"));
+        assert!(!config.is_dependency_enabled("json"));
+
+        config.feature_selection = FeatureSelection {
+            features: vec!["full".to_string()],
+            no_default_features: true,
+            all_features: false,
+        };
+        assert_eq!(
+            config.enabled_features()?,
+            ["full", "json", "std-io"].map(String::from).into()
+        );
+        assert!(config.is_dependency_enabled("json"));
+
+        config.feature_selection.features = vec!["unknown".to_string()];
+        assert!(config.enabled_features().is_err());
+
+        config
+            .features
+            .insert("bad".to_string(), vec!["dep:fmt".to_string()]);
+        assert!(config.check_features().is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+//! Discover the files of the project.
+//!
+//! The files are filtered by the `include` and `exclude` globs of the `[project]` table,
+//! the globs are matched against the paths relative to the project root, like `src/**/*.cpp`.
+//! The `target` directory and the hidden directories like `.git` are always skipped.
+//!
+//! The same filter selects the files of a distributable package, see [`package_files`].
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use glob::{MatchOptions, Pattern};
+
+use crate::{Project, CONFIG_FILE};
+
+type E = Box<dyn std::error::Error>;
+
+/// The extensions of the C++ source files.
+pub const SOURCE_EXTENSIONS: &[&str] = &["cpp", "cc", "cxx", "c++", "c"];
+
+/// The extensions of the C++ header files.
+pub const HEADER_EXTENSIONS: &[&str] = &["hpp", "hh", "hxx", "h++", "h", "ipp", "inl"];
+
+/// The directories which never belong to the project files.
+const SKIPPED_DIRS: &[&str] = &["target"];
+
+/// The options of the glob matching, `*` does not match the path separator.
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// The filter of the project files, built from the `include` and `exclude` globs.
+#[derive(Debug, Default)]
+pub struct FileFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl FileFilter {
+    /// Build the filter from the `[project]` table.
+    /// The globs must be valid and relative to the project root.
+    pub fn new(project: &Project) -> Result<Self, E> {
+        let compile = |globs: &[String]| {
+            globs
+                .iter()
+                .map(|glob| {
+                    if glob.starts_with(['/', '\\']) || glob.split(['/', '\\']).any(|c| c == "..") {
+                        return Err(format!(
+                            "Invalid glob `{}`: it must be relative to the project root",
+                            glob
+                        ));
+                    }
+                    Pattern::new(glob).map_err(|e| format!("Invalid glob `{}`: {}", glob, e))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        Ok(Self {
+            include: compile(&project.include)?,
+            exclude: compile(&project.exclude)?,
+        })
+    }
+
+    /// Check if the path, relative to the project root, belongs to the project.
+    /// If no `include` glob is given, every path is included.
+    ///
+    /// # Example
+    /// ```rust
+    /// use coppo_config::{files::FileFilter, Project};
+    ///
+    /// let project = Project {
+    ///     exclude: vec!["third_party/**".to_string()],
+    ///     ..Default::default()
+    /// };
+    /// let filter = FileFilter::new(&project).unwrap();
+    ///
+    /// assert!(filter.matches("src/main.cpp"));
+    /// assert!(!filter.matches("third_party/json/json.hpp"));
+    /// ```
+    pub fn matches(&self, path: impl AsRef<Path>) -> bool {
+        let path = path.as_ref();
+        let matches = |pattern: &Pattern| pattern.matches_path_with(path, MATCH_OPTIONS);
+
+        (self.include.is_empty() || self.include.iter().any(matches))
+            && !self.exclude.iter().any(matches)
+    }
+}
+
+/// Get the files of the project with the given extensions,
+/// the paths are relative to the project root and sorted.
+pub fn project_files(
+    project: &Project,
+    root: impl AsRef<Path>,
+    extensions: &[&str],
+) -> Result<Vec<PathBuf>, E> {
+    let filter = FileFilter::new(project)?;
+
+    walk(root.as_ref(), |path| {
+        path.extension()
+            .is_some_and(|ext| extensions.iter().any(|extension| ext == *extension))
+            && filter.matches(path)
+    })
+}
+
+/// Get the files of a distributable package of the project,
+/// all the files matching the `include` and `exclude` globs, whatever their extensions are.
+/// The manifest is always a part of the package.
+pub fn package_files(project: &Project, root: impl AsRef<Path>) -> Result<Vec<PathBuf>, E> {
+    let filter = FileFilter::new(project)?;
+
+    walk(root.as_ref(), |path| {
+        path == Path::new(CONFIG_FILE) || filter.matches(path)
+    })
+}
+
+/// Walk the project directory, and get the sorted files accepted by the predicate.
+fn walk(root: &Path, accept: impl Fn(&Path) -> bool) -> Result<Vec<PathBuf>, E> {
+    let mut files = vec![];
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(root.join(&dir))? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let path = dir.join(&*name);
+
+            if entry.file_type()?.is_dir() {
+                if !name.starts_with('.') && !SKIPPED_DIRS.contains(&&*name) {
+                    dirs.push(path);
+                }
+            } else if accept(&path) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_filter() -> Result<(), E> {
+        let project = Project {
+            include: vec!["src/**".to_string(), "include/**/*.hpp".to_string()],
+            exclude: vec!["src/generated/*".to_string()],
+            ..Default::default()
+        };
+        let filter = FileFilter::new(&project)?;
+
+        assert!(filter.matches("src/main.cpp"));
+        assert!(filter.matches("src/core/core.cpp"));
+        assert!(filter.matches("include/core/core.hpp"));
+        assert!(!filter.matches("include/core/core.h"));
+        assert!(!filter.matches("src/generated/parser.cpp"));
+        assert!(!filter.matches("tests/smoke.cpp"));
+
+        for glob in ["[", "/usr/include/**", "../shared/**"] {
+            let project = Project {
+                include: vec![glob.to_string()],
+                ..Default::default()
+            };
+            assert!(FileFilter::new(&project).is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_package_files() -> Result<(), E> {
+        let root = std::env::temp_dir().join(format!("coppo-package-{}", std::process::id()));
+        for file in [
+            CONFIG_FILE,
+            "README.md",
+            "src/main.cpp",
+            "docs/notes.md",
+            "target/debug/app",
+            ".git/HEAD",
+        ] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, "")?;
+        }
+
+        let project = Project {
+            exclude: vec!["docs/**".to_string()],
+            ..Default::default()
+        };
+        let files = package_files(&project, &root);
+        fs::remove_dir_all(&root)?;
+
+        assert_eq!(
+            files?,
+            [CONFIG_FILE, "README.md", "src/main.cpp"].map(PathBuf::from)
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,500 @@
+//! The global configuration of Coppo.
+//! The file is `~/.coppo/config.toml`, and it looks like this:
+//!
+//! ```toml
+//! [new]
+//! authors = ["Your Name <your@email.com>"]
+//!
+//! [build]
+//! compiler = "g++"
+//! flags = ["-Wall"]
+//!
+//! [target.x86_64-w64-mingw32]
+//! compiler = "x86_64-w64-mingw32-g++"
+//! sysroot = "/usr/x86_64-w64-mingw32"
+//!
+//! [registries.company]
+//! index = "https://git.example.com/cpp-index"
+//! mirrors = ["https://mirror.example.com/cpp-index"]
+//!
+//! [http]
+//! proxy = "http://proxy.example.com:8080"
+//! timeout = 30
+//! retries = 2
+//!
+//! [term]
+//! color = "auto"
+//! ```
+//!
+//! The CLI loads it into [`Config::global`](crate::Config::global),
+//! the settings of the project take precedence over it.
+//!
+//! It can be edited with `coppo config set <KEY> <VALUE>`,
+//! the formatting and the comments of the file are preserved.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use toml_edit::{Array, DocumentMut, Item, Table, Value};
+
+use crate::{coppo_home, E};
+
+/// The global configuration file name, it is in the Coppo home directory.
+pub const GLOBAL_CONFIG_FILE: &str = "config.toml";
+
+/// The global configuration in `~/.coppo/config.toml`.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct GlobalConfig {
+    /// The defaults of `coppo new`.
+    #[serde(default)]
+    pub new: NewSettings,
+    /// The defaults of `coppo build`.
+    #[serde(default)]
+    pub build: BuildSettings,
+    /// The settings of the cross-compilation targets, the `[target.<triple>]` tables.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub target: BTreeMap<String, TargetSettings>,
+    /// The package registries, the `[registries.<name>]` tables.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub registries: BTreeMap<String, Registry>,
+    /// The settings of the downloads.
+    #[serde(default)]
+    pub http: HttpSettings,
+    /// The settings of the terminal output.
+    #[serde(default)]
+    pub term: TermSettings,
+}
+
+/// The `[new]` section of the global configuration.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct NewSettings {
+    /// The default authors of the new projects.
+    /// If it is empty, the authors will be detected from git and the environment.
+    #[serde(default)]
+    pub authors: Vec<String>,
+    /// The default version control system of the new projects, `git` or `none`.
+    /// If it is not specified, a git repository will be initialized.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vcs: Option<String>,
+    /// The default SPDX license expression of the new projects, like `MIT OR Apache-2.0`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+}
+
+/// The `[build]` section of the global configuration.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct BuildSettings {
+    /// The compiler to use instead of the default one, like `g++`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compiler: Option<String>,
+    /// The compiler wrapper of all the projects, like `ccache`, an empty one disables it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wrapper: Option<String>,
+    /// The compile flags of all the projects, before the flags of the project.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<String>,
+}
+
+/// The settings of a cross-compilation target, a `[target.<triple>]` table,
+/// used by `coppo build --target <triple>`.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TargetSettings {
+    /// The compiler of the target, like `x86_64-w64-mingw32-g++`.
+    /// Without it, the compiler of the host is used with `--target=<triple>`, like Clang.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compiler: Option<String>,
+    /// The root directory of the headers and the libraries of the target, `--sysroot`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sysroot: Option<PathBuf>,
+    /// The linker, like `lld`, `-fuse-ld=<linker>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub linker: Option<String>,
+    /// The extra compile flags of the target.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<String>,
+}
+
+/// A package registry, a `[registries.<name>]` table of the global configuration.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Registry {
+    /// The URL of the index of the registry.
+    pub index: String,
+    /// The URLs of the mirrors of the index, tried in order before the index itself.
+    /// They are only used to download, the lockfile keeps the URL of the index.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<String>,
+}
+
+/// The `[http]` section of the global configuration, the settings of the downloads.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpSettings {
+    /// The proxy, like `http://proxy.example.com:8080`.
+    /// Without it, the `https_proxy` and `http_proxy` environment variables are used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// The timeout of connecting to a server, or of a stalled download, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// How many times a download failing with a transient error is retried.
+    #[serde(default)]
+    pub retries: u32,
+}
+
+impl HttpSettings {
+    /// The arguments of `curl` for the settings.
+    ///
+    /// ```rust
+    /// use coppo_config::global::HttpSettings;
+    ///
+    /// let http = HttpSettings {
+    ///     proxy: Some("http://proxy:8080".to_string()),
+    ///     timeout: None,
+    ///     retries: 2,
+    /// };
+    /// assert_eq!(http.curl_args(), ["--proxy", "http://proxy:8080", "--retry", "2"]);
+    /// ```
+    pub fn curl_args(&self) -> Vec<String> {
+        let mut args = vec![];
+        if let Some(proxy) = &self.proxy {
+            args.extend(["--proxy".to_owned(), proxy.clone()]);
+        }
+        if let Some(timeout) = self.timeout {
+            args.extend([
+                "--connect-timeout".to_owned(),
+                timeout.to_string(),
+                "--speed-limit".to_owned(),
+                "1".to_owned(),
+                "--speed-time".to_owned(),
+                timeout.to_string(),
+            ]);
+        }
+        if self.retries > 0 {
+            args.extend(["--retry".to_owned(), self.retries.to_string()]);
+        }
+
+        args
+    }
+}
+
+/// The `[term]` section of the global configuration.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TermSettings {
+    /// When the output is colored.
+    #[serde(default)]
+    pub color: ColorChoice,
+}
+
+/// When the output is colored.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// Colored if the output is a terminal and `NO_COLOR` is not set.
+    #[default]
+    Auto,
+    /// Always colored.
+    Always,
+    /// Never colored.
+    Never,
+}
+
+/// The type of the value of a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    /// A string.
+    String,
+    /// A list of strings, given as `a, b` or as a TOML array.
+    StringList,
+    /// A non-negative integer.
+    Integer,
+}
+
+/// A key of the global configuration which can be set by `coppo config set`.
+#[derive(Debug, Clone, Copy)]
+pub struct Key {
+    /// The dotted name of the key, like `build.compiler`.
+    pub name: &'static str,
+    /// The type of the value.
+    pub kind: ValueKind,
+    /// The description of the key.
+    pub description: &'static str,
+}
+
+/// All the keys of the global configuration.
+pub const KEYS: &[Key] = &[
+    Key {
+        name: "new.authors",
+        kind: ValueKind::StringList,
+        description: "The default authors of the new projects",
+    },
+    Key {
+        name: "new.vcs",
+        kind: ValueKind::String,
+        description: "The default version control system of the new projects, `git` or `none`",
+    },
+    Key {
+        name: "new.license",
+        kind: ValueKind::String,
+        description: "The default SPDX license expression of the new projects, like `MIT`",
+    },
+    Key {
+        name: "build.compiler",
+        kind: ValueKind::String,
+        description: "The compiler to use instead of the default one",
+    },
+    Key {
+        name: "build.wrapper",
+        kind: ValueKind::String,
+        description: "The compiler wrapper, like `ccache`, or an empty one to disable it",
+    },
+    Key {
+        name: "build.flags",
+        kind: ValueKind::StringList,
+        description: "The compile flags of all the projects",
+    },
+    Key {
+        name: "http.proxy",
+        kind: ValueKind::String,
+        description: "The proxy of the downloads, like `http://proxy.example.com:8080`",
+    },
+    Key {
+        name: "http.timeout",
+        kind: ValueKind::Integer,
+        description: "The timeout of connecting to a server or of a stalled download, in seconds",
+    },
+    Key {
+        name: "http.retries",
+        kind: ValueKind::Integer,
+        description: "How many times a download failing with a transient error is retried",
+    },
+    Key {
+        name: "term.color",
+        kind: ValueKind::String,
+        description: "When the output is colored, `auto`, `always` or `never`",
+    },
+];
+
+impl Key {
+    /// Find the key by its name.
+    pub fn find(name: &str) -> Result<&'static Key, String> {
+        KEYS.iter().find(|key| key.name == name).ok_or_else(|| {
+            let names = KEYS.iter().map(|key| key.name).collect::<Vec<_>>();
+            format!(
+                "Unknown key `{}`, the available keys are: {}",
+                name,
+                names.join(", ")
+            )
+        })
+    }
+
+    /// The table name and the field name of the key.
+    fn split(&self) -> (&'static str, &'static str) {
+        self.name.split_once('.').unwrap_or(("", self.name))
+    }
+
+    /// Parse the value from the command line.
+    pub fn parse(&self, value: &str) -> Result<Value, String> {
+        match self.kind {
+            ValueKind::String => Ok(Value::from(value)),
+            ValueKind::Integer => value
+                .trim()
+                .parse::<u32>()
+                .map(|value| Value::from(i64::from(value)))
+                .map_err(|_| format!("`{}` must be a non-negative integer", self.name)),
+            ValueKind::StringList if value.trim_start().starts_with('[') => {
+                let array = value
+                    .parse::<Value>()
+                    .map_err(|e| format!("Invalid array `{}`: {}", value, e))?;
+                match array.as_array() {
+                    Some(items) if items.iter().all(|item| item.is_str()) => Ok(array),
+                    _ => Err(format!("`{}` must be an array of strings", self.name)),
+                }
+            }
+            ValueKind::StringList => Ok(Value::Array(
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .collect::<Array>(),
+            )),
+        }
+    }
+}
+
+impl GlobalConfig {
+    /// The path of the global configuration file.
+    pub fn path() -> Option<PathBuf> {
+        coppo_home().map(|home| home.join(GLOBAL_CONFIG_FILE))
+    }
+
+    /// Parse the global configuration file.
+    /// It returns the default configuration if the file does not exist.
+    pub fn from_file() -> Result<GlobalConfig, E> {
+        match GlobalConfig::path() {
+            Some(path) if path.exists() => {
+                let config = fs::read_to_string(&path)?;
+                toml::from_str(&config)
+                    .map_err(|e| format!("Failed to parse `{}`: {}", path.display(), e).into())
+            }
+            _ => Ok(GlobalConfig::default()),
+        }
+    }
+
+    /// Get the value of the key, it returns `None` if the key is not set.
+    /// A string is returned as it is, and other values are returned in the TOML format.
+    pub fn get(name: &str) -> Result<Option<String>, E> {
+        let key = Key::find(name)?;
+        let (_, document) = document()?;
+
+        let (table, field) = key.split();
+        let value = document
+            .get(table)
+            .and_then(|table| table.get(field))
+            .and_then(Item::as_value);
+
+        Ok(value.map(|value| match value.as_str() {
+            Some(value) => value.to_owned(),
+            None => value.to_string().trim().to_owned(),
+        }))
+    }
+
+    /// Get all the keys which are set, with their values.
+    pub fn list() -> Result<Vec<(&'static str, String)>, E> {
+        let mut values = vec![];
+        for key in KEYS {
+            if let Some(value) = GlobalConfig::get(key.name)? {
+                values.push((key.name, value));
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Set the value of the key, the rest of the file is kept as it is.
+    pub fn set(name: &str, value: &str) -> Result<(), E> {
+        let key = Key::find(name)?;
+        let mut value = key.parse(value)?;
+
+        edit(|document| {
+            let (table, field) = key.split();
+            let table = document
+                .entry(table)
+                .or_insert_with(|| Item::Table(Table::new()))
+                .as_table_like_mut()
+                .ok_or_else(|| format!("`{}` is not a table", table))?;
+            // Keep the comments around the old value.
+            if let Some(old) = table.get(field).and_then(Item::as_value) {
+                *value.decor_mut() = old.decor().clone();
+            }
+            table.insert(field, Item::Value(value));
+            Ok(())
+        })
+    }
+
+    /// Remove the key, the rest of the file is kept as it is.
+    pub fn unset(name: &str) -> Result<(), E> {
+        let key = Key::find(name)?;
+
+        edit(|document| {
+            let (table, field) = key.split();
+            if let Some(table) = document.get_mut(table).and_then(Item::as_table_like_mut) {
+                table.remove(field);
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Read the global configuration file as a document, it is empty if the file does not exist.
+fn document() -> Result<(PathBuf, DocumentMut), E> {
+    let path = GlobalConfig::path().ok_or("Could not find the Coppo home directory.")?;
+    let content = match path.exists() {
+        true => fs::read_to_string(&path)?,
+        false => String::new(),
+    };
+    let document = content
+        .parse::<DocumentMut>()
+        .map_err(|e| format!("Failed to parse `{}`: {}", path.display(), e))?;
+
+    Ok((path, document))
+}
+
+/// Edit the global configuration file,
+/// the result is validated before it is written.
+fn edit(f: impl FnOnce(&mut DocumentMut) -> Result<(), E>) -> Result<(), E> {
+    let (path, mut document) = document()?;
+    f(&mut document)?;
+
+    let content = document.to_string();
+    toml::from_str::<GlobalConfig>(&content)
+        .map_err(|e| format!("Invalid configuration: {}", e))?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_value() {
+        let authors = Key::find("new.authors").unwrap();
+        assert_eq!(
+            authors.parse("A <a@b.c>, B").unwrap().to_string(),
+            r#"["A <a@b.c>", "B"]"#
+        );
+        assert_eq!(
+            authors.parse(r#"["A", "B"]"#).unwrap().to_string(),
+            r#"["A", "B"]"#
+        );
+        assert!(authors.parse("[1, 2]").is_err());
+
+        let compiler = Key::find("build.compiler").unwrap();
+        assert_eq!(compiler.parse("g++").unwrap().as_str(), Some("g++"));
+
+        let retries = Key::find("http.retries").unwrap();
+        assert_eq!(retries.parse("3").unwrap().as_integer(), Some(3));
+        assert!(retries.parse("-1").is_err());
+
+        assert!(Key::find("build.linker").is_err());
+    }
+
+    #[test]
+    fn test_global_config() {
+        let global: GlobalConfig = toml::from_str(
+            r#"
+            [build]
+            flags = ["-Wall"]
+
+            [registries.company]
+            index = "https://git.example.com/cpp-index"
+            mirrors = ["https://mirror.example.com/cpp-index"]
+
+            [http]
+            proxy = "http://proxy.example.com:8080"
+            retries = 2
+
+            [term]
+            color = "never"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(global.build.flags, vec!["-Wall"]);
+        assert_eq!(
+            global.registries["company"].index,
+            "https://git.example.com/cpp-index"
+        );
+        assert_eq!(global.registries["company"].mirrors.len(), 1);
+        assert_eq!(global.http.retries, 2);
+        assert_eq!(global.http.timeout, None);
+        assert_eq!(global.term.color, ColorChoice::Never);
+        assert!(toml::from_str::<GlobalConfig>("[term]\ncolor = \"yes\"").is_err());
+    }
+}
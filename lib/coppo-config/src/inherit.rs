@@ -0,0 +1,226 @@
+//! Inherit the fields of the project from the workspace root.
+//!
+//! The members share the fields of the `[workspace.package]` table of the root,
+//! by writing `<field>.workspace = true` instead of the value:
+//!
+//! ```toml
+//! # Coppo.toml of the root.
+//! [workspace]
+//! members = ["libs/*"]
+//!
+//! [workspace.package]
+//! version = "1.2.0"
+//! authors = ["The App Team"]
+//! license = "MIT"
+//!
+//! # libs/core/Coppo.toml
+//! [project]
+//! name = "core"
+//! version.workspace = true
+//! authors.workspace = true
+//! ```
+//!
+//! The inherited fields are resolved when the manifest is loaded by [`Config::from_path`],
+//! and they are kept as they are when the manifest is saved.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use toml_edit::{DocumentMut, Item};
+
+use crate::error::ParseError;
+use crate::{Config, ConfigError, Version, CONFIG_FILE};
+
+/// The fields of the project which can be inherited.
+pub const INHERITABLE: &[&str] = &["version", "authors", "license", "repository"];
+
+/// The fields shared by the members, the `[workspace.package]` table.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct WorkspacePackage {
+    /// The version of the members.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<Version>,
+    /// The authors of the members.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authors: Option<Vec<String>>,
+    /// The license of the members.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// The repository of the members.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+}
+
+/// Replace the inherited fields of the manifest with the values of the workspace root,
+/// and return the resolved manifest with the names of the inherited fields.
+/// The manifest is returned as it is if it does not inherit any field.
+pub(crate) fn resolve(
+    source: &str,
+    path: &Path,
+) -> Result<(String, BTreeSet<String>), ConfigError> {
+    let mut inherited = BTreeSet::new();
+    // The invalid manifest is reported by the parser.
+    let Ok(mut document) = source.parse::<DocumentMut>() else {
+        return Ok((source.to_owned(), inherited));
+    };
+    let Some((table, project)) = project_table(&mut document) else {
+        return Ok((source.to_owned(), inherited));
+    };
+    for field in INHERITABLE {
+        if project.get(field).is_some_and(is_inherited) {
+            inherited.insert(field.to_string());
+        }
+    }
+    if inherited.is_empty() {
+        return Ok((source.to_owned(), inherited));
+    }
+
+    let error = |message: String, field: &str, suggestion: Option<String>| {
+        ConfigError::Parse(Box::new(ParseError {
+            message,
+            file: Some(path.to_owned()),
+            span: None,
+            key: Some(format!("{}.{}", table, field)),
+            suggestion,
+        }))
+    };
+    let Some((root, package)) = workspace_package(path)? else {
+        let field = inherited.first().cloned().unwrap_or_default();
+        return Err(error(
+            format!(
+                "`{}` inherits from the workspace, but no workspace is found",
+                field
+            ),
+            &field,
+            Some("add the project to the `members` of a `[workspace]`".to_owned()),
+        ));
+    };
+    for field in &inherited {
+        let Some(value) = package.get(field) else {
+            return Err(error(
+                format!(
+                    "`{}` inherits from the workspace, but `{}` does not have `workspace.package.{}`",
+                    field,
+                    root.display(),
+                    field
+                ),
+                field,
+                Some(format!("add `{} = ...` to `[workspace.package]`", field)),
+            ));
+        };
+        project.insert(field, Item::Value(value.clone()));
+    }
+
+    Ok((document.to_string(), inherited))
+}
+
+/// The name and the table of the project, `[project]` or `[package]`.
+fn project_table(
+    document: &mut DocumentMut,
+) -> Option<(&'static str, &mut dyn toml_edit::TableLike)> {
+    let table = ["project", "package"]
+        .into_iter()
+        .find(|table| document.contains_key(table))?;
+    let project = document.get_mut(table)?.as_table_like_mut()?;
+
+    Some((table, project))
+}
+
+/// Check if the item is `{ workspace = true }`.
+fn is_inherited(item: &Item) -> bool {
+    item.as_table_like()
+        .and_then(|table| table.get("workspace"))
+        .and_then(Item::as_bool)
+        .unwrap_or(false)
+}
+
+/// The values of the `[workspace.package]` table.
+type Package = BTreeMap<String, toml_edit::Value>;
+
+/// Find the nearest workspace root of the manifest, and the values of its `[workspace.package]` table.
+/// The manifest itself can be the root.
+fn workspace_package(path: &Path) -> Result<Option<(PathBuf, Package)>, ConfigError> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_owned());
+    for ancestor in dir.ancestors() {
+        let manifest = ancestor.join(CONFIG_FILE);
+        let Ok(source) = fs::read_to_string(&manifest) else {
+            continue;
+        };
+        let document = toml::from_str::<toml::Table>(&source)
+            .map_err(|e| ConfigError::from_toml(e, &source).with_file(&manifest))?;
+        let Some(workspace) = document.get("workspace").and_then(toml::Value::as_table) else {
+            continue;
+        };
+
+        let mut package = BTreeMap::new();
+        if let Some(table) = workspace.get("package").and_then(toml::Value::as_table) {
+            for (key, value) in table {
+                // The TOML value is written in the inline form, it is always a valid value.
+                if let Ok(value) = value.to_string().parse::<toml_edit::Value>() {
+                    package.insert(key.clone(), value);
+                }
+            }
+        }
+        return Ok(Some((manifest, package)));
+    }
+
+    Ok(None)
+}
+
+impl Config {
+    /// Restore the inherited fields of the project in the edited manifest,
+    /// so they still inherit from the workspace after the manifest is saved.
+    pub(crate) fn keep_inherited(&self, original: &DocumentMut, edited: &mut DocumentMut) {
+        let mut original = original.clone();
+        let (Some((_, old)), Some((_, new))) =
+            (project_table(&mut original), project_table(edited))
+        else {
+            return;
+        };
+        for field in &self.project.inherited {
+            if let Some(item) = old.get(field) {
+                new.insert(field, item.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_inherit() -> Result<(), Box<dyn std::error::Error>> {
+        let root = std::env::temp_dir().join(format!("coppo-inherit-{}", std::process::id()));
+        let member = root.join("libs/core");
+        fs::create_dir_all(&member)?;
+        fs::write(
+            root.join(CONFIG_FILE),
+            "[workspace]\nmembers = [\"libs/*\"]\n\n[workspace.package]\nversion = \"1.2.0\"\nauthors = [\"Team\"]\n",
+        )?;
+        let manifest = "[project]\nname = \"core\"\nversion.workspace = true\nauthors = { workspace = true }\n\n[dependencies]\n";
+        fs::write(member.join(CONFIG_FILE), manifest)?;
+
+        let config = Config::from_path(&member.join(CONFIG_FILE));
+        let missing = fs::write(
+            member.join(CONFIG_FILE),
+            "[project]\nname = \"core\"\nversion = \"0.1.0\"\nauthors = []\nlicense.workspace = true\n",
+        )
+        .map(|_| Config::from_path(&member.join(CONFIG_FILE)));
+        fs::remove_dir_all(&root)?;
+
+        let config = config?;
+        assert_eq!(config.project.version, Version::new(1, 2, 0));
+        assert_eq!(config.project.authors, ["Team"]);
+        assert_eq!(config.edit_str(manifest)?, manifest);
+
+        let e = missing?.unwrap_err().to_string();
+        assert!(e.contains("does not have `workspace.package.license`"));
+        assert!(e.contains("(at `project.license`)"));
+
+        Ok(())
+    }
+}
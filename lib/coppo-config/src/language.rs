@@ -0,0 +1,83 @@
+//! The language of the project, the `language` of the `[project]` table.
+//!
+//! The projects are written in C++ by default, and plain C projects set it to `c`:
+//!
+//! ```toml
+//! [project]
+//! name = "hello"
+//! version = "0.1.0"
+//! language = "c"
+//! ```
+//!
+//! A C project is built with a C compiler, like `clang` or `gcc`, with [`standard::DEFAULT_C_STD`],
+//! and its new files are `.c` sources and `.h` headers.
+//!
+//! [`standard::DEFAULT_C_STD`]: crate::standard::DEFAULT_C_STD
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// The language of the project.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    /// C++, the default.
+    #[default]
+    #[serde(rename = "c++", alias = "cpp")]
+    Cpp,
+    /// Plain C.
+    #[serde(rename = "c")]
+    C,
+}
+
+impl Language {
+    /// The names of the languages, as written in `Coppo.toml`.
+    pub const NAMES: [&'static str; 2] = ["c++", "c"];
+
+    /// Check if it is C++, the default language.
+    pub fn is_cpp(&self) -> bool {
+        *self == Language::Cpp
+    }
+
+    /// The extension of the new source files, like `cpp` for `src/main.cpp`.
+    pub fn source_extension(&self) -> &'static str {
+        match self {
+            Language::Cpp => "cpp",
+            Language::C => "c",
+        }
+    }
+
+    /// The extension of the new headers, like `hpp` for `include/core/core.hpp`.
+    pub fn header_extension(&self) -> &'static str {
+        match self {
+            Language::Cpp => "hpp",
+            Language::C => "h",
+        }
+    }
+}
+
+impl FromStr for Language {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "c++" | "cpp" => Ok(Language::Cpp),
+            "c" => Ok(Language::C),
+            _ => Err(format!(
+                "Unknown language `{}`, it should be one of {}",
+                s,
+                Language::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Language::Cpp => write!(f, "C++"),
+            Language::C => write!(f, "C"),
+        }
+    }
+}
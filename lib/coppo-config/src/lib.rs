@@ -17,16 +17,83 @@
 //! ```
 
 #![forbid(unsafe_code)]
-#![feature(assert_matches)]
 #![allow(clippy::should_implement_trait)]
 
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde::de::{self, DeserializeOwned, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::env;
+use std::fmt;
 use std::fs;
+use std::path::{Path, PathBuf};
+
+pub mod download;
+pub mod edit;
+pub mod error;
+pub mod features;
+pub mod files;
+pub mod global;
+pub mod inherit;
+pub mod language;
+pub mod lock;
+pub mod name;
+pub mod platform;
+pub mod profile;
+pub mod standard;
+pub mod toolchain;
+pub mod unused;
+
+pub use error::ConfigError;
+pub use features::FeatureSelection;
+pub use global::GlobalConfig;
+pub use language::Language;
+pub use platform::Platform;
+pub use profile::Profile;
+pub use semver::{Version, VersionReq};
 
 /// configuration file name
 pub const CONFIG_FILE: &str = "Coppo.toml";
 
+/// The name of the Coppo home directory.
+/// It is placed in the home directory of the user, like `~/.coppo`.
+pub const COPPO_HOME: &str = ".coppo";
+
+/// Get the Coppo home directory.
+/// It defaults to `~/.coppo`,
+/// and can be overridden by the `COPPO_HOME` environment variable.
+pub fn coppo_home() -> Option<PathBuf> {
+    match env::var_os("COPPO_HOME") {
+        Some(home) if !home.is_empty() => Some(PathBuf::from(home)),
+        _ => dirs::home_dir().map(|home| home.join(COPPO_HOME)),
+    }
+}
+
+/// The global artifact cache directory, relative to the Coppo home directory.
+/// The artifacts of each project are stored in the sub-directory named after the project.
+pub const ARTIFACT_CACHE_DIR: &str = "cache/artifacts";
+
+/// Get the global artifact cache directory of the project, like `~/.coppo/cache/artifacts/<name>`.
+pub fn artifact_cache(project: &str) -> Option<PathBuf> {
+    coppo_home().map(|home| home.join(ARTIFACT_CACHE_DIR).join(project))
+}
+
+/// The global download cache directory, relative to the Coppo home directory.
+/// The downloads of each package are stored in the sub-directory named after the package.
+pub const DOWNLOAD_CACHE_DIR: &str = "cache/downloads";
+
+/// Get the global download cache directory, like `~/.coppo/cache/downloads`.
+pub fn download_cache() -> Option<PathBuf> {
+    coppo_home().map(|home| home.join(DOWNLOAD_CACHE_DIR))
+}
+
+/// The cache directory of the compiled scripts, relative to the Coppo home directory.
+pub const SCRIPT_CACHE_DIR: &str = "script-cache";
+
+/// Get the cache directory of the compiled scripts, like `~/.coppo/script-cache`.
+pub fn script_cache() -> Option<PathBuf> {
+    coppo_home().map(|home| home.join(SCRIPT_CACHE_DIR))
+}
+
 /// Any error that can occur while parsing the configuration file.
 type E = Box<dyn std::error::Error>;
 
@@ -43,11 +110,85 @@ type E = Box<dyn std::error::Error>;
 /// license = "MIT"
 ///
 /// [dependencies]
+///
+/// [dev-dependencies]
+///
+/// [lib]
+/// name = "core"
+///
+/// [[bin]]
+/// name = "server"
+/// path = "src/bin/server.cpp"
 /// ```
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Config {
+    /// The project, it is empty in the manifest of a workspace without a root project.
+    /// It can also be written as `[package]`.
+    #[serde(default, alias = "package", skip_serializing_if = "Project::is_empty")]
     pub project: Project,
+    #[serde(default, deserialize_with = "name::deserialize_dependencies")]
     pub dependencies: HashMap<String, Dependency>,
+    /// The dependencies only used by the tests, the `[dev-dependencies]` table.
+    /// `coppo build` ignores them.
+    #[serde(
+        default,
+        rename = "dev-dependencies",
+        alias = "dev_dependencies",
+        skip_serializing_if = "HashMap::is_empty",
+        deserialize_with = "name::deserialize_dependencies"
+    )]
+    pub dev_dependencies: HashMap<String, Dependency>,
+    /// The target-specific dependencies, the `[target.<spec>]` tables, see [`platform`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub target: BTreeMap<String, platform::TargetDependencies>,
+    /// The features of the project, the `[features]` table, see [`features`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub features: BTreeMap<String, Vec<String>>,
+    /// The library target of the project.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lib: Option<LibTarget>,
+    /// The binary targets of the project.
+    /// If it is empty, `src/main.cpp` will be the binary named after the project.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bin: Vec<BinTarget>,
+    /// The compiler settings, the `[build]` table.
+    #[serde(default, skip_serializing_if = "BuildSettings::is_empty")]
+    pub build: BuildSettings,
+    /// The settings of `coppo lint`.
+    #[serde(default, skip_serializing_if = "LintSettings::is_empty")]
+    pub lint: LintSettings,
+    /// The settings of `coppo doc`.
+    #[serde(default, skip_serializing_if = "DocSettings::is_empty")]
+    pub doc: DocSettings,
+    /// The settings of `coppo dist`.
+    #[serde(default, skip_serializing_if = "DistSettings::is_empty")]
+    pub dist: DistSettings,
+    /// The build profiles, the `[profile.<name>]` tables, see [`profile`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profile: BTreeMap<String, Profile>,
+    /// The workspace whose root is the project.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<Workspace>,
+    /// The global configuration of the user, it is not a part of the manifest.
+    /// It is loaded by the CLI, see [`global`].
+    #[serde(skip)]
+    pub global: GlobalConfig,
+    /// The features selected on the command line, it is not a part of the manifest.
+    #[serde(skip)]
+    pub feature_selection: FeatureSelection,
+    /// The target triple selected on the command line, it is not a part of the manifest.
+    /// It is `None` for the host, see [`Config::platform`].
+    #[serde(skip)]
+    pub build_target: Option<String>,
+    /// The root directory of the project, where `Coppo.toml` is found by [`Config::find_and_load`].
+    #[serde(skip)]
+    pub root: Option<PathBuf>,
+    /// The directory Coppo is run in, it can be a sub-directory of the root.
+    #[serde(skip)]
+    pub invocation_dir: Option<PathBuf>,
+    /// The keys of the manifest which are not used, see [`unused`].
+    #[serde(skip)]
+    pub unused_keys: Vec<unused::UnusedKey>,
 }
 
 /// The project configuration.
@@ -59,17 +200,24 @@ pub struct Config {
 /// - `description`: The description of the project.
 /// - `license`: The license of the project.
 /// - `repository`: The repository of the project.
-#[derive(Debug, Default, Serialize, Deserialize)]
+/// - `language`: The language of the project, `c++` by default or `c`, see [`language`].
+/// - `include`: The globs of the files which belong to the project.
+/// - `exclude`: The globs of the files which do not belong to the project.
+/// - `metadata`: The settings of the add-ons, the `[project.metadata.<addon>]` tables.
+///
+/// The `version`, `authors`, `license` and `repository` can be inherited from the workspace,
+/// see [`inherit`].
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Project {
     /// The name of the project.
     /// It defaults to the name of the directory.
     /// But it's not necessary to be the same as the directory name.
-    /// You can name it whatever you want.
+    /// You can name it whatever you want, as long as it is valid, see [`name::validate`].
+    #[serde(deserialize_with = "name::deserialize")]
     pub name: String,
-    /// The version of the project.
-    /// The format `x.y.z` is recommended.
-    /// It can also be `x.y.z-alpha` or `x.y.z-beta`.
-    pub version: String,
+    /// The version of the project, a semantic version `x.y.z`.
+    /// It can also have a pre-release tag, like `x.y.z-alpha` or `x.y.z-beta`.
+    pub version: Version,
     /// The authors of the project.
     /// It should be in the format: `Name <email>` or just `Name`.
     pub authors: Vec<String>,
@@ -79,21 +227,524 @@ pub struct Project {
     pub license: Option<String>,
     /// The repository of the project.
     pub repository: Option<String>,
+    /// The language of the project, C++ unless it is `c`, see [`language`].
+    #[serde(default, skip_serializing_if = "Language::is_cpp")]
+    pub language: Language,
+    /// The globs of the files which belong to the project, like `src/**/*.cpp`.
+    /// If it is empty, all the files except the excluded ones belong to the project.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    /// The globs of the files which do not belong to the project, like `third_party/**`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+    /// The settings of the add-ons, keyed by the names of the add-ons.
+    /// Coppo keeps them as they are, see [`Config::metadata`].
+    #[serde(default, skip_serializing_if = "toml::Table::is_empty")]
+    pub metadata: toml::Table,
+    /// The fields inherited from the workspace, like `version` of `version.workspace = true`.
+    #[serde(skip)]
+    pub inherited: BTreeSet<String>,
+}
+
+impl Project {
+    /// Check if the project is not given, like in the manifest of a workspace.
+    pub fn is_empty(&self) -> bool {
+        self.name.is_empty()
+    }
+}
+
+impl Default for Project {
+    fn default() -> Self {
+        Project {
+            name: String::new(),
+            version: Version::new(0, 0, 0),
+            authors: vec![],
+            description: None,
+            license: None,
+            repository: None,
+            language: Language::Cpp,
+            include: vec![],
+            exclude: vec![],
+            metadata: toml::Table::new(),
+            inherited: BTreeSet::new(),
+        }
+    }
 }
 
-/// The dependency configuration.
+/// A dependency, the value of a key of the `[dependencies]` table.
+///
+/// It is either the version requirement, or a table with the details:
+///
+/// ```toml
+/// [dependencies]
+/// fmt = "10.2"
+/// util = { path = "../util" }
+/// json = { version = "3.11", git = "https://github.com/nlohmann/json", branch = "develop" }
+/// spdlog = { version = "1.12", features = ["fmt"] }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum Dependency {
+    /// The version requirement, like `fmt = "10.2"`.
+    Simple(VersionReq),
+    /// The table form, like `util = { path = "../util" }`.
+    Detailed(DetailedDependency),
+}
+
+/// The table form of a dependency.
 ///
 /// It contains the following fields:
-/// - `name`: The name of the dependency.
-/// - `version`: The version of the dependency.
-#[derive(Debug, Default, Serialize, Deserialize)]
-pub struct Dependency {
-    /// The name of the dependency.
+/// - `name`: The name of the package, defaults to the key.
+/// - `version`: The version requirement, defaults to `*`.
+/// - `git`: The URL of the git repository of the package.
+/// - `branch`: The branch of the git repository, defaults to the default branch.
+/// - `path`: The directory of the package, relative to the project root.
+/// - `registry`: The registry of the package, a `[registries.<name>]` of the global configuration.
+/// - `features`: The features of the package to enable.
+/// - `optional`: Whether the dependency is only used if a feature enables it.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DetailedDependency {
+    /// The name of the package.
     /// It should be the same as the name of the project.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The version requirement.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<VersionReq>,
+    /// The URL of the git repository.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<String>,
+    /// The branch of the git repository, only used with `git`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// The directory of the package.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// The name of the registry the package is downloaded from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
+    /// The features of the package to enable.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    /// Whether the dependency is only used if a feature enables it with `dep:<name>`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+}
+
+impl Default for Dependency {
+    fn default() -> Self {
+        Dependency::Simple(VersionReq::STAR)
+    }
+}
+
+impl<'de> Deserialize<'de> for Dependency {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DependencyVisitor;
+
+        impl<'de> Visitor<'de> for DependencyVisitor {
+            type Value = Dependency;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(
+                    "a version requirement like \"1.2\" or a table like { path = \"../foo\" }",
+                )
+            }
+
+            fn visit_str<Er: de::Error>(self, version: &str) -> Result<Dependency, Er> {
+                VersionReq::parse(version)
+                    .map(Dependency::Simple)
+                    .map_err(|e| {
+                        Er::custom(format!("invalid version requirement `{}`: {}", version, e))
+                    })
+            }
+
+            fn visit_map<M: MapAccess<'de>>(self, map: M) -> Result<Dependency, M::Error> {
+                DetailedDependency::deserialize(de::value::MapAccessDeserializer::new(map))
+                    .map(Dependency::Detailed)
+            }
+        }
+
+        deserializer.deserialize_any(DependencyVisitor)
+    }
+}
+
+impl Dependency {
+    /// The version requirement, it is `*` if it is not specified.
+    pub fn version(&self) -> VersionReq {
+        match self {
+            Dependency::Simple(version) => version.clone(),
+            Dependency::Detailed(detail) => detail.version.clone().unwrap_or(VersionReq::STAR),
+        }
+    }
+
+    /// The details of the dependency, it is `None` for the simple form.
+    pub fn detail(&self) -> Option<&DetailedDependency> {
+        match self {
+            Dependency::Simple(_) => None,
+            Dependency::Detailed(detail) => Some(detail),
+        }
+    }
+
+    /// The name of the package, if it is given in the table form.
+    pub fn name(&self) -> Option<&str> {
+        self.detail().and_then(|detail| detail.name.as_deref())
+    }
+
+    /// The URL of the git repository.
+    pub fn git(&self) -> Option<&str> {
+        self.detail().and_then(|detail| detail.git.as_deref())
+    }
+
+    /// The directory of the package.
+    pub fn path(&self) -> Option<&Path> {
+        self.detail().and_then(|detail| detail.path.as_deref())
+    }
+
+    /// The name of the registry of the package.
+    pub fn registry(&self) -> Option<&str> {
+        self.detail().and_then(|detail| detail.registry.as_deref())
+    }
+
+    /// The features of the package to enable.
+    pub fn features(&self) -> &[String] {
+        self.detail()
+            .map(|detail| detail.features.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Whether the dependency is only used if a feature enables it.
+    pub fn optional(&self) -> bool {
+        self.detail().is_some_and(|detail| detail.optional)
+    }
+
+    /// Check if the sources of the dependency are consistent.
+    pub fn validate(&self) -> Result<(), String> {
+        let Some(detail) = self.detail() else {
+            return Ok(());
+        };
+        if detail.git.is_some() && detail.path.is_some() {
+            return Err("Only one of `git` and `path` can be specified".to_owned());
+        }
+        if detail.registry.is_some() && (detail.git.is_some() || detail.path.is_some()) {
+            return Err("`registry` can not be used with `git` or `path`".to_owned());
+        }
+        if detail.branch.is_some() && detail.git.is_none() {
+            return Err("`branch` can only be used with `git`".to_owned());
+        }
+
+        Ok(())
+    }
+}
+
+/// The library target configuration, the `[lib]` table.
+///
+/// It contains the following fields:
+/// - `name`: The name of the library.
+/// - `path`: The directory of the library sources, defaults to `src/<name>`.
+/// - `include`: The directory of the public headers, defaults to `include`.
+/// - `kind`: The kinds of the library to build, `static` and `shared`, defaults to `["static"]`.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct LibTarget {
+    /// The name of the library.
     pub name: String,
-    /// The version of the dependency.
-    /// If it is not specified, it should be `*`.
-    pub version: String,
+    /// The directory of the library sources, or a single source file.
+    /// If it is not specified, it will be `src/<name>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// The directory of the public headers.
+    /// If it is not specified, it will be `include`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include: Option<PathBuf>,
+    /// The kinds of the library to build.
+    /// If it is not specified, only the static library is built.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub kind: Vec<LibKind>,
+}
+
+/// The kinds of the library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LibKind {
+    /// The static library, like `lib<name>.a` or `<name>.lib`.
+    Static,
+    /// The shared library, like `lib<name>.so`, `lib<name>.dylib` or `<name>.dll`.
+    Shared,
+}
+
+impl LibTarget {
+    /// The directory of the library sources, or a single source file.
+    pub fn path(&self) -> PathBuf {
+        self.path
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("src").join(&self.name))
+    }
+
+    /// The directory of the public headers.
+    pub fn include(&self) -> PathBuf {
+        self.include
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("include"))
+    }
+
+    /// Check if the kind of the library is built, the static library is built by default.
+    ///
+    /// # Example
+    /// ```rust
+    /// use coppo_config::{Config, LibKind};
+    /// let config = Config::from_str(r#"
+    ///     [lib]
+    ///     name = "core"
+    ///     kind = ["shared"]
+    /// "#).expect("Failed to parse config file.");
+    ///
+    /// let lib = config.lib.unwrap();
+    /// assert!(lib.builds(LibKind::Shared));
+    /// assert!(!lib.builds(LibKind::Static));
+    /// ```
+    pub fn builds(&self, kind: LibKind) -> bool {
+        match self.kind.is_empty() {
+            true => kind == LibKind::Static,
+            false => self.kind.contains(&kind),
+        }
+    }
+}
+
+/// The binary target configuration, an element of the `[[bin]]` array.
+///
+/// It contains the following fields:
+/// - `name`: The name of the executable.
+/// - `path`: The source file with the `main` function, defaults to `src/bin/<name>.cpp`.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct BinTarget {
+    /// The name of the executable.
+    pub name: String,
+    /// The source file with the `main` function.
+    /// If it is not specified, it will be `src/bin/<name>.cpp`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+impl BinTarget {
+    /// The source file with the `main` function.
+    pub fn path(&self) -> PathBuf {
+        self.path
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(format!("src/bin/{}.cpp", self.name)))
+    }
+}
+
+/// The compiler settings, the `[build]` table.
+///
+/// It contains the following fields:
+/// - `compiler`: The compiler, like `g++`, defaults to the one of the global configuration.
+/// - `wrapper`: The compiler wrapper, like `ccache` or `sccache`, an empty one disables it.
+/// - `std`: The standard, like `c++20` or `20`, or `c17` for C, see [`standard`].
+/// - `cxxflags`: The extra compile flags.
+/// - `ldflags`: The extra link flags.
+/// - `include-dirs`: The extra include directories, relative to the project root.
+/// - `defines`: The macros to define, like `USE_SSL` or `LEVEL=2`.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BuildSettings {
+    /// The compiler, a C compiler for the C projects.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compiler: Option<String>,
+    /// The compiler wrapper, the compiler is run through it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wrapper: Option<String>,
+    /// The standard, `-std=<std>`, [`standard::default_std`] if it is not given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub std: Option<String>,
+    /// The extra compile flags.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cxxflags: Vec<String>,
+    /// The extra link flags, only passed when linking the binaries.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ldflags: Vec<String>,
+    /// The extra include directories, `-I<dir>`.
+    #[serde(default, alias = "include_dirs", skip_serializing_if = "Vec::is_empty")]
+    pub include_dirs: Vec<PathBuf>,
+    /// The macros to define, `-D<define>`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub defines: Vec<String>,
+}
+
+impl BuildSettings {
+    /// Check if no compiler setting is given.
+    pub fn is_empty(&self) -> bool {
+        self.compiler.is_none()
+            && self.wrapper.is_none()
+            && self.std.is_none()
+            && self.cxxflags.is_empty()
+            && self.ldflags.is_empty()
+            && self.include_dirs.is_empty()
+            && self.defines.is_empty()
+    }
+
+    /// The compile flags of the settings, the standard of the language, the include directories,
+    /// the macros and the extra flags in order.
+    ///
+    /// # Example
+    /// ```rust
+    /// use coppo_config::{Config, Language};
+    /// let config = Config::from_str(r#"
+    ///     [build]
+    ///     std = "20"
+    ///     include-dirs = ["third_party"]
+    ///     defines = ["USE_SSL"]
+    ///     cxxflags = ["-Wall"]
+    /// "#).expect("Failed to parse config file.");
+    ///
+    /// assert_eq!(
+    ///     config.build.compile_flags(Language::Cpp),
+    ///     ["-std=c++20", "-Ithird_party", "-DUSE_SSL", "-Wall"]
+    /// );
+    /// ```
+    pub fn compile_flags(&self, language: Language) -> Vec<String> {
+        let mut flags = vec![];
+        if let Some(std) = &self.std {
+            flags.push(standard::flag(std, language));
+        }
+        flags.extend(
+            self.include_dirs
+                .iter()
+                .map(|dir| format!("-I{}", dir.display())),
+        );
+        flags.extend(self.defines.iter().map(|define| format!("-D{}", define)));
+        flags.extend(self.cxxflags.iter().cloned());
+
+        flags
+    }
+}
+
+/// The lint settings, the `[lint]` table.
+///
+/// It contains the following fields:
+/// - `checks`: The `clang-tidy` checks, like `["-*", "modernize-*"]`.
+/// - `warnings-as-errors`: The checks whose warnings are treated as errors.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LintSettings {
+    /// The `clang-tidy` checks, the ones starting with `-` are disabled.
+    /// If it is empty, the checks are taken from the `.clang-tidy` file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checks: Vec<String>,
+    /// The checks whose warnings are treated as errors.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings_as_errors: Vec<String>,
+}
+
+impl LintSettings {
+    /// Check if no lint setting is given.
+    pub fn is_empty(&self) -> bool {
+        self.checks.is_empty() && self.warnings_as_errors.is_empty()
+    }
+}
+
+/// The documentation settings, the `[doc]` table.
+///
+/// It contains the following fields:
+/// - `generator`: The documentation generator, `doxygen` or `builtin`.
+/// - `title`: The title of the documentation, defaults to the project name.
+/// - `input`: The directories or files to document, defaults to the public headers.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct DocSettings {
+    /// The documentation generator.
+    /// If it is not specified, Doxygen is used if it is installed,
+    /// otherwise the built-in header parser is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generator: Option<DocGenerator>,
+    /// The title of the documentation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The directories or files to document.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub input: Vec<PathBuf>,
+}
+
+impl DocSettings {
+    /// Check if no documentation setting is given.
+    pub fn is_empty(&self) -> bool {
+        self.generator.is_none() && self.title.is_none() && self.input.is_empty()
+    }
+}
+
+/// The release packaging settings, the `[dist]` table.
+///
+/// It contains the following fields:
+/// - `targets`: The target triples to package, defaults to the host.
+/// - `include`: The extra files to bundle, like `["README.md"]`.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct DistSettings {
+    /// The target triples, like `x86_64-unknown-linux-gnu`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<String>,
+    /// The extra files to bundle, relative to the project root.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<PathBuf>,
+}
+
+impl DistSettings {
+    /// Check if no packaging setting is given.
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty() && self.include.is_empty()
+    }
+}
+
+/// The documentation generators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DocGenerator {
+    /// Doxygen, it must be installed.
+    Doxygen,
+    /// The built-in header parser, it extracts the `///` and `/** */` comments.
+    Builtin,
+}
+
+/// The workspace configuration, the `[workspace]` table.
+///
+/// It contains the following fields:
+/// - `members`: The directories of the member projects, globs like `libs/*` are allowed.
+///
+/// # Example
+/// ```toml
+/// [workspace]
+/// members = ["core", "apps/*"]
+/// ```
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    /// The directories of the member projects, relative to the workspace root.
+    #[serde(default)]
+    pub members: Vec<String>,
+    /// The fields inherited by the members, the `[workspace.package]` table.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<inherit::WorkspacePackage>,
+}
+
+impl Workspace {
+    /// The directories of the members, only the ones with a `Coppo.toml` are members.
+    /// The globs are expanded in the alphabetical order.
+    pub fn members(&self, root: &Path) -> Result<Vec<PathBuf>, E> {
+        let mut members = vec![];
+        for member in &self.members {
+            let pattern = root.join(member);
+            let pattern = pattern.to_string_lossy();
+            let mut dirs = glob::glob(&pattern)
+                .map_err(|e| format!("Invalid workspace member `{}`: {}", member, e))?
+                .flatten()
+                .filter(|dir| dir.join(CONFIG_FILE).is_file())
+                .collect::<Vec<_>>();
+            dirs.sort();
+
+            for dir in dirs {
+                if !members.contains(&dir) {
+                    members.push(dir);
+                }
+            }
+        }
+
+        Ok(members)
+    }
 }
 
 impl Config {
@@ -102,21 +753,63 @@ impl Config {
         fs::metadata(CONFIG_FILE).is_ok()
     }
 
+    /// Find the nearest configuration file in the directory or its ancestors.
+    pub fn locate(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|dir| dir.join(CONFIG_FILE))
+            .find(|path| path.is_file())
+    }
+
     /// Check if the configuration is empty.
-    /// It will return `true` if the project name and version are empty.
+    /// It will return `true` if the project name is empty.
     pub fn is_empty(&self) -> bool {
-        self.project.name.is_empty() && self.project.version.is_empty()
+        self.project.name.is_empty()
     }
 
     /// Parse the configuration file `Coppo.toml` in the root directory of the project.
-    pub fn from_file() -> Result<Config, E> {
-        let config_file = fs::read_to_string(CONFIG_FILE)?;
+    pub fn from_file() -> Result<Config, ConfigError> {
+        Config::from_path(Path::new(CONFIG_FILE))
+    }
 
-        Config::from_str(&config_file)
+    /// Find the nearest `Coppo.toml` in the current directory or its ancestors, and parse it.
+    /// The directory of the manifest is recorded as [`Config::root`],
+    /// and the current directory as [`Config::invocation_dir`].
+    /// It is `None` if no manifest is found.
+    pub fn find_and_load() -> Result<Option<Config>, ConfigError> {
+        let cwd = env::current_dir().map_err(|error| ConfigError::Read {
+            file: PathBuf::from(CONFIG_FILE),
+            error,
+        })?;
+        let Some(manifest) = Config::locate(&cwd) else {
+            return Ok(None);
+        };
+
+        let mut config = Config::from_path(&manifest)?;
+        config.root = manifest.parent().map(Path::to_owned);
+        config.invocation_dir = Some(cwd);
+
+        Ok(Some(config))
+    }
+
+    /// Parse the configuration file of the path, the errors point at the file.
+    /// The fields inherited from the workspace are resolved, see [`inherit`].
+    pub fn from_path(path: &Path) -> Result<Config, ConfigError> {
+        let config_file = fs::read_to_string(path).map_err(|error| ConfigError::Read {
+            file: path.to_owned(),
+            error,
+        })?;
+        let (config_file, inherited) = inherit::resolve(&config_file, path)?;
+
+        let mut config = Config::from_str(&config_file).map_err(|e| e.with_file(path))?;
+        config.project.inherited = inherited;
+
+        Ok(config)
     }
 
     /// Parse the configuration file from a string.
-    /// It's a packing function for `toml::from_str`.
+    /// It's a packing function for `toml::from_str`,
+    /// the errors are located in the string, see [`ConfigError`],
+    /// and the unused keys are collected into [`Config::unused_keys`].
     ///
     /// # Example
     /// ```rust
@@ -133,28 +826,85 @@ impl Config {
     /// "#).expect("Failed to parse config file.");
     ///
     /// assert_eq!(config.project.name, "my_project");
-    /// assert_eq!(config.project.version, "1.0.0-alpha");
+    /// assert_eq!(config.project.version.to_string(), "1.0.0-alpha");
     /// assert_eq!(config.project.authors, vec!["My name <my_email>"]);
     /// assert_eq!(config.project.description, Some("This is a simple project.".to_string()));
     /// assert_eq!(config.project.license, Some("MIT".to_string()));
     /// assert_eq!(config.project.repository, None);
     /// ```
-    pub fn from_str(config_str: &str) -> Result<Config, E> {
-        toml::from_str(config_str).map_err(Into::into)
+    pub fn from_str(config_str: &str) -> Result<Config, ConfigError> {
+        unused::parse(config_str)
+    }
+
+    /// The settings of the add-on, the `[project.metadata.<addon>]` table,
+    /// it is `None` if the table is not given.
+    ///
+    /// # Example
+    /// ```rust
+    /// use coppo_config::Config;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Settings {
+    ///     level: u8,
+    /// }
+    ///
+    /// let config = Config::from_str(r#"
+    ///     [package]
+    ///     name = "app"
+    ///     version = "0.1.0"
+    ///     authors = []
+    ///
+    ///     [package.metadata.sanitize]
+    ///     level = 2
+    /// "#).expect("Failed to parse config file.");
+    ///
+    /// let settings = config.metadata::<Settings>("sanitize").unwrap().unwrap();
+    /// assert_eq!(settings.level, 2);
+    /// assert!(config.metadata::<Settings>("bench").unwrap().is_none());
+    /// ```
+    pub fn metadata<T: DeserializeOwned>(&self, addon: &str) -> Result<Option<T>, E> {
+        let Some(value) = self.project.metadata.get(addon) else {
+            return Ok(None);
+        };
+
+        value
+            .clone()
+            .try_into()
+            .map(Some)
+            .map_err(|e| format!("Invalid `[project.metadata.{}]`: {}", addon, e.message()).into())
+    }
+
+    /// Set the settings of the add-on, they are written by [`Config::save`].
+    pub fn set_metadata<T: Serialize>(&mut self, addon: &str, settings: &T) -> Result<(), E> {
+        let value = toml::Value::try_from(settings)?;
+        self.project.metadata.insert(addon.to_owned(), value);
+
+        Ok(())
     }
 }
 
 pub mod prelude {
-    pub use super::{Config, Dependency, Project, CONFIG_FILE};
+    pub use super::{
+        artifact_cache, coppo_home, download_cache, script_cache, BinTarget, BuildSettings, Config,
+        ConfigError, Dependency, DetailedDependency, DistSettings, DocGenerator, DocSettings,
+        GlobalConfig, Language, LibKind, LibTarget, LintSettings, Platform, Profile, Project,
+        Version, VersionReq, Workspace, CONFIG_FILE, COPPO_HOME,
+    };
     pub use toml;
 }
 
 #[cfg(test)]
 mod test {
-    use std::assert_matches::assert_matches;
-
     use super::*;
 
+    /// The unstable `std::assert_matches::assert_matches` on the stable toolchain.
+    macro_rules! assert_matches {
+        ($expr:expr, $pat:pat $(if $guard:expr)? $(,)?) => {
+            assert!(matches!($expr, $pat $(if $guard)?))
+        };
+    }
+
     #[test]
     fn test_config() -> Result<(), E> {
         let config = Config::from_str(
@@ -179,10 +929,12 @@ mod test {
                     description,
                     license,
                     repository,
+                    ..
                 },
                 dependencies,
+                ..
             } if name == "my_project"
-                && version == "0.1.0"
+                && version == Version::new(0, 1, 0)
                 && authors == vec![
                     "John Doe <example@123.com>".to_string()
                 ]
@@ -194,4 +946,79 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_metadata() -> Result<(), E> {
+        let manifest = r#"[project]
+name = "app"
+version = "0.1.0"
+authors = []
+
+[project.metadata.sanitize]
+checks = ["address", "undefined"] # The default ones.
+
+[dependencies]
+"#;
+        let mut config = Config::from_str(manifest)?;
+        assert_eq!(config.edit_str(manifest)?, manifest);
+
+        let checks = config.metadata::<BTreeMap<String, Vec<String>>>("sanitize")?;
+        assert_eq!(checks.unwrap()["checks"], ["address", "undefined"]);
+        assert!(config.metadata::<Vec<String>>("sanitize").is_err());
+
+        config.set_metadata("bench", &BTreeMap::from([("runs", 10)]))?;
+        assert!(config
+            .edit_str(manifest)?
+            .contains("[project.metadata.bench]\nruns = 10\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dependencies() -> Result<(), E> {
+        let config = Config::from_str(
+            r#"
+            [dependencies]
+            fmt = "10.2"
+            util = { path = "../util" }
+            json = { git = "https://github.com/nlohmann/json", branch = "develop", features = ["diagnostics"] }
+
+            [dev-dependencies]
+            gtest = { version = "^1.14" }
+            "#,
+        )?;
+
+        assert_eq!(
+            config.dependencies["fmt"],
+            Dependency::Simple(VersionReq::parse("10.2")?)
+        );
+        assert_eq!(config.dependencies["util"].version(), VersionReq::STAR);
+        assert_eq!(
+            config.dependencies["util"].path(),
+            Some(Path::new("../util"))
+        );
+        assert_eq!(config.dependencies["json"].features(), ["diagnostics"]);
+        assert!(config.dependencies["json"].validate().is_ok());
+        assert_eq!(
+            config.dev_dependencies["gtest"].version().to_string(),
+            "^1.14"
+        );
+
+        let config =
+            Config::from_str("[dependencies]\nfoo = { branch = \"main\", path = \"foo\" }")?;
+        assert!(config.dependencies["foo"].validate().is_err());
+        let config = Config::from_str(
+            "[dependencies]\nfoo = { version = \"1.0\", registry = \"company\" }",
+        )?;
+        assert_eq!(config.dependencies["foo"].registry(), Some("company"));
+        let config =
+            Config::from_str("[dependencies]\nfoo = { registry = \"company\", path = \"foo\" }")?;
+        assert!(config.dependencies["foo"].validate().is_err());
+
+        let e = Config::from_str("[dependencies]\nfoo = \">=x\"").unwrap_err();
+        assert!(e.to_string().contains("invalid version requirement `>=x`"));
+        assert!(Config::from_str("[project]\nname = \"app\"\nversion = \"1.0\"").is_err());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,158 @@
+//! The lockfile `Coppo.lock`, the exact versions of the dependencies.
+//!
+//! It is written next to `Coppo.toml` by the first build, and by `coppo update`.
+//! It should be committed, so every build of the project uses the same dependencies:
+//!
+//! ```toml
+//! # This file is generated by Coppo, it is not intended for manual editing.
+//! version = 1
+//!
+//! [[package]]
+//! name = "util"
+//! version = "0.1.0"
+//! source = "path+libs/util"
+//! dependencies = ["zlib"]
+//!
+//! [[package]]
+//! name = "zlib"
+//! version = "1.3.1"
+//! source = "system"
+//! checksum = "sha256:2f3c..."
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::E;
+
+/// The lockfile name.
+pub const LOCK_FILE: &str = "Coppo.lock";
+
+/// The version of the lockfile format.
+pub const LOCK_VERSION: u32 = 1;
+
+/// The header comment of the lockfile.
+const HEADER: &str = "# This file is generated by Coppo, it is not intended for manual editing.\n";
+
+/// The lockfile.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lockfile {
+    /// The version of the format.
+    pub version: u32,
+    /// The resolved packages, sorted by name.
+    #[serde(default, rename = "package", skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<LockedPackage>,
+}
+
+/// A resolved package, an element of the `[[package]]` array.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    /// The name of the package.
+    pub name: String,
+    /// The exact version.
+    pub version: String,
+    /// Where the package comes from, like `path+libs/util` for a workspace member,
+    /// or `system` for a package found with `pkg-config`.
+    pub source: String,
+    /// The checksum of the package, like `sha256:<hex>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+    /// The names of the packages it depends on.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
+}
+
+impl Default for Lockfile {
+    fn default() -> Self {
+        Lockfile {
+            version: LOCK_VERSION,
+            packages: vec![],
+        }
+    }
+}
+
+impl Lockfile {
+    /// Parse the lockfile in the directory, it is `None` if there is no lockfile.
+    pub fn from_file(dir: &Path) -> Result<Option<Lockfile>, E> {
+        let path = dir.join(LOCK_FILE);
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        Lockfile::from_str(&fs::read_to_string(&path)?)
+            .map(Some)
+            .map_err(|e| format!("Failed to parse `{}`: {}", path.display(), e).into())
+    }
+
+    /// Parse the lockfile from a string.
+    ///
+    /// # Example
+    /// ```rust
+    /// use coppo_config::lock::Lockfile;
+    /// let lockfile = Lockfile::from_str(r#"
+    ///     version = 1
+    ///
+    ///     [[package]]
+    ///     name = "zlib"
+    ///     version = "1.3.1"
+    ///     source = "system"
+    /// "#).expect("Failed to parse the lockfile.");
+    ///
+    /// assert_eq!(lockfile.get("zlib").map(|package| package.version.as_str()), Some("1.3.1"));
+    /// ```
+    pub fn from_str(lockfile: &str) -> Result<Lockfile, E> {
+        let lockfile: Lockfile = toml::from_str(lockfile)?;
+        if lockfile.version != LOCK_VERSION {
+            return Err(format!(
+                "The lockfile version {} is not supported, the supported version is {}",
+                lockfile.version, LOCK_VERSION
+            )
+            .into());
+        }
+
+        Ok(lockfile)
+    }
+
+    /// Write the lockfile into the directory.
+    pub fn to_file(&self, dir: &Path) -> Result<(), E> {
+        fs::write(dir.join(LOCK_FILE), self.to_string()?)?;
+
+        Ok(())
+    }
+
+    /// Serialize the lockfile, with its header.
+    pub fn to_string(&self) -> Result<String, E> {
+        Ok(format!("{}{}", HEADER, toml::to_string(self)?))
+    }
+
+    /// The locked package of the name.
+    pub fn get(&self, name: &str) -> Option<&LockedPackage> {
+        self.packages.iter().find(|package| package.name == name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let lockfile = Lockfile {
+            version: LOCK_VERSION,
+            packages: vec![LockedPackage {
+                name: "util".to_string(),
+                version: "0.1.0".to_string(),
+                source: "path+libs/util".to_string(),
+                checksum: None,
+                dependencies: vec!["zlib".to_string()],
+            }],
+        };
+
+        let s = lockfile.to_string().unwrap();
+        assert!(s.starts_with(HEADER));
+        assert_eq!(Lockfile::from_str(&s).unwrap(), lockfile);
+        assert!(Lockfile::from_str("version = 2").is_err());
+    }
+}
@@ -0,0 +1,268 @@
+//! Validate and normalize the names of projects.
+//!
+//! A valid name only contains ASCII letters, digits, `-` and `_`,
+//! does not start with a digit, and is not a reserved word or a C++ keyword.
+//! The names of the dependencies are the ones of the registries and the system packages,
+//! like `gtk+-3.0`, so they only can not be empty or contain whitespaces and path separators.
+//!
+//! The names are checked when the manifest is loaded, see [`deserialize`].
+
+use std::collections::HashMap;
+
+use serde::{de, Deserialize, Deserializer};
+
+/// The reserved words which can not be used as a name.
+/// They collide with the directories of Coppo, or the device names on Windows.
+pub const RESERVED: &[&str] = &[
+    "test", "target", "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6",
+    "com7", "com8", "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+/// The C++ keywords, which can not be used as a name,
+/// the name of a library is the namespace of its headers.
+pub const KEYWORDS: &[&str] = &[
+    "alignas",
+    "alignof",
+    "and",
+    "and_eq",
+    "asm",
+    "auto",
+    "bitand",
+    "bitor",
+    "bool",
+    "break",
+    "case",
+    "catch",
+    "char",
+    "char8_t",
+    "char16_t",
+    "char32_t",
+    "class",
+    "compl",
+    "concept",
+    "const",
+    "consteval",
+    "constexpr",
+    "constinit",
+    "const_cast",
+    "continue",
+    "co_await",
+    "co_return",
+    "co_yield",
+    "decltype",
+    "default",
+    "delete",
+    "do",
+    "double",
+    "dynamic_cast",
+    "else",
+    "enum",
+    "explicit",
+    "export",
+    "extern",
+    "false",
+    "float",
+    "for",
+    "friend",
+    "goto",
+    "if",
+    "inline",
+    "int",
+    "long",
+    "mutable",
+    "namespace",
+    "new",
+    "noexcept",
+    "not",
+    "not_eq",
+    "nullptr",
+    "operator",
+    "or",
+    "or_eq",
+    "private",
+    "protected",
+    "public",
+    "register",
+    "reinterpret_cast",
+    "requires",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "static_assert",
+    "static_cast",
+    "struct",
+    "switch",
+    "template",
+    "this",
+    "thread_local",
+    "throw",
+    "true",
+    "try",
+    "typedef",
+    "typeid",
+    "typename",
+    "union",
+    "unsigned",
+    "using",
+    "virtual",
+    "void",
+    "volatile",
+    "wchar_t",
+    "while",
+    "xor",
+    "xor_eq",
+];
+
+/// Check if the name is valid.
+/// The error message contains a normalized suggestion if there is one.
+///
+/// # Example
+/// ```rust
+/// use coppo_config::name;
+///
+/// assert!(name::validate("my_project").is_ok());
+/// assert!(name::validate("My Project").is_err());
+/// ```
+pub fn validate(name: &str) -> Result<(), String> {
+    let reason = if name.is_empty() {
+        return Err("The name can not be empty".to_owned());
+    } else if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || *c == '-' || *c == '_'))
+    {
+        format!("the character `{}` is not allowed", c)
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        "it can not start with a digit".to_owned()
+    } else if is_reserved(name) {
+        "it is a reserved word".to_owned()
+    } else if KEYWORDS.contains(&name) {
+        "it is a C++ keyword".to_owned()
+    } else {
+        return Ok(());
+    };
+
+    Err(format!(
+        "Invalid name `{}`: {}, did you mean `{}`?",
+        name,
+        reason,
+        normalize(name)
+    ))
+}
+
+/// Normalize the name into a valid one, like `My Project` to `my_project`.
+///
+/// # Example
+/// ```rust
+/// use coppo_config::name;
+///
+/// assert_eq!(name::normalize("My Project"), "my_project");
+/// ```
+pub fn normalize(name: &str) -> String {
+    let mut normalized = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() || c == '-' {
+            normalized.push(c.to_ascii_lowercase());
+        } else if !normalized.ends_with('_') {
+            normalized.push('_');
+        }
+    }
+    let normalized = normalized.trim_matches('_');
+
+    if normalized.is_empty() {
+        "my_project".to_owned()
+    } else if normalized.starts_with(|c: char| c.is_ascii_digit())
+        || is_reserved(normalized)
+        || KEYWORDS.contains(&normalized)
+    {
+        format!("my_{}", normalized)
+    } else {
+        normalized.to_owned()
+    }
+}
+
+/// Check if the name of the dependency is valid.
+///
+/// # Example
+/// ```rust
+/// use coppo_config::name;
+///
+/// assert!(name::validate_dependency("gtk+-3.0").is_ok());
+/// assert!(name::validate_dependency("../fmt").is_err());
+/// ```
+pub fn validate_dependency(name: &str) -> Result<(), String> {
+    let reason = if name.trim().is_empty() {
+        "it can not be empty"
+    } else if name.contains(char::is_whitespace) {
+        "it can not contain whitespaces"
+    } else if name.contains(['/', '\\']) || name == "." || name == ".." {
+        "it can not be a path, use `path` to depend on a directory"
+    } else {
+        return Ok(());
+    };
+
+    Err(format!("Invalid dependency name `{}`: {}", name, reason))
+}
+
+/// Deserialize the name of the project, and check if it is valid, see [`validate`].
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    validate(&name).map_err(de::Error::custom)?;
+
+    Ok(name)
+}
+
+/// Deserialize the dependencies, and check if their names are valid,
+/// see [`validate_dependency`].
+pub fn deserialize_dependencies<'de, D, T>(deserializer: D) -> Result<HashMap<String, T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let dependencies = HashMap::<String, T>::deserialize(deserializer)?;
+    for name in dependencies.keys() {
+        validate_dependency(name).map_err(de::Error::custom)?;
+    }
+
+    Ok(dependencies)
+}
+
+/// Check if the name is a reserved word, it is case-insensitive.
+fn is_reserved(name: &str) -> bool {
+    RESERVED
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert!(validate("hello-world_2").is_ok());
+        assert!(validate("").is_err());
+        assert!(validate("2d").is_err());
+        assert!(validate("Target").is_err());
+        assert!(validate("a/b").is_err());
+        assert!(validate("my project").is_err());
+        assert_eq!(
+            validate("class").unwrap_err(),
+            "Invalid name `class`: it is a C++ keyword, did you mean `my_class`?"
+        );
+        assert!(validate("classic").is_ok());
+
+        assert!(validate_dependency("boost-asio").is_ok());
+        assert!(validate_dependency("").is_err());
+        assert!(validate_dependency("my fmt").is_err());
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("My  Project!"), "my_project");
+        assert_eq!(normalize("3d engine"), "my_3d_engine");
+        assert_eq!(normalize("test"), "my_test");
+        assert_eq!(normalize("???"), "my_project");
+    }
+}
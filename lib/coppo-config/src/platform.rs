@@ -0,0 +1,408 @@
+//! The platforms of the target-specific dependencies, the `[target.<spec>]` tables.
+//!
+//! The spec is either a target triple, or a `cfg(...)` expression like Rust's:
+//!
+//! ```toml
+//! [target.'cfg(windows)'.dependencies]
+//! ws2_32 = "*"
+//!
+//! [target.'cfg(all(unix, not(target_os = "macos")))'.dependencies]
+//! pthread = "*"
+//!
+//! [target.x86_64-unknown-linux-gnu.dependencies]
+//! numa = "2"
+//! ```
+//!
+//! The expression supports `windows`, `unix`, `target_os`, `target_family`,
+//! `target_arch` and `target_env`, combined with `all(...)`, `any(...)` and `not(...)`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Config, Dependency};
+
+/// The dependencies of a platform, the `[target.<spec>]` table.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TargetDependencies {
+    /// The `[target.<spec>.dependencies]` table.
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        deserialize_with = "crate::name::deserialize_dependencies"
+    )]
+    pub dependencies: HashMap<String, Dependency>,
+    /// The `[target.<spec>.dev-dependencies]` table.
+    #[serde(
+        default,
+        rename = "dev-dependencies",
+        alias = "dev_dependencies",
+        skip_serializing_if = "HashMap::is_empty",
+        deserialize_with = "crate::name::deserialize_dependencies"
+    )]
+    pub dev_dependencies: HashMap<String, Dependency>,
+}
+
+/// A platform, which the target-specific dependencies are selected for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Platform {
+    /// The operating system, like `linux`, `windows` or `macos`.
+    pub os: String,
+    /// The family of the operating system, `unix` or `windows`.
+    pub family: String,
+    /// The architecture, like `x86_64` or `aarch64`.
+    pub arch: String,
+    /// The environment or the ABI, like `gnu`, `musl` or `msvc`, it is empty if there is none.
+    pub env: String,
+}
+
+impl Platform {
+    /// The platform Coppo runs on.
+    pub fn host() -> Self {
+        let env = if cfg!(target_env = "gnu") {
+            "gnu"
+        } else if cfg!(target_env = "musl") {
+            "musl"
+        } else if cfg!(target_env = "msvc") {
+            "msvc"
+        } else {
+            ""
+        };
+
+        Platform {
+            os: env::consts::OS.to_owned(),
+            family: env::consts::FAMILY.to_owned(),
+            arch: env::consts::ARCH.to_owned(),
+            env: env.to_owned(),
+        }
+    }
+
+    /// The platform of the target triple, like `x86_64-unknown-linux-gnu`.
+    pub fn from_triple(triple: &str) -> Self {
+        let parts = triple.split('-').collect::<Vec<_>>();
+        // MinGW targets Windows with the GNU toolchain, like `x86_64-w64-mingw32`.
+        let mingw = triple.contains("mingw");
+        let os = if triple.contains("windows") || mingw {
+            "windows"
+        } else if triple.contains("darwin") || triple.contains("apple") {
+            "macos"
+        } else {
+            ["linux", "android", "freebsd", "netbsd", "openbsd"]
+                .into_iter()
+                .find(|os| parts.contains(os))
+                .unwrap_or_default()
+        };
+        let env = parts
+            .last()
+            .and_then(|last| {
+                ["gnu", "musl", "msvc"]
+                    .into_iter()
+                    .find(|env| last.starts_with(env))
+            })
+            .unwrap_or(if mingw { "gnu" } else { "" });
+
+        Platform {
+            os: os.to_owned(),
+            family: match os {
+                "windows" => "windows",
+                _ => "unix",
+            }
+            .to_owned(),
+            arch: parts.first().copied().unwrap_or_default().to_owned(),
+            env: env.to_owned(),
+        }
+    }
+
+    /// Check if the platform is Windows, its binaries have the `.exe` extension.
+    pub fn is_windows(&self) -> bool {
+        self.os == "windows"
+    }
+
+    /// Check if the spec of a `[target.<spec>]` table applies to the platform.
+    ///
+    /// # Example
+    /// ```rust
+    /// use coppo_config::platform::Platform;
+    ///
+    /// let linux = Platform::from_triple("x86_64-unknown-linux-gnu");
+    /// assert_eq!(linux.matches("cfg(unix)"), Ok(true));
+    /// assert_eq!(linux.matches(r#"cfg(any(windows, target_os = "macos"))"#), Ok(false));
+    /// assert_eq!(linux.matches("x86_64-unknown-linux-gnu"), Ok(true));
+    /// assert!(linux.matches("cfg(all(unix)").is_err());
+    /// ```
+    pub fn matches(&self, spec: &str) -> Result<bool, String> {
+        let spec = spec.trim();
+        let Some(expression) = spec
+            .strip_prefix("cfg(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        else {
+            return Ok(Platform::from_triple(spec) == *self);
+        };
+
+        let tokens =
+            tokenize(expression).map_err(|e| format!("Invalid platform `{}`: {}", spec, e))?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+        };
+        let cfg = parser
+            .parse()
+            .and_then(|cfg| match parser.peek() {
+                None => Ok(cfg),
+                Some(token) => Err(format!("unexpected `{}`", token)),
+            })
+            .map_err(|e| format!("Invalid platform `{}`: {}", spec, e))?;
+
+        self.evaluate(&cfg)
+            .map_err(|e| format!("Invalid platform `{}`: {}", spec, e))
+    }
+
+    fn evaluate(&self, cfg: &Cfg) -> Result<bool, String> {
+        Ok(match cfg {
+            Cfg::Name(name) => match name.as_str() {
+                "windows" | "unix" => self.family == *name,
+                _ => return Err(format!("unknown condition `{}`", name)),
+            },
+            Cfg::KeyValue(key, value) => match key.as_str() {
+                "target_os" => self.os == *value,
+                "target_family" => self.family == *value,
+                "target_arch" => self.arch == *value,
+                "target_env" => self.env == *value,
+                _ => return Err(format!("unknown key `{}`", key)),
+            },
+            Cfg::All(cfgs) => {
+                let mut all = true;
+                for cfg in cfgs {
+                    all &= self.evaluate(cfg)?;
+                }
+                all
+            }
+            Cfg::Any(cfgs) => {
+                let mut any = false;
+                for cfg in cfgs {
+                    any |= self.evaluate(cfg)?;
+                }
+                any
+            }
+            Cfg::Not(cfg) => !self.evaluate(cfg)?,
+        })
+    }
+}
+
+impl Config {
+    /// The platform the project is built for, the one of [`Config::build_target`] or the host.
+    pub fn platform(&self) -> Platform {
+        match &self.build_target {
+            Some(triple) => Platform::from_triple(triple),
+            None => Platform::host(),
+        }
+    }
+
+    /// Check if the specs of the `[target.<spec>]` tables are valid.
+    pub fn check_targets(&self) -> Result<(), String> {
+        let host = Platform::host();
+        for spec in self.target.keys() {
+            host.matches(spec)?;
+        }
+
+        Ok(())
+    }
+
+    /// The dependencies used on the platform,
+    /// the `[dependencies]` with the ones of the `[target.<spec>]` tables matching it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use coppo_config::Config;
+    /// use coppo_config::platform::Platform;
+    /// let config = Config::from_str(r#"
+    ///     [dependencies]
+    ///     fmt = "10"
+    ///
+    ///     [target.'cfg(windows)'.dependencies]
+    ///     ws2_32 = "*"
+    /// "#).expect("Failed to parse config file.");
+    ///
+    /// let windows = Platform::from_triple("x86_64-pc-windows-msvc");
+    /// let linux = Platform::from_triple("x86_64-unknown-linux-gnu");
+    /// assert_eq!(config.dependencies_for(&windows).len(), 2);
+    /// assert_eq!(config.dependencies_for(&linux).into_keys().collect::<Vec<_>>(), ["fmt"]);
+    /// ```
+    pub fn dependencies_for(&self, platform: &Platform) -> BTreeMap<&str, &Dependency> {
+        self.select(platform, &self.dependencies, |target| &target.dependencies)
+    }
+
+    /// The dev-dependencies used on the platform, like [`Config::dependencies_for`].
+    pub fn dev_dependencies_for(&self, platform: &Platform) -> BTreeMap<&str, &Dependency> {
+        self.select(platform, &self.dev_dependencies, |target| {
+            &target.dev_dependencies
+        })
+    }
+
+    /// The dependency of the name, in `[dependencies]` or any `[target.<spec>.dependencies]`.
+    pub fn find_dependency(&self, name: &str) -> Option<&Dependency> {
+        self.dependencies.get(name).or_else(|| {
+            self.target
+                .values()
+                .find_map(|target| target.dependencies.get(name))
+        })
+    }
+
+    fn select<'a>(
+        &'a self,
+        platform: &Platform,
+        common: &'a HashMap<String, Dependency>,
+        table: impl Fn(&'a TargetDependencies) -> &'a HashMap<String, Dependency>,
+    ) -> BTreeMap<&'a str, &'a Dependency> {
+        let mut dependencies = common
+            .iter()
+            .map(|(name, dependency)| (name.as_str(), dependency))
+            .collect::<BTreeMap<_, _>>();
+        for (spec, target) in &self.target {
+            // The invalid specs are reported by `check_targets`.
+            if platform.matches(spec) == Ok(true) {
+                dependencies.extend(
+                    table(target)
+                        .iter()
+                        .map(|(name, dependency)| (name.as_str(), dependency)),
+                );
+            }
+        }
+
+        dependencies
+    }
+}
+
+/// A parsed `cfg(...)` expression.
+#[derive(Debug, PartialEq, Eq)]
+enum Cfg {
+    Name(String),
+    KeyValue(String, String),
+    All(Vec<Cfg>),
+    Any(Vec<Cfg>),
+    Not(Box<Cfg>),
+}
+
+/// Split the expression into the identifiers, the strings and the punctuations.
+fn tokenize(expression: &str) -> Result<Vec<String>, String> {
+    let mut tokens = vec![];
+    let mut chars = expression.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' | ')' | ',' | '=' => tokens.push(c.to_string()),
+            '"' => {
+                let mut string = String::from('"');
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => string.push(c),
+                        None => return Err("unterminated string".to_owned()),
+                    }
+                }
+                tokens.push(string);
+            }
+            c if c.is_ascii_alphanumeric() || c == '_' => {
+                let mut ident = String::from(c);
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                    ident.push(c);
+                }
+                tokens.push(ident);
+            }
+            c => return Err(format!("unexpected `{}`", c)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!("expected `{}`, found `{}`", expected, token)),
+            None => Err(format!("expected `{}`", expected)),
+        }
+    }
+
+    fn parse(&mut self) -> Result<Cfg, String> {
+        let Some(name) = self.next().filter(|token| !token.starts_with('"')) else {
+            return Err("expected a condition".to_owned());
+        };
+        match (name.as_str(), self.peek()) {
+            ("all" | "any", Some("(")) => {
+                self.expect("(")?;
+                let mut cfgs = vec![];
+                while self.peek() != Some(")") {
+                    cfgs.push(self.parse()?);
+                    if self.peek() != Some(")") {
+                        self.expect(",")?;
+                    }
+                }
+                self.expect(")")?;
+                Ok(match name.as_str() {
+                    "all" => Cfg::All(cfgs),
+                    _ => Cfg::Any(cfgs),
+                })
+            }
+            ("not", Some("(")) => {
+                self.expect("(")?;
+                let cfg = self.parse()?;
+                self.expect(")")?;
+                Ok(Cfg::Not(Box::new(cfg)))
+            }
+            (_, Some("=")) => {
+                self.expect("=")?;
+                match self.next() {
+                    Some(value) if value.starts_with('"') => {
+                        Ok(Cfg::KeyValue(name, value[1..].to_owned()))
+                    }
+                    _ => Err(format!("expected a string after `{} =`", name)),
+                }
+            }
+            _ if name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') => Ok(Cfg::Name(name)),
+            _ => Err(format!("unexpected `{}`", name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        let windows = Platform::from_triple("x86_64-pc-windows-msvc");
+        assert_eq!(windows.os, "windows");
+        assert_eq!(windows.env, "msvc");
+        assert_eq!(windows.matches("cfg(windows)"), Ok(true));
+        assert_eq!(
+            windows.matches(r#"cfg(all(target_arch = "x86_64", not(target_env = "gnu")))"#),
+            Ok(true)
+        );
+
+        let mac = Platform::from_triple("aarch64-apple-darwin");
+        assert_eq!(mac.matches(r#"cfg(target_os = "macos")"#), Ok(true));
+        assert_eq!(mac.matches("cfg(any(windows, not(unix)))"), Ok(false));
+
+        assert!(mac.matches("cfg(linux)").is_err());
+        assert!(mac.matches("cfg(target_os = macos)").is_err());
+        assert!(mac.matches(r#"cfg(unix, windows)"#).is_err());
+    }
+}
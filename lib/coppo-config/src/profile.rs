@@ -0,0 +1,307 @@
+//! The build profiles, the `[profile.<name>]` tables.
+//!
+//! The `debug` and `release` profiles are built in,
+//! their tables in `Coppo.toml` only override some settings.
+//! The other profiles must inherit one of them:
+//!
+//! ```toml
+//! [profile.release]
+//! opt-level = 2
+//!
+//! [profile.profiling]
+//! inherits = "release"
+//! debug = true
+//! flags = ["-fno-omit-frame-pointer"]
+//! ```
+//!
+//! `lto` enables the link-time optimization, `true` for the full one or `"thin"`:
+//!
+//! ```toml
+//! [profile.release]
+//! lto = "thin"
+//! ```
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Config, E};
+
+/// The profile used if no profile is given.
+pub const DEBUG_PROFILE: &str = "debug";
+
+/// The profile of `--release`.
+pub const RELEASE_PROFILE: &str = "release";
+
+/// The optimization level, `0` to `3`, or `"s"` and `"z"` to optimize for size.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OptLevel {
+    /// `0` to `3`.
+    Level(u8),
+    /// `"s"` or `"z"`.
+    Size(String),
+}
+
+impl fmt::Display for OptLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptLevel::Level(level) => write!(f, "{}", level),
+            OptLevel::Size(size) => write!(f, "{}", size),
+        }
+    }
+}
+
+impl OptLevel {
+    /// Check if the level is supported by the compilers.
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            OptLevel::Level(0..=3) => Ok(()),
+            OptLevel::Size(size) if size == "s" || size == "z" => Ok(()),
+            _ => Err(format!(
+                "The opt-level `{}` is invalid, it should be 0 to 3, \"s\" or \"z\"",
+                self
+            )),
+        }
+    }
+}
+
+/// The link-time optimization, `true` or `false`, or `"thin"` for the parallel one of Clang.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Lto {
+    /// `true` or `false`.
+    Enabled(bool),
+    /// `"thin"`.
+    Mode(String),
+}
+
+impl fmt::Display for Lto {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lto::Enabled(enabled) => write!(f, "{}", enabled),
+            Lto::Mode(mode) => write!(f, "{}", mode),
+        }
+    }
+}
+
+impl Lto {
+    /// Check if the mode is supported by the compilers.
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Lto::Enabled(_) => Ok(()),
+            Lto::Mode(mode) if mode == "thin" => Ok(()),
+            _ => Err(format!(
+                "The lto `{}` is invalid, it should be true, false or \"thin\"",
+                self
+            )),
+        }
+    }
+
+    /// The flag of the compile and link steps, like `-flto=thin`, it is `None` if disabled.
+    pub fn flag(&self) -> Option<String> {
+        match self {
+            Lto::Enabled(true) => Some("-flto".to_owned()),
+            Lto::Enabled(false) => None,
+            Lto::Mode(mode) => Some(format!("-flto={}", mode)),
+        }
+    }
+}
+
+/// A build profile, the `[profile.<name>]` table.
+///
+/// It contains the following fields:
+/// - `inherits`: The built-in profile it is based on, needed by the other profiles.
+/// - `opt-level`: The optimization level, like `-O2`.
+/// - `debug`: Whether the debug symbols are generated, like `-g`.
+/// - `debug-assertions`: Whether `assert` is enabled, `-DNDEBUG` disables it.
+/// - `lto`: The link-time optimization, `true` or `"thin"`, like `-flto`.
+/// - `flags`: The extra compile flags.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Profile {
+    /// The built-in profile it is based on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inherits: Option<String>,
+    /// The optimization level.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opt_level: Option<OptLevel>,
+    /// Whether the debug symbols are generated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug: Option<bool>,
+    /// Whether `assert` is enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug_assertions: Option<bool>,
+    /// The link-time optimization.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lto: Option<Lto>,
+    /// The extra compile flags, after the ones of the inherited profile.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<String>,
+}
+
+impl Profile {
+    /// The built-in `debug` profile, without optimization and with the debug symbols.
+    pub fn debug() -> Profile {
+        Profile {
+            inherits: None,
+            opt_level: Some(OptLevel::Level(0)),
+            debug: Some(true),
+            debug_assertions: Some(true),
+            lto: None,
+            flags: vec![],
+        }
+    }
+
+    /// The built-in `release` profile, optimized and without the assertions.
+    pub fn release() -> Profile {
+        Profile {
+            inherits: None,
+            opt_level: Some(OptLevel::Level(3)),
+            debug: Some(false),
+            debug_assertions: Some(false),
+            lto: None,
+            flags: vec![],
+        }
+    }
+
+    /// The built-in profile of the name.
+    pub fn builtin(name: &str) -> Option<Profile> {
+        match name {
+            DEBUG_PROFILE => Some(Profile::debug()),
+            RELEASE_PROFILE => Some(Profile::release()),
+            _ => None,
+        }
+    }
+
+    /// Override the settings with the ones given by the other profile.
+    fn merge(mut self, other: &Profile) -> Profile {
+        if other.opt_level.is_some() {
+            self.opt_level = other.opt_level.clone();
+        }
+        if other.debug.is_some() {
+            self.debug = other.debug;
+        }
+        if other.debug_assertions.is_some() {
+            self.debug_assertions = other.debug_assertions;
+        }
+        if other.lto.is_some() {
+            self.lto = other.lto.clone();
+        }
+        self.flags.extend(other.flags.iter().cloned());
+
+        self
+    }
+}
+
+impl Config {
+    /// The profile of the name, the built-in or inherited settings overridden by its table.
+    ///
+    /// # Example
+    /// ```rust
+    /// use coppo_config::Config;
+    /// use coppo_config::profile::OptLevel;
+    /// let config = Config::from_str(r#"
+    ///     [profile.release]
+    ///     opt-level = 2
+    ///
+    ///     [profile.profiling]
+    ///     inherits = "release"
+    ///     debug = true
+    /// "#).expect("Failed to parse config file.");
+    ///
+    /// let profile = config.profile("profiling").unwrap();
+    /// assert_eq!(profile.opt_level, Some(OptLevel::Level(2)));
+    /// assert_eq!(profile.debug, Some(true));
+    /// assert!(config.profile("unknown").is_err());
+    /// ```
+    pub fn profile(&self, name: &str) -> Result<Profile, E> {
+        let table = self.profile.get(name);
+        let base = match Profile::builtin(name) {
+            Some(builtin) => builtin,
+            None => {
+                let table = table.ok_or_else(|| format!("The profile `{}` is not found", name))?;
+                let inherits = table.inherits.as_deref().ok_or_else(|| {
+                    format!(
+                        "The profile `{}` must inherit `{}` or `{}` with `inherits`",
+                        name, DEBUG_PROFILE, RELEASE_PROFILE
+                    )
+                })?;
+                if Profile::builtin(inherits).is_none() {
+                    return Err(format!(
+                        "The profile `{}` can only inherit `{}` or `{}`, not `{}`",
+                        name, DEBUG_PROFILE, RELEASE_PROFILE, inherits
+                    )
+                    .into());
+                }
+                self.profile(inherits)?
+            }
+        };
+
+        let profile = match table {
+            Some(table) => base.merge(table),
+            None => base,
+        };
+        if let Some(opt_level) = &profile.opt_level {
+            opt_level
+                .validate()
+                .map_err(|e| format!("Invalid profile `{}`: {}", name, e))?;
+        }
+        if let Some(lto) = &profile.lto {
+            lto.validate()
+                .map_err(|e| format!("Invalid profile `{}`: {}", name, e))?;
+        }
+
+        Ok(profile)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_profile() -> Result<(), E> {
+        let config = Config::from_str(
+            r#"
+            [profile.debug]
+            flags = ["-fsanitize=address"]
+
+            [profile.small]
+            inherits = "release"
+            opt-level = "z"
+
+            [profile.bad]
+            inherits = "small"
+            "#,
+        )?;
+
+        let debug = config.profile(DEBUG_PROFILE)?;
+        assert_eq!(debug.opt_level, Some(OptLevel::Level(0)));
+        assert_eq!(debug.flags, vec!["-fsanitize=address"]);
+        assert_eq!(config.profile(RELEASE_PROFILE)?, Profile::release());
+
+        let small = config.profile("small")?;
+        assert_eq!(small.opt_level, Some(OptLevel::Size("z".to_string())));
+        assert_eq!(small.debug_assertions, Some(false));
+
+        assert!(config.profile("bad").is_err());
+        assert!(Config::from_str("[profile.release]\nopt-level = 4")?
+            .profile(RELEASE_PROFILE)
+            .is_err());
+
+        let config = Config::from_str("[profile.release]\nlto = \"thin\"")?;
+        let release = config.profile(RELEASE_PROFILE)?;
+        assert_eq!(release.lto, Some(Lto::Mode("thin".to_string())));
+        assert_eq!(
+            release.lto.and_then(|lto| lto.flag()).as_deref(),
+            Some("-flto=thin")
+        );
+        assert_eq!(config.profile(DEBUG_PROFILE)?.lto, None);
+        assert!(Config::from_str("[profile.release]\nlto = \"fat\"")?
+            .profile(RELEASE_PROFILE)
+            .is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+//! The C++ and C standards, the `std` of the `[build]` table.
+//!
+//! The standard is a year like `c++20`, or `20` for short,
+//! or a GNU dialect like `gnu++20`, and the drafts like `c++2c` are accepted too:
+//!
+//! ```toml
+//! [build]
+//! std = "c++20"
+//! ```
+//!
+//! The standards of the C projects, see [`Language`], are like `c17`, `17` or `gnu17`.
+//!
+//! Without `std`, the projects are built with [`DEFAULT_STD`], or [`DEFAULT_C_STD`] for C,
+//! instead of the default of the compiler, so they do not change with the version of the compiler.
+
+use crate::language::Language;
+
+/// The standard used if none is given.
+pub const DEFAULT_STD: &str = "c++17";
+
+/// The standard of the C projects used if none is given.
+pub const DEFAULT_C_STD: &str = "c17";
+
+/// The C standards, from the oldest to the newest.
+pub const C_STANDARDS: &[&str] = &["c89", "c99", "c11", "c17", "c23"];
+
+/// The names of the drafts of the C standards.
+const C_DRAFTS: &[&str] = &["9x", "1x", "2x", "18"];
+
+/// The standards, from the oldest to the newest.
+pub const STANDARDS: &[&str] = &[
+    "c++98", "c++03", "c++11", "c++14", "c++17", "c++20", "c++23", "c++26",
+];
+
+/// The names of the drafts of the standards.
+const DRAFTS: &[&str] = &["0x", "1y", "1z", "2a", "2b", "2c"];
+
+/// The standard used if none is given for the language.
+pub fn default_std(language: Language) -> &'static str {
+    match language {
+        Language::Cpp => DEFAULT_STD,
+        Language::C => DEFAULT_C_STD,
+    }
+}
+
+/// The standard of the `std` value, like `c++20` for `20`, or `c17` for `17` in C.
+///
+/// # Example
+/// ```rust
+/// use coppo_config::language::Language;
+/// use coppo_config::standard::normalize;
+///
+/// assert_eq!(normalize("20", Language::Cpp), "c++20");
+/// assert_eq!(normalize("gnu++2a", Language::Cpp), "gnu++2a");
+/// assert_eq!(normalize("11", Language::C), "c11");
+/// ```
+pub fn normalize(std: &str, language: Language) -> String {
+    match (std.chars().all(|c| c.is_ascii_digit()), language) {
+        (true, Language::Cpp) => format!("c++{}", std),
+        (true, Language::C) => format!("c{}", std),
+        (false, _) => std.to_owned(),
+    }
+}
+
+/// The compile flag of the standard, like `-std=c++20`.
+pub fn flag(std: &str, language: Language) -> String {
+    format!("-std={}", normalize(std, language))
+}
+
+/// Check if the standard is known, and is one of the language.
+pub fn validate(std: &str, language: Language) -> Result<(), String> {
+    if language == Language::C {
+        return validate_c(std);
+    }
+
+    let std = normalize(std, language);
+    let version = std
+        .strip_prefix("c++")
+        .or_else(|| std.strip_prefix("gnu++"));
+    let known = version.is_some_and(|version| {
+        DRAFTS.contains(&version) || STANDARDS.iter().any(|known| known[3..] == *version)
+    });
+
+    match known {
+        true => Ok(()),
+        false => Err(format!(
+            "The C++ standard `{}` is invalid, it should be one of {}, or a GNU dialect like `gnu++20`",
+            std,
+            STANDARDS.join(", ")
+        )),
+    }
+}
+
+/// Check if the C standard is known.
+fn validate_c(std: &str) -> Result<(), String> {
+    let std = normalize(std, Language::C);
+    let version = std
+        .strip_prefix("gnu")
+        .or_else(|| std.strip_prefix('c'))
+        .filter(|version| !version.starts_with("++"));
+    let known = version.is_some_and(|version| {
+        C_DRAFTS.contains(&version) || C_STANDARDS.iter().any(|known| known[1..] == *version)
+    });
+
+    match known {
+        true => Ok(()),
+        false => Err(format!(
+            "The C standard `{}` is invalid, it should be one of {}, or a GNU dialect like `gnu17`",
+            std,
+            C_STANDARDS.join(", ")
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        for std in ["17", "c++20", "gnu++23", "c++2c", "98"] {
+            assert!(validate(std, Language::Cpp).is_ok(), "{}", std);
+        }
+        for std in ["c++21", "cpp20", "", "c++", "c17"] {
+            assert!(validate(std, Language::Cpp).is_err(), "{}", std);
+        }
+        assert_eq!(flag("20", Language::Cpp), "-std=c++20");
+        assert!(validate(DEFAULT_STD, Language::Cpp).is_ok());
+
+        for std in ["17", "c99", "gnu11", "c2x", "89"] {
+            assert!(validate(std, Language::C).is_ok(), "{}", std);
+        }
+        for std in ["c++17", "c20", "gnu++17", ""] {
+            assert!(validate(std, Language::C).is_err(), "{}", std);
+        }
+        assert_eq!(flag("11", Language::C), "-std=c11");
+        assert!(validate(DEFAULT_C_STD, Language::C).is_ok());
+    }
+}
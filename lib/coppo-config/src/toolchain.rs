@@ -0,0 +1,149 @@
+//! The toolchains managed by `coppo toolchain`.
+//!
+//! The toolchains are installed in `~/.coppo/toolchains/<name>`,
+//! and the toolchain of a project is selected by an override,
+//! which is stored in `~/.coppo/toolchains/overrides.toml` like this:
+//!
+//! ```toml
+//! [overrides]
+//! "/home/me/projects/app" = "llvm-18.1.8"
+//! ```
+//!
+//! The override applies to the directory and all its sub-directories.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{coppo_home, E};
+
+/// The directory of the toolchains, relative to the Coppo home directory.
+pub const TOOLCHAINS_DIR: &str = "toolchains";
+
+/// The file of the overrides, in the toolchains directory.
+pub const OVERRIDES_FILE: &str = "overrides.toml";
+
+/// Get the directory of the toolchains, like `~/.coppo/toolchains`.
+pub fn toolchains_dir() -> Option<PathBuf> {
+    coppo_home().map(|home| home.join(TOOLCHAINS_DIR))
+}
+
+/// The C++ compiler of the toolchain, like `~/.coppo/toolchains/llvm-18.1.8/bin/clang++`.
+pub fn compiler(toolchain: &Path) -> PathBuf {
+    toolchain
+        .join("bin")
+        .join("clang++")
+        .with_extension(env::consts::EXE_EXTENSION)
+}
+
+/// The names of the installed toolchains.
+pub fn installed() -> Vec<String> {
+    let Some(Ok(entries)) = toolchains_dir().map(fs::read_dir) else {
+        return vec![];
+    };
+
+    let mut names = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        // The unfinished downloads are hidden.
+        .filter(|name| !name.starts_with('.'))
+        .collect::<Vec<_>>();
+    names.sort();
+
+    names
+}
+
+/// The compiler of the toolchain selected for the current directory,
+/// it is `None` if there is no override or the toolchain is not installed.
+pub fn active_compiler() -> Option<PathBuf> {
+    let dir = env::current_dir().ok()?;
+    let name = Overrides::from_file().ok()?.get(&dir)?.to_owned();
+    let compiler = compiler(&toolchains_dir()?.join(name));
+
+    compiler.is_file().then_some(compiler)
+}
+
+/// The toolchain overrides of the projects.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Overrides {
+    /// The toolchain names, keyed by the absolute paths of the directories.
+    #[serde(default)]
+    pub overrides: BTreeMap<String, String>,
+}
+
+impl Overrides {
+    /// The path of the overrides file.
+    pub fn path() -> Option<PathBuf> {
+        toolchains_dir().map(|dir| dir.join(OVERRIDES_FILE))
+    }
+
+    /// Parse the overrides file, it returns no override if the file does not exist.
+    pub fn from_file() -> Result<Overrides, E> {
+        match Overrides::path() {
+            Some(path) if path.exists() => {
+                let overrides = fs::read_to_string(&path)?;
+                toml::from_str(&overrides)
+                    .map_err(|e| format!("Failed to parse `{}`: {}", path.display(), e).into())
+            }
+            _ => Ok(Overrides::default()),
+        }
+    }
+
+    /// Write the overrides file.
+    pub fn to_file(&self) -> Result<(), E> {
+        let path = Overrides::path().ok_or("Could not find the Coppo home directory.")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, toml::to_string(self)?)?;
+
+        Ok(())
+    }
+
+    /// The toolchain of the directory, the override of the nearest ancestor is used.
+    pub fn get(&self, dir: &Path) -> Option<&str> {
+        dir.ancestors()
+            .find_map(|dir| self.overrides.get(&*dir.to_string_lossy()))
+            .map(String::as_str)
+    }
+
+    /// Set the toolchain of the directory.
+    pub fn set(&mut self, dir: &Path, toolchain: &str) {
+        self.overrides
+            .insert(dir.to_string_lossy().into_owned(), toolchain.to_owned());
+    }
+
+    /// Remove the override of the directory, it returns the removed toolchain.
+    pub fn unset(&mut self, dir: &Path) -> Option<String> {
+        self.overrides.remove(&*dir.to_string_lossy())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_overrides() {
+        let mut overrides = Overrides::default();
+        let project = Path::new("/projects/app");
+        overrides.set(project, "llvm-18.1.8");
+
+        assert_eq!(overrides.get(project), Some("llvm-18.1.8"));
+        assert_eq!(overrides.get(&project.join("src")), Some("llvm-18.1.8"));
+        assert_eq!(overrides.get(Path::new("/projects")), None);
+
+        overrides.set(&project.join("vendor"), "mingw-20240619");
+        assert_eq!(
+            overrides.get(&project.join("vendor/zlib")),
+            Some("mingw-20240619")
+        );
+
+        assert_eq!(overrides.unset(project), Some("llvm-18.1.8".to_string()));
+        assert_eq!(overrides.get(project), None);
+    }
+}
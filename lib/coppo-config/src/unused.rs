@@ -0,0 +1,238 @@
+//! The unused keys of the manifest.
+//!
+//! The keys which are not a part of the manifest are collected when it is parsed,
+//! instead of being silently ignored, so a typo like `lisense` can be reported:
+//!
+//! ```text
+//! warning: unused manifest key: project.lisense
+//! help: did you mean `license`?
+//! ```
+//!
+//! They are warnings by default, and errors with `coppo --strict`.
+
+use std::fmt;
+
+use crate::error::distance;
+use crate::inherit::INHERITABLE;
+use crate::{Config, ConfigError};
+
+const MANIFEST: &[&str] = &[
+    "project",
+    "package",
+    "dependencies",
+    "dev-dependencies",
+    "target",
+    "features",
+    "lib",
+    "bin",
+    "build",
+    "lint",
+    "doc",
+    "dist",
+    "profile",
+    "workspace",
+];
+const PROJECT: &[&str] = &[
+    "name",
+    "version",
+    "authors",
+    "description",
+    "license",
+    "repository",
+    "include",
+    "exclude",
+    "metadata",
+];
+const DEPENDENCY: &[&str] = &[
+    "name", "version", "git", "branch", "path", "registry", "features", "optional",
+];
+const TARGET: &[&str] = &["dependencies", "dev-dependencies"];
+const LIB: &[&str] = &["name", "path", "include", "kind"];
+const BIN: &[&str] = &["name", "path"];
+const BUILD: &[&str] = &[
+    "compiler",
+    "wrapper",
+    "std",
+    "cxxflags",
+    "ldflags",
+    "include-dirs",
+    "defines",
+];
+const LINT: &[&str] = &["checks", "warnings-as-errors"];
+const DOC: &[&str] = &["generator", "title", "input"];
+const DIST: &[&str] = &["targets", "include"];
+const PROFILE: &[&str] = &[
+    "inherits",
+    "opt-level",
+    "debug",
+    "debug-assertions",
+    "lto",
+    "flags",
+];
+const WORKSPACE: &[&str] = &["members", "package"];
+
+/// A key of the manifest which is not used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnusedKey {
+    /// The dotted key, like `project.lisense`.
+    pub key: String,
+    /// The known key which is the closest to the unused one, like `license`.
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for UnusedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unused manifest key: {}", self.key)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " - did you mean `{}`?", suggestion)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl UnusedKey {
+    fn new(path: &[String]) -> Self {
+        let (key, table) = path.split_last().expect("The unused key is never the root");
+        let table = table.iter().map(String::as_str).collect::<Vec<_>>();
+        let suggestion = known_keys(&table)
+            .iter()
+            .map(|known| (distance(key, known), *known))
+            .filter(|(distance, _)| *distance <= 2)
+            .min()
+            .map(|(_, known)| known.to_owned());
+
+        UnusedKey {
+            key: path.join("."),
+            suggestion,
+        }
+    }
+}
+
+/// The keys of the table of the manifest, it is empty if the keys of the table are free,
+/// like the names of the dependencies.
+fn known_keys(table: &[&str]) -> &'static [&'static str] {
+    match table {
+        [] => MANIFEST,
+        ["project" | "package"] => PROJECT,
+        ["dependencies" | "dev-dependencies" | "dev_dependencies", _] => DEPENDENCY,
+        ["target", _] => TARGET,
+        ["target", _, "dependencies" | "dev-dependencies" | "dev_dependencies", _] => DEPENDENCY,
+        ["lib"] => LIB,
+        ["bin", _] => BIN,
+        ["build"] => BUILD,
+        ["lint"] => LINT,
+        ["doc"] => DOC,
+        ["dist"] => DIST,
+        ["profile", _] => PROFILE,
+        ["workspace"] => WORKSPACE,
+        ["workspace", "package"] => INHERITABLE,
+        _ => &[],
+    }
+}
+
+/// The segments of the path, without the ones of the options and the newtypes.
+fn segments(path: &serde_ignored::Path, out: &mut Vec<String>) {
+    match path {
+        serde_ignored::Path::Root => {}
+        serde_ignored::Path::Seq { parent, index } => {
+            segments(parent, out);
+            out.push(index.to_string());
+        }
+        serde_ignored::Path::Map { parent, key } => {
+            segments(parent, out);
+            out.push(key.clone());
+        }
+        serde_ignored::Path::Some { parent }
+        | serde_ignored::Path::NewtypeStruct { parent }
+        | serde_ignored::Path::NewtypeVariant { parent } => segments(parent, out),
+    }
+}
+
+/// Parse the manifest, and collect its unused keys into [`Config::unused_keys`].
+pub(crate) fn parse(source: &str) -> Result<Config, ConfigError> {
+    let mut unused = vec![];
+    let deserializer = toml::Deserializer::new(source);
+    let mut config: Config = serde_ignored::deserialize(deserializer, |path| {
+        let mut path_segments = vec![];
+        segments(&path, &mut path_segments);
+        unused.push(UnusedKey::new(&path_segments));
+    })
+    .map_err(|e| ConfigError::from_toml(e, source))?;
+    config.unused_keys = unused;
+
+    Ok(config)
+}
+
+impl Config {
+    /// Turn the unused keys into an error, used by `coppo --strict`.
+    pub fn deny_unused_keys(&self) -> Result<(), String> {
+        match self.unused_keys.as_slice() {
+            [] => Ok(()),
+            keys => Err(keys
+                .iter()
+                .map(UnusedKey::to_string)
+                .collect::<Vec<_>>()
+                .join("\n")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unused_keys() -> Result<(), ConfigError> {
+        let config = Config::from_str(
+            r#"
+            [project]
+            name = "app"
+            version = "0.1.0"
+            authors = []
+            lisense = "MIT"
+
+            [dependencies]
+            fmt = { verison = "10" }
+
+            [target.'cfg(unix)'.dependencies]
+            pthread = { path = "../pthread", optinal = true }
+
+            [profile.release]
+            opt-levle = 3
+
+            [[bin]]
+            name = "app"
+            unknown = true
+
+            [project.metadata.tool]
+            anything = 1
+            "#,
+        )?;
+        let mut keys = config
+            .unused_keys
+            .iter()
+            .map(|key| (key.key.as_str(), key.suggestion.as_deref()))
+            .collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(
+            keys,
+            [
+                ("bin.0.unknown", None),
+                ("dependencies.fmt.verison", Some("version")),
+                ("profile.release.opt-levle", Some("opt-level")),
+                ("project.lisense", Some("license")),
+                (
+                    "target.cfg(unix).dependencies.pthread.optinal",
+                    Some("optional")
+                ),
+            ]
+        );
+        assert!(config
+            .deny_unused_keys()
+            .unwrap_err()
+            .contains("unused manifest key: project.lisense - did you mean `license`?"));
+
+        Ok(())
+    }
+}
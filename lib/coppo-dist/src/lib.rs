@@ -0,0 +1,354 @@
+//! The `coppo-dist` crate is a Coppo addon that packages the release binaries of the current project,
+//! ready to attach to a release.
+//!
+//! For each target triple of `dist.targets`, or the host if there is none,
+//! the binaries are built with the `release` profile and stripped,
+//! and bundled with the licenses, the extra files of `dist.include` and a `manifest.json`
+//! into `target/dist/<name>-<version>-<target>.tar.gz`, or `.zip` for the Windows targets.
+//! The SHA-256 checksums of the archives are written into `target/dist/SHA256SUMS`.
+//!
+//! ```toml
+//! [dist]
+//! targets = ["x86_64-unknown-linux-gnu", "x86_64-pc-windows-gnu"]
+//! include = ["README.md"]
+//! ```
+//!
+//! # Usage
+//! ```sh
+//! coppo dist [--target <TRIPLE>]...
+//! ```
+
+#![forbid(unsafe_code)]
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use coppo_addons::prelude::*;
+use coppo_build::env::which;
+use coppo_build::{bins, build_bin, find_compiler, lto, msvc, profile_flags, target_dir};
+use coppo_config::profile::{Profile, RELEASE_PROFILE};
+use coppo_logger::prelude::*;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The packages are stored in `target/dist`.
+pub const DIST_DIR: &str = "dist";
+
+/// The file of the checksums of the archives.
+pub const CHECKSUMS_FILE: &str = "SHA256SUMS";
+
+/// The manifest bundled in each archive.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// The prefixes of the license files in the project root, compared case-insensitively.
+const LICENSE_PREFIXES: &[&str] = &["license", "licence", "copying", "notice"];
+
+/// The `Coppo dist` add-on.
+/// Package the release binaries of the current project for each target.
+pub struct CoppoDistAddon;
+
+impl_addon! {
+    CoppoDistAddon,
+    name => "dist",
+    description => "Package the release binaries for distribution",
+    args => [
+        arg!(--target <TRIPLE> "The target triple to package, it overrides `dist.targets`")
+            .action(ArgAction::Append)
+            .value_parser(value_parser!(String)),
+    ],
+    run => |config, matches| {
+        if !Config::exists() {
+            return Err("The project does not have a `Coppo.toml` file.".into());
+        }
+        if config.is_empty() {
+            return Err("The project name and version is needed".into());
+        }
+        if bins(config).is_empty() {
+            return Err("The project does not have a binary target to package.".into());
+        }
+
+        let host = host(config)?;
+        let targets = match matches.get_many::<String>("target") {
+            Some(targets) => targets.cloned().collect(),
+            None if !config.dist.targets.is_empty() => config.dist.targets.clone(),
+            None => vec![host.clone()],
+        };
+
+        let dir = target_dir().join(DIST_DIR);
+        fs::create_dir_all(&dir)?;
+
+        let mut archives = vec![];
+        for target in &targets {
+            info!("Packaging `{}` for `{}`...", config.project.name, target);
+            let archive = package(config, target, &host, &dir)?;
+            info!("Created `{}`", archive.display());
+            archives.push(archive);
+        }
+
+        let checksums = dir.join(CHECKSUMS_FILE);
+        fs::write(&checksums, checksums_of(&archives)?)?;
+        info!("Created `{}`", checksums.display());
+
+        success!("Packaged {} target(s) into `{}`.", targets.len(), dir.display());
+    }
+}
+
+/// The target triple of the compiler, like `x86_64-unknown-linux-gnu`.
+pub fn host(config: &Config) -> Result<String> {
+    let compiler = find_compiler(config)?;
+    // MSVC only targets Windows, and does not tell its target.
+    if msvc::is_msvc(&compiler) {
+        return Ok(format!("{}-pc-windows-msvc", std::env::consts::ARCH));
+    }
+    let output = process::Command::new(&compiler)
+        .arg("-dumpmachine")
+        .output()
+        .map_err(|e| format!("Failed to run `{}`: {}", compiler, e))?;
+    if !output.status.success() {
+        return Err(format!("Failed to get the target triple of `{}`.", compiler).into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+/// Check if the target is Windows, its binaries have the `.exe` extension and it uses `.zip`.
+pub fn is_windows(target: &str) -> bool {
+    target.contains("windows") || target.contains("mingw")
+}
+
+/// The compile flags of the target, the ones of the `release` profile,
+/// and `--target` which is only passed when cross-compiling.
+pub fn flags(release: &Profile, target: &str, host: &str) -> Vec<String> {
+    let mut flags = profile_flags(release);
+    if target != host {
+        flags.push(format!("--target={}", target));
+    }
+
+    flags
+}
+
+/// The name of the package, like `app-0.1.0-x86_64-unknown-linux-gnu`.
+pub fn package_name(config: &Config, target: &str) -> String {
+    format!(
+        "{}-{}-{}",
+        config.project.name, config.project.version, target
+    )
+}
+
+/// The manifest bundled in each archive.
+#[derive(Debug, Serialize)]
+pub struct Manifest {
+    /// The project name.
+    pub name: String,
+    /// The project version.
+    pub version: String,
+    /// The target triple.
+    pub target: String,
+    /// The binaries, relative to the package root.
+    pub binaries: Vec<String>,
+    /// The other bundled files, relative to the package root.
+    pub files: Vec<String>,
+}
+
+/// Build, strip and bundle the binaries for the target, and return the path of the archive.
+pub fn package(config: &Config, target: &str, host: &str, dir: &Path) -> Result<PathBuf> {
+    let name = package_name(config, target);
+    let staging = dir.join(&name);
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    fs::create_dir_all(&staging)?;
+
+    let release = config.profile(RELEASE_PROFILE)?;
+    // The linker of a cross target is only known by building the project.
+    if target == host {
+        lto::check(
+            &find_compiler(config)?,
+            config.project.language,
+            &release,
+            &config.build.ldflags,
+        )?;
+    }
+    let flags = flags(&release, target, host);
+    let mut binaries = vec![];
+    for bin in bins(config) {
+        let file_name = match is_windows(target) {
+            true => format!("{}.exe", bin.name),
+            false => bin.name.clone(),
+        };
+        let binary = staging.join(&file_name);
+        build_bin(config, &bin, &binary, &flags)?;
+        strip(&binary, target != host);
+        binaries.push(file_name);
+    }
+
+    let mut files = vec![];
+    for file in licenses()?.into_iter().chain(config.dist.include.clone()) {
+        let file_name = file
+            .file_name()
+            .ok_or_else(|| format!("Invalid file `{}`", file.display()))?
+            .to_string_lossy()
+            .into_owned();
+        fs::copy(&file, staging.join(&file_name))
+            .map_err(|e| format!("Failed to bundle `{}`: {}", file.display(), e))?;
+        files.push(file_name);
+    }
+
+    let manifest = Manifest {
+        name: config.project.name.clone(),
+        version: config.project.version.to_string(),
+        target: target.to_owned(),
+        binaries,
+        files,
+    };
+    fs::write(
+        staging.join(MANIFEST_FILE),
+        serde_json::to_string_pretty(&manifest)? + "\n",
+    )?;
+
+    let archive = if is_windows(target) {
+        let archive = dir.join(format!("{}.zip", name));
+        zip_dir(&staging, &name, &archive)?;
+        archive
+    } else {
+        let archive = dir.join(format!("{}.tar.gz", name));
+        tar_dir(&staging, &name, &archive)?;
+        archive
+    };
+    fs::remove_dir_all(&staging)?;
+
+    Ok(archive)
+}
+
+/// Strip the debug symbols of the binary,
+/// `llvm-strip` is preferred since it understands the binaries of all targets.
+fn strip(binary: &Path, cross: bool) {
+    let strip = match which("llvm-strip") {
+        Some(_) => "llvm-strip",
+        None if !cross && which("strip").is_some() => "strip",
+        None => {
+            warn!(
+                "`llvm-strip` is not found, `{}` is not stripped.",
+                binary.display()
+            );
+            return;
+        }
+    };
+
+    let stripped = process::Command::new(strip)
+        .arg(binary)
+        .status()
+        .is_ok_and(|status| status.success());
+    if !stripped {
+        warn!("Failed to strip `{}`.", binary.display());
+    }
+}
+
+/// The license files in the project root, like `LICENSE` or `COPYING.md`.
+fn licenses() -> Result<Vec<PathBuf>> {
+    let mut licenses = vec![];
+    for entry in fs::read_dir(".")? {
+        let path = entry?.path();
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        if path.is_file()
+            && LICENSE_PREFIXES
+                .iter()
+                .any(|prefix| name.starts_with(prefix))
+        {
+            licenses.push(path.strip_prefix(".").unwrap_or(&path).to_owned());
+        }
+    }
+    licenses.sort();
+
+    Ok(licenses)
+}
+
+/// The files in the directory, sorted.
+fn files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    files.sort();
+
+    Ok(files)
+}
+
+/// Bundle the files of the directory into a `.tar.gz` archive, under the `prefix` directory.
+fn tar_dir(dir: &Path, prefix: &str, archive: &Path) -> Result<()> {
+    let encoder = GzEncoder::new(File::create(archive)?, Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    for file in files(dir)? {
+        let name = file.file_name().unwrap_or_default();
+        builder.append_path_with_name(&file, Path::new(prefix).join(name))?;
+    }
+    builder.into_inner()?.finish()?;
+
+    Ok(())
+}
+
+/// Bundle the files of the directory into a `.zip` archive, under the `prefix` directory.
+fn zip_dir(dir: &Path, prefix: &str, archive: &Path) -> Result<()> {
+    let mut zip = ZipWriter::new(File::create(archive)?);
+    for file in files(dir)? {
+        let name = file.file_name().unwrap_or_default().to_string_lossy();
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .unix_permissions(0o755);
+        zip.start_file(format!("{}/{}", prefix, name), options)?;
+        zip.write_all(&fs::read(&file)?)?;
+    }
+    zip.finish()?;
+
+    Ok(())
+}
+
+/// The checksums file of the archives, in the format of `sha256sum`.
+pub fn checksums_of(archives: &[PathBuf]) -> Result<String> {
+    let mut checksums = String::new();
+    for archive in archives {
+        let hash = Sha256::digest(fs::read(archive)?);
+        let name = archive.file_name().unwrap_or_default().to_string_lossy();
+        checksums += &format!("{:x}  {}\n", hash, name);
+    }
+
+    Ok(checksums)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_flags() {
+        let host = "x86_64-unknown-linux-gnu";
+        let release = Profile::release();
+        assert_eq!(flags(&release, host, host), ["-O3", "-DNDEBUG"]);
+        assert_eq!(
+            flags(&release, "aarch64-unknown-linux-gnu", host),
+            ["-O3", "-DNDEBUG", "--target=aarch64-unknown-linux-gnu"]
+        );
+
+        assert!(is_windows("x86_64-pc-windows-msvc"));
+        assert!(is_windows("x86_64-w64-mingw32"));
+        assert!(!is_windows(host));
+
+        let mut config = Config::default();
+        config.project.name = "app".to_string();
+        config.project.version = coppo_config::Version::new(0, 1, 0);
+        assert_eq!(
+            package_name(&config, host),
+            "app-0.1.0-x86_64-unknown-linux-gnu"
+        );
+    }
+}
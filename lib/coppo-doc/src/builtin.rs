@@ -0,0 +1,308 @@
+//! The built-in documentation generator.
+//!
+//! It parses the headers for the doc comments, `///` or `/** */`,
+//! and renders every documented declaration into a single HTML page,
+//! with the links to the pages of the documented dependencies:
+//!
+//! ```cpp
+//! /// Get a greeting from the library.
+//! std::string hello();
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use coppo_config::files::{self, HEADER_EXTENSIONS};
+use coppo_config::prelude::*;
+
+use crate::{title, Result};
+
+/// A documented declaration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Item {
+    /// The doc comment, without the comment markers.
+    pub doc: String,
+    /// The declaration, without the body.
+    pub declaration: String,
+    /// The line of the declaration, starting from `1`.
+    pub line: usize,
+}
+
+/// Parse the documented declarations of the header.
+///
+/// # Example
+/// ```rust
+/// use coppo_doc::builtin::parse;
+///
+/// let items = parse("/// Say hello.\nvoid hello();\n\n// Not documented.\nvoid bye();\n");
+///
+/// assert_eq!(items.len(), 1);
+/// assert_eq!(items[0].doc, "Say hello.");
+/// assert_eq!(items[0].declaration, "void hello();");
+/// ```
+pub fn parse(source: &str) -> Vec<Item> {
+    let mut items = vec![];
+    let mut doc: Vec<String> = vec![];
+    let mut in_block = false;
+    let mut lines = source.lines().enumerate();
+
+    while let Some((index, line)) = lines.next() {
+        let trimmed = line.trim();
+
+        if in_block {
+            let (content, end) = match trimmed.split_once("*/") {
+                Some((content, _)) => (content, true),
+                None => (trimmed, false),
+            };
+            doc.push(strip_doc(content.trim_start_matches('*')));
+            in_block = !end;
+        } else if let Some(block) = trimmed
+            .strip_prefix("/**")
+            .filter(|block| !block.starts_with('*') && !block.starts_with('/'))
+        {
+            match block.split_once("*/") {
+                Some((content, _)) => doc.push(strip_doc(content)),
+                None => {
+                    doc.push(strip_doc(block));
+                    in_block = true;
+                }
+            }
+        } else if let Some(content) = trimmed
+            .strip_prefix("///")
+            .filter(|content| !content.starts_with('/'))
+        {
+            doc.push(strip_doc(content));
+        } else if trimmed.is_empty() || trimmed.starts_with("//") || trimmed.starts_with("/*") {
+            // The blank lines and the normal comments between the doc and the declaration.
+        } else if !doc.iter().all(String::is_empty) {
+            // The declaration ends at the body or `;`, the macros end at the line.
+            let mut declaration = trimmed.to_owned();
+            while !trimmed.starts_with('#') && !declaration.contains(['{', ';']) {
+                match lines.next() {
+                    Some((_, line)) => {
+                        declaration.push(' ');
+                        declaration.push_str(line.trim());
+                    }
+                    None => break,
+                }
+            }
+            if let Some((head, _)) = declaration.split_once('{') {
+                declaration = head.trim_end().to_owned();
+            }
+
+            items.push(Item {
+                doc: doc.join("\n").trim().to_owned(),
+                declaration,
+                line: index + 1,
+            });
+            doc.clear();
+        } else {
+            doc.clear();
+        }
+    }
+
+    items
+}
+
+/// Strip the leading space and the `@brief` command of a doc comment line.
+fn strip_doc(line: &str) -> String {
+    let line = line.strip_prefix(' ').unwrap_or(line).trim_end();
+    line.strip_prefix("@brief ")
+        .or_else(|| line.strip_prefix("\\brief "))
+        .unwrap_or(line)
+        .to_owned()
+}
+
+/// Escape the text for HTML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Render the documented headers into an HTML page,
+/// with the links to the documentation of the dependencies, by their names.
+pub fn render(
+    config: &Config,
+    headers: &[(PathBuf, Vec<Item>)],
+    dependencies: &[(String, String)],
+) -> String {
+    let title = escape(&title(config));
+    let mut page = format!(
+        "<!DOCTYPE html>\n\
+        <html>\n\
+        <head>\n\
+        <meta charset=\"utf-8\">\n\
+        <title>{title}</title>\n\
+        <style>\n\
+        body {{ font-family: sans-serif; max-width: 960px; margin: auto; }}\n\
+        pre {{ background: #f4f4f4; padding: 8px; overflow-x: auto; }}\n\
+        </style>\n\
+        </head>\n\
+        <body>\n\
+        <h1>{title} {version}</h1>\n",
+        title = title,
+        version = escape(&config.project.version.to_string()),
+    );
+    if let Some(description) = &config.project.description {
+        page += &format!("<p>{}</p>\n", escape(description));
+    }
+
+    page += "<ul>\n";
+    for (i, (header, _)) in headers.iter().enumerate() {
+        page += &format!(
+            "<li><a href=\"#header-{}\">{}</a></li>\n",
+            i,
+            escape(&header.display().to_string())
+        );
+    }
+    page += "</ul>\n";
+
+    if !dependencies.is_empty() {
+        page += "<h2>Dependencies</h2>\n<ul>\n";
+        for (name, link) in dependencies {
+            page += &format!(
+                "<li><a href=\"{}\">{}</a></li>\n",
+                escape(link),
+                escape(name)
+            );
+        }
+        page += "</ul>\n";
+    }
+
+    for (i, (header, items)) in headers.iter().enumerate() {
+        page += &format!(
+            "<h2 id=\"header-{}\">{}</h2>\n",
+            i,
+            escape(&header.display().to_string())
+        );
+        for item in items {
+            page += &format!("<pre><code>{}</code></pre>\n", escape(&item.declaration));
+            for paragraph in item.doc.split("\n\n") {
+                page += &format!("<p>{}</p>\n", escape(paragraph));
+            }
+        }
+    }
+
+    page + "</body>\n</html>\n"
+}
+
+/// Generate the documentation of the project in `dir`, and return the path of its index page.
+/// The dependencies are the names and the index pages of their documentation.
+pub fn generate(
+    config: &Config,
+    dir: &Path,
+    input: &[PathBuf],
+    output: &Path,
+    dependencies: &[(String, PathBuf)],
+) -> Result<PathBuf> {
+    let input = input
+        .iter()
+        .map(|path| path.strip_prefix(".").unwrap_or(path))
+        .collect::<Vec<_>>();
+
+    let mut headers = vec![];
+    for header in files::project_files(&config.project, dir, HEADER_EXTENSIONS)? {
+        if !input.iter().any(|input| header.starts_with(input)) {
+            continue;
+        }
+        let items = parse(&fs::read_to_string(dir.join(&header))?);
+        if !items.is_empty() {
+            headers.push((header, items));
+        }
+    }
+
+    let html = output.join("html");
+    let links = dependencies
+        .iter()
+        .map(|(name, index)| (name.clone(), link(&html, index)))
+        .collect::<Vec<_>>();
+    fs::create_dir_all(&html)?;
+    let index = html.join("index.html");
+    fs::write(&index, render(config, &headers, &links))?;
+
+    Ok(index)
+}
+
+/// The relative link from the directory to the page, both are in the output directory.
+fn link(dir: &Path, page: &Path) -> String {
+    let common = dir
+        .components()
+        .zip(page.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let up = dir.components().skip(common).map(|_| "..".to_owned());
+    let down = page
+        .components()
+        .skip(common)
+        .map(|component| component.as_os_str().to_string_lossy().into_owned());
+
+    up.chain(down).collect::<Vec<_>>().join("/")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_link() {
+        assert_eq!(
+            link(
+                Path::new("/app/target/doc/html"),
+                Path::new("/app/target/doc/deps/fmt/html/index.html")
+            ),
+            "../deps/fmt/html/index.html"
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let items = parse(
+            "#pragma once\n\
+            \n\
+            /**\n \
+             * @brief A point.\n \
+             *\n \
+             * It has two coordinates.\n \
+             */\n\
+            struct Point {\n    \
+                /// The x coordinate.\n    \
+                int x;\n\
+            };\n\
+            \n\
+            /// Add two points.\n\
+            Point add(const Point &a,\n          \
+                      const Point &b);\n\
+            \n\
+            /// The version.\n\
+            #define VERSION 1\n",
+        );
+
+        assert_eq!(
+            items,
+            vec![
+                Item {
+                    doc: "A point.\n\nIt has two coordinates.".to_string(),
+                    declaration: "struct Point".to_string(),
+                    line: 8,
+                },
+                Item {
+                    doc: "The x coordinate.".to_string(),
+                    declaration: "int x;".to_string(),
+                    line: 10,
+                },
+                Item {
+                    doc: "Add two points.".to_string(),
+                    declaration: "Point add(const Point &a, const Point &b);".to_string(),
+                    line: 14,
+                },
+                Item {
+                    doc: "The version.".to_string(),
+                    declaration: "#define VERSION 1".to_string(),
+                    line: 18,
+                },
+            ]
+        );
+    }
+}
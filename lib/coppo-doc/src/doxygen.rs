@@ -0,0 +1,91 @@
+//! Generate the documentation with Doxygen.
+//! The `Doxyfile` is generated from the manifest into `target/doc/Doxyfile`.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use coppo_config::prelude::*;
+
+use crate::{title, Result};
+
+/// The Doxygen executable.
+pub const DOXYGEN: &str = "doxygen";
+
+/// Check if Doxygen is installed.
+pub fn is_installed() -> bool {
+    process::Command::new(DOXYGEN)
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Quote the value of a `Doxyfile` tag.
+fn quote(value: impl AsRef<str>) -> String {
+    format!(
+        "\"{}\"",
+        value.as_ref().replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+/// The `Doxyfile` of the project.
+pub fn doxyfile(config: &Config, input: &[PathBuf], output: &Path) -> String {
+    let list = |values: Vec<String>| values.into_iter().map(quote).collect::<Vec<_>>().join(" ");
+
+    let mut doxyfile = format!(
+        "PROJECT_NAME = {}\n\
+        PROJECT_NUMBER = {}\n\
+        INPUT = {}\n\
+        RECURSIVE = YES\n\
+        EXCLUDE_PATTERNS = {}\n\
+        OUTPUT_DIRECTORY = {}\n\
+        HTML_OUTPUT = html\n\
+        GENERATE_LATEX = NO\n\
+        EXTRACT_ALL = YES\n\
+        QUIET = YES\n\
+        WARN_IF_UNDOCUMENTED = NO\n",
+        quote(title(config)),
+        quote(config.project.version.to_string()),
+        list(
+            input
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect()
+        ),
+        list(config.project.exclude.clone()),
+        quote(output.display().to_string()),
+    );
+    if let Some(description) = &config.project.description {
+        doxyfile += &format!("PROJECT_BRIEF = {}\n", quote(description));
+    }
+
+    doxyfile
+}
+
+/// Generate the documentation of the project in `dir`, and return the path of its index page.
+pub fn generate(config: &Config, dir: &Path, input: &[PathBuf], output: &Path) -> Result<PathBuf> {
+    fs::create_dir_all(output)?;
+    // Doxygen runs in the project directory.
+    let absolute = output.canonicalize()?;
+    let path = absolute.join("Doxyfile");
+    fs::write(&path, doxyfile(config, input, &absolute))?;
+
+    let result = process::Command::new(DOXYGEN)
+        .arg(&path)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => format!("`{}` is not found in PATH.", DOXYGEN),
+            _ => format!("Failed to run `{}`: {}", DOXYGEN, e),
+        })?;
+    if !result.status.success() {
+        return Err(format!(
+            "Doxygen failed: {}",
+            String::from_utf8_lossy(&result.stderr).trim()
+        )
+        .into());
+    }
+
+    Ok(output.join("html").join("index.html"))
+}
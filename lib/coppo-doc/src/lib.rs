@@ -0,0 +1,186 @@
+//! The `coppo-doc` crate is a Coppo addon that generates the API documentation
+//! of the current project into `target/doc`.
+//!
+//! The documentation is generated by Doxygen if it is installed,
+//! otherwise by the built-in header parser, which extracts the `///` and `/** */` comments.
+//! Like `cargo doc`, the members and the packages the project depends on are documented too,
+//! into `target/doc/deps/<name>`, and the built-in page links to them, unless `--no-deps` is given.
+//! The generator can be chosen in the `[doc]` table of `Coppo.toml`:
+//!
+//! ```toml
+//! [doc]
+//! generator = "builtin"
+//! title = "My Project API"
+//! input = ["include"]
+//! ```
+//!
+//! # Usage
+//! ```sh
+//! coppo doc [--open] [--no-deps]
+//! ```
+
+#![forbid(unsafe_code)]
+
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use coppo_addons::prelude::*;
+use coppo_build::{target_dir, workspace};
+use coppo_config::prelude::*;
+use coppo_logger::prelude::*;
+
+pub mod builtin;
+pub mod doxygen;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The documentation will be stored in the `target/doc` directory.
+pub const DOC_OUTPUT: &str = "doc";
+
+/// The documentation of the dependencies is stored in `target/doc/deps/<name>`.
+pub const DEPS_OUTPUT: &str = "deps";
+
+/// The `Coppo doc` add-on.
+/// Generate the API documentation of the current project.
+pub struct CoppoDocAddon;
+
+impl_addon! {
+    CoppoDocAddon,
+    name => "doc",
+    description => "Generate the API documentation of the current project",
+    args => [
+        arg!(--open "Open the documentation in the browser after generating it")
+            .action(ArgAction::SetTrue),
+        arg!(--"no-deps" "Do not document the dependencies").action(ArgAction::SetTrue),
+    ],
+    run => |config, matches| {
+        let index = doc(config, !matches.get_flag("no-deps"))?;
+        success!("The documentation has been generated in `{}`.", index.display());
+
+        if matches.get_flag("open") {
+            open(&index)?;
+        }
+    }
+}
+
+/// The output directory of the documentation.
+pub fn output_dir() -> PathBuf {
+    target_dir().join(DOC_OUTPUT)
+}
+
+/// Generate the documentation, with the one of the dependencies if `deps` is `true`,
+/// and return the path of its index page.
+fn doc(config: &Config, deps: bool) -> Result<PathBuf> {
+    if !Config::exists() {
+        return Err("The project does not have a `Coppo.toml` file.".into());
+    }
+    if config.is_empty() {
+        return Err("The project name and version is needed".into());
+    }
+
+    let here = Path::new(".");
+    let inputs = input(config, here);
+    if inputs.is_empty() {
+        return Err("There is nothing to document, set `input` in the `[doc]` table.".into());
+    }
+
+    let output = output_dir();
+    let mut documented = vec![];
+    if let Some((root, members)) = deps
+        .then(|| workspace::dependencies(config, false, false))
+        .transpose()?
+        .flatten()
+    {
+        for member in members {
+            let dir = root.join(&member.dir);
+            let input = input(&member.config, &dir);
+            if input.is_empty() {
+                continue;
+            }
+
+            info!("Documenting `{}`...", member.name);
+            let deps_output = output.join(DEPS_OUTPUT).join(&member.name);
+            let index = generate(&member.config, &dir, &input, &deps_output, &[])?;
+            documented.push((member.name, index));
+        }
+    }
+
+    info!("Documenting the project...");
+    generate(config, here, &inputs, &output, &documented)
+}
+
+/// Generate the documentation of the project in `dir` into `output`,
+/// with the links to the documented dependencies, and return the path of its index page.
+fn generate(
+    config: &Config,
+    dir: &Path,
+    input: &[PathBuf],
+    output: &Path,
+    dependencies: &[(String, PathBuf)],
+) -> Result<PathBuf> {
+    let generator = match config.doc.generator {
+        Some(generator) => generator,
+        None if doxygen::is_installed() => DocGenerator::Doxygen,
+        None => DocGenerator::Builtin,
+    };
+
+    match generator {
+        DocGenerator::Doxygen => doxygen::generate(config, dir, input, output),
+        DocGenerator::Builtin => builtin::generate(config, dir, input, output, dependencies),
+    }
+}
+
+/// The directories or files to document, relative to the project directory `dir`.
+/// It defaults to the public headers of the library,
+/// or the `include` or `src` directory if there is no library.
+fn input(config: &Config, dir: &Path) -> Vec<PathBuf> {
+    if !config.doc.input.is_empty() {
+        return config.doc.input.clone();
+    }
+
+    let default = match &config.lib {
+        Some(lib) => vec![lib.include()],
+        None => vec![PathBuf::from("include"), PathBuf::from("src")],
+    };
+    default
+        .into_iter()
+        .find(|input| dir.join(input).exists())
+        .into_iter()
+        .collect()
+}
+
+/// The title of the documentation.
+fn title(config: &Config) -> String {
+    config
+        .doc
+        .title
+        .clone()
+        .unwrap_or_else(|| config.project.name.clone())
+}
+
+/// Open the page in the browser.
+/// The browser can be overridden by the `BROWSER` environment variable.
+fn open(page: &Path) -> Result<()> {
+    let mut command = match env::var("BROWSER") {
+        Ok(browser) if !browser.is_empty() => process::Command::new(browser),
+        _ if cfg!(windows) => {
+            let mut command = process::Command::new("cmd");
+            command.args(["/C", "start", ""]);
+            command
+        }
+        _ if cfg!(target_os = "macos") => process::Command::new("open"),
+        _ => process::Command::new("xdg-open"),
+    };
+
+    info!("Opening `{}`...", page.display());
+    command.arg(page).spawn().map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => {
+            "No browser is found, set the `BROWSER` environment variable.".to_owned()
+        }
+        _ => format!("Failed to open the browser: {}", e),
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,153 @@
+//! The Bazel export, like `coppo export bazel`.
+//!
+//! It generates the files in the project root:
+//! - `MODULE.bazel`, the module with a `bazel_dep` for each dependency,
+//!   pinned to the lowest version matching the requirement.
+//! - `BUILD.bazel`, a `cc_library` for the library target and a `cc_binary` for each binary target,
+//!   the binaries depend on the library and the dependencies.
+
+use crate::version::lowest;
+use crate::Targets;
+
+/// The generated files, their names and contents.
+pub fn files(targets: &Targets) -> Vec<(String, String)> {
+    vec![
+        ("MODULE.bazel".to_owned(), module(targets)),
+        ("BUILD.bazel".to_owned(), build(targets)),
+    ]
+}
+
+/// A list of strings in Starlark.
+fn list<S: AsRef<str>>(items: &[S]) -> String {
+    let items = items
+        .iter()
+        .map(|item| format!("{:?}", item.as_ref()))
+        .collect::<Vec<_>>();
+    format!("[{}]", items.join(", "))
+}
+
+/// The `MODULE.bazel`.
+fn module(targets: &Targets) -> String {
+    let mut module = format!(
+        "# Generated by Coppo from `Coppo.toml`.\n\
+        module(\n    \
+            name = {:?},\n    \
+            version = {:?},\n\
+        )\n",
+        targets.name, targets.version
+    );
+
+    if !targets.dependencies.is_empty() {
+        module.push('\n');
+    }
+    for (name, req) in &targets.dependencies {
+        match lowest(req) {
+            Some(version) => {
+                module += &format!(
+                    "bazel_dep(name = {:?}, version = \"{}\")  # {}\n",
+                    name, version, req
+                )
+            }
+            None => module += &format!("bazel_dep(name = {:?})  # {}\n", name, req),
+        }
+    }
+
+    module
+}
+
+/// The `BUILD.bazel`.
+fn build(targets: &Targets) -> String {
+    let dependencies = targets
+        .dependencies
+        .iter()
+        .map(|(name, _)| format!("@{0}//:{0}", name))
+        .collect::<Vec<_>>();
+
+    let mut build = "# Generated by Coppo from `Coppo.toml`.\n".to_owned();
+
+    let mut bin_deps = vec![];
+    if let Some(lib) = &targets.lib {
+        build += &format!(
+            "\n\
+            cc_library(\n    \
+                name = {name:?},\n    \
+                srcs = {srcs},\n    \
+                hdrs = glob([\"{include}/**\"]),\n    \
+                includes = [{include:?}],\n    \
+                deps = {deps},\n    \
+                visibility = [\"//visibility:public\"],\n\
+            )\n",
+            name = lib.name,
+            srcs = list(&lib.sources),
+            include = lib.include,
+            deps = list(&dependencies),
+        );
+        bin_deps.push(format!(":{}", lib.name));
+    }
+    bin_deps.extend(dependencies);
+
+    for bin in &targets.bins {
+        build += &format!(
+            "\n\
+            cc_binary(\n    \
+                name = {:?},\n    \
+                srcs = [{:?}],\n    \
+                deps = {},\n\
+            )\n",
+            bin.name,
+            bin.source,
+            list(&bin_deps),
+        );
+    }
+
+    build
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Bin, Lib};
+    use semver::VersionReq;
+
+    #[test]
+    fn test_files() {
+        let targets = Targets {
+            name: "app".to_string(),
+            version: "0.1.0".to_string(),
+            lib: Some(Lib {
+                name: "core".to_string(),
+                sources: vec!["src/core/core.cpp".to_string()],
+                include: "include".to_string(),
+            }),
+            bins: vec![Bin {
+                name: "server".to_string(),
+                source: "src/bin/server.cpp".to_string(),
+            }],
+            dependencies: vec![("fmt".to_string(), VersionReq::parse("10.2").unwrap())],
+        };
+
+        let files = files(&targets);
+        assert_eq!(
+            files[0].1,
+            "# Generated by Coppo from `Coppo.toml`.\n\
+            module(\n    name = \"app\",\n    version = \"0.1.0\",\n)\n\
+            \n\
+            bazel_dep(name = \"fmt\", version = \"10.2.0\")  # ^10.2\n"
+        );
+        assert!(files[1].1.contains(
+            "cc_library(\n    \
+                name = \"core\",\n    \
+                srcs = [\"src/core/core.cpp\"],\n    \
+                hdrs = glob([\"include/**\"]),\n    \
+                includes = [\"include\"],\n    \
+                deps = [\"@fmt//:fmt\"],\n"
+        ));
+        assert!(files[1].1.contains(
+            "cc_binary(\n    \
+                name = \"server\",\n    \
+                srcs = [\"src/bin/server.cpp\"],\n    \
+                deps = [\":core\", \"@fmt//:fmt\"],\n\
+            )\n"
+        ));
+    }
+}
@@ -0,0 +1,177 @@
+//! The CMake package export, like `coppo export cmake`.
+//!
+//! The library target is built into a static library with the profile, like `coppo build`,
+//! and the package files describe it as the imported target `<project>::<lib>`:
+//! - `<project>Config.cmake`, the entry of `find_package(<project>)`.
+//! - `<project>ConfigVersion.cmake`, the versions with the same major version are compatible.
+//! - `<project>Targets.cmake`, the imported target with its include directory.
+//!
+//! A CMake project can use it like:
+//! ```cmake
+//! find_package(app 0.1 REQUIRED)
+//! target_link_libraries(main PRIVATE app::core)
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+use coppo_build::{build_library, profile_dir, profile_flags};
+use coppo_config::prelude::*;
+use semver::Version;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Build the library with the profile, and generate the package files, their names and contents.
+pub fn export(config: &Config, profile: &str) -> Result<Vec<(String, String)>> {
+    let lib = config.lib.as_ref().ok_or(
+        "The project does not have a library target, only libraries can be exported to CMake.",
+    )?;
+    if !lib.builds(LibKind::Static) {
+        return Err(format!(
+            "The library `{}` is not built as a static library, only static libraries can be exported to CMake.\n\
+            help: add `static` to `kind` of `[lib]`",
+            lib.name
+        )
+        .into());
+    }
+    let version = &config.project.version;
+
+    let flags = profile_flags(&config.profile(profile)?);
+    fs::create_dir_all(profile_dir(config, profile))?;
+    let archive = build_library(config, profile, &flags)?
+        .ok_or("The project does not have a library target.")?;
+    let archive = fs::canonicalize(archive)?;
+    let include = fs::canonicalize(lib.include()).map_err(|e| {
+        format!(
+            "The include directory `{}` is not found: {}",
+            lib.include().display(),
+            e
+        )
+    })?;
+
+    Ok(package(
+        &config.project.name,
+        version,
+        &lib.name,
+        &archive,
+        &include,
+    ))
+}
+
+/// The package files of the library.
+pub fn package(
+    project: &str,
+    version: &Version,
+    lib: &str,
+    archive: &Path,
+    include: &Path,
+) -> Vec<(String, String)> {
+    vec![
+        (
+            format!("{}Config.cmake", project),
+            format!(
+                "# Generated by Coppo, do not edit.\n\
+                include(\"${{CMAKE_CURRENT_LIST_DIR}}/{}Targets.cmake\")\n",
+                project
+            ),
+        ),
+        (
+            format!("{}ConfigVersion.cmake", project),
+            config_version(version),
+        ),
+        (
+            format!("{}Targets.cmake", project),
+            targets(project, lib, archive, include),
+        ),
+    ]
+}
+
+/// The version file, the versions with the same major version are compatible.
+fn config_version(version: &Version) -> String {
+    format!(
+        "# Generated by Coppo, do not edit.\n\
+        set(PACKAGE_VERSION \"{version}\")\n\
+        \n\
+        if(NOT PACKAGE_FIND_VERSION)\n  \
+          set(PACKAGE_VERSION_COMPATIBLE TRUE)\n\
+        elseif(PACKAGE_FIND_VERSION_MAJOR STREQUAL \"{major}\" AND NOT PACKAGE_VERSION VERSION_LESS PACKAGE_FIND_VERSION)\n  \
+          set(PACKAGE_VERSION_COMPATIBLE TRUE)\n  \
+          if(PACKAGE_FIND_VERSION STREQUAL PACKAGE_VERSION)\n    \
+            set(PACKAGE_VERSION_EXACT TRUE)\n  \
+          endif()\n\
+        else()\n  \
+          set(PACKAGE_VERSION_COMPATIBLE FALSE)\n\
+        endif()\n",
+        version = version,
+        major = version.major,
+    )
+}
+
+/// The targets file, the imported static library.
+fn targets(project: &str, lib: &str, archive: &Path, include: &Path) -> String {
+    format!(
+        "# Generated by Coppo, do not edit.\n\
+        if(TARGET {target})\n  \
+          return()\n\
+        endif()\n\
+        \n\
+        add_library({target} STATIC IMPORTED)\n\
+        set_target_properties({target} PROPERTIES\n  \
+          IMPORTED_LOCATION \"{archive}\"\n  \
+          INTERFACE_INCLUDE_DIRECTORIES \"{include}\"\n\
+        )\n",
+        target = format!("{}::{}", project, lib),
+        archive = cmake_path(archive),
+        include = cmake_path(include),
+    )
+}
+
+/// CMake uses `/` as the path separator on all platforms.
+fn cmake_path(path: &Path) -> String {
+    path.display().to_string().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_package() {
+        let files = package(
+            "app",
+            &Version::new(1, 2, 3),
+            "core",
+            Path::new("/p/target/libcore.a"),
+            Path::new("/p/include"),
+        );
+        let names = files
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "appConfig.cmake",
+                "appConfigVersion.cmake",
+                "appTargets.cmake"
+            ]
+        );
+
+        assert!(files[0]
+            .1
+            .contains("include(\"${CMAKE_CURRENT_LIST_DIR}/appTargets.cmake\")"));
+        assert!(files[1].1.contains("set(PACKAGE_VERSION \"1.2.3\")"));
+        assert!(files[1]
+            .1
+            .contains("PACKAGE_FIND_VERSION_MAJOR STREQUAL \"1\""));
+        assert!(files[2]
+            .1
+            .contains("add_library(app::core STATIC IMPORTED)"));
+        assert!(files[2]
+            .1
+            .contains("IMPORTED_LOCATION \"/p/target/libcore.a\""));
+        assert!(files[2]
+            .1
+            .contains("INTERFACE_INCLUDE_DIRECTORIES \"/p/include\""));
+    }
+}
@@ -0,0 +1,249 @@
+//! The `coppo-export` crate is a Coppo addon that exports the current project to other build systems,
+//! so the projects using them can consume a Coppo project during a migration,
+//! or a project can be authored with Coppo where another build system is mandated.
+//!
+//! The supported formats:
+//! - `cmake`, a CMake package of the library in `target/cmake`, see [`cmake`].
+//! - `bazel`, the `MODULE.bazel` and `BUILD.bazel` files of the project, see [`bazel`].
+//! - `meson`, the `meson.build` file of the project, see [`meson`].
+//! - `nix`, the `flake.nix` file of the project, see [`nix`].
+//!
+//! # Usage
+//! ```sh
+//! coppo export <FORMAT> [-o <DIR>] [--force] [--release | --profile <NAME>]
+//! ```
+
+#![forbid(unsafe_code)]
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use coppo_addons::prelude::*;
+use coppo_build::{bins, lib_sources, profile_arg, release_arg, selected_profile, target_dir};
+use coppo_config::toolchain::Overrides;
+use coppo_logger::prelude::*;
+use semver::VersionReq;
+
+pub mod bazel;
+pub mod cmake;
+pub mod meson;
+pub mod nix;
+pub mod version;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The `Coppo export` add-on.
+/// Export the current project to another build system.
+pub struct CoppoExportAddon;
+
+impl_addon! {
+    CoppoExportAddon,
+    name => "export",
+    description => "Export the current project to another build system",
+    args => [
+        arg!(<FORMAT> "The build system to export to")
+            .value_parser(Format::NAMES),
+        arg!(-o --output <DIR> "The directory of the CMake package, defaults to `target/cmake`")
+            .value_parser(value_parser!(PathBuf)),
+        arg!(-f --force "Overwrite the existing build files in the project")
+            .action(ArgAction::SetTrue),
+        release_arg(),
+        profile_arg(),
+    ],
+    run => |config, matches| {
+        if !Config::exists() {
+            return Err("The project does not have a `Coppo.toml` file.".into());
+        }
+        if config.is_empty() {
+            return Err("The project name and version is needed".into());
+        }
+
+        let format = matches.get_one::<String>("FORMAT").unwrap().parse::<Format>()?;
+        let output = match (format, matches.get_one::<PathBuf>("output")) {
+            (Format::Cmake, Some(output)) => output.clone(),
+            (Format::Cmake, None) => target_dir().join("cmake"),
+            (_, Some(_)) => {
+                return Err(format!(
+                    "The {} build files must be placed in the project, `--output` is only for CMake.",
+                    format
+                )
+                .into());
+            }
+            (_, None) => PathBuf::from("."),
+        };
+
+        let files = match format {
+            Format::Cmake => cmake::export(config, selected_profile(matches))?,
+            Format::Bazel => bazel::files(&Targets::from_config(config)?),
+            Format::Meson => meson::files(&Targets::from_config(config)?),
+            Format::Nix => {
+                let overrides = Overrides::from_file()?;
+                let toolchain = overrides.get(&env::current_dir()?);
+                nix::files(&Targets::from_config(config)?, toolchain)
+            }
+        };
+        write(&output, &files, format == Format::Cmake || matches.get_flag("force"))?;
+
+        success!("The project has been exported to {}.", format);
+        if format == Format::Cmake {
+            info!(
+                "Use `-D{}_DIR={}` to find it with `find_package({})`.",
+                config.project.name,
+                fs::canonicalize(&output)?.display(),
+                config.project.name
+            );
+        }
+    }
+}
+
+/// Write the files into the directory,
+/// the existing ones are refused unless `force` is `true`, since they may be written by hand.
+fn write(dir: &Path, files: &[(String, String)], force: bool) -> Result<()> {
+    if !force {
+        let existing = files
+            .iter()
+            .map(|(name, _)| dir.join(name))
+            .filter(|path| path.exists())
+            .map(|path| format!("`{}`", path.display()))
+            .collect::<Vec<_>>();
+        if !existing.is_empty() {
+            return Err(format!(
+                "The files already exist: {}. Use `--force` to overwrite them.",
+                existing.join(", ")
+            )
+            .into());
+        }
+    }
+
+    fs::create_dir_all(dir)?;
+    for (name, content) in files {
+        let path = dir.join(name);
+        fs::write(&path, content)?;
+        info!("Generated `{}`", path.display());
+    }
+
+    Ok(())
+}
+
+/// The targets and dependencies of the project, for the formats describing how to build it.
+/// The paths are relative to the project root, with `/` as the separator.
+#[derive(Debug, Clone, Default)]
+pub struct Targets {
+    /// The project name.
+    pub name: String,
+    /// The project version.
+    pub version: String,
+    /// The library target.
+    pub lib: Option<Lib>,
+    /// The binary targets.
+    pub bins: Vec<Bin>,
+    /// The dependencies and their version requirements, sorted by name.
+    pub dependencies: Vec<(String, VersionReq)>,
+}
+
+/// The library target of [`Targets`].
+#[derive(Debug, Clone, Default)]
+pub struct Lib {
+    /// The name of the library.
+    pub name: String,
+    /// The source files.
+    pub sources: Vec<String>,
+    /// The directory of the public headers.
+    pub include: String,
+}
+
+/// A binary target of [`Targets`].
+#[derive(Debug, Clone, Default)]
+pub struct Bin {
+    /// The name of the executable.
+    pub name: String,
+    /// The source file with the `main` function.
+    pub source: String,
+}
+
+impl Targets {
+    /// Collect the targets of the project, the library sources are listed from the disk.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let lib = match &config.lib {
+            Some(lib) => Some(Lib {
+                name: lib.name.clone(),
+                sources: lib_sources(lib)?.iter().map(|path| slash(path)).collect(),
+                include: slash(&lib.include()),
+            }),
+            None => None,
+        };
+        let bins = bins(config)
+            .iter()
+            .map(|bin| Bin {
+                name: bin.name.clone(),
+                source: slash(&bin.path()),
+            })
+            .collect();
+
+        let mut dependencies = config
+            .dependencies
+            .iter()
+            .map(|(name, dependency)| (name.clone(), dependency.version()))
+            .collect::<Vec<_>>();
+        dependencies.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(Targets {
+            name: config.project.name.clone(),
+            version: config.project.version.to_string(),
+            lib,
+            bins,
+            dependencies,
+        })
+    }
+}
+
+/// The path with `/` as the separator.
+fn slash(path: &Path) -> String {
+    path.display().to_string().replace('\\', "/")
+}
+
+/// The supported build systems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// A CMake package, found by `find_package()`.
+    Cmake,
+    /// A Bazel module with the `cc_library` and `cc_binary` rules.
+    Bazel,
+    /// A Meson project.
+    Meson,
+    /// A Nix flake.
+    Nix,
+}
+
+impl Format {
+    /// All the names of the supported build systems.
+    pub const NAMES: [&'static str; 4] = ["cmake", "bazel", "meson", "nix"];
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "cmake" => Ok(Format::Cmake),
+            "bazel" => Ok(Format::Bazel),
+            "meson" => Ok(Format::Meson),
+            "nix" => Ok(Format::Nix),
+            _ => Err(format!("Unknown export format `{}`", s)),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::Cmake => write!(f, "CMake"),
+            Format::Bazel => write!(f, "Bazel"),
+            Format::Meson => write!(f, "Meson"),
+            Format::Nix => write!(f, "Nix"),
+        }
+    }
+}
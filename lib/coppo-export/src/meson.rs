@@ -0,0 +1,147 @@
+//! The Meson export, like `coppo export meson`.
+//!
+//! It generates the `meson.build` file in the project root:
+//! - a `dependency()` for each dependency, with the version requirement as the plain comparisons.
+//! - a `static_library()` for the library target, and its `declare_dependency()`.
+//! - an `executable()` for each binary target, it depends on the library and the dependencies.
+
+use crate::version::bounds;
+use crate::Targets;
+
+/// The generated files, their names and contents.
+pub fn files(targets: &Targets) -> Vec<(String, String)> {
+    vec![("meson.build".to_owned(), build(targets))]
+}
+
+/// A string in Meson.
+fn string(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// An array of strings in Meson.
+fn array<S: AsRef<str>>(items: &[S]) -> String {
+    let items = items
+        .iter()
+        .map(|item| string(item.as_ref()))
+        .collect::<Vec<_>>();
+    format!("[{}]", items.join(", "))
+}
+
+/// A variable name in Meson, `-` is not allowed in identifiers.
+fn variable(name: &str, suffix: &str) -> String {
+    format!("{}_{}", name.replace('-', "_"), suffix)
+}
+
+/// The `meson.build`.
+fn build(targets: &Targets) -> String {
+    let mut build = format!(
+        "# Generated by Coppo from `Coppo.toml`.\n\
+        project({}, 'cpp', version: {})\n",
+        string(&targets.name),
+        string(&targets.version)
+    );
+
+    let mut dependencies = vec![];
+    if !targets.dependencies.is_empty() {
+        build.push('\n');
+    }
+    for (name, req) in &targets.dependencies {
+        let dependency = variable(name, "dep");
+        let bounds = bounds(req);
+        if bounds.is_empty() {
+            build += &format!("{} = dependency({})\n", dependency, string(name));
+        } else {
+            build += &format!(
+                "{} = dependency({}, version: {})\n",
+                dependency,
+                string(name),
+                array(&bounds)
+            );
+        }
+        dependencies.push(dependency);
+    }
+
+    let mut bin_dependencies = vec![];
+    if let Some(lib) = &targets.lib {
+        let inc = variable(&lib.name, "inc");
+        let dependency = variable(&lib.name, "dep");
+        build += &format!(
+            "\n\
+            {inc} = include_directories({include})\n\
+            {lib} = static_library(\n  \
+              {name},\n  \
+              {sources},\n  \
+              include_directories: {inc},\n  \
+              dependencies: [{dependencies}],\n\
+            )\n\
+            {dep} = declare_dependency(link_with: {lib}, include_directories: {inc})\n",
+            inc = inc,
+            include = string(&lib.include),
+            lib = variable(&lib.name, "lib"),
+            name = string(&lib.name),
+            sources = array(&lib.sources),
+            dependencies = dependencies.join(", "),
+            dep = dependency,
+        );
+        bin_dependencies.push(dependency);
+    }
+    bin_dependencies.extend(dependencies);
+
+    if !targets.bins.is_empty() {
+        build.push('\n');
+    }
+    for bin in &targets.bins {
+        build += &format!(
+            "executable({}, {}, dependencies: [{}])\n",
+            string(&bin.name),
+            string(&bin.source),
+            bin_dependencies.join(", ")
+        );
+    }
+
+    build
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Bin, Lib};
+    use semver::VersionReq;
+
+    #[test]
+    fn test_files() {
+        let targets = Targets {
+            name: "app".to_string(),
+            version: "0.1.0".to_string(),
+            lib: Some(Lib {
+                name: "core".to_string(),
+                sources: vec!["src/core/core.cpp".to_string()],
+                include: "include".to_string(),
+            }),
+            bins: vec![Bin {
+                name: "server".to_string(),
+                source: "src/bin/server.cpp".to_string(),
+            }],
+            dependencies: vec![("fmt".to_string(), VersionReq::parse("10").unwrap())],
+        };
+
+        assert_eq!(
+            files(&targets)[0].1,
+            "# Generated by Coppo from `Coppo.toml`.\n\
+            project('app', 'cpp', version: '0.1.0')\n\
+            \n\
+            fmt_dep = dependency('fmt', version: ['>=10.0.0', '<11.0.0'])\n\
+            \n\
+            core_inc = include_directories('include')\n\
+            core_lib = static_library(\n  \
+              'core',\n  \
+              ['src/core/core.cpp'],\n  \
+              include_directories: core_inc,\n  \
+              dependencies: [fmt_dep],\n\
+            )\n\
+            core_dep = declare_dependency(link_with: core_lib, include_directories: core_inc)\n\
+            \n\
+            executable('server', 'src/bin/server.cpp', dependencies: [core_dep, fmt_dep])\n"
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! The Nix export, like `coppo export nix`.
+//!
+//! It generates the `flake.nix` file in the project root,
+//! the package builds the targets like `coppo build` without Coppo itself:
+//! - The toolchain is pinned to the LLVM version selected by `coppo toolchain override`,
+//!   like `llvmPackages_18` for `llvm-18.1.8`, or the default Clang of nixpkgs.
+//! - The dependencies are the nixpkgs packages of the same names, found with `pkg-config`.
+//!   nixpkgs is pinned by the `flake.lock` Nix writes on the first build.
+//! - The binaries are installed into `bin`, the library into `lib` and its headers into `include`.
+
+use crate::Targets;
+
+/// The nixpkgs branch of the flake input.
+pub const NIXPKGS: &str = "github:NixOS/nixpkgs/nixos-24.05";
+
+/// The generated files, their names and contents.
+pub fn files(targets: &Targets, toolchain: Option<&str>) -> Vec<(String, String)> {
+    vec![("flake.nix".to_owned(), flake(targets, toolchain))]
+}
+
+/// The standard environment of the toolchain.
+pub fn stdenv(toolchain: Option<&str>) -> String {
+    let major = toolchain
+        .and_then(|toolchain| toolchain.strip_prefix("llvm-"))
+        .and_then(|version| version.split('.').next())
+        .filter(|major| !major.is_empty() && major.chars().all(|c| c.is_ascii_digit()));
+
+    match major {
+        Some(major) => format!("pkgs.llvmPackages_{}.stdenv", major),
+        None => "pkgs.clangStdenv".to_owned(),
+    }
+}
+
+/// Quote the argument for the shell if needed.
+fn quote(arg: &str) -> String {
+    if arg
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-_./=+".contains(c))
+    {
+        arg.to_owned()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// The commands of the build phase.
+fn build_commands(targets: &Targets) -> Vec<String> {
+    let mut flags = vec![];
+    if let Some(lib) = &targets.lib {
+        flags.push(quote(&format!("-I{}", lib.include)));
+    }
+    if !targets.dependencies.is_empty() {
+        let names = targets
+            .dependencies
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        flags.push(format!("$(pkg-config --cflags --libs {})", names.join(" ")));
+    }
+    let flags = flags.join(" ");
+
+    let mut commands = vec!["mkdir -p target".to_owned()];
+    if let Some(lib) = &targets.lib {
+        let mut objects = vec![];
+        for (i, source) in lib.sources.iter().enumerate() {
+            let object = format!("target/{}-{}.o", lib.name, i);
+            commands.push(format!("$CXX -c {} {} -o {}", quote(source), flags, object));
+            objects.push(object);
+        }
+        commands.push(format!(
+            "$AR rcs {} {}",
+            quote(&format!("target/lib{}.a", lib.name)),
+            objects.join(" ")
+        ));
+    }
+    for bin in &targets.bins {
+        let mut command = format!("$CXX {}", quote(&bin.source));
+        // The library sources are compiled into every binary, like `coppo build`.
+        if let Some(lib) = &targets.lib {
+            for source in &lib.sources {
+                command += &format!(" {}", quote(source));
+            }
+        }
+        command += &format!(" {} -o {}", flags, quote(&format!("target/{}", bin.name)));
+        commands.push(command);
+    }
+
+    commands
+}
+
+/// The commands of the install phase.
+fn install_commands(targets: &Targets) -> Vec<String> {
+    let mut commands = vec![];
+    for bin in &targets.bins {
+        commands.push(format!(
+            "install -Dm755 {} $out/bin/{}",
+            quote(&format!("target/{}", bin.name)),
+            quote(&bin.name)
+        ));
+    }
+    if let Some(lib) = &targets.lib {
+        let archive = format!("lib{}.a", lib.name);
+        commands.push(format!(
+            "install -Dm644 {} $out/lib/{}",
+            quote(&format!("target/{}", archive)),
+            quote(&archive)
+        ));
+        commands.push("mkdir -p $out/include".to_owned());
+        commands.push(format!("cp -r {}/. $out/include/", quote(&lib.include)));
+    }
+
+    commands
+}
+
+/// A phase of the derivation, the commands between the hooks.
+fn phase(name: &str, commands: &[String]) -> String {
+    let mut phase = format!("          {}Phase = ''\n", name);
+    phase += &format!("            runHook pre{}\n", capitalize(name));
+    for command in commands {
+        phase += &format!("            {}\n", command);
+    }
+    phase += &format!("            runHook post{}\n", capitalize(name));
+    phase += "          '';\n";
+
+    phase
+}
+
+/// Like `build` to `Build`, for the names of the hooks.
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// The `flake.nix`.
+fn flake(targets: &Targets, toolchain: Option<&str>) -> String {
+    let (build_inputs, native_build_inputs) = match targets.dependencies.is_empty() {
+        true => ("[ ]".to_owned(), "[ ]"),
+        false => {
+            let packages = targets
+                .dependencies
+                .iter()
+                .map(|(name, req)| format!("\n            pkgs.{} # {}", name, req))
+                .collect::<String>();
+            (format!("[{}\n          ]", packages), "[ pkgs.pkg-config ]")
+        }
+    };
+
+    let mut flake = format!(
+        "# Generated by Coppo from `Coppo.toml`.\n\
+        {{\n  \
+          description = {name:?};\n\
+        \n  \
+          inputs.nixpkgs.url = {nixpkgs:?};\n\
+        \n  \
+          outputs = {{ self, nixpkgs }}:\n    \
+            let\n      \
+              systems = [ \"x86_64-linux\" \"aarch64-linux\" \"x86_64-darwin\" \"aarch64-darwin\" ];\n      \
+              forAllSystems = f: nixpkgs.lib.genAttrs systems (system: f nixpkgs.legacyPackages.${{system}});\n    \
+            in {{\n      \
+              packages = forAllSystems (pkgs: {{\n        \
+                default = {stdenv}.mkDerivation {{\n          \
+                  pname = {name:?};\n          \
+                  version = {version:?};\n          \
+                  src = ./.;\n          \
+                  nativeBuildInputs = {native_build_inputs};\n          \
+                  buildInputs = {build_inputs};\n",
+        name = targets.name,
+        nixpkgs = NIXPKGS,
+        stdenv = stdenv(toolchain),
+        version = targets.version,
+        native_build_inputs = native_build_inputs,
+        build_inputs = build_inputs,
+    );
+    flake += &phase("build", &build_commands(targets));
+    flake += &phase("install", &install_commands(targets));
+    flake += "        };\n      \
+        });\n    \
+      };\n\
+    }\n";
+
+    flake
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Bin, Lib};
+    use semver::VersionReq;
+
+    #[test]
+    fn test_flake() {
+        assert_eq!(stdenv(Some("llvm-18.1.8")), "pkgs.llvmPackages_18.stdenv");
+        assert_eq!(stdenv(Some("mingw-20240619")), "pkgs.clangStdenv");
+        assert_eq!(stdenv(None), "pkgs.clangStdenv");
+
+        let targets = Targets {
+            name: "app".to_string(),
+            version: "0.1.0".to_string(),
+            lib: Some(Lib {
+                name: "core".to_string(),
+                sources: vec!["src/core/core.cpp".to_string()],
+                include: "include".to_string(),
+            }),
+            bins: vec![Bin {
+                name: "server".to_string(),
+                source: "src/bin/server.cpp".to_string(),
+            }],
+            dependencies: vec![("fmt".to_string(), VersionReq::parse("10").unwrap())],
+        };
+
+        let flake = &files(&targets, Some("llvm-18.1.8"))[0].1;
+        assert!(flake.contains("default = pkgs.llvmPackages_18.stdenv.mkDerivation {"));
+        assert!(flake.contains("pkgs.fmt # ^10\n"));
+        assert!(flake.contains(
+            "$CXX src/bin/server.cpp src/core/core.cpp -Iinclude \
+            $(pkg-config --cflags --libs fmt) -o target/server\n"
+        ));
+        assert!(flake.contains("install -Dm755 target/server $out/bin/server\n"));
+        assert!(flake.contains("install -Dm644 target/libcore.a $out/lib/libcore.a\n"));
+    }
+}
@@ -0,0 +1,85 @@
+//! The translation of the dependency version requirements,
+//! the other build systems only understand the plain comparisons like `>=1.2.0` and `<2.0.0`.
+
+use semver::{Comparator, Op, Version, VersionReq};
+
+/// The plain comparisons equivalent to the requirement, `*` has none of them.
+/// The pre-release parts are ignored.
+pub fn bounds(req: &VersionReq) -> Vec<String> {
+    req.comparators.iter().flat_map(comparator_bounds).collect()
+}
+
+/// The lowest version matching the requirement, if it has a lower bound.
+pub fn lowest(req: &VersionReq) -> Option<Version> {
+    bounds(req).iter().find_map(|bound| {
+        bound
+            .strip_prefix(">=")
+            .and_then(|version| Version::parse(version).ok())
+    })
+}
+
+fn comparator_bounds(comparator: &Comparator) -> Vec<String> {
+    let major = comparator.major;
+    let minor = comparator.minor;
+    let patch = comparator.patch;
+    let version = Version::new(major, minor.unwrap_or(0), patch.unwrap_or(0));
+
+    // The first version after the versions matched by the given parts, like `1.3.0` for `1.2`.
+    let next = || match (minor, patch) {
+        (None, _) => Version::new(major + 1, 0, 0),
+        (Some(minor), None) => Version::new(major, minor + 1, 0),
+        (Some(minor), Some(patch)) => Version::new(major, minor, patch + 1),
+    };
+    let range = |upper: Version| vec![format!(">={}", version), format!("<{}", upper)];
+
+    match comparator.op {
+        Op::Exact | Op::Wildcard if patch.is_some() => vec![format!("=={}", version)],
+        Op::Exact | Op::Wildcard => range(next()),
+        Op::Greater if patch.is_some() => vec![format!(">{}", version)],
+        Op::Greater => vec![format!(">={}", next())],
+        Op::GreaterEq => vec![format!(">={}", version)],
+        Op::Less => vec![format!("<{}", version)],
+        Op::LessEq if patch.is_some() => vec![format!("<={}", version)],
+        Op::LessEq => vec![format!("<{}", next())],
+        Op::Tilde => match minor {
+            Some(minor) => range(Version::new(major, minor + 1, 0)),
+            None => range(Version::new(major + 1, 0, 0)),
+        },
+        // Cargo's semantics: the left-most non-zero part must not change.
+        Op::Caret => match (minor, patch) {
+            _ if major > 0 => range(Version::new(major + 1, 0, 0)),
+            (None, _) => range(Version::new(1, 0, 0)),
+            (Some(minor), _) if minor > 0 => range(Version::new(0, minor + 1, 0)),
+            (Some(_), None) => range(Version::new(0, 1, 0)),
+            (Some(_), Some(patch)) => range(Version::new(0, 0, patch + 1)),
+        },
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bounds_of(req: &str) -> Vec<String> {
+        bounds(&VersionReq::parse(req).unwrap())
+    }
+
+    #[test]
+    fn test_bounds() {
+        assert_eq!(bounds_of("10"), [">=10.0.0", "<11.0.0"]);
+        assert_eq!(bounds_of("^0.2.3"), [">=0.2.3", "<0.3.0"]);
+        assert_eq!(bounds_of("^0.0.3"), [">=0.0.3", "<0.0.4"]);
+        assert_eq!(bounds_of("~1.2"), [">=1.2.0", "<1.3.0"]);
+        assert_eq!(bounds_of("=1.2.3"), ["==1.2.3"]);
+        assert_eq!(bounds_of("1.*"), [">=1.0.0", "<2.0.0"]);
+        assert_eq!(bounds_of(">1.2, <=2"), [">=1.3.0", "<3.0.0"]);
+        assert!(bounds_of("*").is_empty());
+
+        assert_eq!(
+            lowest(&VersionReq::parse("^1.2").unwrap()),
+            Some(Version::new(1, 2, 0))
+        );
+        assert_eq!(lowest(&VersionReq::parse("<2").unwrap()), None);
+    }
+}
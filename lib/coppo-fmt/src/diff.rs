@@ -0,0 +1,148 @@
+//! The unified diff of a file and its formatted content, printed by `coppo fmt --check`.
+
+/// A line of the diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Line<'a> {
+    /// A line of both.
+    Same(&'a str),
+    /// A line only in the old content.
+    Removed(&'a str),
+    /// A line only in the new content.
+    Added(&'a str),
+}
+
+/// The lines of the old and the new content, from their longest common subsequence.
+fn lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Line<'a>> {
+    // The length of the common subsequence of `old[i..]` and `new[j..]`.
+    let width = new.len() + 1;
+    let mut common = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i * width + j] = match old[i] == new[j] {
+                true => common[(i + 1) * width + j + 1] + 1,
+                false => common[(i + 1) * width + j].max(common[i * width + j + 1]),
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = vec![];
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(Line::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len()
+            && (j == new.len() || common[(i + 1) * width + j] >= common[i * width + j + 1])
+        {
+            lines.push(Line::Removed(old[i]));
+            i += 1;
+        } else {
+            lines.push(Line::Added(new[j]));
+            j += 1;
+        }
+    }
+
+    lines
+}
+
+/// The unified diff of the old and the new content of the file,
+/// with `context` unchanged lines around the changes, like `diff -u`.
+/// It is empty if they are the same.
+///
+/// ```rust
+/// use coppo_fmt::diff::unified;
+///
+/// let diff = unified("main.cpp", "int main(){\n}\n", "int main() {\n}\n", 3);
+/// assert_eq!(
+///     diff,
+///     "--- a/main.cpp\n+++ b/main.cpp\n@@ -1,2 +1,2 @@\n-int main(){\n+int main() {\n }\n"
+/// );
+/// ```
+pub fn unified(file: &str, old: &str, new: &str, context: usize) -> String {
+    let old_lines = old.lines().collect::<Vec<_>>();
+    let new_lines = new.lines().collect::<Vec<_>>();
+    let lines = lines(&old_lines, &new_lines);
+    let changes = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Line::Same(_)))
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    // The ranges of the hunks, the changes closer than twice the context are merged.
+    let mut hunks: Vec<(usize, usize)> = vec![];
+    for change in changes {
+        let start = change.saturating_sub(context);
+        let end = (change + context + 1).min(lines.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut diff = format!("--- a/{0}\n+++ b/{0}\n", file);
+    // The line numbers before each line of the diff.
+    let (mut old_line, mut new_line) = (1, 1);
+    let mut position = 0;
+    for (start, end) in hunks {
+        for line in &lines[position..start] {
+            match line {
+                Line::Same(_) => {
+                    old_line += 1;
+                    new_line += 1;
+                }
+                Line::Removed(_) => old_line += 1,
+                Line::Added(_) => new_line += 1,
+            }
+        }
+
+        let hunk = &lines[start..end];
+        let old_count = hunk
+            .iter()
+            .filter(|line| !matches!(line, Line::Added(_)))
+            .count();
+        let new_count = hunk
+            .iter()
+            .filter(|line| !matches!(line, Line::Removed(_)))
+            .count();
+        diff += &format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_line, old_count, new_line, new_count
+        );
+        for line in hunk {
+            diff += &match line {
+                Line::Same(line) => format!(" {}\n", line),
+                Line::Removed(line) => format!("-{}\n", line),
+                Line::Added(line) => format!("+{}\n", line),
+            };
+        }
+        old_line += old_count;
+        new_line += new_count;
+        position = end;
+    }
+
+    diff
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unified() {
+        let old = (1..=10).map(|i| format!("{}\n", i)).collect::<String>();
+        let new = old.replace("2\n", "two\n").replace("9\n", "");
+
+        assert_eq!(
+            unified("a.h", &old, &new, 1),
+            "--- a/a.h\n+++ b/a.h\n\
+            @@ -1,3 +1,3 @@\n 1\n-2\n+two\n 3\n\
+            @@ -8,3 +8,2 @@\n 8\n-9\n 10\n"
+        );
+        assert_eq!(unified("a.h", &old, &old, 3), "");
+    }
+}
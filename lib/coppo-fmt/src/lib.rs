@@ -0,0 +1,155 @@
+//! The `coppo-fmt` crate is a Coppo addon that formats the sources of the current project
+//! with `clang-format`.
+//!
+//! The style is taken from the `.clang-format` file of the project,
+//! and one with the `LLVM` style is created if there is none, so the editors use the same style.
+//! The files are filtered by the `include` and `exclude` globs of the `[project]` table.
+//!
+//! With `--check`, nothing is changed, the differences are printed as a unified diff,
+//! see [`diff::unified`], and it fails if any file is not formatted, for the CI.
+//!
+//! # Usage
+//! ```sh
+//! coppo fmt [--check]
+//! ```
+
+#![forbid(unsafe_code)]
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+
+use coppo_addons::prelude::*;
+use coppo_config::files::{self, HEADER_EXTENSIONS, SOURCE_EXTENSIONS};
+use coppo_logger::prelude::*;
+
+pub mod diff;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The formatter executable.
+pub const CLANG_FORMAT: &str = "clang-format";
+
+/// The style used if the project does not have a `.clang-format` file.
+pub const FALLBACK_STYLE: &str = "LLVM";
+
+/// The style file of `clang-format`.
+pub const STYLE_FILE: &str = ".clang-format";
+
+/// The `Coppo fmt` add-on.
+/// Format all the sources and headers of the current project.
+/// With `--check`, the files are not changed, their differences are printed,
+/// and it fails if any of them is not formatted.
+pub struct CoppoFmtAddon;
+
+impl_addon! {
+    CoppoFmtAddon,
+    name => "fmt",
+    description => "Format the sources of the current project with clang-format",
+    args => [
+        arg!(--check "Check if the files are formatted without changing them")
+            .action(ArgAction::SetTrue),
+    ],
+    run => |config, matches| {
+        fmt(config, matches.get_flag("check"))?;
+    }
+}
+
+fn fmt(config: &Config, check: bool) -> Result<()> {
+    if !Config::exists() {
+        return Err("The project does not have a `Coppo.toml` file.".into());
+    }
+
+    if !check && !Path::new(STYLE_FILE).exists() && !Path::new("_clang-format").exists() {
+        fs::write(STYLE_FILE, default_style()?)?;
+        info!(
+            "Created `{}` with the `{}` style.",
+            STYLE_FILE, FALLBACK_STYLE
+        );
+    }
+
+    let extensions = [SOURCE_EXTENSIONS, HEADER_EXTENSIONS].concat();
+    let files = files::project_files(&config.project, ".", &extensions)?;
+
+    let mut unformatted = vec![];
+    for file in &files {
+        let original = fs::read_to_string(file)?;
+        let formatted = format(file)?;
+        if original == formatted {
+            continue;
+        }
+
+        if check {
+            warn!("`{}` is not formatted", file.display());
+            let name = file.display().to_string().replace('\\', "/");
+            print!("{}", diff::unified(&name, &original, &formatted, 3));
+        } else {
+            fs::write(file, formatted)?;
+            info!("Formatted `{}`", file.display());
+        }
+        unformatted.push(file);
+    }
+
+    match (check, unformatted.len()) {
+        (true, 0) => success!("All {} files are formatted.", files.len()),
+        (true, n) => {
+            return Err(format!("{} of {} files are not formatted.", n, files.len()).into())
+        }
+        (false, n) => success!("Formatted {} of {} files.", n, files.len()),
+    }
+
+    Ok(())
+}
+
+/// Get the formatted content of the file.
+fn format(file: &Path) -> Result<String> {
+    let output = clang_format(
+        process::Command::new(CLANG_FORMAT)
+            .arg("--style=file")
+            .arg(format!("--fallback-style={}", FALLBACK_STYLE))
+            .arg(file),
+    )?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to format `{}`: {}",
+            file.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// The content of the default style file, the fallback style dumped by `clang-format`.
+fn default_style() -> Result<String> {
+    let output = clang_format(
+        process::Command::new(CLANG_FORMAT)
+            .arg(format!("--style={}", FALLBACK_STYLE))
+            .arg("--dump-config"),
+    )?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to create `{}`: {}",
+            STYLE_FILE,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Run `clang-format`, it fails if it can not run.
+fn clang_format(command: &mut process::Command) -> Result<process::Output> {
+    command.output().map_err(|e| {
+        match e.kind() {
+            io::ErrorKind::NotFound => format!("`{}` is not found in PATH.", CLANG_FORMAT),
+            _ => format!("Failed to run `{}`: {}", CLANG_FORMAT, e),
+        }
+        .into()
+    })
+}
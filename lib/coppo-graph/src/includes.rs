@@ -0,0 +1,107 @@
+//! The include graph of the project files.
+//!
+//! The `#include` directives are resolved like the preprocessor does,
+//! `"header"` is searched in the directory of the including file first,
+//! then both forms are searched in the include directories of the project.
+//! The headers out of the project, like the system headers, are not in the graph.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use coppo_config::files::{self, HEADER_EXTENSIONS, SOURCE_EXTENSIONS};
+use coppo_config::prelude::*;
+
+use crate::{EdgeKind, Graph, Result};
+
+/// Parse the `#include` directive, and return the header and whether it is quoted.
+fn parse_include(line: &str) -> Option<(&str, bool)> {
+    let directive = line.trim_start().strip_prefix('#')?.trim_start();
+    let header = directive.strip_prefix("include")?.trim();
+
+    if let Some(header) = header.strip_prefix('"') {
+        Some((header.split('"').next()?, true))
+    } else {
+        Some((header.strip_prefix('<')?.split('>').next()?, false))
+    }
+}
+
+/// The include directories of the project.
+fn include_dirs(config: &Config) -> Vec<PathBuf> {
+    match &config.lib {
+        Some(lib) => vec![lib.include()],
+        None => vec![PathBuf::from("include")],
+    }
+}
+
+/// Resolve the header included by the file.
+fn resolve(file: &Path, header: &str, quoted: bool, include_dirs: &[PathBuf]) -> Option<PathBuf> {
+    let local = quoted
+        .then(|| file.parent().unwrap_or(Path::new("")).join(header))
+        .into_iter();
+    local
+        .chain(include_dirs.iter().map(|dir| dir.join(header)))
+        .find(|path| path.is_file())
+}
+
+/// The include graph of the project files.
+/// The source files are the roots, and the headers are included by them.
+pub fn include_graph(config: &Config) -> Result<Graph> {
+    let include_dirs = include_dirs(config);
+    let extensions = [SOURCE_EXTENSIONS, HEADER_EXTENSIONS].concat();
+    let files = files::project_files(&config.project, ".", &extensions)?;
+
+    let mut graph = Graph::default();
+    for file in &files {
+        let from = graph.node(file.display().to_string());
+        for line in fs::read_to_string(file)?.lines() {
+            let Some((header, quoted)) = parse_include(line) else {
+                continue;
+            };
+            if let Some(header) = resolve(file, header, quoted, &include_dirs) {
+                let to = graph.node(normalize(&header).display().to_string());
+                graph.edge(from, to, EdgeKind::Normal);
+            }
+        }
+    }
+
+    Ok(graph)
+}
+
+/// Remove the `.` and `..` components of the relative path.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_include() {
+        assert_eq!(
+            parse_include("#include <core/core.hpp>"),
+            Some(("core/core.hpp", false))
+        );
+        assert_eq!(
+            parse_include("  #  include \"util.hpp\" // util"),
+            Some(("util.hpp", true))
+        );
+        assert_eq!(parse_include("#define X"), None);
+        assert_eq!(parse_include("int x;"), None);
+        assert_eq!(
+            normalize(Path::new("src/../include/./a.hpp")),
+            PathBuf::from("include/a.hpp")
+        );
+    }
+}
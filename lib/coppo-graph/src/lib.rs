@@ -0,0 +1,466 @@
+//! The `coppo-graph` crate is a Coppo addon that exports the dependency graph of the current project
+//! as Graphviz DOT or Mermaid.
+//!
+//! By default the package graph is exported, the project and its dependencies.
+//! With `--includes`, the include graph of the project files is exported instead.
+//! It also provides the `tree` add-on, it prints the resolved packages as an indented tree,
+//! see [`tree`].
+//!
+//! # Usage
+//! ```sh
+//! coppo graph [--format <dot|mermaid>] [--includes] [--edges <KINDS>] [--depth <N>] [-o <FILE>]
+//! coppo tree [--depth <N>] [--invert <PACKAGE>] [--duplicates]
+//! ```
+
+#![forbid(unsafe_code)]
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use coppo_addons::prelude::*;
+use coppo_logger::prelude::*;
+
+pub mod includes;
+pub mod tree;
+
+pub use tree::CoppoTreeAddon;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The `Coppo graph` add-on.
+/// Export the dependency graph of the current project.
+pub struct CoppoGraphAddon;
+
+impl_addon! {
+    CoppoGraphAddon,
+    name => "graph",
+    description => "Export the dependency graph as Graphviz DOT or Mermaid",
+    args => [
+        arg!(--format <FORMAT> "The format of the graph")
+            .value_parser(Format::NAMES)
+            .default_value("dot"),
+        arg!(--includes "Export the include graph of the project files instead of the package graph")
+            .action(ArgAction::SetTrue),
+        arg!(--edges <KINDS> "The kinds of the dependency edges to export, like `normal,dev`")
+            .value_parser(EdgeKind::NAMES)
+            .value_delimiter(','),
+        arg!(--depth <N> "The maximum depth from the roots of the graph")
+            .value_parser(value_parser!(usize)),
+        arg!(-o --output <FILE> "Write the graph into the file instead of the standard output")
+            .value_parser(value_parser!(PathBuf)),
+    ],
+    run => |config, matches| {
+        if !Config::exists() {
+            return Err("The project does not have a `Coppo.toml` file.".into());
+        }
+
+        let format = matches.get_one::<String>("format").unwrap().parse::<Format>()?;
+        let mut graph = if matches.get_flag("includes") {
+            includes::include_graph(config)?
+        } else {
+            package_graph(config)
+        };
+
+        if let Some(kinds) = matches.get_many::<String>("edges") {
+            let kinds = kinds
+                .map(|kind| kind.parse::<EdgeKind>())
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            graph.retain_edges(|kind| kinds.contains(&kind));
+        }
+        if let Some(depth) = matches.get_one::<usize>("depth") {
+            graph.limit_depth(*depth);
+        }
+
+        let rendered = graph.render(format);
+        match matches.get_one::<PathBuf>("output") {
+            Some(output) => {
+                fs::write(output, rendered)?;
+                success!("The graph has been written to `{}`.", output.display());
+            }
+            None => print!("{}", rendered),
+        }
+    }
+}
+
+/// The formats of the graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Graphviz DOT, render it with `dot -Tsvg`.
+    Dot,
+    /// Mermaid, it is rendered by GitHub and GitLab in Markdown.
+    Mermaid,
+}
+
+impl Format {
+    /// All the names of the formats.
+    pub const NAMES: [&'static str; 2] = ["dot", "mermaid"];
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "dot" => Ok(Format::Dot),
+            "mermaid" => Ok(Format::Mermaid),
+            _ => Err(format!("Unknown graph format `{}`", s)),
+        }
+    }
+}
+
+/// The kinds of the dependency edges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// A normal dependency, or an include.
+    Normal,
+    /// A dependency only for the tests and the examples.
+    Dev,
+    /// A dependency only for the build.
+    Build,
+}
+
+impl EdgeKind {
+    /// All the names of the edge kinds.
+    pub const NAMES: [&'static str; 3] = ["normal", "dev", "build"];
+
+    /// The name of the edge kind.
+    pub fn name(self) -> &'static str {
+        match self {
+            EdgeKind::Normal => "normal",
+            EdgeKind::Dev => "dev",
+            EdgeKind::Build => "build",
+        }
+    }
+}
+
+impl FromStr for EdgeKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "normal" => Ok(EdgeKind::Normal),
+            "dev" => Ok(EdgeKind::Dev),
+            "build" => Ok(EdgeKind::Build),
+            _ => Err(format!("Unknown edge kind `{}`", s)),
+        }
+    }
+}
+
+/// A directed graph, the nodes without incoming edges are the roots.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Graph {
+    /// The labels of the nodes.
+    pub nodes: Vec<String>,
+    /// The edges, from a node to another node.
+    pub edges: Vec<(usize, usize, EdgeKind)>,
+}
+
+impl Graph {
+    /// Get the node with the label, it is added if it does not exist.
+    pub fn node(&mut self, label: impl Into<String>) -> usize {
+        let label = label.into();
+        match self.nodes.iter().position(|node| *node == label) {
+            Some(node) => node,
+            None => {
+                self.nodes.push(label);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    /// Add an edge, the duplicated edges are ignored.
+    pub fn edge(&mut self, from: usize, to: usize, kind: EdgeKind) {
+        if !self.edges.contains(&(from, to, kind)) {
+            self.edges.push((from, to, kind));
+        }
+    }
+
+    /// Only keep the edges of the kinds,
+    /// and remove the nodes which are no longer reachable from the roots.
+    pub fn retain_edges(&mut self, keep: impl Fn(EdgeKind) -> bool) {
+        let roots = self.roots();
+        self.edges.retain(|(_, _, kind)| keep(*kind));
+        self.retain_reachable(&roots, usize::MAX);
+    }
+
+    /// Remove the nodes deeper than the depth from the roots, the roots are at depth `0`.
+    pub fn limit_depth(&mut self, depth: usize) {
+        let roots = self.roots();
+        self.retain_reachable(&roots, depth);
+    }
+
+    /// The graph with the edges reversed, from the dependencies to their dependents.
+    pub fn inverted(&self) -> Graph {
+        Graph {
+            nodes: self.nodes.clone(),
+            edges: self
+                .edges
+                .iter()
+                .map(|(from, to, kind)| (*to, *from, *kind))
+                .collect(),
+        }
+    }
+
+    /// The nodes with more than one incoming edge.
+    pub fn shared(&self) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|node| self.edges.iter().filter(|(_, to, _)| to == node).count() > 1)
+            .collect()
+    }
+
+    /// The nodes without incoming edges.
+    fn roots(&self) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|node| !self.edges.iter().any(|(_, to, _)| to == node))
+            .collect()
+    }
+
+    /// Only keep the nodes reachable from the roots within the depth.
+    fn retain_reachable(&mut self, roots: &[usize], depth: usize) {
+        let mut depths = vec![None; self.nodes.len()];
+        let mut queue = roots.iter().map(|root| (*root, 0)).collect::<VecDeque<_>>();
+        while let Some((node, d)) = queue.pop_front() {
+            if depths[node].is_some() {
+                continue;
+            }
+            depths[node] = Some(d);
+            if d < depth {
+                for (_, to, _) in self.edges.iter().filter(|(from, ..)| *from == node) {
+                    queue.push_back((*to, d + 1));
+                }
+            }
+        }
+
+        // Renumber the kept nodes.
+        let mut index = vec![None; self.nodes.len()];
+        let mut nodes = vec![];
+        for (node, label) in self.nodes.drain(..).enumerate() {
+            if depths[node].is_some() {
+                index[node] = Some(nodes.len());
+                nodes.push(label);
+            }
+        }
+        self.nodes = nodes;
+        self.edges = self
+            .edges
+            .iter()
+            .filter_map(|(from, to, kind)| Some((index[*from]?, index[*to]?, *kind)))
+            .collect();
+    }
+
+    /// Render the graph in the format.
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Dot => self.dot(),
+            Format::Mermaid => self.mermaid(),
+        }
+    }
+
+    /// Render the nodes reachable from the root within the depth as an indented tree.
+    /// The children are sorted, the dev and build ones follow the normal ones
+    /// under `[dev-dependencies]` and `[build-dependencies]`,
+    /// and a node already shown is marked with `(*)` instead of showing its children again.
+    pub fn tree(&self, root: usize, depth: usize) -> String {
+        let mut tree = format!("{}\n", self.nodes[root]);
+        let mut shown = vec![false; self.nodes.len()];
+        shown[root] = true;
+        self.write_tree(root, "", depth, &mut shown, &mut tree);
+        tree
+    }
+
+    fn write_tree(
+        &self,
+        node: usize,
+        prefix: &str,
+        depth: usize,
+        shown: &mut [bool],
+        tree: &mut String,
+    ) {
+        if depth == 0 {
+            return;
+        }
+        for kind in [EdgeKind::Normal, EdgeKind::Build, EdgeKind::Dev] {
+            let mut children = self
+                .edges
+                .iter()
+                .filter(|(from, _, k)| *from == node && *k == kind)
+                .map(|(_, to, _)| *to)
+                .collect::<Vec<_>>();
+            if children.is_empty() {
+                continue;
+            }
+            children.sort_by(|a, b| self.nodes[*a].cmp(&self.nodes[*b]));
+            if kind != EdgeKind::Normal {
+                *tree += &format!("{}[{}-dependencies]\n", prefix, kind.name());
+            }
+
+            for (i, child) in children.iter().enumerate() {
+                let last = i + 1 == children.len();
+                let branch = if last { "└── " } else { "├── " };
+                *tree += &format!("{}{}{}", prefix, branch, self.nodes[*child]);
+                let has_children = self.edges.iter().any(|(from, ..)| from == child);
+                if shown[*child] && has_children {
+                    *tree += " (*)\n";
+                    continue;
+                }
+                *tree += "\n";
+                shown[*child] = true;
+                let prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+                self.write_tree(*child, &prefix, depth - 1, shown, tree);
+            }
+        }
+    }
+
+    /// Render the graph as Graphviz DOT.
+    pub fn dot(&self) -> String {
+        let quote =
+            |label: &str| format!("\"{}\"", label.replace('\\', "\\\\").replace('"', "\\\""));
+
+        let mut dot = "digraph {\n".to_owned();
+        for (node, label) in self.nodes.iter().enumerate() {
+            dot += &format!("    n{} [label={}];\n", node, quote(label));
+        }
+        for (from, to, kind) in &self.edges {
+            dot += &match kind {
+                EdgeKind::Normal => format!("    n{} -> n{};\n", from, to),
+                EdgeKind::Dev => {
+                    format!("    n{} -> n{} [style=dashed, label=\"dev\"];\n", from, to)
+                }
+                EdgeKind::Build => {
+                    format!(
+                        "    n{} -> n{} [style=dotted, label=\"build\"];\n",
+                        from, to
+                    )
+                }
+            };
+        }
+
+        dot + "}\n"
+    }
+
+    /// Render the graph as a Mermaid flowchart.
+    pub fn mermaid(&self) -> String {
+        let quote = |label: &str| format!("\"{}\"", label.replace('"', "#quot;"));
+
+        let mut mermaid = "graph TD\n".to_owned();
+        for (node, label) in self.nodes.iter().enumerate() {
+            mermaid += &format!("    n{}[{}]\n", node, quote(label));
+        }
+        for (from, to, kind) in &self.edges {
+            mermaid += &match kind {
+                EdgeKind::Normal => format!("    n{} --> n{}\n", from, to),
+                EdgeKind::Dev => format!("    n{} -. dev .-> n{}\n", from, to),
+                EdgeKind::Build => format!("    n{} -. build .-> n{}\n", from, to),
+            };
+        }
+
+        mermaid
+    }
+}
+
+/// The package graph, the project and its dependencies.
+pub fn package_graph(config: &Config) -> Graph {
+    let mut graph = Graph::default();
+    let root = graph.node(format!(
+        "{} {}",
+        config.project.name, config.project.version
+    ));
+
+    let mut dependencies = config.dependencies.iter().collect::<Vec<_>>();
+    dependencies.sort_by_key(|(name, _)| *name);
+    for (name, dependency) in dependencies {
+        let node = graph.node(format!("{} {}", name, dependency.version()));
+        graph.edge(root, node, EdgeKind::Normal);
+    }
+
+    graph
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn graph() -> Graph {
+        let mut graph = Graph::default();
+        let app = graph.node("app");
+        let fmt = graph.node("fmt");
+        let gtest = graph.node("gtest");
+        let gmock = graph.node("gmock");
+        graph.edge(app, fmt, EdgeKind::Normal);
+        graph.edge(app, gtest, EdgeKind::Dev);
+        graph.edge(gtest, gmock, EdgeKind::Normal);
+        graph
+    }
+
+    #[test]
+    fn test_filter() {
+        let mut filtered = graph();
+        filtered.retain_edges(|kind| kind == EdgeKind::Normal);
+        assert_eq!(filtered.nodes, vec!["app", "fmt"]);
+        assert_eq!(filtered.edges, vec![(0, 1, EdgeKind::Normal)]);
+
+        let mut limited = graph();
+        limited.limit_depth(1);
+        assert_eq!(limited.nodes, vec!["app", "fmt", "gtest"]);
+    }
+
+    #[test]
+    fn test_tree() {
+        let mut graph = graph();
+        let fmt = graph.node("fmt");
+        let gmock = graph.node("gmock");
+        graph.edge(gmock, fmt, EdgeKind::Normal);
+
+        assert_eq!(
+            graph.tree(0, usize::MAX),
+            "app\n\
+            └── fmt\n\
+            [dev-dependencies]\n\
+            └── gtest\n    \
+                └── gmock\n        \
+                    └── fmt\n"
+        );
+        assert_eq!(
+            graph.tree(0, 1),
+            "app\n└── fmt\n[dev-dependencies]\n└── gtest\n"
+        );
+        assert_eq!(graph.shared(), vec![fmt]);
+        assert_eq!(
+            graph.inverted().tree(fmt, usize::MAX),
+            "fmt\n\
+            ├── app\n\
+            └── gmock\n    \
+                └── gtest\n        \
+                    [dev-dependencies]\n        \
+                    └── app\n"
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let mut graph = graph();
+        graph.limit_depth(1);
+
+        assert_eq!(
+            graph.dot(),
+            "digraph {\n    \
+                n0 [label=\"app\"];\n    \
+                n1 [label=\"fmt\"];\n    \
+                n2 [label=\"gtest\"];\n    \
+                n0 -> n1;\n    \
+                n0 -> n2 [style=dashed, label=\"dev\"];\n\
+            }\n"
+        );
+        assert_eq!(
+            graph.mermaid(),
+            "graph TD\n    \
+                n0[\"app\"]\n    \
+                n1[\"fmt\"]\n    \
+                n2[\"gtest\"]\n    \
+                n0 --> n1\n    \
+                n0 -. dev .-> n2\n"
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! The `coppo tree` add-on, it prints the resolved packages as an indented tree.
+//!
+//! The packages and their dependencies are read from `Coppo.lock`,
+//! or resolved without writing it if there is none, see [`coppo_build::lock`],
+//! so nothing is downloaded once the project is locked.
+//! The dev-dependencies of the project follow its dependencies under `[dev-dependencies]`.
+//!
+//! `--invert <PACKAGE>` prints what depends on the package instead,
+//! and `--duplicates` prints it for every package which more than one package depends on.
+//!
+//! # Usage
+//! ```sh
+//! coppo tree [--depth <N>] [--invert <PACKAGE>] [--duplicates]
+//! ```
+
+use std::path::Path;
+
+use coppo_addons::prelude::*;
+use coppo_build::lock;
+use coppo_config::lock::{LockedPackage, Lockfile, LOCK_FILE};
+use coppo_config::Platform;
+use coppo_logger::prelude::*;
+
+use crate::{EdgeKind, Graph};
+
+/// The `Coppo tree` add-on.
+/// Print the resolved dependencies of the current project as a tree.
+pub struct CoppoTreeAddon;
+
+impl_addon! {
+    CoppoTreeAddon,
+    name => "tree",
+    description => "Print the resolved dependencies as a tree",
+    args => [
+        arg!(--depth <N> "The maximum depth of the tree, the project is at depth 0")
+            .value_parser(value_parser!(usize)),
+        arg!(-i --invert <PACKAGE> "Print the packages which depend on the package")
+            .value_parser(value_parser!(String)),
+        arg!(-d --duplicates "Print the packages which more than one package depends on, inverted")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("invert"),
+    ],
+    run => |config, matches| {
+        if !Config::exists() {
+            return Err("The project does not have a `Coppo.toml` file.".into());
+        }
+
+        let locked = match Lockfile::from_file(Path::new("."))? {
+            Some(locked) => locked,
+            None => lock::resolve(config, None)?,
+        };
+        let graph = locked_graph(config, &locked);
+        let depth = matches.get_one::<usize>("depth").copied().unwrap_or(usize::MAX);
+
+        if let Some(name) = matches.get_one::<String>("invert") {
+            let node = find(&graph, name).ok_or_else(|| {
+                format!("The package `{}` is not found in `{}`.", name, LOCK_FILE)
+            })?;
+            print!("{}", graph.inverted().tree(node, depth));
+        } else if matches.get_flag("duplicates") {
+            let shared = graph.shared();
+            if shared.is_empty() {
+                info!("No package is depended on by more than one package.");
+            }
+            let inverted = graph.inverted();
+            for (i, node) in shared.into_iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                print!("{}", inverted.tree(node, depth));
+            }
+        } else {
+            print!("{}", graph.tree(0, depth));
+        }
+    }
+}
+
+/// The label of the locked package, like `fmt 10.2.1 (registry+https://example.com/index)`.
+fn label(package: &LockedPackage) -> String {
+    format!("{} {} ({})", package.name, package.version, package.source)
+}
+
+/// The node of the package named `name`.
+fn find(graph: &Graph, name: &str) -> Option<usize> {
+    graph
+        .nodes
+        .iter()
+        .position(|label| label.split(' ').next() == Some(name))
+}
+
+/// The graph of the locked packages, the project is the node `0`,
+/// with an edge to each of its dependencies and dev-dependencies on the host.
+pub fn locked_graph(config: &Config, locked: &Lockfile) -> Graph {
+    let mut graph = Graph::default();
+    let root = graph.node(format!(
+        "{} {}",
+        config.project.name, config.project.version
+    ));
+    for package in &locked.packages {
+        graph.node(label(package));
+    }
+    let edge = |graph: &mut Graph, from: usize, name: &str, kind: EdgeKind| {
+        if let Some(package) = locked.get(name) {
+            let to = graph.node(label(package));
+            graph.edge(from, to, kind);
+        }
+    };
+
+    let platform = Platform::host();
+    for name in config.dependencies_for(&platform).into_keys() {
+        edge(&mut graph, root, name, EdgeKind::Normal);
+    }
+    for name in config.dev_dependencies_for(&platform).into_keys() {
+        edge(&mut graph, root, name, EdgeKind::Dev);
+    }
+    for package in &locked.packages {
+        let from = graph.node(label(package));
+        for name in &package.dependencies {
+            edge(&mut graph, from, name, EdgeKind::Normal);
+        }
+    }
+
+    graph
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn package(name: &str, source: &str, dependencies: &[&str]) -> LockedPackage {
+        LockedPackage {
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            source: source.to_string(),
+            checksum: None,
+            dependencies: dependencies.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_locked_graph() {
+        let config = Config::from_str(
+            r#"
+            [project]
+            name = "app"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            util = { path = "../util" }
+            zlib = "1"
+
+            [dev-dependencies]
+            gtest = "1"
+            "#,
+        )
+        .unwrap();
+        let locked = Lockfile {
+            packages: vec![
+                package("gtest", "system", &[]),
+                package("util", "path+../util", &["zlib"]),
+                package("zlib", "system", &[]),
+            ],
+            ..Default::default()
+        };
+
+        let graph = locked_graph(&config, &locked);
+        assert_eq!(
+            graph.tree(0, usize::MAX),
+            "app 0.1.0\n\
+            ├── util 1.0.0 (path+../util)\n\
+            │   └── zlib 1.0.0 (system)\n\
+            └── zlib 1.0.0 (system)\n\
+            [dev-dependencies]\n\
+            └── gtest 1.0.0 (system)\n"
+        );
+        assert_eq!(find(&graph, "zlib"), Some(3));
+        assert_eq!(graph.shared(), vec![3]);
+    }
+}
@@ -0,0 +1,123 @@
+//! The `coppo-ide` crate is a Coppo addon that generates the editor integration files of the current project,
+//! so the editor can build, test and debug it with Coppo.
+//!
+//! The supported editors:
+//! - `vscode`, see [`vscode`].
+//!
+//! # Usage
+//! ```sh
+//! coppo ide <EDITOR> [--force]
+//! ```
+
+#![forbid(unsafe_code)]
+
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use coppo_addons::prelude::*;
+use coppo_build::compdb;
+use coppo_logger::prelude::*;
+
+pub mod vscode;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The `Coppo ide` add-on.
+/// Generate the editor integration files of the current project.
+pub struct CoppoIdeAddon;
+
+impl_addon! {
+    CoppoIdeAddon,
+    name => "ide",
+    description => "Generate the editor integration files of the current project",
+    args => [
+        arg!(<EDITOR> "The editor to integrate with")
+            .value_parser(Editor::NAMES),
+        arg!(-f --force "Overwrite the existing files")
+            .action(ArgAction::SetTrue),
+    ],
+    run => |config, matches| {
+        if !Config::exists() {
+            return Err("The project does not have a `Coppo.toml` file.".into());
+        }
+
+        let editor = matches.get_one::<String>("EDITOR").unwrap().parse::<Editor>()?;
+        let files = editor.files(config)?;
+        write(&files, matches.get_flag("force"))?;
+
+        // The language servers read the compilation database, so it is generated right away.
+        let compdb = compdb::write(config)?;
+        info!("Generated `{}`", compdb.display());
+
+        success!("The {} integration files have been generated.", editor);
+    }
+}
+
+/// The supported editors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Editor {
+    /// Visual Studio Code, the `.vscode` directory.
+    Vscode,
+}
+
+impl Editor {
+    /// All the names of the supported editors.
+    pub const NAMES: [&'static str; 1] = ["vscode"];
+
+    /// The integration files of the editor, their paths and contents.
+    pub fn files(&self, config: &Config) -> Result<Vec<(PathBuf, String)>> {
+        match self {
+            Editor::Vscode => vscode::files(config),
+        }
+    }
+}
+
+impl FromStr for Editor {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "vscode" => Ok(Editor::Vscode),
+            _ => Err(format!("Unknown editor `{}`", s)),
+        }
+    }
+}
+
+impl fmt::Display for Editor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Editor::Vscode => write!(f, "VS Code"),
+        }
+    }
+}
+
+/// Write the files, the existing ones are refused unless `force` is `true`,
+/// since they are likely customized by the user.
+fn write(files: &[(PathBuf, String)], force: bool) -> Result<()> {
+    if !force {
+        let existing = files
+            .iter()
+            .filter(|(path, _)| path.exists())
+            .map(|(path, _)| format!("`{}`", path.display()))
+            .collect::<Vec<_>>();
+        if !existing.is_empty() {
+            return Err(format!(
+                "The files already exist: {}. Use `--force` to overwrite them.",
+                existing.join(", ")
+            )
+            .into());
+        }
+    }
+
+    for (path, content) in files {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, content)?;
+        info!("Generated `{}`", path.display());
+    }
+
+    Ok(())
+}
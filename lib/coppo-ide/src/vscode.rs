@@ -0,0 +1,163 @@
+//! The Visual Studio Code integration, like `coppo ide vscode`.
+//!
+//! It generates the files in the `.vscode` directory:
+//! - `tasks.json`, the build, test and clean tasks calling Coppo.
+//! - `launch.json`, a debug configuration for each binary target and profile.
+//! - `c_cpp_properties.json`, the C/C++ extension reads the compilation database.
+//! - `settings.json`, clangd reads the compilation database.
+
+use std::path::{Path, PathBuf};
+
+use coppo_build::compdb::COMPILE_COMMANDS;
+use coppo_build::{bin_path, bins, compiler, target_dir, DEFAULT_PROFILE};
+use coppo_config::prelude::*;
+use serde_json::{json, Value};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The directory of the integration files.
+pub const VSCODE_DIR: &str = ".vscode";
+
+/// The label of the build task, the debug configurations build the project before launching.
+pub const BUILD_TASK: &str = "coppo: build";
+
+/// The profiles to generate the debug configurations for.
+pub const PROFILES: &[&str] = &[DEFAULT_PROFILE];
+
+/// The integration files, their paths and contents.
+pub fn files(config: &Config) -> Result<Vec<(PathBuf, String)>> {
+    let dir = Path::new(VSCODE_DIR);
+    [
+        ("tasks.json", tasks()),
+        ("launch.json", launch(config)),
+        ("c_cpp_properties.json", c_cpp_properties(config)),
+        ("settings.json", settings()),
+    ]
+    .into_iter()
+    .map(|(name, value)| Ok((dir.join(name), serde_json::to_string_pretty(&value)? + "\n")))
+    .collect()
+}
+
+/// The path in the editor, the relative paths are based on the workspace folder.
+fn editor_path(path: &Path) -> String {
+    if path.is_absolute() {
+        path.display().to_string()
+    } else {
+        format!("${{workspaceFolder}}/{}", path.display())
+    }
+}
+
+/// The `tasks.json`, the tasks calling Coppo.
+pub fn tasks() -> Value {
+    let task = |label: &str, args: &[&str], group: Value| {
+        json!({
+            "label": label,
+            "type": "shell",
+            "command": "coppo",
+            "args": args,
+            "group": group,
+            "problemMatcher": ["$gcc"],
+        })
+    };
+
+    json!({
+        "version": "2.0.0",
+        "tasks": [
+            task(BUILD_TASK, &["build"], json!({ "kind": "build", "isDefault": true })),
+            task("coppo: test", &["test"], json!({ "kind": "test", "isDefault": true })),
+            task("coppo: clean", &["clean"], json!("none")),
+        ],
+    })
+}
+
+/// The `launch.json`, a debug configuration for each binary target and profile.
+pub fn launch(config: &Config) -> Value {
+    let debugger = if cfg!(target_os = "macos") {
+        "lldb"
+    } else {
+        "gdb"
+    };
+
+    let configurations = bins(config)
+        .iter()
+        .flat_map(|bin| {
+            PROFILES.iter().map(move |profile| {
+                json!({
+                    "name": format!("Debug `{}` ({})", bin.name, profile),
+                    "type": "cppdbg",
+                    "request": "launch",
+                    "program": editor_path(Path::new(&bin_path(config, &bin.name, profile))),
+                    "args": [],
+                    "cwd": "${workspaceFolder}",
+                    "MIMode": debugger,
+                    "preLaunchTask": BUILD_TASK,
+                })
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "version": "0.2.0",
+        "configurations": configurations,
+    })
+}
+
+/// The `c_cpp_properties.json`, the C/C++ extension reads the compilation database.
+pub fn c_cpp_properties(config: &Config) -> Value {
+    let include_path = match &config.lib {
+        Some(lib) => vec![editor_path(&lib.include())],
+        None => vec![],
+    };
+
+    json!({
+        "version": 4,
+        "configurations": [{
+            "name": "Coppo",
+            "compilerPath": compiler(config),
+            "compileCommands": editor_path(&target_dir().join(COMPILE_COMMANDS)),
+            "includePath": include_path,
+        }],
+    })
+}
+
+/// The `settings.json`, clangd reads the compilation database.
+pub fn settings() -> Value {
+    json!({
+        "clangd.arguments": [
+            format!("--compile-commands-dir={}", editor_path(&target_dir())),
+        ],
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_launch() {
+        let mut config = Config::default();
+        config.project.name = "app".to_string();
+        for name in ["server", "client"] {
+            config.bin.push(BinTarget {
+                name: name.to_string(),
+                ..Default::default()
+            });
+        }
+
+        let launch = launch(&config);
+        let configurations = launch["configurations"].as_array().unwrap();
+        assert_eq!(configurations.len(), 2 * PROFILES.len());
+        assert_eq!(configurations[0]["name"], "Debug `server` (debug)");
+        assert_eq!(
+            configurations[0]["program"],
+            editor_path(Path::new(&bin_path(&config, "server", DEFAULT_PROFILE)))
+        );
+        assert_eq!(configurations[0]["preLaunchTask"], BUILD_TASK);
+        assert_eq!(configurations[1]["name"], "Debug `client` (debug)");
+
+        assert_eq!(
+            editor_path(Path::new("target/app")),
+            "${workspaceFolder}/target/app"
+        );
+    }
+}
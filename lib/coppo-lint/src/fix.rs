@@ -0,0 +1,179 @@
+//! The `coppo fix` add-on, it applies the fixes suggested by `clang-tidy`.
+//!
+//! Every source file is linted in parallel, and the fixes are exported into `target/fix`.
+//! Then they are applied at once by `clang-apply-replacements`,
+//! so the fixes in the shared headers are applied only once.
+//!
+//! The fixes can not be undone easily without a VCS,
+//! so it refuses to run if the git work tree has uncommitted changes, unless `--allow-dirty`.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use coppo_addons::prelude::*;
+use coppo_build::parallel::{default_jobs, run_parallel};
+use coppo_build::target_dir;
+use coppo_config::files::{self, HEADER_EXTENSIONS, SOURCE_EXTENSIONS};
+use coppo_logger::prelude::*;
+use similar::{ChangeTag, TextDiff};
+
+use crate::{prepare, tidy, Result};
+
+/// The executable which applies the exported fixes.
+pub const CLANG_APPLY_REPLACEMENTS: &str = "clang-apply-replacements";
+
+/// The exported fixes are stored in the `target/fix` directory.
+const FIX_OUTPUT: &str = "fix";
+
+/// The `Coppo fix` add-on.
+/// Apply the fixes suggested by `clang-tidy` to the current project.
+pub struct CoppoFixAddon;
+
+impl_addon! {
+    CoppoFixAddon,
+    name => "fix",
+    description => "Apply the fixes suggested by clang-tidy",
+    args => [
+        arg!(-j --jobs <N> "The number of files linted in parallel, defaults to the number of CPUs")
+            .value_parser(value_parser!(u64).range(1..)),
+        arg!(--"allow-dirty" "Fix even if the work tree has uncommitted changes")
+            .action(ArgAction::SetTrue),
+    ],
+    run => |config, matches| {
+        let jobs = matches
+            .get_one::<u64>("jobs")
+            .map_or_else(default_jobs, |jobs| *jobs as usize);
+        fix(config, jobs, matches.get_flag("allow-dirty"))?;
+    }
+}
+
+fn fix(config: &Config, jobs: usize, allow_dirty: bool) -> Result<()> {
+    if !Config::exists() {
+        return Err("The project does not have a `Coppo.toml` file.".into());
+    }
+    if config.is_empty() {
+        return Err("The project name and version is needed".into());
+    }
+    if !allow_dirty {
+        check_clean()?;
+    }
+
+    let (sources, args) = prepare(config)?;
+    if sources.is_empty() {
+        warn!("There is no source file to fix.");
+        return Ok(());
+    }
+
+    // Take a snapshot of the project files to summarize the changes.
+    let extensions = [SOURCE_EXTENSIONS, HEADER_EXTENSIONS].concat();
+    let before = snapshot(&files::project_files(&config.project, ".", &extensions)?)?;
+
+    let output = target_dir().join(FIX_OUTPUT);
+    if output.exists() {
+        fs::remove_dir_all(&output)?;
+    }
+    fs::create_dir_all(&output)?;
+
+    info!("Linting {} files...", sources.len());
+    let sources = sources.into_iter().enumerate().collect::<Vec<_>>();
+    let results = run_parallel(&sources, jobs, |(i, file)| {
+        // Every file exports its fixes into its own file, so the threads do not conflict.
+        let export = output.join(format!("{}.yaml", i));
+        let export = format!("--export-fixes={}", export.display());
+        tidy(&[args.as_slice(), &[export]].concat(), file)
+    });
+    for result in results {
+        result?;
+    }
+
+    if fs::read_dir(&output)?.next().is_none() {
+        success!("There is nothing to fix.");
+        return Ok(());
+    }
+
+    info!("Applying the fixes...");
+    apply(&output)?;
+
+    let after = snapshot(&before.keys().cloned().collect::<Vec<_>>())?;
+    let mut changed = 0;
+    for (file, old) in &before {
+        let new = &after[file];
+        if old == new {
+            continue;
+        }
+
+        let diff = TextDiff::from_lines(old, new);
+        let count = |tag| diff.iter_all_changes().filter(|c| c.tag() == tag).count();
+        info!(
+            "Fixed `{}` (+{} -{})",
+            file.display(),
+            count(ChangeTag::Insert),
+            count(ChangeTag::Delete)
+        );
+        changed += 1;
+    }
+    success!("Fixed {} files.", changed);
+
+    Ok(())
+}
+
+/// Refuse to fix if the git work tree has uncommitted changes.
+/// The project out of a git work tree is not checked.
+pub(crate) fn check_clean() -> Result<()> {
+    let output = match process::Command::new("git")
+        .args(["status", "--porcelain", "--untracked-files=no"])
+        .output()
+    {
+        Ok(output) if output.status.success() => output,
+        _ => return Ok(()),
+    };
+
+    let dirty = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.get(3..).unwrap_or(line).to_owned())
+        .collect::<Vec<_>>();
+    if dirty.is_empty() {
+        return Ok(());
+    }
+
+    Err(format!(
+        "The work tree has uncommitted changes in {}, \
+        commit or stash them first, or use `--allow-dirty`.",
+        dirty.join(", ")
+    )
+    .into())
+}
+
+/// Read the contents of the files.
+fn snapshot(files: &[PathBuf]) -> Result<BTreeMap<PathBuf, String>> {
+    files
+        .iter()
+        .map(|file| Ok((file.clone(), fs::read_to_string(file)?)))
+        .collect()
+}
+
+/// Apply the exported fixes in the directory.
+fn apply(dir: &Path) -> Result<()> {
+    let output = process::Command::new(CLANG_APPLY_REPLACEMENTS)
+        .arg(dir)
+        .output()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => {
+                format!("`{}` is not found in PATH.", CLANG_APPLY_REPLACEMENTS)
+            }
+            _ => format!("Failed to run `{}`: {}", CLANG_APPLY_REPLACEMENTS, e),
+        })?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to apply the fixes: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,795 @@
+//! The `coppo includes` add-on, an include-what-you-use style analysis of the project headers.
+//!
+//! For each translation unit, the headers it reaches are taken from the dependency output
+//! of the compiler (`-MM`), so only the project headers are analyzed, not the system headers.
+//! The names declared by every header are extracted with a light-weight parser,
+//! then the unit is checked against the names it uses:
+//! - A direct include is unused if none of the names declared by the header is used.
+//! - An include is missing if a used name is only declared by a header reached indirectly.
+//!
+//! With `--fix`, the unused includes are removed and the missing ones are added,
+//! and the edit of a file is reverted if the file does not compile anymore.
+//! Like `coppo fix`, it refuses to fix if the git work tree has uncommitted changes.
+//!
+//! # Usage
+//! ```sh
+//! coppo includes [--fix] [--allow-dirty]
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use coppo_addons::prelude::*;
+use coppo_build::compdb::{self, CompileCommand};
+use coppo_config::files::FileFilter;
+use coppo_logger::prelude::*;
+
+use crate::fix::check_clean;
+use crate::Result;
+
+/// The `Coppo includes` add-on.
+/// Report the unused and missing includes of the project headers in each translation unit.
+pub struct CoppoIncludesAddon;
+
+impl_addon! {
+    CoppoIncludesAddon,
+    name => "includes",
+    description => "Report the unused and missing includes of the project headers",
+    args => [
+        arg!(--fix "Remove the unused includes and add the missing ones")
+            .action(ArgAction::SetTrue),
+        arg!(--"allow-dirty" "Fix even if the work tree has uncommitted changes")
+            .action(ArgAction::SetTrue)
+            .requires("fix"),
+    ],
+    run => |config, matches| {
+        includes(config, matches.get_flag("fix"), matches.get_flag("allow-dirty"))?;
+    }
+}
+
+/// The C++ keywords and the common built-in names, they are never declared by a header.
+const KEYWORDS: &[&str] = &[
+    "alignas",
+    "alignof",
+    "asm",
+    "auto",
+    "bool",
+    "break",
+    "case",
+    "catch",
+    "char",
+    "class",
+    "const",
+    "consteval",
+    "constexpr",
+    "constinit",
+    "const_cast",
+    "continue",
+    "decltype",
+    "default",
+    "delete",
+    "do",
+    "double",
+    "dynamic_cast",
+    "else",
+    "enum",
+    "explicit",
+    "export",
+    "extern",
+    "false",
+    "final",
+    "float",
+    "for",
+    "friend",
+    "goto",
+    "if",
+    "inline",
+    "int",
+    "long",
+    "mutable",
+    "namespace",
+    "new",
+    "noexcept",
+    "nullptr",
+    "operator",
+    "override",
+    "private",
+    "protected",
+    "public",
+    "register",
+    "reinterpret_cast",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "static_assert",
+    "static_cast",
+    "struct",
+    "switch",
+    "template",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typedef",
+    "typeid",
+    "typename",
+    "union",
+    "unsigned",
+    "using",
+    "virtual",
+    "void",
+    "volatile",
+    "while",
+];
+
+/// The findings of a translation unit.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    /// The unused direct includes, their 0-based line numbers and headers.
+    pub unused: Vec<(usize, PathBuf)>,
+    /// The missing headers, with the used names they declare.
+    pub missing: BTreeMap<PathBuf, BTreeSet<String>>,
+}
+
+impl Report {
+    /// Check if nothing is found.
+    pub fn is_empty(&self) -> bool {
+        self.unused.is_empty() && self.missing.is_empty()
+    }
+}
+
+fn includes(config: &Config, fix: bool, allow_dirty: bool) -> Result<()> {
+    if !Config::exists() {
+        return Err("The project does not have a `Coppo.toml` file.".into());
+    }
+    if config.is_empty() {
+        return Err("The project name and version is needed".into());
+    }
+    if fix && !allow_dirty {
+        check_clean()?;
+    }
+
+    let filter = FileFilter::new(&config.project)?;
+    let commands = compdb::compile_commands(config)?
+        .into_iter()
+        .filter(|command| filter.matches(&command.file))
+        .collect::<Vec<_>>();
+
+    let mut declarations = BTreeMap::new();
+    let (mut unused, mut missing, mut files) = (0, 0, 0);
+    for command in &commands {
+        let unit = Unit::load(command, &mut declarations)?;
+        let report = unit.analyze(&declarations);
+        if report.is_empty() {
+            continue;
+        }
+
+        files += 1;
+        unused += report.unused.len();
+        missing += report.missing.len();
+        info!("{}:", command.file.display());
+        for (line, _) in &report.unused {
+            warn!(
+                "  - {} (line {}, unused)",
+                unit.lines[*line].trim(),
+                line + 1
+            );
+        }
+        for (header, names) in &report.missing {
+            let names = names.iter().cloned().collect::<Vec<_>>();
+            warn!(
+                "  + #include {} (for {})",
+                unit.spelling(header),
+                names.join(", ")
+            );
+        }
+
+        if fix {
+            unit.fix(command, &report)?;
+        }
+    }
+
+    if files == 0 {
+        success!("No include issue found.");
+    } else if !fix {
+        warn!(
+            "Found {} unused and {} missing includes in {} files, use `--fix` to fix them.",
+            unused, missing, files
+        );
+    }
+
+    Ok(())
+}
+
+/// A translation unit, a source file with the project headers it reaches.
+struct Unit {
+    /// The lines of the source file.
+    lines: Vec<String>,
+    /// The direct includes of the project headers, their 0-based line numbers and headers.
+    direct: Vec<(usize, PathBuf)>,
+    /// The project headers reached by the unit, directly or indirectly.
+    reached: Vec<PathBuf>,
+    /// The include directories, to spell the missing includes.
+    include_dirs: Vec<PathBuf>,
+    /// The directory of the source file.
+    dir: PathBuf,
+}
+
+impl Unit {
+    /// Load the unit, and parse the declarations of the headers not parsed yet.
+    fn load(
+        command: &CompileCommand,
+        declarations: &mut BTreeMap<PathBuf, HashSet<String>>,
+    ) -> Result<Unit> {
+        let source = fs::read_to_string(&command.file)?;
+        let reached = dependencies(command)?;
+        for header in &reached {
+            if !declarations.contains_key(header) {
+                declarations.insert(header.clone(), declared(&fs::read_to_string(header)?));
+            }
+        }
+
+        let include_dirs = command
+            .arguments
+            .iter()
+            .filter_map(|argument| argument.strip_prefix("-I"))
+            .map(PathBuf::from)
+            .collect::<Vec<_>>();
+        let dir = command.file.parent().unwrap_or(Path::new("")).to_owned();
+
+        let lines = source.lines().map(str::to_owned).collect::<Vec<_>>();
+        let direct = lines
+            .iter()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let (header, quoted) = parse_include(line)?;
+                let local = quoted.then(|| dir.join(header));
+                let header = local
+                    .into_iter()
+                    .chain(include_dirs.iter().map(|include| include.join(header)))
+                    .filter_map(|path| fs::canonicalize(path).ok())
+                    .find(|path| reached.contains(path))?;
+                Some((i, header))
+            })
+            .collect();
+
+        Ok(Unit {
+            lines,
+            direct,
+            reached,
+            include_dirs,
+            dir,
+        })
+    }
+
+    /// Check the unit against the declarations of the headers.
+    fn analyze(&self, declarations: &BTreeMap<PathBuf, HashSet<String>>) -> Report {
+        analyze(
+            &self.lines.join("\n"),
+            &self.direct,
+            &self.reached,
+            declarations,
+        )
+    }
+
+    /// The spelling of the include of the header,
+    /// `<...>` relative to an include directory, or `"..."` relative to the source file.
+    fn spelling(&self, header: &Path) -> String {
+        for dir in &self.include_dirs {
+            if let Ok(relative) = fs::canonicalize(dir).and_then(|dir| {
+                header
+                    .strip_prefix(dir)
+                    .map(Path::to_owned)
+                    .map_err(|_| std::io::ErrorKind::NotFound.into())
+            }) {
+                return format!("<{}>", relative.display());
+            }
+        }
+
+        let relative = fs::canonicalize(&self.dir)
+            .ok()
+            .and_then(|dir| header.strip_prefix(dir).ok().map(Path::to_owned))
+            .unwrap_or_else(|| header.to_owned());
+        format!("\"{}\"", relative.display())
+    }
+
+    /// Remove the unused includes and add the missing ones,
+    /// the edit is reverted if the file does not compile anymore.
+    fn fix(&self, command: &CompileCommand, report: &Report) -> Result<()> {
+        let removed = report
+            .unused
+            .iter()
+            .map(|(line, _)| *line)
+            .filter(|line| is_whole_include(&self.lines[*line]))
+            .collect::<HashSet<_>>();
+        let added = report
+            .missing
+            .keys()
+            .map(|header| format!("#include {}", self.spelling(header)))
+            .collect::<Vec<_>>();
+
+        // The missing includes are added after the last include.
+        let last = (0..self.lines.len())
+            .rev()
+            .find(|i| parse_include(&self.lines[*i]).is_some());
+        let mut lines = vec![];
+        if last.is_none() {
+            lines.extend(added.iter().cloned());
+        }
+        for (i, line) in self.lines.iter().enumerate() {
+            if !removed.contains(&i) {
+                lines.push(line.clone());
+            }
+            if Some(i) == last {
+                lines.extend(added.iter().cloned());
+            }
+        }
+
+        let original = fs::read_to_string(&command.file)?;
+        let mut fixed = lines.join("\n");
+        if original.ends_with('\n') {
+            fixed.push('\n');
+        }
+        fs::write(&command.file, &fixed)?;
+
+        if compiles(command) {
+            info!("Fixed `{}`", command.file.display());
+        } else {
+            fs::write(&command.file, original)?;
+            warn!(
+                "The includes of `{}` can not be fixed safely, it is left unchanged.",
+                command.file.display()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// The arguments of the compile command without `-c <file>`.
+fn flags(command: &CompileCommand) -> Vec<String> {
+    let file = command.file.display().to_string();
+    command.arguments[1..]
+        .iter()
+        .filter(|argument| **argument != "-c" && **argument != file)
+        .cloned()
+        .collect()
+}
+
+/// The project headers reached by the source file, from the `-MM` output of the compiler.
+fn dependencies(command: &CompileCommand) -> Result<Vec<PathBuf>> {
+    let output = process::Command::new(&command.arguments[0])
+        .args(flags(command))
+        .arg("-MM")
+        .arg(&command.file)
+        .output()
+        .map_err(|e| format!("Failed to run `{}`: {}", command.arguments[0], e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to get the dependencies of `{}`: {}",
+            command.file.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    Ok(parse_dependencies(&String::from_utf8_lossy(&output.stdout))
+        .into_iter()
+        .skip(1)
+        .filter_map(|path| fs::canonicalize(path).ok())
+        .collect())
+}
+
+/// Check if the file still compiles.
+fn compiles(command: &CompileCommand) -> bool {
+    process::Command::new(&command.arguments[0])
+        .args(flags(command))
+        .arg("-fsyntax-only")
+        .arg(&command.file)
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Parse the make rule of `-MM`, and return the prerequisites, the source file comes first.
+fn parse_dependencies(rule: &str) -> Vec<String> {
+    let rule = rule.replace("\\\n", " ");
+    let prerequisites = rule.split_once(": ").map_or("", |(_, rest)| rest);
+
+    let mut paths = vec![];
+    let mut path = String::new();
+    let mut chars = prerequisites.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            // A space in a path is escaped.
+            '\\' if chars.peek() == Some(&' ') => {
+                path.push(' ');
+                chars.next();
+            }
+            c if c.is_whitespace() => {
+                if !path.is_empty() {
+                    paths.push(std::mem::take(&mut path));
+                }
+            }
+            c => path.push(c),
+        }
+    }
+    if !path.is_empty() {
+        paths.push(path);
+    }
+
+    paths
+}
+
+/// Parse the `#include` directive, and return the header and whether it is quoted.
+fn parse_include(line: &str) -> Option<(&str, bool)> {
+    let directive = line.trim_start().strip_prefix('#')?.trim_start();
+    let header = directive.strip_prefix("include")?.trim();
+
+    if let Some(header) = header.strip_prefix('"') {
+        Some((header.split('"').next()?, true))
+    } else {
+        Some((header.strip_prefix('<')?.split('>').next()?, false))
+    }
+}
+
+/// Check if the line is only an include, so removing it does not remove any code.
+fn is_whole_include(line: &str) -> bool {
+    let Some((header, _)) = parse_include(line) else {
+        return false;
+    };
+    let rest = line
+        .split_once(header)
+        .map_or("", |(_, rest)| rest)
+        .trim_start_matches(['"', '>'])
+        .trim();
+
+    rest.is_empty() || rest.starts_with("//")
+}
+
+/// Check the source against the declarations of the headers it reaches.
+fn analyze(
+    source: &str,
+    direct: &[(usize, PathBuf)],
+    reached: &[PathBuf],
+    declarations: &BTreeMap<PathBuf, HashSet<String>>,
+) -> Report {
+    let used = identifiers(source);
+    let empty = HashSet::new();
+    let declared_by = |header: &PathBuf| declarations.get(header).unwrap_or(&empty);
+
+    // A header without any parsed declaration is never reported, the parser may miss them.
+    let unused = direct
+        .iter()
+        .filter(|(_, header)| {
+            let names = declared_by(header);
+            !names.is_empty() && names.is_disjoint(&used)
+        })
+        .cloned()
+        .collect();
+
+    let mut provided = declared(source);
+    for (_, header) in direct {
+        provided.extend(declared_by(header).iter().cloned());
+    }
+    let mut missing: BTreeMap<PathBuf, BTreeSet<String>> = BTreeMap::new();
+    for name in used.difference(&provided) {
+        let header = reached
+            .iter()
+            .filter(|header| !direct.iter().any(|(_, direct)| direct == *header))
+            .find(|header| declared_by(header).contains(name));
+        if let Some(header) = header {
+            missing
+                .entry(header.clone())
+                .or_default()
+                .insert(name.clone());
+        }
+    }
+
+    Report { unused, missing }
+}
+
+/// A token of the code, the comments and the preprocessor lines are dropped,
+/// and the literals are kept as empty placeholders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Identifier(String),
+    Literal,
+    Punct(char),
+}
+
+/// Split the code into tokens.
+fn tokenize(code: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut chars = code.chars().peekable();
+    let mut line_start = true;
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line_start = true,
+            c if c.is_whitespace() => {}
+            // A preprocessor line, with its continuations.
+            '#' if line_start => {
+                let mut previous = '#';
+                for c in chars.by_ref() {
+                    if c == '\n' && previous != '\\' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line_start = true;
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '"' | '\'' => {
+                let mut escaped = false;
+                for next in chars.by_ref() {
+                    match next {
+                        _ if escaped => escaped = false,
+                        '\\' => escaped = true,
+                        next if next == c => break,
+                        _ => {}
+                    }
+                }
+                tokens.push(Token::Literal);
+                line_start = false;
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut identifier = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if !(next.is_alphanumeric() || next == '_') {
+                        break;
+                    }
+                    identifier.push(next);
+                    chars.next();
+                }
+                // The numbers are literals.
+                if c.is_ascii_digit() {
+                    tokens.push(Token::Literal);
+                } else {
+                    tokens.push(Token::Identifier(identifier));
+                }
+                line_start = false;
+            }
+            c => {
+                tokens.push(Token::Punct(c));
+                line_start = false;
+            }
+        }
+    }
+
+    tokens
+}
+
+/// The names used by the code, the keywords are excluded.
+fn identifiers(code: &str) -> HashSet<String> {
+    tokenize(code)
+        .into_iter()
+        .filter_map(|token| match token {
+            Token::Identifier(name) if !KEYWORDS.contains(&name.as_str()) => Some(name),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The names declared by the code at the namespace level:
+/// the macros, the types, the aliases, the functions, the variables and the enumerators.
+fn declared(code: &str) -> HashSet<String> {
+    let mut names = HashSet::new();
+
+    for line in code.lines() {
+        let Some(directive) = line.trim_start().strip_prefix('#') else {
+            continue;
+        };
+        if let Some(definition) = directive.trim_start().strip_prefix("define") {
+            let name = definition
+                .trim_start()
+                .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .next()
+                .unwrap_or_default();
+            if !name.is_empty() {
+                names.insert(name.to_owned());
+            }
+        }
+    }
+
+    #[derive(PartialEq)]
+    enum Scope {
+        /// A namespace or an `extern "C"` block, its names are visible.
+        Transparent,
+        /// The enumerators of an enum.
+        Enum,
+        /// A class or a function body.
+        Opaque,
+    }
+
+    let tokens = tokenize(code);
+    let name = |i: usize| match tokens.get(i) {
+        Some(Token::Identifier(name)) if !KEYWORDS.contains(&name.as_str()) => Some(name.clone()),
+        _ => None,
+    };
+    let is = |i: usize, expected: &str| matches!(tokens.get(i), Some(Token::Identifier(name)) if name == expected);
+    let punct = |i: usize, expected: char| tokens.get(i) == Some(&Token::Punct(expected));
+
+    let mut scopes: Vec<Scope> = vec![];
+    let mut parens = 0usize;
+    // The start of the current statement, and the kind of its first keyword.
+    let mut statement = 0;
+    let mut i = 0;
+    while i < tokens.len() {
+        let visible = scopes.iter().all(|scope| *scope == Scope::Transparent);
+        match &tokens[i] {
+            Token::Punct('{') => {
+                let scope = if (statement..i).any(|j| is(j, "namespace"))
+                    || (i >= 2 && is(i - 2, "extern") && tokens[i - 1] == Token::Literal)
+                {
+                    Scope::Transparent
+                } else if visible && (statement..i).any(|j| is(j, "enum")) {
+                    Scope::Enum
+                } else {
+                    Scope::Opaque
+                };
+                scopes.push(scope);
+                statement = i + 1;
+            }
+            Token::Punct('}') => {
+                scopes.pop();
+                statement = i + 1;
+            }
+            Token::Punct(';') if parens == 0 => {
+                statement = i + 1;
+            }
+            Token::Punct('(') => parens += 1,
+            Token::Punct(')') => parens = parens.saturating_sub(1),
+            // The template parameters are skipped.
+            Token::Identifier(keyword) if keyword == "template" && punct(i + 1, '<') => {
+                let mut depth = 0;
+                i += 1;
+                while i < tokens.len() {
+                    match tokens[i] {
+                        Token::Punct('<') => depth += 1,
+                        Token::Punct('>') => depth -= 1,
+                        _ => {}
+                    }
+                    if depth == 0 {
+                        break;
+                    }
+                    i += 1;
+                }
+                statement = i + 1;
+            }
+            Token::Identifier(keyword)
+                if visible
+                    && parens == 0
+                    && ["class", "struct", "union", "enum"].contains(&keyword.as_str()) =>
+            {
+                let next = if is(i + 1, "class") || is(i + 1, "struct") {
+                    i + 2
+                } else {
+                    i + 1
+                };
+                names.extend(name(next));
+            }
+            Token::Identifier(_) if visible && parens == 0 => {
+                let alias = is(statement, "using") && punct(i + 1, '=');
+                let typedef = is(statement, "typedef") && punct(i + 1, ';');
+                let function = punct(i + 1, '(');
+                let variable = !is(statement, "using")
+                    && !is(statement, "typedef")
+                    && (punct(i + 1, '=') || punct(i + 1, ';') || punct(i + 1, '['))
+                    && i > statement;
+                if alias || typedef || function || variable {
+                    names.extend(name(i));
+                }
+            }
+            Token::Identifier(_)
+                if parens == 0
+                    && scopes.last() == Some(&Scope::Enum)
+                    && scopes[..scopes.len() - 1]
+                        .iter()
+                        .all(|scope| *scope == Scope::Transparent)
+                    && (punct(i + 1, ',') || punct(i + 1, '=') || punct(i + 1, '}')) =>
+            {
+                names.extend(name(i));
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    names
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_declared() {
+        let names = declared(
+            r#"
+            #pragma once
+            #define CORE_VERSION 3
+            namespace core {
+            /// A point.
+            struct Point { int x; int y; };
+            enum class Color { Red, Green = 2 };
+            using Id = unsigned long;
+            typedef int Count;
+            template <typename T = int> T max(T a, T b) { int local = 0; return a; }
+            extern "C" { int c_api(void); }
+            inline constexpr int LIMIT = 8;
+            }
+            "#,
+        );
+        let mut names = names.into_iter().collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "CORE_VERSION",
+                "Color",
+                "Count",
+                "Green",
+                "Id",
+                "LIMIT",
+                "Point",
+                "Red",
+                "c_api",
+                "max"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_analyze() {
+        let util = PathBuf::from("/p/include/util/util.hpp");
+        let core = PathBuf::from("/p/include/core/core.hpp");
+        let log = PathBuf::from("/p/include/log/log.hpp");
+        let declarations = BTreeMap::from([
+            (util.clone(), HashSet::from(["util_value".to_string()])),
+            (core.clone(), HashSet::from(["core_value".to_string()])),
+            (log.clone(), HashSet::from(["log".to_string()])),
+        ]);
+
+        let source = "#include <util/util.hpp>\n\
+            #include <log/log.hpp>\n\
+            int main() { return core_value() + util_value(); } // log\n";
+        let direct = [(0, util.clone()), (1, log.clone())];
+        let reached = [util.clone(), core.clone(), log.clone()];
+
+        let report = analyze(source, &direct, &reached, &declarations);
+        assert_eq!(report.unused, [(1, log)]);
+        assert_eq!(
+            report.missing,
+            BTreeMap::from([(core, BTreeSet::from(["core_value".to_string()]))])
+        );
+    }
+
+    #[test]
+    fn test_parse_dependencies() {
+        assert_eq!(
+            parse_dependencies("main.o: src/main.cpp include/a\\ b.hpp \\\n include/c.hpp\n"),
+            ["src/main.cpp", "include/a b.hpp", "include/c.hpp"]
+        );
+        assert!(is_whole_include("#include <a.hpp> // comment"));
+        assert!(!is_whole_include("#include <a.hpp> int x;"));
+    }
+}
@@ -0,0 +1,171 @@
+//! The `coppo-lint` crate is a Coppo addon that lints the current project with `clang-tidy`.
+//! It also provides the `fix` add-on, which applies the fixes suggested by `clang-tidy`,
+//! and the `includes` add-on, which reports the unused and missing includes of the project headers.
+//!
+//! The compilation database `target/compile_commands.json` is generated before linting,
+//! so `clang-tidy` sees the same include paths as `coppo build`.
+//! The checks are taken from the `[lint]` table of `Coppo.toml`:
+//!
+//! ```toml
+//! [lint]
+//! checks = ["-*", "modernize-*", "bugprone-*"]
+//! warnings-as-errors = ["bugprone-*"]
+//! ```
+//!
+//! # Usage
+//! ```sh
+//! coppo lint [-j <N>]
+//! coppo fix [-j <N>] [--allow-dirty]
+//! coppo includes [--fix] [--allow-dirty]
+//! ```
+
+#![forbid(unsafe_code)]
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use coppo_addons::prelude::*;
+use coppo_build::compdb;
+use coppo_build::parallel::{default_jobs, run_parallel};
+use coppo_config::files::FileFilter;
+use coppo_config::prelude::*;
+use coppo_logger::prelude::*;
+
+pub mod fix;
+pub mod includes;
+
+pub use fix::CoppoFixAddon;
+pub use includes::CoppoIncludesAddon;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The linter executable.
+pub const CLANG_TIDY: &str = "clang-tidy";
+
+/// The `Coppo lint` add-on.
+/// Lint all the source files of the current project in parallel,
+/// it fails if any error is found.
+pub struct CoppoLintAddon;
+
+impl_addon! {
+    CoppoLintAddon,
+    name => "lint",
+    description => "Lint the current project with clang-tidy",
+    args => [
+        arg!(-j --jobs <N> "The number of files linted in parallel, defaults to the number of CPUs")
+            .value_parser(value_parser!(u64).range(1..)),
+    ],
+    run => |config, matches| {
+        let jobs = matches
+            .get_one::<u64>("jobs")
+            .map_or_else(default_jobs, |jobs| *jobs as usize);
+        lint(config, jobs)?;
+    }
+}
+
+fn lint(config: &Config, jobs: usize) -> Result<()> {
+    if !Config::exists() {
+        return Err("The project does not have a `Coppo.toml` file.".into());
+    }
+    if config.is_empty() {
+        return Err("The project name and version is needed".into());
+    }
+
+    let (files, args) = prepare(config)?;
+    if files.is_empty() {
+        warn!("There is no source file to lint.");
+        return Ok(());
+    }
+
+    info!("Linting {} files...", files.len());
+    let outputs = run_parallel(&files, jobs, |file| tidy(&args, file));
+
+    // The diagnostics in the headers are reported once for every source including them.
+    let mut diagnostics = vec![];
+    for output in outputs {
+        for diagnostic in Diagnostic::parse_clang(&output?) {
+            if !diagnostics.contains(&diagnostic) {
+                diagnostics.push(diagnostic);
+            }
+        }
+    }
+    diagnostics.sort_by(|a, b| a.location.cmp(&b.location));
+
+    let logger = LOGGER.get_or_init(|| Logger::new(false));
+    for diagnostic in &diagnostics {
+        logger.diagnostic(diagnostic);
+    }
+
+    let count = |level| diagnostics.iter().filter(|d| d.level == level).count();
+    let (errors, warnings) = (count(Level::Error), count(Level::Warning));
+    if errors > 0 {
+        return Err(format!("Lint found {} errors and {} warnings.", errors, warnings).into());
+    }
+    if warnings > 0 {
+        warn!("Lint found {} warnings.", warnings);
+    } else {
+        success!("No lint issue found.");
+    }
+
+    Ok(())
+}
+
+/// Write the compilation database, and return the source files to lint
+/// with the arguments of `clang-tidy` shared by all of them.
+fn prepare(config: &Config) -> Result<(Vec<PathBuf>, Vec<String>)> {
+    let database = compdb::write(config)?;
+    let database_dir = database.parent().unwrap_or(Path::new("."));
+
+    let filter = FileFilter::new(&config.project)?;
+    let files = compdb::compile_commands(config)?
+        .into_iter()
+        .map(|command| command.file)
+        .filter(|file| filter.matches(file))
+        .collect();
+
+    Ok((files, args(&config.lint, database_dir)))
+}
+
+/// The arguments of `clang-tidy` shared by all the files.
+fn args(settings: &LintSettings, database_dir: &Path) -> Vec<String> {
+    let mut args = vec![
+        "--quiet".to_owned(),
+        format!("-p={}", database_dir.display()),
+    ];
+    if !settings.checks.is_empty() {
+        args.push(format!("--checks={}", settings.checks.join(",")));
+    }
+    if !settings.warnings_as_errors.is_empty() {
+        args.push(format!(
+            "--warnings-as-errors={}",
+            settings.warnings_as_errors.join(",")
+        ));
+    }
+
+    args
+}
+
+/// Lint a file, and return the output of `clang-tidy`.
+/// The error is a `String`, so it can be sent across the threads.
+fn tidy(args: &[String], file: &Path) -> std::result::Result<String, String> {
+    let output = process::Command::new(CLANG_TIDY)
+        .args(args)
+        .arg(file)
+        .output()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => format!("`{}` is not found in PATH.", CLANG_TIDY),
+            _ => format!("Failed to run `{}`: {}", CLANG_TIDY, e),
+        })?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    if !output.status.success() && Diagnostic::parse_clang(&stdout).is_empty() {
+        return Err(format!(
+            "Failed to lint `{}`: {}",
+            file.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(stdout)
+}
@@ -0,0 +1,286 @@
+//! The structured diagnostics, rendered like the diagnostics of `rustc`:
+//!
+//! ```text
+//! warning: use nullptr [modernize-use-nullptr]
+//!  --> src/main.cpp:3:14
+//!   |
+//! 3 |     int *p = NULL;
+//!   |              ^
+//! ```
+//!
+//! The diagnostics can be parsed from the output of the Clang tools,
+//! like `clang++` and `clang-tidy`.
+
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+use colored::Colorize;
+
+/// The severity of the diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// An error, it fails the command.
+    Error,
+    /// A warning.
+    Warning,
+    /// A note attached to another diagnostic.
+    Note,
+    /// A suggestion attached to another diagnostic.
+    Help,
+}
+
+impl Level {
+    /// Parse the level from the Clang output, like `warning`.
+    fn from_clang(level: &str) -> Option<Self> {
+        match level {
+            "error" | "fatal error" => Some(Level::Error),
+            "warning" => Some(Level::Warning),
+            "note" => Some(Level::Note),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Error => write!(f, "error"),
+            Level::Warning => write!(f, "warning"),
+            Level::Note => write!(f, "note"),
+            Level::Help => write!(f, "help"),
+        }
+    }
+}
+
+/// The location in a source file, the line and column start from `1`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Location {
+    /// The source file.
+    pub file: PathBuf,
+    /// The line number.
+    pub line: usize,
+    /// The column number.
+    pub column: usize,
+    /// The number of the columns to underline, at least one is underlined.
+    pub length: usize,
+}
+
+/// A diagnostic, with the notes attached to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The severity of the diagnostic.
+    pub level: Level,
+    /// The message of the diagnostic.
+    pub message: String,
+    /// The code of the diagnostic, like the name of the `clang-tidy` check.
+    pub code: Option<String>,
+    /// The location of the diagnostic.
+    pub location: Option<Location>,
+    /// The notes attached to the diagnostic.
+    pub notes: Vec<Diagnostic>,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic without location.
+    pub fn new(level: Level, message: impl Into<String>) -> Self {
+        Self {
+            level,
+            message: message.into(),
+            code: None,
+            location: None,
+            notes: vec![],
+        }
+    }
+
+    /// Parse the diagnostics from the output of the Clang tools.
+    /// The lines like `file:line:column: level: message [code]` start a diagnostic,
+    /// and the notes are attached to the diagnostic before them.
+    /// The other lines, like the source snippets, are ignored.
+    ///
+    /// # Example
+    /// ```rust
+    /// use coppo_logger::diagnostic::{Diagnostic, Level};
+    ///
+    /// let diagnostics = Diagnostic::parse_clang(
+    ///     "src/main.cpp:3:14: warning: use nullptr [modernize-use-nullptr]\n\
+    ///     \x20   int *p = NULL;\n\
+    ///     \x20            ^\n",
+    /// );
+    ///
+    /// assert_eq!(diagnostics.len(), 1);
+    /// assert_eq!(diagnostics[0].level, Level::Warning);
+    /// assert_eq!(diagnostics[0].code.as_deref(), Some("modernize-use-nullptr"));
+    /// ```
+    pub fn parse_clang(output: &str) -> Vec<Diagnostic> {
+        let mut diagnostics: Vec<Diagnostic> = vec![];
+        for diagnostic in output.lines().filter_map(parse_clang_line) {
+            match (diagnostic.level, diagnostics.last_mut()) {
+                (Level::Note, Some(last)) => last.notes.push(diagnostic),
+                _ => diagnostics.push(diagnostic),
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Render the diagnostic with the source snippet, if the source file can be read.
+    pub fn render(&self) -> String {
+        let mut rendered = self.render_one();
+        for note in &self.notes {
+            rendered += &note.render_one();
+        }
+
+        rendered
+    }
+
+    fn render_one(&self) -> String {
+        let level = match self.level {
+            Level::Error => self.level.to_string().bright_red(),
+            Level::Warning => self.level.to_string().bright_yellow(),
+            Level::Note | Level::Help => self.level.to_string().bright_green(),
+        }
+        .bold();
+        let mut rendered = match &self.code {
+            Some(code) => format!("{}: {} [{}]\n", level, self.message.bold(), code),
+            None => format!("{}: {}\n", level, self.message.bold()),
+        };
+
+        let Some(location) = &self.location else {
+            return rendered;
+        };
+        let gutter = " ".repeat(location.line.to_string().len());
+        rendered += &format!(
+            "{}{} {}:{}:{}\n",
+            gutter,
+            "-->".bright_blue(),
+            location.file.display(),
+            location.line,
+            location.column
+        );
+
+        let source = fs::read_to_string(&location.file).ok();
+        if let Some(line) = source
+            .as_deref()
+            .and_then(|source| source.lines().nth(location.line.saturating_sub(1)))
+        {
+            let bar = "|".bright_blue();
+            let marker = " ".repeat(location.column.saturating_sub(1));
+            rendered += &format!("{} {}\n", gutter, bar);
+            rendered += &format!(
+                "{} {} {}\n",
+                location.line.to_string().bright_blue(),
+                bar,
+                line
+            );
+            let underline = "^".repeat(location.length.max(1));
+            rendered += &format!("{} {} {}{}\n", gutter, bar, marker, underline.bright_blue());
+        }
+
+        rendered
+    }
+}
+
+/// Parse a line like `file:line:column: level: message [code]`.
+/// The file may contain `:`, like `C:\project\main.cpp`, so it is split from the right.
+fn parse_clang_line(line: &str) -> Option<Diagnostic> {
+    let (level, level_start) = ["fatal error", "error", "warning", "note"]
+        .iter()
+        .filter_map(|level| {
+            line.find(&format!(": {}: ", level))
+                .map(|start| (*level, start))
+        })
+        .min_by_key(|(_, start)| *start)?;
+
+    let mut location = line[..level_start].rsplitn(3, ':');
+    let column = location.next()?.parse().ok()?;
+    let line_number = location.next()?.parse().ok()?;
+    let file = PathBuf::from(location.next()?);
+
+    let message = &line[level_start + level.len() + 4..];
+    let (message, code) = match message.strip_suffix(']').and_then(|m| m.rsplit_once(" [")) {
+        Some((message, code)) if !code.contains(' ') => (message, Some(code.to_owned())),
+        _ => (message, None),
+    };
+
+    Some(Diagnostic {
+        level: Level::from_clang(level)?,
+        message: message.to_owned(),
+        code,
+        location: Some(Location {
+            file,
+            line: line_number,
+            column,
+            length: 1,
+        }),
+        notes: vec![],
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_clang() {
+        let diagnostics = Diagnostic::parse_clang(
+            "1 warning generated.\n\
+            src/main.cpp:4:5: error: use of undeclared identifier 'foo'\n\
+            \x20   foo();\n\
+            \x20   ^\n\
+            include/core/core.hpp:2:6: note: 'bar' declared here\n\
+            C:\\app\\src\\lib.cpp:10:1: warning: function is too long [readability-function-size]\n",
+        );
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].level, Level::Error);
+        assert_eq!(diagnostics[0].message, "use of undeclared identifier 'foo'");
+        assert_eq!(diagnostics[0].code, None);
+        assert_eq!(diagnostics[0].notes.len(), 1);
+        assert_eq!(
+            diagnostics[0].notes[0].location,
+            Some(Location {
+                file: PathBuf::from("include/core/core.hpp"),
+                line: 2,
+                column: 6,
+                length: 1,
+            })
+        );
+        assert_eq!(
+            diagnostics[1].location.as_ref().map(|l| &l.file),
+            Some(&PathBuf::from("C:\\app\\src\\lib.cpp"))
+        );
+        assert_eq!(
+            diagnostics[1].code.as_deref(),
+            Some("readability-function-size")
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        colored::control::set_override(false);
+
+        let diagnostic = Diagnostic {
+            code: Some("clippy".to_string()),
+            location: Some(Location {
+                file: PathBuf::from(file!())
+                    .strip_prefix("lib/coppo-logger")
+                    .unwrap()
+                    .into(),
+                line: 1,
+                column: 5,
+                length: 3,
+            }),
+            ..Diagnostic::new(Level::Warning, "a message")
+        };
+
+        assert_eq!(
+            diagnostic.render(),
+            "warning: a message [clippy]\n \
+            --> src/diagnostic.rs:1:5\n  \
+            |\n\
+            1 | //! The structured diagnostics, rendered like the diagnostics of `rustc`:\n  \
+            |     ^^^\n"
+        );
+    }
+}
@@ -8,10 +8,15 @@
 
 #![forbid(unsafe_code)]
 
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::OnceLock;
 
 use colored::Colorize;
 
+pub mod diagnostic;
+
+use diagnostic::Diagnostic;
+
 /// A simple logger for Coppo.
 /// # Example
 /// ```rust
@@ -61,6 +66,42 @@ impl Logger {
             println!("{}", message.bright_green());
         }
     }
+
+    /// Output a verbose message with the `dimmed` style,
+    /// only if the verbose messages are enabled, see [`set_verbose`].
+    pub fn verbose(&self, message: &str) {
+        if !self.quiet && is_verbose() {
+            println!("{}", message.dimmed());
+        }
+    }
+
+    /// Output a diagnostic with its source snippet.
+    /// The errors are always output, even if the logger is quiet.
+    pub fn diagnostic(&self, diagnostic: &Diagnostic) {
+        if !self.quiet || diagnostic.level == diagnostic::Level::Error {
+            eprintln!("{}", diagnostic.render());
+        }
+    }
+}
+
+/// Force the messages to be colored or not,
+/// by default they are colored if the output is a terminal and `NO_COLOR` is not set.
+pub fn set_colored(colored: bool) {
+    colored::control::set_override(colored);
+}
+
+/// Whether the verbose messages are output, see [`set_verbose`].
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable the verbose messages, like the commands run by Coppo,
+/// they are disabled by default.
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+/// Check if the verbose messages are enabled.
+pub fn is_verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
 }
 
 /// Initialize the global logger for Coppo.
@@ -116,9 +157,19 @@ macro_rules! success {
     };
 }
 
+/// Output a verbose message with the `dimmed` style, if the verbose messages are enabled.
+/// It use the global logger for Coppo.
+#[macro_export]
+macro_rules! verbose {
+    ($( $arg:expr ),*) => {
+        $crate::LOGGER.get_or_init(|| Logger::new(false)).verbose(&format!($( $arg ),*));
+    };
+}
+
 pub mod prelude {
-    pub use crate::{error, info, success, warn};
-    pub use crate::{init_logger, Logger, LOGGER};
+    pub use crate::diagnostic::{Diagnostic, Level, Location};
+    pub use crate::{error, info, success, verbose, warn};
+    pub use crate::{init_logger, is_verbose, set_colored, set_verbose, Logger, LOGGER};
 }
 
 #[cfg(test)]
@@ -131,5 +182,6 @@ mod test {
         warn!("This is a warning message");
         error!("This is an error message");
         success!("This is a success message");
+        verbose!("This is a verbose message");
     }
 }
@@ -0,0 +1,53 @@
+//! Detect the authors of the new project.
+//!
+//! The authors given by `--author` are used as they are,
+//! otherwise they are taken from the first available source:
+//! 1. The `new.authors` list in the global configuration `~/.coppo/config.toml`.
+//! 2. The `user.name` and `user.email` of git config.
+//! 3. The `COPPO_AUTHOR` environment variable.
+//! 4. The name of the OS user.
+
+use std::env;
+use std::path::Path;
+
+use coppo_config::prelude::*;
+
+use crate::vcs::git;
+
+/// Detect the authors of the new project.
+/// It returns an empty list if no author is found.
+pub fn detect(global: &GlobalConfig) -> Vec<String> {
+    if !global.new.authors.is_empty() {
+        return global.new.authors.clone();
+    }
+
+    git_author()
+        .or_else(|| non_empty_var("COPPO_AUTHOR"))
+        .or_else(|| non_empty_var("USER"))
+        .or_else(|| non_empty_var("USERNAME"))
+        .into_iter()
+        .collect()
+}
+
+/// Get the author from git config, like `Name <email>`.
+fn git_author() -> Option<String> {
+    let config = |key| {
+        git(Path::new("."), &["config", "--get", key])
+            .ok()
+            .filter(|value| !value.is_empty())
+    };
+
+    let name = config("user.name")?;
+    match config("user.email") {
+        Some(email) => Some(format!("{} <{}>", name, email)),
+        None => Some(name),
+    }
+}
+
+/// Get the value of the environment variable if it is not empty.
+fn non_empty_var(key: &str) -> Option<String> {
+    env::var(key)
+        .ok()
+        .map(|value| value.trim().to_owned())
+        .filter(|value| !value.is_empty())
+}
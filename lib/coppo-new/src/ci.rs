@@ -0,0 +1,153 @@
+//! The CI workflow generation for the new project, like `coppo new --ci github`.
+//!
+//! The pipeline runs the steps in `STEPS` on every platform of the matrix,
+//! so it stays in sync with the commands Coppo actually provides.
+
+use std::str::FromStr;
+
+use crate::scaffold::Scaffold;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The command to install Coppo in the CI.
+const INSTALL: &str = "cargo install --git https://github.com/hacbit/Coppo coppo";
+
+/// A step of the pipeline.
+#[derive(Debug, Clone, Copy)]
+pub struct Step {
+    /// The name of the step.
+    pub name: &'static str,
+    /// The Coppo command of the step.
+    pub command: &'static str,
+}
+
+/// The steps of the pipeline, in order.
+pub const STEPS: &[Step] = &[
+    Step {
+        name: "Build",
+        command: "coppo build",
+    },
+    Step {
+        name: "Test",
+        command: "coppo test",
+    },
+];
+
+/// The CI platforms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ci {
+    /// GitHub Actions, `.github/workflows/ci.yml`.
+    Github,
+    /// GitLab CI/CD, `.gitlab-ci.yml`.
+    Gitlab,
+}
+
+impl Ci {
+    /// All the names of the supported CI platforms.
+    pub const NAMES: [&'static str; 2] = ["github", "gitlab"];
+
+    /// The path of the pipeline file.
+    pub fn path(&self) -> &'static str {
+        match self {
+            Ci::Github => ".github/workflows/ci.yml",
+            Ci::Gitlab => ".gitlab-ci.yml",
+        }
+    }
+
+    /// The content of the pipeline file.
+    pub fn pipeline(&self) -> String {
+        match self {
+            Ci::Github => github(),
+            Ci::Gitlab => gitlab(),
+        }
+    }
+
+    /// Write the pipeline file into the project.
+    pub fn write(&self, scaffold: &mut Scaffold) -> Result<()> {
+        scaffold.write(self.path(), self.pipeline())?;
+
+        Ok(())
+    }
+}
+
+impl FromStr for Ci {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "github" => Ok(Ci::Github),
+            "gitlab" => Ok(Ci::Gitlab),
+            _ => Err(format!("Unknown CI platform `{}`", s)),
+        }
+    }
+}
+
+/// The GitHub Actions workflow, it runs on Linux, macOS and Windows.
+fn github() -> String {
+    let mut workflow = format!(
+        "name: CI\n\
+        \n\
+        on:\n  \
+          push:\n  \
+          pull_request:\n\
+        \n\
+        jobs:\n  \
+          check:\n    \
+            strategy:\n      \
+              fail-fast: false\n      \
+              matrix:\n        \
+                os: [ubuntu-latest, macos-latest, windows-latest]\n    \
+            runs-on: ${{{{ matrix.os }}}}\n    \
+            steps:\n      \
+              - uses: actions/checkout@v4\n      \
+              - name: Install Coppo\n        \
+                run: {}\n",
+        INSTALL
+    );
+
+    for step in STEPS {
+        workflow += &format!(
+            "      - name: {}\n        run: {}\n",
+            step.name, step.command
+        );
+    }
+
+    workflow
+}
+
+/// The GitLab CI/CD pipeline, it runs on the Linux and Windows shared runners.
+fn gitlab() -> String {
+    let mut pipeline = format!(
+        "stages:\n  \
+          - check\n\
+        \n\
+        .linux:\n  \
+          image: rust:latest\n  \
+          before_script:\n    \
+            - apt-get update && apt-get install -y clang\n    \
+            - {install}\n\
+        \n\
+        .windows:\n  \
+          tags: [saas-windows-medium-amd64]\n  \
+          before_script:\n    \
+            - choco install -y rust-ms llvm\n    \
+            - {install}\n",
+        install = INSTALL
+    );
+
+    for platform in ["linux", "windows"] {
+        pipeline += &format!(
+            "\n\
+            check-{platform}:\n  \
+              extends: .{platform}\n  \
+              stage: check\n  \
+              script:\n",
+            platform = platform
+        );
+        for step in STEPS {
+            pipeline += &format!("    - {}\n", step.command);
+        }
+    }
+
+    pipeline
+}
@@ -0,0 +1,91 @@
+//! The `CMakeLists.txt` generation for the new project,
+//! so the project can still be consumed by CMake during the migration.
+
+use coppo_config::prelude::*;
+
+use crate::scaffold::Scaffold;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The name of the CMake project file.
+pub const CMAKE_LISTS: &str = "CMakeLists.txt";
+
+/// Generate the content of `CMakeLists.txt`, it mirrors the target layout of Coppo:
+/// - The `[lib]` target is a static library with the public `include` directory.
+/// - The `[[bin]]` targets are executables linked with the library,
+///   or `src/main.cpp` is the executable named after the project if there is no target.
+/// - A C project, see [`Language`], is a CMake project of C with the `.c` sources.
+///
+/// All the executables are placed in the `target` directory like `coppo build`.
+pub fn cmake_lists(config: &Config) -> String {
+    let name = &config.project.name;
+    // CMake only accepts the numeric version like `x.y.z`.
+    let version = &config.project.version;
+    let version = format!("{}.{}.{}", version.major, version.minor, version.patch);
+    let language = config.project.language;
+    let extension = language.source_extension();
+    let (languages, extensions) = match language {
+        Language::Cpp => ("CXX", "cpp|cc|cxx"),
+        Language::C => ("C", "c"),
+    };
+
+    let mut cmake = format!(
+        "# This file is generated by `coppo new --cmake-compat`.\n\
+        # `{config}` is the source of truth of the project, keep this file in sync with it.\n\
+        cmake_minimum_required(VERSION 3.14)\n\
+        \n\
+        project({name} VERSION {version} LANGUAGES {languages})\n\
+        \n\
+        # Place the executables in the same directory as `coppo build`.\n\
+        set(CMAKE_RUNTIME_OUTPUT_DIRECTORY ${{CMAKE_CURRENT_SOURCE_DIR}}/target)\n",
+        config = CONFIG_FILE,
+        name = name,
+        version = version,
+        languages = languages,
+    );
+
+    if let Some(lib) = &config.lib {
+        cmake += &format!(
+            "\n\
+            file(GLOB_RECURSE {name}_SOURCES CONFIGURE_DEPENDS {path}/*.{extension} {path})\n\
+            list(FILTER {name}_SOURCES INCLUDE REGEX \"\\\\.({extensions})$\")\n\
+            add_library({name} STATIC ${{{name}_SOURCES}})\n\
+            target_include_directories({name} PUBLIC {include})\n",
+            name = lib.name,
+            path = lib.path().display(),
+            include = lib.include().display(),
+            extension = extension,
+            extensions = extensions,
+        );
+    }
+
+    let bins = if config.bin.is_empty() && config.lib.is_none() {
+        vec![BinTarget {
+            name: name.to_owned(),
+            path: Some(format!("src/main.{}", extension).into()),
+        }]
+    } else {
+        let mut bins = config.bin.clone();
+        for bin in &mut bins {
+            bin.path
+                .get_or_insert_with(|| format!("src/bin/{}.{}", bin.name, extension).into());
+        }
+        bins
+    };
+
+    for bin in bins {
+        cmake += &format!("\nadd_executable({} {})\n", bin.name, bin.path().display());
+        if let Some(lib) = &config.lib {
+            cmake += &format!("target_link_libraries({} PRIVATE {})\n", bin.name, lib.name);
+        }
+    }
+
+    cmake
+}
+
+/// Write `CMakeLists.txt` into the project.
+pub fn write(scaffold: &mut Scaffold, config: &Config) -> Result<()> {
+    scaffold.write(CMAKE_LISTS, cmake_lists(config))?;
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+//! The `Dockerfile` and dev container generation for the new project,
+//! so new contributors get a reproducible environment with the compiler and Coppo preinstalled.
+
+use serde::Serialize;
+
+use crate::scaffold::Scaffold;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The base image, it provides `cargo` to install Coppo.
+const BASE_IMAGE: &str = "rust:1-bookworm";
+
+/// The `.devcontainer/devcontainer.json`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DevContainer<'a> {
+    name: &'a str,
+    build: DevContainerBuild,
+    customizations: Customizations,
+    post_create_command: &'static str,
+}
+
+#[derive(Serialize)]
+struct DevContainerBuild {
+    dockerfile: &'static str,
+}
+
+#[derive(Serialize)]
+struct Customizations {
+    vscode: VsCode,
+}
+
+#[derive(Serialize)]
+struct VsCode {
+    extensions: Vec<&'static str>,
+}
+
+/// The Debian package which provides the compiler.
+fn compiler_package(compiler: &str) -> &str {
+    match compiler {
+        "clang++" | "clang" => "clang",
+        "g++" | "gcc" => "g++",
+        compiler => compiler,
+    }
+}
+
+/// The instructions to install the compiler toolchain and Coppo.
+fn toolchain(compiler: &str) -> String {
+    format!(
+        "FROM {image}\n\
+        \n\
+        # The compiler toolchain used by `coppo build`.\n\
+        RUN apt-get update \\\n    \
+            && apt-get install -y --no-install-recommends {package} \\\n    \
+            && rm -rf /var/lib/apt/lists/*\n\
+        \n\
+        RUN cargo install --git https://github.com/hacbit/Coppo coppo\n",
+        image = BASE_IMAGE,
+        package = compiler_package(compiler),
+    )
+}
+
+/// The `Dockerfile` which builds the project in the container.
+pub fn dockerfile(compiler: &str) -> String {
+    format!(
+        "{}\n\
+        WORKDIR /workspace\n\
+        COPY . .\n\
+        RUN coppo build\n\
+        \n\
+        CMD [\"coppo\", \"run\"]\n",
+        toolchain(compiler)
+    )
+}
+
+/// The `Dockerfile` of the dev container, the project will be mounted into it.
+pub fn devcontainer_dockerfile(compiler: &str) -> String {
+    toolchain(compiler)
+}
+
+/// The `devcontainer.json` of the dev container.
+pub fn devcontainer_json(name: &str) -> Result<String> {
+    let config = DevContainer {
+        name,
+        build: DevContainerBuild {
+            dockerfile: "Dockerfile",
+        },
+        customizations: Customizations {
+            vscode: VsCode {
+                extensions: vec!["llvm-vs-code-extensions.vscode-clangd"],
+            },
+        },
+        post_create_command: "coppo build",
+    };
+
+    Ok(serde_json::to_string_pretty(&config)? + "\n")
+}
+
+/// Write the `Dockerfile` into the project.
+pub fn write_docker(scaffold: &mut Scaffold, compiler: &str) -> Result<()> {
+    scaffold.write("Dockerfile", dockerfile(compiler))?;
+    scaffold.write(".dockerignore", "/target\n/.git\n")?;
+
+    Ok(())
+}
+
+/// Write the `.devcontainer` directory into the project.
+pub fn write_devcontainer(scaffold: &mut Scaffold, name: &str, compiler: &str) -> Result<()> {
+    scaffold.write(
+        ".devcontainer/Dockerfile",
+        devcontainer_dockerfile(compiler),
+    )?;
+    scaffold.write(".devcontainer/devcontainer.json", devcontainer_json(name)?)?;
+
+    Ok(())
+}
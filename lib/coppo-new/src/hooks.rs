@@ -0,0 +1,120 @@
+//! The post-generation hooks of the templates.
+//!
+//! A template can declare the commands to run in the new project directory:
+//!
+//! ```toml
+//! [template]
+//! hooks = ["git submodule add https://github.com/fmtlib/fmt extern/fmt", "chmod +x scripts/*"]
+//! ```
+//!
+//! The commands run in the shell after the project is created,
+//! they need a confirmation, or `--allow-hooks` for non-interactive use.
+//!
+//! The variables of the template, like `{{author}}`, are not pasted into the commands,
+//! they can contain quotes or `$`. They are passed as environment variables instead,
+//! like `COPPO_TEMPLATE_AUTHOR`, and `{{author}}` becomes a quoted reference to it,
+//! `"${COPPO_TEMPLATE_AUTHOR}"`, or `%COPPO_TEMPLATE_AUTHOR%` on Windows.
+//!
+//! The files created by the hooks are removed if the creation fails, like the other files.
+
+use std::io::{self, IsTerminal};
+use std::process;
+
+use coppo_logger::prelude::*;
+
+use crate::scaffold::Scaffold;
+use crate::template::{self, Variables};
+use crate::wizard::{yes_or_no, Wizard};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The prefix of the environment variables of the template variables.
+pub const ENV_PREFIX: &str = "COPPO_TEMPLATE_";
+
+/// The environment variable of the template variable, like `COPPO_TEMPLATE_NAME` for `name`.
+pub fn env_name(variable: &str) -> String {
+    format!("{}{}", ENV_PREFIX, variable.to_ascii_uppercase())
+}
+
+/// Replace the variables in the hook with the quoted references to their environment variables.
+///
+/// # Example
+/// ```rust
+/// use coppo_new::hooks;
+/// use coppo_new::template::Variables;
+///
+/// let vars = Variables::from([("name", "$(rm -rf ~)".to_owned())]);
+/// let hook = hooks::render("echo {{name}}", &vars);
+/// assert!(!hook.contains("rm -rf"));
+/// ```
+pub fn render(hook: &str, vars: &Variables) -> String {
+    let references = vars
+        .keys()
+        .map(|key| {
+            let reference = match cfg!(windows) {
+                true => format!("%{}%", env_name(key)),
+                false => format!("\"${{{}}}\"", env_name(key)),
+            };
+            (*key, reference)
+        })
+        .collect::<Variables>();
+
+    template::render(hook, &references)
+}
+
+/// Run the hooks in the project directory, with the template variables in the environment.
+/// If `allow` is `false`, the user will be asked for confirmation,
+/// and the hooks will be skipped if the input is not a terminal.
+/// The paths created by the hooks are tracked by the scaffold, so they are removed on rollback.
+pub fn run(scaffold: &mut Scaffold, hooks: &[String], vars: &Variables, allow: bool) -> Result<()> {
+    if hooks.is_empty() {
+        return Ok(());
+    }
+
+    if !allow {
+        if !io::stdin().is_terminal() {
+            warn!("Skipped the template hooks, use `--allow-hooks` to run them non-interactively.");
+            return Ok(());
+        }
+
+        info!("The template wants to run the following commands:");
+        for hook in hooks {
+            info!("    {}", hook);
+        }
+        if !Wizard::stdio().ask("Run them? (y/n)", "n", yes_or_no)? {
+            warn!("Skipped the template hooks.");
+            return Ok(());
+        }
+    }
+
+    let before = scaffold.paths()?;
+    let result = (|| -> Result<()> {
+        for hook in hooks {
+            info!("Running the hook `{}`", hook);
+            let status = shell(hook)
+                .current_dir(scaffold.root())
+                .envs(vars.iter().map(|(key, value)| (env_name(key), value)))
+                .status()?;
+            if !status.success() {
+                return Err(format!("The hook `{}` failed with {}", hook, status).into());
+            }
+        }
+        Ok(())
+    })();
+    scaffold.track_since(&before)?;
+
+    result
+}
+
+/// Create the shell command to run the hook.
+fn shell(command: &str) -> process::Command {
+    if cfg!(windows) {
+        let mut shell = process::Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = process::Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    }
+}
@@ -0,0 +1,144 @@
+//! The `.gitignore` composition for the new project.
+//!
+//! The `.gitignore` is composed of:
+//! - The base ignore of Coppo, like `/target`.
+//! - The `.gitignore` fragment shipped by the template.
+//! - The IDE-specific ignores specified by `--ignore clion,vscode`.
+//!
+//! The duplicated patterns will only appear once.
+
+use std::str::FromStr;
+
+/// The base ignore of Coppo.
+pub const BASE: &str = "/target\n";
+
+/// The IDEs which have preset ignores.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ide {
+    /// `clion`, JetBrains CLion.
+    Clion,
+    /// `vscode`, Visual Studio Code.
+    Vscode,
+    /// `vs`, Visual Studio.
+    VisualStudio,
+    /// `xcode`, Apple Xcode.
+    Xcode,
+    /// `vim`, the swap and session files of Vim.
+    Vim,
+    /// `emacs`, the backup and lock files of Emacs.
+    Emacs,
+}
+
+impl Ide {
+    /// All the names of the supported IDEs.
+    pub const NAMES: [&'static str; 6] = ["clion", "vscode", "vs", "xcode", "vim", "emacs"];
+
+    /// The display name of the IDE.
+    fn title(&self) -> &'static str {
+        match self {
+            Ide::Clion => "CLion",
+            Ide::Vscode => "Visual Studio Code",
+            Ide::VisualStudio => "Visual Studio",
+            Ide::Xcode => "Xcode",
+            Ide::Vim => "Vim",
+            Ide::Emacs => "Emacs",
+        }
+    }
+
+    /// The ignore patterns of the IDE.
+    fn patterns(&self) -> &'static [&'static str] {
+        match self {
+            Ide::Clion => &[".idea/", "cmake-build-*/"],
+            Ide::Vscode => &[".vscode/*", "!.vscode/tasks.json", "!.vscode/launch.json"],
+            Ide::VisualStudio => &[".vs/", "*.vcxproj.user", "*.suo"],
+            Ide::Xcode => &["xcuserdata/", "*.xcworkspace/", "DerivedData/"],
+            Ide::Vim => &["*.swp", "*.swo", "Session.vim"],
+            Ide::Emacs => &["*~", "\\#*\\#", ".#*"],
+        }
+    }
+}
+
+impl FromStr for Ide {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "clion" => Ok(Ide::Clion),
+            "vscode" => Ok(Ide::Vscode),
+            "vs" => Ok(Ide::VisualStudio),
+            "xcode" => Ok(Ide::Xcode),
+            "vim" => Ok(Ide::Vim),
+            "emacs" => Ok(Ide::Emacs),
+            _ => Err(format!("Unknown IDE `{}`", s)),
+        }
+    }
+}
+
+/// Compose the `.gitignore` from the base ignore,
+/// the template fragment and the IDE-specific ignores.
+pub fn compose(fragment: Option<&str>, ides: &[Ide]) -> String {
+    let mut gitignore = Gitignore::default();
+
+    gitignore.section(None, BASE.lines());
+    if let Some(fragment) = fragment {
+        gitignore.section(Some("From the template"), fragment.lines());
+    }
+    for ide in ides {
+        gitignore.section(Some(ide.title()), ide.patterns().iter().copied());
+    }
+
+    gitignore.content
+}
+
+/// The `.gitignore` being composed.
+#[derive(Default)]
+struct Gitignore {
+    content: String,
+    patterns: Vec<String>,
+}
+
+impl Gitignore {
+    /// Append a section of patterns,
+    /// the patterns which already exist will be skipped.
+    fn section<'a>(&mut self, title: Option<&str>, lines: impl Iterator<Item = &'a str>) {
+        let mut section = String::new();
+        for line in lines {
+            let pattern = line.trim();
+            if pattern.is_empty() || pattern.starts_with('#') {
+                continue;
+            }
+            if self.patterns.iter().any(|p| p == pattern) {
+                continue;
+            }
+
+            self.patterns.push(pattern.to_owned());
+            section += pattern;
+            section += "\n";
+        }
+
+        if section.is_empty() {
+            return;
+        }
+        if !self.content.is_empty() {
+            self.content += "\n";
+        }
+        if let Some(title) = title {
+            self.content += &format!("# {}\n", title);
+        }
+        self.content += &section;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_compose() {
+        assert_eq!(compose(None, &[]), "/target\n");
+        assert_eq!(
+            compose(Some("# build\n/target\n/build\n"), &[Ide::Clion]),
+            "/target\n\n# From the template\n/build\n\n# CLion\n.idea/\ncmake-build-*/\n"
+        );
+    }
+}
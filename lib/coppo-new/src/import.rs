@@ -0,0 +1,550 @@
+//! Import an existing CMake project, like `coppo init --from-cmake`.
+//!
+//! Only the simple `CMakeLists.txt` can be translated:
+//! - `project()` is the `[project]` table.
+//! - The first `add_library()` is the `[lib]` target,
+//!   and its public directory of `target_include_directories()` is the include directory.
+//! - `add_executable()` is a `[[bin]]` target, its first source is the source with `main`.
+//! - `find_package()` is a dependency.
+//!
+//! The variables of `set()` and `file(GLOB)` are expanded,
+//! and the other constructs are reported as warnings.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use coppo_config::prelude::*;
+
+/// The result of the import.
+#[derive(Debug, Default)]
+pub struct Import {
+    /// The translated configuration.
+    pub config: Config,
+    /// The constructs which can not be translated.
+    pub warnings: Vec<String>,
+}
+
+/// A command invocation of CMake, like `add_executable(app main.cpp)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Command {
+    /// The name of the command in lowercase, the command names are case-insensitive.
+    pub name: String,
+    /// The arguments, and whether they are quoted.
+    pub args: Vec<(String, bool)>,
+    /// The line of the command.
+    pub line: usize,
+}
+
+/// Parse the commands of the CMake script.
+pub fn commands(source: &str) -> Result<Vec<Command>, String> {
+    let chars = source.chars().collect::<Vec<_>>();
+    let mut commands = vec![];
+    let mut line = 1;
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\n' => {
+                line += 1;
+                i += 1;
+            }
+            '#' => i = skip_comment(&chars, i, &mut line),
+            c if c.is_whitespace() => i += 1,
+            c if c.is_ascii_alphanumeric() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                let name = chars[start..i].iter().collect::<String>();
+                while i < chars.len() && matches!(chars[i], ' ' | '\t') {
+                    i += 1;
+                }
+                if chars.get(i) != Some(&'(') {
+                    return Err(format!("Expected `(` after `{}` at line {}", name, line));
+                }
+
+                let command_line = line;
+                let (args, end) = arguments(&chars, i + 1, &mut line)
+                    .ok_or_else(|| format!("Unclosed `{}(` at line {}", name, command_line))?;
+                commands.push(Command {
+                    name: name.to_ascii_lowercase(),
+                    args,
+                    line: command_line,
+                });
+                i = end;
+            }
+            c => return Err(format!("Unexpected `{}` at line {}", c, line)),
+        }
+    }
+
+    Ok(commands)
+}
+
+/// Skip the comment starting at `#`, and return the index after it.
+fn skip_comment(chars: &[char], mut i: usize, line: &mut usize) -> usize {
+    // A bracket comment like `#[[ ... ]]`.
+    if chars[i + 1..].starts_with(&['[', '[']) {
+        while i < chars.len() && !chars[i..].starts_with(&[']', ']']) {
+            if chars[i] == '\n' {
+                *line += 1;
+            }
+            i += 1;
+        }
+        return (i + 2).min(chars.len());
+    }
+
+    while i < chars.len() && chars[i] != '\n' {
+        i += 1;
+    }
+    i
+}
+
+/// Parse the arguments after `(`, and return them with the index after the closing `)`.
+fn arguments(
+    chars: &[char],
+    mut i: usize,
+    line: &mut usize,
+) -> Option<(Vec<(String, bool)>, usize)> {
+    let mut args = vec![];
+    let mut depth = 0;
+
+    loop {
+        match *chars.get(i)? {
+            '\n' => {
+                *line += 1;
+                i += 1;
+            }
+            '#' => i = skip_comment(chars, i, line),
+            c if c.is_whitespace() => i += 1,
+            // The nested parentheses of the conditions are not arguments.
+            '(' => {
+                depth += 1;
+                i += 1;
+            }
+            ')' if depth == 0 => return Some((args, i + 1)),
+            ')' => {
+                depth -= 1;
+                i += 1;
+            }
+            '"' => {
+                let mut arg = String::new();
+                i += 1;
+                loop {
+                    match *chars.get(i)? {
+                        '"' => break,
+                        '\\' => {
+                            i += 1;
+                            arg.push(*chars.get(i)?);
+                        }
+                        c => {
+                            if c == '\n' {
+                                *line += 1;
+                            }
+                            arg.push(c);
+                        }
+                    }
+                    i += 1;
+                }
+                args.push((arg, true));
+                i += 1;
+            }
+            _ => {
+                let start = i;
+                while i < chars.len() && !chars[i].is_whitespace() && !"()\"#".contains(chars[i]) {
+                    i += 1;
+                }
+                args.push((chars[start..i].iter().collect(), false));
+            }
+        }
+    }
+}
+
+/// The directories which are the root of the project in CMake.
+const ROOT_VARIABLES: [&str; 4] = [
+    "CMAKE_CURRENT_SOURCE_DIR",
+    "CMAKE_SOURCE_DIR",
+    "PROJECT_SOURCE_DIR",
+    "CMAKE_CURRENT_LIST_DIR",
+];
+
+/// The commands which do not affect the translation.
+const IGNORED_COMMANDS: [&str; 4] = [
+    "cmake_minimum_required",
+    "target_link_libraries",
+    "enable_testing",
+    "message",
+];
+
+/// The keywords of `add_library` and `add_executable` which are not sources.
+const TARGET_KEYWORDS: [&str; 8] = [
+    "STATIC",
+    "SHARED",
+    "MODULE",
+    "OBJECT",
+    "INTERFACE",
+    "WIN32",
+    "MACOSX_BUNDLE",
+    "EXCLUDE_FROM_ALL",
+];
+
+/// The translator of the commands.
+#[derive(Default)]
+struct Importer {
+    variables: HashMap<String, Vec<String>>,
+    import: Import,
+}
+
+impl Importer {
+    /// Expand the variables of the arguments, the unquoted lists are split.
+    fn expand(&self, args: &[(String, bool)]) -> Vec<String> {
+        let mut expanded = vec![];
+        for (arg, quoted) in args {
+            let mut value = arg.clone();
+            // Expand the innermost variables first, like `${${NAME}_SOURCES}`.
+            while let Some(start) = value.rfind("${") {
+                let Some(end) = value[start..].find('}').map(|end| start + end) else {
+                    break;
+                };
+                let name = &value[start + 2..end];
+                let replacement = match ROOT_VARIABLES.contains(&name) {
+                    true => ".".to_owned(),
+                    false => self
+                        .variables
+                        .get(name)
+                        .map(|values| values.join(";"))
+                        .unwrap_or_default(),
+                };
+                value.replace_range(start..=end, &replacement);
+            }
+
+            match quoted {
+                true => expanded.push(value),
+                false => expanded.extend(
+                    value
+                        .split(';')
+                        .filter(|value| !value.is_empty())
+                        .map(str::to_owned),
+                ),
+            }
+        }
+
+        expanded
+    }
+
+    fn warn(&mut self, command: &Command, message: impl AsRef<str>) {
+        self.import
+            .warnings
+            .push(format!("line {}: {}", command.line, message.as_ref()));
+    }
+
+    fn run(&mut self, command: &Command) {
+        let args = self.expand(&command.args);
+        match (command.name.as_str(), args.as_slice()) {
+            ("project", [name, rest @ ..]) => {
+                self.variables
+                    .insert("PROJECT_NAME".to_owned(), vec![name.clone()]);
+                self.import.config.project.name = name.clone();
+                self.import.config.project.version = rest
+                    .iter()
+                    .position(|arg| arg == "VERSION")
+                    .and_then(|i| rest.get(i + 1))
+                    .map(|version| semantic_version(version))
+                    .unwrap_or_else(|| Version::new(0, 1, 0));
+                if let Some(i) = rest.iter().position(|arg| arg == "DESCRIPTION") {
+                    self.import.config.project.description = rest.get(i + 1).cloned();
+                }
+            }
+            ("set", [name, values @ ..]) => {
+                let values = values
+                    .iter()
+                    .take_while(|value| *value != "CACHE" && *value != "PARENT_SCOPE")
+                    .cloned()
+                    .collect();
+                self.variables.insert(name.clone(), values);
+            }
+            ("file", [mode, name, patterns @ ..]) if mode == "GLOB" || mode == "GLOB_RECURSE" => {
+                let patterns = patterns
+                    .iter()
+                    .filter(|pattern| *pattern != "CONFIGURE_DEPENDS")
+                    .map(|pattern| normalize(pattern))
+                    .collect();
+                self.variables.insert(name.clone(), patterns);
+            }
+            ("add_library", [name, rest @ ..]) => self.add_library(command, name, rest),
+            ("add_executable", [name, rest @ ..]) => self.add_executable(command, name, rest),
+            ("target_include_directories", [target, rest @ ..]) => {
+                self.include_directories(command, Some(target), rest)
+            }
+            ("include_directories", dirs) => self.include_directories(command, None, dirs),
+            ("find_package", [name, rest @ ..]) => {
+                let version = rest
+                    .first()
+                    .filter(|version| version.starts_with(|c: char| c.is_ascii_digit()))
+                    .and_then(|version| VersionReq::parse(&format!("^{}", version)).ok())
+                    .unwrap_or(VersionReq::STAR);
+                let key = name.to_ascii_lowercase();
+                self.import
+                    .config
+                    .dependencies
+                    .insert(key, Dependency::Simple(version));
+            }
+            ("if" | "elseif" | "else" | "endif", _) => self.warn(
+                command,
+                format!(
+                    "`{}()` is ignored, all the branches are imported",
+                    command.name
+                ),
+            ),
+            (name, _) if IGNORED_COMMANDS.contains(&name) => {}
+            (name, _) => self.warn(command, format!("`{}()` can not be translated", name)),
+        }
+    }
+
+    fn add_library(&mut self, command: &Command, name: &str, args: &[String]) {
+        if self.import.config.lib.is_some() {
+            self.warn(
+                command,
+                format!(
+                    "Only one library is supported, the library `{}` is skipped",
+                    name
+                ),
+            );
+            return;
+        }
+        if args.iter().any(|arg| arg == "SHARED") {
+            self.warn(
+                command,
+                format!("The library `{}` will be built as a static library", name),
+            );
+        }
+
+        let sources = sources(args);
+        let path = match sources.as_slice() {
+            [] => {
+                self.warn(
+                    command,
+                    format!("The library `{}` does not have a source", name),
+                );
+                None
+            }
+            [source] if !is_glob(source) => Some(source.clone()),
+            sources => common_dir(sources),
+        };
+        // The sources are in `src/<name>` by default.
+        let path = path.filter(|path| *path != Path::new("src").join(name));
+
+        self.import.config.lib = Some(LibTarget {
+            name: name.to_owned(),
+            path,
+            ..Default::default()
+        });
+    }
+
+    fn add_executable(&mut self, command: &Command, name: &str, args: &[String]) {
+        let sources = sources(args);
+        let Some(main) = sources.first() else {
+            self.warn(
+                command,
+                format!("The executable `{}` does not have a source", name),
+            );
+            return;
+        };
+        if is_glob(main) {
+            self.warn(
+                command,
+                format!(
+                    "The sources of `{}` are a glob, the source with `main` is unknown",
+                    name
+                ),
+            );
+            return;
+        }
+        if sources.len() > 1 {
+            self.warn(
+                command,
+                format!(
+                    "Only `{}` is the source of `{}`, the other sources should be moved into the library",
+                    main.display(),
+                    name
+                ),
+            );
+        }
+
+        self.import.config.bin.push(BinTarget {
+            name: name.to_owned(),
+            path: Some(main.clone()),
+        });
+    }
+
+    fn include_directories(&mut self, command: &Command, target: Option<&String>, args: &[String]) {
+        let dirs = args
+            .iter()
+            .filter(|arg| {
+                !matches!(
+                    arg.as_str(),
+                    "SYSTEM" | "BEFORE" | "AFTER" | "PUBLIC" | "PRIVATE" | "INTERFACE"
+                )
+            })
+            .map(|dir| normalize(dir))
+            .collect::<Vec<_>>();
+
+        let Some(lib) = self
+            .import
+            .config
+            .lib
+            .as_mut()
+            .filter(|lib| target.is_none_or(|target| *target == lib.name))
+        else {
+            self.warn(
+                command,
+                "Only the include directory of the library can be translated",
+            );
+            return;
+        };
+
+        if let Some((include, rest)) = dirs.split_first() {
+            lib.include = Some(PathBuf::from(include)).filter(|dir| dir != Path::new("include"));
+            if !rest.is_empty() {
+                let rest = rest.join(", ");
+                self.warn(
+                    command,
+                    format!(
+                        "Only one include directory is supported, `{}` are skipped",
+                        rest
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Import the CMake project from the content of `CMakeLists.txt`.
+pub fn from_cmake(source: &str) -> Result<Import, String> {
+    let mut importer = Importer::default();
+    for command in commands(source)? {
+        importer.run(&command);
+    }
+
+    let import = importer.import;
+    if import.config.project.name.is_empty() {
+        return Err("The `project()` command is not found".to_owned());
+    }
+    Ok(import)
+}
+
+/// Pad the numeric version of CMake to a semantic version, like `1.2` to `1.2.0`.
+fn semantic_version(version: &str) -> Version {
+    let mut parts = version
+        .split('.')
+        .map(|part| part.parse().unwrap_or_default());
+    let mut next = || parts.next().unwrap_or_default();
+
+    Version::new(next(), next(), next())
+}
+
+/// Remove the `./` prefix which comes from the root variables.
+fn normalize(path: &str) -> String {
+    path.trim_start_matches("./").to_owned()
+}
+
+/// The sources of a target, without the keywords.
+fn sources(args: &[String]) -> Vec<PathBuf> {
+    args.iter()
+        .filter(|arg| !TARGET_KEYWORDS.contains(&arg.as_str()))
+        .map(|arg| PathBuf::from(normalize(arg)))
+        .collect()
+}
+
+fn is_glob(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?', '['])
+}
+
+/// The deepest directory containing all the sources, the globs are cut at the first wildcard.
+fn common_dir(sources: &[PathBuf]) -> Option<PathBuf> {
+    let dirs = sources.iter().map(|source| {
+        let mut dir = PathBuf::new();
+        for component in source.parent().unwrap_or(Path::new("")).components() {
+            if is_glob(Path::new(component.as_os_str())) {
+                break;
+            }
+            dir.push(component);
+        }
+        dir
+    });
+
+    dirs.reduce(|common, dir| {
+        common
+            .components()
+            .zip(dir.components())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a)
+            .collect()
+    })
+    .filter(|dir| !dir.as_os_str().is_empty())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_cmake() {
+        let import = from_cmake(
+            r#"
+            cmake_minimum_required(VERSION 3.14)
+            project(app VERSION 1.2 LANGUAGES CXX)
+
+            # The library.
+            file(GLOB_RECURSE CORE_SOURCES CONFIGURE_DEPENDS ${CMAKE_CURRENT_SOURCE_DIR}/lib/core/*.cpp)
+            add_library(core STATIC ${CORE_SOURCES})
+            target_include_directories(core PUBLIC inc PRIVATE lib/core)
+
+            set(SERVER_SOURCES src/server.cpp src/util.cpp)
+            add_executable(server ${SERVER_SOURCES})
+            ADD_EXECUTABLE(client "src/client.cpp")
+            target_link_libraries(server PRIVATE core fmt::fmt)
+
+            find_package(fmt 10.1 REQUIRED)
+            find_package(Threads REQUIRED)
+            install(TARGETS server)
+            "#,
+        )
+        .unwrap();
+
+        let config = import.config;
+        assert_eq!(config.project.name, "app");
+        assert_eq!(config.project.version, Version::new(1, 2, 0));
+
+        let lib = config.lib.unwrap();
+        assert_eq!(lib.name, "core");
+        assert_eq!(lib.path, Some(PathBuf::from("lib/core")));
+        assert_eq!(lib.include, Some(PathBuf::from("inc")));
+
+        let bins = config
+            .bin
+            .iter()
+            .map(|bin| (bin.name.as_str(), bin.path.clone().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            bins,
+            vec![
+                ("server", PathBuf::from("src/server.cpp")),
+                ("client", PathBuf::from("src/client.cpp")),
+            ]
+        );
+
+        assert_eq!(config.dependencies["fmt"].version().to_string(), "^10.1");
+        assert_eq!(config.dependencies["threads"].version(), VersionReq::STAR);
+
+        assert_eq!(import.warnings.len(), 3);
+        assert!(import.warnings[0].starts_with("line 8: Only one include directory"));
+        assert!(import.warnings[1].starts_with("line 11: Only `src/server.cpp`"));
+        assert_eq!(
+            import.warnings[2],
+            "line 17: `install()` can not be translated"
+        );
+
+        assert!(from_cmake("add_executable(app main.cpp)").is_err());
+        assert!(from_cmake("project(app").is_err());
+    }
+}
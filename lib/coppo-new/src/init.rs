@@ -0,0 +1,124 @@
+//! The `Coppo init` add-on.
+//! This add-on is used to create a new project in the current directory,
+//! or to import an existing CMake project.
+//!
+//! Usage:
+//! ```sh
+//! coppo init [--name <NAME>] [--lang <LANG>] [--author <AUTHOR>]... [--from-cmake [FILE]]
+//! ```
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use coppo_addons::prelude::*;
+use coppo_config::name;
+use coppo_config::prelude::*;
+use coppo_logger::prelude::*;
+
+use crate::{author, cmake, import, CoppoNew};
+
+/// The `Coppo init` add-on.
+/// Create a new project in the current directory, like `coppo new . --force`.
+/// With `--from-cmake`, only the `Coppo.toml` is created, it is translated from `CMakeLists.txt`.
+pub struct CoppoInitAddon;
+
+impl_addon! {
+    CoppoInitAddon,
+    name => "init",
+    description => "Create a new project in the current directory",
+    project => false,
+    args => [
+        arg!(-n --name <NAME> "The name of the project, defaults to the name of the directory")
+            .value_parser(value_parser!(String)),
+        arg!(--lang <LANG> "The language of the project, `c++` or `c`")
+            .value_parser(Language::NAMES)
+            .default_value("c++"),
+        arg!(--author <AUTHOR> "The author of the project, like `Name <email>`, it can be specified multiple times")
+            .action(ArgAction::Append)
+            .value_parser(value_parser!(String)),
+        arg!(--vcs <VCS> "The version control system to initialize, defaults to `new.vcs` of the global configuration or `git`")
+            .value_parser(crate::Vcs::NAMES),
+        arg!(--"from-cmake" [FILE] "Import the targets and dependencies from `CMakeLists.txt`")
+            .value_parser(value_parser!(PathBuf))
+            .default_missing_value(cmake::CMAKE_LISTS),
+    ],
+    run => |config, matches| {
+        if Config::exists() {
+            return Err(format!("`{}` already exists in the current directory.", CONFIG_FILE).into());
+        }
+        let name = matches.get_one::<String>("name").cloned();
+        let authors = matches
+            .get_many::<String>("author")
+            .unwrap_or_default()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        match matches.get_one::<PathBuf>("from-cmake") {
+            Some(file) => from_cmake(file, name, authors, &config.global)?,
+            None => {
+                let new = CoppoNew {
+                    path: PathBuf::from("."),
+                    name: match name {
+                        Some(name) => name,
+                        None => CoppoNew::default_name(&env::current_dir()?)?,
+                    },
+                    language: match matches.get_one::<String>("lang") {
+                        Some(language) => language.parse()?,
+                        None => Language::default(),
+                    },
+                    authors,
+                    vcs: match matches.get_one::<String>("vcs") {
+                        Some(vcs) => vcs.parse()?,
+                        None => crate::Vcs::configured(&config.global)?,
+                    },
+                    force: true,
+                    ..Default::default()
+                };
+                new.create(config)?;
+                success!("Created a new project in {}", env::current_dir()?.display());
+            }
+        }
+    }
+}
+
+/// Create `Coppo.toml` from the CMake project file.
+/// The authors are detected unless they are specified.
+fn from_cmake(
+    file: &Path,
+    name: Option<String>,
+    authors: Vec<String>,
+    global: &GlobalConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let source = fs::read_to_string(file)
+        .map_err(|e| format!("Failed to read `{}`: {}", file.display(), e))?;
+    let import::Import {
+        mut config,
+        warnings,
+    } = import::from_cmake(&source)
+        .map_err(|e| format!("Failed to import `{}`: {}", file.display(), e))?;
+
+    if let Some(name) = name {
+        config.project.name = name;
+    }
+    if let Err(e) = name::validate(&config.project.name) {
+        return Err(format!("{}\nUse `--name` to specify another project name.", e).into());
+    }
+    config.project.authors = match authors.is_empty() {
+        true => author::detect(global),
+        false => authors,
+    };
+
+    for warning in &warnings {
+        warn!("{}: {}", file.display(), warning);
+    }
+    config.save()?;
+
+    success!(
+        "Imported `{}` into `{}` with {} warning(s).",
+        file.display(),
+        CONFIG_FILE,
+        warnings.len()
+    );
+    Ok(())
+}
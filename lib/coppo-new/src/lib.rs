@@ -8,12 +8,40 @@
 
 #![forbid(unsafe_code)]
 
-use std::{fs, path::PathBuf};
+use std::fs;
+use std::path::{Path, PathBuf};
 
 use coppo_addons::prelude::*;
 use coppo_config::prelude::*;
+use coppo_config::{name, standard};
 use coppo_logger::prelude::*;
 
+pub mod author;
+pub mod ci;
+pub mod cmake;
+pub mod docker;
+pub mod hooks;
+pub mod ignore;
+pub mod import;
+pub mod init;
+pub mod license;
+pub mod readme;
+pub mod scaffold;
+pub mod style;
+pub mod targets;
+pub mod template;
+pub mod vcs;
+pub mod wizard;
+
+use ci::Ci;
+use ignore::Ide;
+pub use init::CoppoInitAddon;
+use scaffold::Scaffold;
+use style::Style;
+use template::{Template, Variables};
+use vcs::Vcs;
+use wizard::Wizard;
+
 /// The `Coppo new` command options.
 #[derive(Debug, Default)]
 pub struct CoppoNew {
@@ -24,6 +52,57 @@ pub struct CoppoNew {
     /// The name of the project.
     /// If not specified, the name of the project will be same as the name of the directory.
     pub name: String,
+    /// The description of the project, it is shown in `README.md`.
+    pub description: Option<String>,
+    /// The language of the project, C++ by default.
+    /// The sources of a C project are created as `.c` files and its headers as `.h` files.
+    pub language: Language,
+    /// The standard of the project, like `c++20`, `std` of `[build]`.
+    /// If not specified, the default standard of the language will be used.
+    pub std: Option<String>,
+    /// The name of the user-defined template in `~/.coppo/templates`,
+    /// the path of a template directory, or the url of a git repository.
+    /// If not specified, the default project layout will be used.
+    pub template: Option<String>,
+    /// The authors of the project, like `Name <email>`.
+    /// If it is empty, the authors will be detected, see the `author` module.
+    pub authors: Vec<String>,
+    /// The version control system to initialize for the project.
+    pub vcs: Vcs,
+    /// The SPDX license expression of the project, like `MIT OR Apache-2.0`.
+    /// If specified, the license files will be created.
+    /// It defaults to `new.license` of the global configuration, `none` overrides it.
+    pub license: Option<String>,
+    /// The code style preset of the project.
+    /// If specified, `.clang-format` and `.editorconfig` will be created.
+    pub style: Option<Style>,
+    /// Whether to create a `CMakeLists.txt` mirroring the Coppo target layout.
+    pub cmake_compat: bool,
+    /// Whether to create a `README.md` from the manifest.
+    pub readme: bool,
+    /// Whether to create `tests/basic_test.cpp`.
+    pub with_tests: bool,
+    /// Whether to create `examples/hello.cpp`.
+    pub with_examples: bool,
+    /// Whether to create the project in a non-empty directory.
+    /// The existing files will not be overwritten without confirmation.
+    pub force: bool,
+    /// The IDEs whose specific files will be ignored in `.gitignore`.
+    pub ignores: Vec<Ide>,
+    /// The name of the library target.
+    /// If it is empty, the name of the project will be used.
+    pub lib: Option<String>,
+    /// The names of the binary targets.
+    /// If it is empty, `src/main.cpp` will be created unless there is a library target.
+    pub bins: Vec<String>,
+    /// The CI platform whose pipeline will be created.
+    pub ci: Option<Ci>,
+    /// Whether to create a `Dockerfile` building the project.
+    pub docker: bool,
+    /// Whether to create a dev container in `.devcontainer`.
+    pub devcontainer: bool,
+    /// Whether to run the hooks of the template without confirmation.
+    pub allow_hooks: bool,
 }
 
 /// The `Coppo new` add-on.
@@ -31,60 +110,424 @@ pub struct CoppoNew {
 /// The project will be created in the specified directory.
 /// If the name of the project is not specified, the name of the project will be same as the name of the directory.
 /// It will create the following files:
-/// - src/main.cpp
+/// - src/main.cpp, or src/main.c with `--lang c` (or the targets specified by `--lib` and `--bin`,
+///   the library has a test in `tests/<name>_test.cpp`)
 /// - Coppo.toml
 /// - .gitignore
+/// - tests/basic_test.cpp, run by `coppo test` (unless `--minimal` is specified)
+/// - README.md from the manifest, with the `--description` (unless `--minimal` is specified)
+/// - .clang-format and .editorconfig, used by `coppo fmt`,
+///   with the LLVM style or the `--style` preset (unless `--minimal` is specified)
+/// - LICENSE (if `--license` or `new.license` of the global configuration is specified)
+/// - CMakeLists.txt (if `--cmake-compat` is specified)
+/// - examples/hello.cpp (if `--with-examples` is specified)
+/// - the CI pipeline (if `--ci` is specified)
+/// - Dockerfile (if `--docker` is specified)
+/// - .devcontainer (if `--devcontainer` is specified)
+///
+/// If a template is specified, the files of the template will be created instead,
+/// a user-defined template or one published by a registry, see `--list-templates`,
+/// and the `Coppo.toml` will be created only if the template does not have it.
+/// The test, the readme and the style files are not added to the template
+/// unless `--with-tests`, `--readme` or `--style` is specified.
+/// The `.gitignore` of the template will be merged with the base ignore and the IDE-specific ignores.
+///
+/// A non-empty directory will be refused unless `--force` is specified,
+/// and the existing files in it will not be overwritten without confirmation.
+///
+/// A git repository will be initialized with an initial commit by default,
+/// unless `--vcs none` is specified or the project is already inside a git repository.
+/// The default can be changed with `new.vcs` in the global configuration.
+///
+/// If `--interactive` is specified or the path is omitted,
+/// the options will be asked interactively.
 pub struct CoppoNewAddon;
 
 impl_addon! {
     CoppoNewAddon,
     name => "new",
     description => "Create a new project",
+    project => false,
     args => [
         arg!(["path"] "The path where the project will be created")
-            .required(true)
             .value_parser(value_parser!(PathBuf)),
         arg!(-n --name "The name of the project")
             .action(ArgAction::Set)
             .value_parser(value_parser!(String)),
+        arg!(--description <TEXT> "The description of the project, it is shown in `README.md`")
+            .value_parser(value_parser!(String)),
+        arg!(--lang <LANG> "The language of the project, `c++` or `c`")
+            .value_parser(Language::NAMES)
+            .default_value("c++"),
+        arg!(--std <STD> "The standard of the project, like `c++20` or `20`, defaults to `c++17`, or `c17` for C")
+            .value_parser(value_parser!(String)),
+        arg!(-t --template "The name of the template in `~/.coppo/templates` or a registry, its directory, or a git repository url")
+            .action(ArgAction::Set)
+            .value_parser(value_parser!(String)),
+        arg!(--"list-templates" "List the user-defined templates and the ones published by the registries")
+            .action(ArgAction::SetTrue),
+        arg!(--author <AUTHOR> "The author of the project, like `Name <email>`, it can be specified multiple times")
+            .action(ArgAction::Append)
+            .value_parser(value_parser!(String)),
+        arg!(--vcs <VCS> "The version control system to initialize, defaults to `new.vcs` of the global configuration or `git`")
+            .value_parser(Vcs::NAMES),
+        arg!(--license <LICENSE> "The SPDX license expression of the project, like `MIT OR Apache-2.0`, or `none`")
+            .value_parser(value_parser!(String)),
+        arg!(--style <STYLE> "The code style preset of `.clang-format` and `.editorconfig`, defaults to `llvm`")
+            .value_parser(Style::NAMES),
+        arg!(--"cmake-compat" "Create a `CMakeLists.txt` mirroring the Coppo target layout")
+            .action(ArgAction::SetTrue),
+        arg!(--readme "Create a `README.md` with the build instructions and license notice, even with `--minimal`")
+            .action(ArgAction::SetTrue),
+        arg!(--"with-tests" "Create a basic test in the `tests` directory, even with `--minimal`")
+            .action(ArgAction::SetTrue),
+        arg!(--minimal "Do not create the basic test, `README.md` and the code style files")
+            .action(ArgAction::SetTrue),
+        arg!(--"with-examples" "Create an example in the `examples` directory")
+            .action(ArgAction::SetTrue),
+        arg!(-f --force "Create the project even if the directory is not empty")
+            .action(ArgAction::SetTrue),
+        arg!(--ignore <IDES> "Append the IDE-specific ignores to `.gitignore`, like `clion,vscode`")
+            .value_delimiter(',')
+            .value_parser(Ide::NAMES),
+        arg!(--lib [NAME] "Create a library target, its headers are in `include/<NAME>`, defaults to the project name")
+            .value_parser(value_parser!(String))
+            .num_args(0..=1)
+            .default_missing_value(""),
+        arg!(--bin <NAME> "Create a binary target in `src/bin/<NAME>.cpp`, it can be specified multiple times")
+            .action(ArgAction::Append)
+            .value_parser(value_parser!(String)),
+        arg!(--ci <PLATFORM> "Create a CI pipeline running the Coppo commands")
+            .value_parser(Ci::NAMES),
+        arg!(--docker "Create a `Dockerfile` with the compiler toolchain and Coppo preinstalled")
+            .action(ArgAction::SetTrue),
+        arg!(--devcontainer "Create a dev container with the compiler toolchain and Coppo preinstalled")
+            .action(ArgAction::SetTrue),
+        arg!(--"allow-hooks" "Run the post-generation hooks of the template without confirmation")
+            .action(ArgAction::SetTrue),
+        arg!(-i --interactive "Ask the options of the project interactively")
+            .action(ArgAction::SetTrue),
     ],
     run => |config, matches| {
+        if matches.get_flag("list-templates") {
+            list_templates()?;
+            return Ok(());
+        }
+
+        let mut new = CoppoNew::from_matches(&config.global, matches)?;
+        if matches.get_flag("interactive") || !matches.contains_id("path") {
+            Wizard::stdio().run(&mut new)?;
+        }
+
+        new.create(config)?;
+
+        // Print the success message.
+        success!("Created a new project at {}", new.path.canonicalize()?.display());
+    }
+}
+
+/// Parse the standard of `--std`, like `20` for `c++20`.
+/// The default standard of the language is not written into the manifest.
+pub fn parse_std(std: &str, language: Language) -> Result<Option<String>, String> {
+    if std.is_empty() {
+        return Ok(None);
+    }
+    standard::validate(std, language)?;
+
+    let std = standard::normalize(std, language);
+    match std == standard::default_std(language) {
+        true => Ok(None),
+        false => Ok(Some(std)),
+    }
+}
+
+/// Print the templates which can be used with `--template`.
+fn list_templates() -> Result<(), Box<dyn std::error::Error>> {
+    let entries = Template::catalog()?;
+    if entries.is_empty() {
+        info!(
+            "No templates, place them in `~/.coppo/templates` or add a registry publishing them."
+        );
+        return Ok(());
+    }
+
+    let width = entries
+        .iter()
+        .map(|entry| entry.name.len())
+        .max()
+        .unwrap_or(0);
+    for entry in entries {
+        println!(
+            "{:width$}  {} ({})",
+            entry.name,
+            entry.description.as_deref().unwrap_or("-"),
+            entry.source,
+            width = width
+        );
+    }
+
+    Ok(())
+}
+
+impl CoppoNew {
+    /// Get the options from the command line arguments,
+    /// the defaults come from the global configuration.
+    pub fn from_matches(
+        global: &GlobalConfig,
+        matches: &ArgMatches,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         let mut new = CoppoNew::default();
         if let Some(path) = matches.get_one::<PathBuf>("path") {
             new.path = path.to_owned();
         }
         if let Some(name) = matches.get_one::<String>("name") {
             new.name = name.to_owned();
-        } else {
+        } else if !new.path.as_os_str().is_empty() {
             // If the name is not specified, get the name of the directory.
-            new.name = new
-                .path
-                .file_name()
-                .ok_or("Failed to get the name of the directory.")?
-                .to_str()
-                .ok_or("Failed to convert the name of the directory to a string.")?
-                .to_owned();
+            new.name = CoppoNew::default_name(&new.path)?;
+        }
+        new.description = matches.get_one::<String>("description").cloned();
+        if let Some(language) = matches.get_one::<String>("lang") {
+            new.language = language.parse()?;
+        }
+        if let Some(std) = matches.get_one::<String>("std") {
+            new.std = parse_std(std, new.language)?;
+        }
+        new.template = matches.get_one::<String>("template").cloned();
+        if let Some(authors) = matches.get_many::<String>("author") {
+            new.authors = authors.cloned().collect();
+        }
+        new.vcs = match matches.get_one::<String>("vcs") {
+            Some(vcs) => vcs.parse()?,
+            None => Vcs::configured(global)?,
+        };
+        new.license = matches
+            .get_one::<String>("license")
+            .or(global.new.license.as_ref())
+            .filter(|license| !license.eq_ignore_ascii_case("none"))
+            .cloned();
+        // A fresh project can be tested and formatted right away,
+        // the template has its own layout.
+        let scaffolded = !matches.get_flag("minimal") && new.template.is_none();
+        new.style = match matches.get_one::<String>("style") {
+            Some(style) => Some(style.parse()?),
+            None if scaffolded => Some(Style::Llvm),
+            None => None,
+        };
+        new.cmake_compat = matches.get_flag("cmake-compat");
+        new.readme = scaffolded || matches.get_flag("readme");
+        new.with_tests = scaffolded || matches.get_flag("with-tests");
+        new.with_examples = matches.get_flag("with-examples");
+        new.force = matches.get_flag("force");
+        if let Some(ides) = matches.get_many::<String>("ignore") {
+            new.ignores = ides.map(|ide| ide.parse()).collect::<Result<_, _>>()?;
+        }
+        new.lib = matches.get_one::<String>("lib").cloned();
+        if let Some(bins) = matches.get_many::<String>("bin") {
+            new.bins = bins.cloned().collect();
+        }
+        if let Some(ci) = matches.get_one::<String>("ci") {
+            new.ci = Some(ci.parse()?);
+        }
+        new.docker = matches.get_flag("docker");
+        new.devcontainer = matches.get_flag("devcontainer");
+        new.allow_hooks = matches.get_flag("allow-hooks");
+
+        Ok(new)
+    }
+
+    /// Get the default name of the project, which is the name of the directory.
+    pub fn default_name(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(path
+            .file_name()
+            .ok_or("Failed to get the name of the directory.")?
+            .to_str()
+            .ok_or("Failed to convert the name of the directory to a string.")?
+            .to_owned())
+    }
+
+    /// Create the project files.
+    pub fn create(&self, config: &mut Config) -> Result<(), Box<dyn std::error::Error>> {
+        if let Err(e) = name::validate(&self.name) {
+            return Err(format!("{}\nUse `--name` to specify another project name.", e).into());
+        }
+
+        // The manifest of the current directory, like a workspace root, is not inherited,
+        // but the global configuration is kept, like the compiler of the containers.
+        *config = Config {
+            global: std::mem::take(&mut config.global),
+            ..Default::default()
+        };
+        config.project.name = self.name.clone();
+        config.project.version = Version::new(0, 1, 0);
+        config.project.description = self.description.clone();
+        config.project.language = self.language;
+        config.build.std = self.std.clone();
+        config.project.authors = match self.authors.is_empty() {
+            true => author::detect(&config.global),
+            false => self.authors.clone(),
+        };
+
+        // Load the template and check the options before creating anything,
+        // so a wrong option does not leave an empty directory.
+        let template = self
+            .template
+            .as_deref()
+            .map(Template::resolve)
+            .transpose()?;
+        targets::validate(self.lib(), &self.bins)?;
+        let licenses = self.license.as_deref().map(license::parse).transpose()?;
+        if let Some(licenses) = &licenses {
+            config.project.license = Some(license::expression(licenses));
+        }
+
+        // Prepare the project directory,
+        // a non-empty directory will be refused unless `--force` is specified.
+        let mut scaffold = Scaffold::new(&self.path, self.force)?;
+
+        // Remove what has been created if it fails halfway,
+        // so the directory is left as it was.
+        if let Err(e) = self.scaffold(&mut scaffold, config, template, licenses) {
+            if let Err(rollback) = scaffold.rollback() {
+                warn!("Failed to remove the created files: {}", rollback);
+            }
+            return Err(e);
         }
 
-        config.project.name = new.name.clone();
-        config.project.version = "0.1.0".to_owned();
+        Ok(())
+    }
+
+    /// Create the project files in the scaffold, with the loaded template and licenses.
+    fn scaffold(
+        &self,
+        scaffold: &mut Scaffold,
+        config: &mut Config,
+        template: Option<Template>,
+        licenses: Option<Vec<license::License>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut gitignore_fragment = None;
+        let mut hooks = vec![];
+        let vars = self.variables(config);
+        match template {
+            Some(template) => {
+                let created = template.instantiate(scaffold, &vars)?;
+                info!("Created {} files from the template", created.len());
+                gitignore_fragment = template.gitignore(&vars)?;
+                hooks = template.hooks(&vars);
+            }
+            None if self.lib().is_none() && self.bins.is_empty() => {
+                // Create the main source file.
+                match self.language {
+                    Language::Cpp => scaffold.write("src/main.cpp", MAIN_CPP)?,
+                    Language::C => scaffold.write("src/main.c", MAIN_C)?,
+                };
+            }
+            None => {}
+        }
 
-        // Create the project directory.
-        fs::create_dir_all(&new.path)?;
-        fs::create_dir(new.path.join("src"))?;
+        // Create the library and binary targets.
+        targets::write(scaffold, config, self.lib(), &self.bins)?;
 
-        // Create the src/main.cpp file.
-        fs::write(new.path.join("src/main.cpp"), MAIN_CPP)?;
+        // Create the tests and examples.
+        match self.language {
+            Language::Cpp => {
+                if self.with_tests {
+                    scaffold.write("tests/basic_test.cpp", BASIC_TEST_CPP)?;
+                }
+                if self.with_examples {
+                    scaffold.write("examples/hello.cpp", HELLO_CPP)?;
+                }
+            }
+            Language::C => {
+                if self.with_tests {
+                    scaffold.write("tests/basic_test.c", BASIC_TEST_C)?;
+                }
+                if self.with_examples {
+                    scaffold.write("examples/hello.c", HELLO_C)?;
+                }
+            }
+        }
 
         // Create the configuration file.
-        let toml = toml::to_string(&config)?;
-        fs::write(new.path.join(CONFIG_FILE), toml)?;
+        scaffold.write(CONFIG_FILE, toml::to_string(&config)?)?;
 
         // Create the gitignore file.
-        fs::write(new.path.join(".gitignore"), GITIGNORE)?;
+        scaffold.write(
+            ".gitignore",
+            ignore::compose(gitignore_fragment.as_deref(), &self.ignores),
+        )?;
 
-        // Print the success message.
-        success!("Created a new project at {}", new.path.canonicalize()?.display());
+        // Create the license files.
+        if let Some(licenses) = &licenses {
+            license::write(
+                scaffold,
+                licenses,
+                &config.project.authors,
+                &config.project.name,
+            )?;
+        }
+
+        // Create the code style files.
+        if let Some(style) = self.style {
+            style.write(scaffold)?;
+        }
+
+        // Create the CMake project file.
+        if self.cmake_compat {
+            cmake::write(scaffold, config)?;
+        }
+
+        // Create the CI pipeline.
+        if let Some(ci) = self.ci {
+            ci.write(scaffold)?;
+        }
+
+        // Create the container files.
+        if self.docker {
+            docker::write_docker(scaffold, &coppo_build::compiler(config))?;
+        }
+        if self.devcontainer {
+            docker::write_devcontainer(scaffold, &self.name, &coppo_build::compiler(config))?;
+        }
+
+        // Create the readme file from the manifest,
+        // which may be provided by the template.
+        if self.readme {
+            let manifest = fs::read_to_string(self.path.join(CONFIG_FILE))?;
+            readme::write(scaffold, &Config::from_str(&manifest)?)?;
+        }
+
+        // Initialize the version control system after all files are created,
+        // so they can be included in the initial commit.
+        scaffold.track(".git");
+        self.vcs.init(&self.path)?;
+
+        // Run the template hooks at last,
+        // so they can work with the repository, like `git submodule add`.
+        hooks::run(scaffold, &hooks, &vars, self.allow_hooks)?;
+
+        Ok(())
+    }
+
+    /// The name of the library target, `--lib` without a name uses the project name.
+    fn lib(&self) -> Option<&str> {
+        self.lib.as_deref().map(|lib| match lib {
+            "" => self.name.as_str(),
+            lib => lib,
+        })
+    }
+
+    /// The variables which can be used in the templates.
+    fn variables(&self, config: &Config) -> Variables {
+        Variables::from([
+            ("name", config.project.name.clone()),
+            ("version", config.project.version.to_string()),
+            (
+                "author",
+                license::author(&config.project.authors, &config.project.name),
+            ),
+            ("year", license::current_year().to_string()),
+        ])
     }
 }
 
@@ -96,5 +539,41 @@ int main() {
 }
 "#;
 
-const GITIGNORE: &str = r#"/target
+const BASIC_TEST_CPP: &str = r#"#include <coppo/test.hpp>
+
+// A basic test, run it with `coppo test`.
+COPPO_TEST(basic) {
+    COPPO_ASSERT_EQ(1 + 1, 2);
+}
+"#;
+
+const HELLO_CPP: &str = r#"#include <iostream>
+
+int main() {
+    std::cout << "Hello from the example!" << std::endl;
+    return 0;
+}
+"#;
+
+const MAIN_C: &str = r#"#include <stdio.h>
+
+int main(void) {
+    printf("Hello, World!\n");
+    return 0;
+}
+"#;
+
+// The built-in test framework is for C++, a C test passes if it exits with zero.
+const BASIC_TEST_C: &str = r#"// A basic test, run it with `coppo test`.
+int main(void) {
+    return 1 + 1 == 2 ? 0 : 1;
+}
+"#;
+
+const HELLO_C: &str = r#"#include <stdio.h>
+
+int main(void) {
+    printf("Hello from the example!\n");
+    return 0;
+}
 "#;
@@ -0,0 +1,223 @@
+//! License file generation from SPDX identifiers.
+//!
+//! A single license like `MIT` will be written into the `LICENSE` file.
+//! Multiple licenses like `MIT OR Apache-2.0` (or the old style `MIT/Apache-2.0`)
+//! will be written into `LICENSE-MIT` and `LICENSE-APACHE` respectively.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::scaffold::Scaffold;
+use crate::template::{render, Variables};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// A license which can be generated by `coppo new`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct License {
+    /// The SPDX identifier of the license.
+    pub id: &'static str,
+    /// The suffix of the license file when there are multiple licenses,
+    /// like `LICENSE-MIT`, it is unique among the licenses.
+    pub suffix: &'static str,
+    /// The text of the license,
+    /// the `{{year}}` and `{{author}}` variables will be replaced.
+    pub text: &'static str,
+}
+
+/// All the supported licenses.
+pub const LICENSES: &[License] = &[
+    License {
+        id: "MIT",
+        suffix: "MIT",
+        text: include_str!("../licenses/MIT"),
+    },
+    License {
+        id: "Apache-2.0",
+        suffix: "APACHE",
+        text: include_str!("../licenses/Apache-2.0"),
+    },
+    License {
+        id: "BSD-3-Clause",
+        suffix: "BSD",
+        text: include_str!("../licenses/BSD-3-Clause"),
+    },
+    License {
+        id: "GPL-3.0-only",
+        suffix: "GPL-ONLY",
+        text: include_str!("../licenses/GPL-3.0"),
+    },
+    License {
+        id: "GPL-3.0-or-later",
+        suffix: "GPL-OR-LATER",
+        text: include_str!("../licenses/GPL-3.0"),
+    },
+    License {
+        id: "MPL-2.0",
+        suffix: "MPL",
+        text: include_str!("../licenses/MPL-2.0"),
+    },
+];
+
+/// The deprecated SPDX identifiers, and the ones replacing them.
+const DEPRECATED: &[(&str, &str)] = &[
+    ("GPL-3.0", "GPL-3.0-only"),
+    ("GPL-3.0+", "GPL-3.0-or-later"),
+];
+
+impl License {
+    /// Find a license by the SPDX identifier, it is case-insensitive.
+    /// A deprecated identifier like `GPL-3.0` finds the one replacing it.
+    pub fn find(id: &str) -> Option<License> {
+        let id = DEPRECATED
+            .iter()
+            .find(|(deprecated, _)| deprecated.eq_ignore_ascii_case(id))
+            .map_or(id, |(_, replacement)| replacement);
+        LICENSES
+            .iter()
+            .find(|license| license.id.eq_ignore_ascii_case(id))
+            .copied()
+    }
+}
+
+/// Parse a license expression like `MIT OR Apache-2.0`.
+/// Only the `OR` operator is supported.
+pub fn parse(expr: &str) -> Result<Vec<License>> {
+    let licenses = expr
+        .split(" OR ")
+        .flat_map(|id| id.split('/'))
+        .map(str::trim)
+        .map(|id| {
+            License::find(id).ok_or_else(|| {
+                format!(
+                    "Unsupported license `{}`, the supported licenses are: {}",
+                    id,
+                    LICENSES
+                        .iter()
+                        .map(|license| license.id)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    if licenses.is_empty() {
+        return Err("The license can not be empty".into());
+    }
+
+    Ok(licenses)
+}
+
+/// Normalize a license expression, like `mit/apache-2.0` to `MIT OR Apache-2.0`.
+pub fn expression(licenses: &[License]) -> String {
+    licenses
+        .iter()
+        .map(|license| license.id)
+        .collect::<Vec<_>>()
+        .join(" OR ")
+}
+
+/// Write the license files into the project.
+pub fn write(
+    scaffold: &mut Scaffold,
+    licenses: &[License],
+    authors: &[String],
+    name: &str,
+) -> Result<()> {
+    let vars = Variables::from([
+        ("year", current_year().to_string()),
+        ("author", author(authors, name)),
+    ]);
+
+    for license in licenses {
+        scaffold.write(file_name(licenses, license), render(license.text, &vars))?;
+    }
+
+    Ok(())
+}
+
+/// The file name of the license,
+/// it's `LICENSE` for a single license, or `LICENSE-<suffix>` for multiple licenses.
+pub fn file_name(licenses: &[License], license: &License) -> String {
+    if licenses.len() == 1 {
+        "LICENSE".to_owned()
+    } else {
+        format!("LICENSE-{}", license.suffix)
+    }
+}
+
+/// The copyright holder of the license.
+/// It's the authors without their emails, or `The <name> Authors` if there is no author.
+pub fn author(authors: &[String], name: &str) -> String {
+    let names = authors
+        .iter()
+        .map(|author| match author.split_once('<') {
+            Some((name, _)) => name.trim(),
+            None => author.trim(),
+        })
+        .filter(|author| !author.is_empty())
+        .collect::<Vec<_>>();
+
+    if names.is_empty() {
+        format!("The {} Authors", name)
+    } else {
+        names.join(", ")
+    }
+}
+
+/// Get the current year in UTC.
+pub fn current_year() -> i64 {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64 / 86400)
+        .unwrap_or_default();
+
+    // Convert the days since the epoch to the civil year.
+    // See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+
+    yoe + era * 400 + i64::from(month <= 2)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let licenses = parse("mit/apache-2.0").unwrap();
+        assert_eq!(expression(&licenses), "MIT OR Apache-2.0");
+
+        assert!(parse("MIT OR WTFPL").is_err());
+
+        let licenses = parse("gpl-3.0").unwrap();
+        assert_eq!(expression(&licenses), "GPL-3.0-only");
+    }
+
+    #[test]
+    fn test_file_name() {
+        let licenses = parse("GPL-3.0-only OR GPL-3.0-or-later").unwrap();
+        assert_eq!(file_name(&licenses, &licenses[0]), "LICENSE-GPL-ONLY");
+        assert_eq!(file_name(&licenses, &licenses[1]), "LICENSE-GPL-OR-LATER");
+        assert_eq!(file_name(&licenses[..1], &licenses[0]), "LICENSE");
+
+        for (i, license) in LICENSES.iter().enumerate() {
+            assert!(LICENSES[..i]
+                .iter()
+                .all(|other| other.suffix != license.suffix));
+        }
+    }
+
+    #[test]
+    fn test_author() {
+        let authors = vec!["John Doe <john@doe.com>".to_owned(), "Jane".to_owned()];
+        assert_eq!(author(&authors, "demo"), "John Doe, Jane");
+        assert_eq!(author(&[], "demo"), "The demo Authors");
+    }
+}
@@ -0,0 +1,100 @@
+//! The `README.md` generation for the new project.
+
+use coppo_config::prelude::*;
+
+use crate::license;
+use crate::scaffold::Scaffold;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The name of the readme file.
+pub const README: &str = "README.md";
+
+/// Generate the content of `README.md` from the manifest.
+pub fn readme(config: &Config) -> String {
+    let project = &config.project;
+    let licenses = project
+        .license
+        .as_deref()
+        .and_then(|expr| license::parse(expr).ok())
+        .unwrap_or_default();
+
+    let mut readme = format!("# {}\n\n", project.name);
+
+    // Badges.
+    if let Some(expr) = &project.license {
+        let file = licenses
+            .first()
+            .map(|first| license::file_name(&licenses, first))
+            .unwrap_or_else(|| "LICENSE".to_owned());
+        readme += &format!(
+            "[![License: {expr}](https://img.shields.io/badge/license-{badge}-blue.svg)]({file})\n\n",
+            expr = expr,
+            badge = badge_escape(expr),
+            file = file,
+        );
+    }
+
+    if let Some(description) = &project.description {
+        readme += &format!("{}\n\n", description);
+    }
+
+    // Usage.
+    readme += &format!(
+        "## Usage\n\
+        \n\
+        This project is managed by [Coppo](https://github.com/hacbit/Coppo).\n\
+        \n\
+        Build the project, the executable will be placed in the `target` directory:\n\
+        \n\
+        ```sh\n\
+        coppo build\n\
+        ```\n\
+        \n\
+        Build and run the project:\n\
+        \n\
+        ```sh\n\
+        coppo run\n\
+        ```\n\
+        \n\
+        The project settings are in [{config}]({config}).\n",
+        config = CONFIG_FILE,
+    );
+
+    // License notice.
+    match licenses.as_slice() {
+        [] => {}
+        [license] => {
+            readme += &format!(
+                "\n## License\n\
+                \n\
+                This project is licensed under the {} license, see [LICENSE](LICENSE) for details.\n",
+                license.id
+            );
+        }
+        licenses => {
+            readme += "\n## License\n\nThis project is licensed under either of\n\n";
+            for license in licenses {
+                let file = license::file_name(licenses, license);
+                readme += &format!("- {} ([{}]({}))\n", license.id, file, file);
+            }
+            readme += "\nat your option.\n";
+        }
+    }
+
+    readme
+}
+
+/// Write `README.md` into the project.
+pub fn write(scaffold: &mut Scaffold, config: &Config) -> Result<()> {
+    scaffold.write(README, readme(config))?;
+
+    Ok(())
+}
+
+/// Escape the text for the shields.io badge url.
+fn badge_escape(text: &str) -> String {
+    text.replace('-', "--")
+        .replace('_', "__")
+        .replace(' ', "%20")
+}
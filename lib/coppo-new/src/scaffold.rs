@@ -0,0 +1,243 @@
+//! The writer of the project files.
+//!
+//! All the files of the new project are written through the `Scaffold`,
+//! so the existing files in the destination are never overwritten without confirmation.
+//!
+//! The scaffold is transactional, if the creation fails halfway,
+//! [`Scaffold::rollback`] removes what it has created and restores what it has overwritten,
+//! so the destination is left as it was.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+
+use coppo_logger::prelude::*;
+
+use crate::wizard::{yes_or_no, Wizard};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The writer of the project files in the destination directory.
+#[derive(Debug)]
+pub struct Scaffold {
+    /// The root directory of the project.
+    root: PathBuf,
+    /// The files which have been written, relative to the root directory.
+    written: Vec<PathBuf>,
+    /// The outermost directory created for the root, it is removed as a whole on rollback.
+    created_root: Option<PathBuf>,
+    /// The directories and other paths created in the root, relative to the root directory.
+    created: Vec<PathBuf>,
+    /// The original contents of the overwritten files, relative to the root directory.
+    backups: Vec<(PathBuf, Vec<u8>)>,
+}
+
+impl Scaffold {
+    /// Prepare the destination directory.
+    /// A non-empty destination will be refused unless `force` is `true`.
+    pub fn new(root: &Path, force: bool) -> Result<Self> {
+        if root.is_file() {
+            return Err(format!("The destination `{}` is a file.", root.display()).into());
+        }
+
+        if root.is_dir() && fs::read_dir(root)?.next().is_some() && !force {
+            return Err(format!(
+                "The destination `{}` is not empty.\n\
+                Use `--force` to create the project in it anyway, \
+                the existing files will not be overwritten without confirmation.",
+                root.display()
+            )
+            .into());
+        }
+
+        let created_root = root
+            .ancestors()
+            .take_while(|dir| !dir.as_os_str().is_empty() && !dir.exists())
+            .last()
+            .map(Path::to_owned);
+        fs::create_dir_all(root)?;
+
+        Ok(Self {
+            root: root.to_owned(),
+            written: vec![],
+            created_root,
+            created: vec![],
+            backups: vec![],
+        })
+    }
+
+    /// The root directory of the project.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Write a file and create its parent directories, the path is relative to the root directory.
+    /// Return `false` if the file is skipped:
+    /// - The file has been written by the scaffold before, like a `Coppo.toml` from the template.
+    /// - The file existed before, and the user does not confirm to overwrite it.
+    pub fn write(&mut self, path: impl AsRef<Path>, content: impl AsRef<[u8]>) -> Result<bool> {
+        let path = path.as_ref();
+        if self.written.iter().any(|written| written == path) {
+            return Ok(false);
+        }
+
+        let target = self.root.join(path);
+        if target.exists() {
+            if !confirm_overwrite(path)? {
+                warn!("Skipped the existing file `{}`", path.display());
+                return Ok(false);
+            }
+            self.backups.push((path.to_owned(), fs::read(&target)?));
+        }
+
+        if let Some(parent) = path.parent() {
+            self.track_dirs(parent);
+            fs::create_dir_all(self.root.join(parent))?;
+        }
+        fs::write(&target, content)?;
+        self.written.push(path.to_owned());
+
+        Ok(true)
+    }
+
+    /// Record a path created outside the scaffold, like the `.git` directory,
+    /// so it is removed on rollback. The path is relative to the root directory,
+    /// and it should be called before the path is created.
+    pub fn track(&mut self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        if !self.root.join(path).exists() {
+            self.created.push(path.to_owned());
+        }
+    }
+
+    /// The paths in the root directory, relative to it, `.git` is not walked into.
+    /// It is compared with the paths after running a command, see [`Scaffold::track_since`].
+    pub fn paths(&self) -> Result<BTreeSet<PathBuf>> {
+        let mut paths = BTreeSet::new();
+        let mut dirs = vec![PathBuf::new()];
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(self.root.join(&dir))? {
+                let entry = entry?;
+                let path = dir.join(entry.file_name());
+                if entry.file_type()?.is_dir() && path != Path::new(".git") {
+                    dirs.push(path.clone());
+                }
+                paths.insert(path);
+            }
+        }
+
+        Ok(paths)
+    }
+
+    /// Record the paths created outside the scaffold since [`Scaffold::paths`] returned `before`,
+    /// like the files of the template hooks, so they are removed on rollback.
+    pub fn track_since(&mut self, before: &BTreeSet<PathBuf>) -> Result<()> {
+        for path in self.paths()? {
+            // The new directory is removed as a whole.
+            let in_new_dir = path
+                .ancestors()
+                .skip(1)
+                .any(|dir| !dir.as_os_str().is_empty() && !before.contains(dir));
+            if !before.contains(&path) && !in_new_dir && !self.written.contains(&path) {
+                self.created.push(path);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Record the missing directories of the path before they are created.
+    fn track_dirs(&mut self, dir: &Path) {
+        for dir in dir.ancestors() {
+            if dir.as_os_str().is_empty() || self.root.join(dir).exists() {
+                break;
+            }
+            self.created.push(dir.to_owned());
+        }
+    }
+
+    /// Undo the creation, remove the created files and directories,
+    /// and restore the overwritten files.
+    /// The root directory is removed as a whole if it is created by the scaffold.
+    pub fn rollback(self) -> Result<()> {
+        if let Some(root) = &self.created_root {
+            fs::remove_dir_all(root)?;
+            return Ok(());
+        }
+
+        for path in self.written.iter().rev() {
+            let target = self.root.join(path);
+            match self.backups.iter().find(|(backup, _)| backup == path) {
+                Some((_, content)) => fs::write(&target, content)?,
+                None => fs::remove_file(&target)?,
+            }
+        }
+
+        // The deeper paths are removed first, so their parents are empty.
+        let mut created = self.created;
+        created.sort_by_key(|path| std::cmp::Reverse(path.components().count()));
+        for path in created {
+            let target = self.root.join(path);
+            if target.is_dir() {
+                fs::remove_dir_all(&target)?;
+            } else if target.exists() {
+                fs::remove_file(&target)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Ask the user whether to overwrite the existing file.
+/// It will not be overwritten if the input is not a terminal.
+fn confirm_overwrite(path: &Path) -> Result<bool> {
+    if !io::stdin().is_terminal() {
+        return Ok(false);
+    }
+
+    Wizard::stdio().ask(
+        &format!("`{}` already exists, overwrite it? (y/n)", path.display()),
+        "n",
+        yes_or_no,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rollback() {
+        let dir = std::env::temp_dir().join(format!("coppo-scaffold-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("notes.txt"), "keep").unwrap();
+
+        let mut scaffold = Scaffold::new(&dir, true).unwrap();
+        scaffold.write("src/lib/lib.cpp", "").unwrap();
+        scaffold.write("Coppo.toml", "").unwrap();
+        let before = scaffold.paths().unwrap();
+        fs::create_dir_all(dir.join("extern/fmt")).unwrap();
+        fs::write(dir.join("src/gen.cpp"), "").unwrap();
+        scaffold.track_since(&before).unwrap();
+        scaffold.rollback().unwrap();
+
+        assert!(!dir.join("extern").exists());
+        assert!(!dir.join("src/gen.cpp").exists());
+
+        assert!(!dir.join("src/lib").exists());
+        assert!(!dir.join("Coppo.toml").exists());
+        assert!(dir.join("src").is_dir());
+        assert_eq!(fs::read_to_string(dir.join("notes.txt")).unwrap(), "keep");
+
+        let root = dir.join("app");
+        let mut scaffold = Scaffold::new(&root.join("nested"), false).unwrap();
+        scaffold.write("src/main.cpp", "").unwrap();
+        scaffold.rollback().unwrap();
+        assert!(!root.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
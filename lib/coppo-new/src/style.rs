@@ -0,0 +1,113 @@
+//! The `.clang-format` and `.editorconfig` generation for the new project.
+
+use std::str::FromStr;
+
+use crate::scaffold::Scaffold;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The named code style presets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// The LLVM coding standards, 2 spaces indentation and 80 columns.
+    Llvm,
+    /// The Google C++ style guide, 2 spaces indentation and 80 columns.
+    Google,
+    /// The WebKit code style, 4 spaces indentation and no column limit.
+    Webkit,
+}
+
+impl Style {
+    /// All the names of the supported presets.
+    pub const NAMES: [&'static str; 3] = ["llvm", "google", "webkit"];
+
+    /// The `BasedOnStyle` value of `.clang-format`.
+    fn based_on(&self) -> &'static str {
+        match self {
+            Style::Llvm => "LLVM",
+            Style::Google => "Google",
+            Style::Webkit => "WebKit",
+        }
+    }
+
+    /// The indentation width of the preset.
+    fn indent_width(&self) -> usize {
+        match self {
+            Style::Llvm | Style::Google => 2,
+            Style::Webkit => 4,
+        }
+    }
+
+    /// The column limit of the preset, `0` means no limit.
+    fn column_limit(&self) -> usize {
+        match self {
+            Style::Llvm | Style::Google => 80,
+            Style::Webkit => 0,
+        }
+    }
+
+    /// The content of `.clang-format`.
+    pub fn clang_format(&self) -> String {
+        format!(
+            "---\n\
+            Language: Cpp\n\
+            BasedOnStyle: {}\n\
+            IndentWidth: {}\n\
+            ColumnLimit: {}\n\
+            ...\n",
+            self.based_on(),
+            self.indent_width(),
+            self.column_limit()
+        )
+    }
+
+    /// The content of `.editorconfig`.
+    pub fn editorconfig(&self) -> String {
+        let max_line_length = match self.column_limit() {
+            0 => "off".to_owned(),
+            limit => limit.to_string(),
+        };
+
+        format!(
+            "root = true\n\
+            \n\
+            [*]\n\
+            charset = utf-8\n\
+            end_of_line = lf\n\
+            insert_final_newline = true\n\
+            trim_trailing_whitespace = true\n\
+            \n\
+            [*.{{c,cc,cpp,cxx,h,hh,hpp,hxx}}]\n\
+            indent_style = space\n\
+            indent_size = {}\n\
+            max_line_length = {}\n\
+            \n\
+            [*.toml]\n\
+            indent_style = space\n\
+            indent_size = 4\n",
+            self.indent_width(),
+            max_line_length
+        )
+    }
+
+    /// Write `.clang-format` and `.editorconfig` into the project.
+    pub fn write(&self, scaffold: &mut Scaffold) -> Result<()> {
+        scaffold.write(".clang-format", self.clang_format())?;
+        scaffold.write(".editorconfig", self.editorconfig())?;
+
+        Ok(())
+    }
+}
+
+impl FromStr for Style {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "llvm" => Ok(Style::Llvm),
+            "google" => Ok(Style::Google),
+            "webkit" => Ok(Style::Webkit),
+            _ => Err(format!("Unknown code style `{}`", s)),
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! The multi-target scaffolding of the new project,
+//! like `coppo new app --bin server --bin client --lib core`.
+//!
+//! The library `core` will be created as:
+//! - include/core/core.hpp
+//! - src/core/core.cpp
+//! - tests/core_test.cpp, which tests the library with `coppo test`
+//!
+//! And the binary `server` will be created as `src/bin/server.cpp`,
+//! which includes the library if there is one.
+//!
+//! The files of a C project are `.h` headers and `.c` sources instead,
+//! and the test of the library is a plain program, it passes if it exits with zero.
+
+use std::path::PathBuf;
+
+use coppo_config::name;
+use coppo_config::prelude::*;
+
+use crate::scaffold::Scaffold;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Add the targets into the manifest, and create their source stubs.
+pub fn write(
+    scaffold: &mut Scaffold,
+    config: &mut Config,
+    lib: Option<&str>,
+    bins: &[String],
+) -> Result<()> {
+    let language = config.project.language;
+    let extension = language.source_extension();
+    let header = |lib: &str| format!("{0}/{0}.{1}", lib, language.header_extension());
+    if let Some(lib) = lib {
+        let target = LibTarget {
+            name: lib.to_owned(),
+            ..Default::default()
+        };
+        let header = header(lib);
+
+        scaffold.write(target.include().join(&header), lib_header(lib, language))?;
+        scaffold.write(
+            target.path().join(format!("{}.{}", lib, extension)),
+            lib_source(lib, &header, language),
+        )?;
+        scaffold.write(
+            format!("tests/{}_test.{}", lib, extension),
+            lib_test(lib, &header, language),
+        )?;
+        config.lib = Some(target);
+    }
+
+    for bin in bins {
+        let target = BinTarget {
+            name: bin.to_owned(),
+            ..Default::default()
+        };
+        let header = lib.map(|lib| (lib, header(lib)));
+
+        scaffold.write(
+            PathBuf::from(format!("src/bin/{}.{}", bin, extension)),
+            bin_source(bin, header, language),
+        )?;
+        config.bin.push(target);
+    }
+
+    Ok(())
+}
+
+/// Check the names of the targets.
+pub fn validate(lib: Option<&str>, bins: &[String]) -> Result<()> {
+    for target in lib.into_iter().chain(bins.iter().map(String::as_str)) {
+        name::validate(target).map_err(|e| format!("Invalid target name: {}", e))?;
+    }
+
+    if let Some(duplicated) = bins
+        .iter()
+        .enumerate()
+        .find(|(i, bin)| bins[..*i].contains(bin))
+        .map(|(_, bin)| bin)
+    {
+        return Err(format!("The binary target `{}` is specified twice", duplicated).into());
+    }
+
+    Ok(())
+}
+
+/// The C++ namespace of the library, or the prefix of its C functions,
+/// `-` is not allowed in identifiers.
+fn namespace(lib: &str) -> String {
+    lib.replace('-', "_")
+}
+
+/// The public header of the library.
+fn lib_header(lib: &str, language: Language) -> String {
+    if language == Language::C {
+        return format!(
+            "#pragma once\n\
+            \n\
+            /// Get a greeting from the `{lib}` library.\n\
+            const char *{ns}_hello(void);\n",
+            ns = namespace(lib),
+            lib = lib,
+        );
+    }
+
+    format!(
+        "#pragma once\n\
+        \n\
+        #include <string>\n\
+        \n\
+        namespace {ns} {{\n\
+        \n\
+        /// Get a greeting from the `{lib}` library.\n\
+        std::string hello();\n\
+        \n\
+        }} // namespace {ns}\n",
+        ns = namespace(lib),
+        lib = lib,
+    )
+}
+
+/// The source of the library.
+fn lib_source(lib: &str, header: &str, language: Language) -> String {
+    if language == Language::C {
+        return format!(
+            "#include <{header}>\n\
+            \n\
+            const char *{ns}_hello(void) {{\n    \
+                return \"Hello from {lib}!\";\n\
+            }}\n",
+            header = header,
+            ns = namespace(lib),
+            lib = lib,
+        );
+    }
+
+    format!(
+        "#include <{header}>\n\
+        \n\
+        namespace {ns} {{\n\
+        \n\
+        std::string hello() {{\n    \
+            return \"Hello from {lib}!\";\n\
+        }}\n\
+        \n\
+        }} // namespace {ns}\n",
+        header = header,
+        ns = namespace(lib),
+        lib = lib,
+    )
+}
+
+/// The test of the library, with the built-in test framework of `coppo test`,
+/// or a plain program for C.
+fn lib_test(lib: &str, header: &str, language: Language) -> String {
+    if language == Language::C {
+        return format!(
+            "#include <string.h>\n\
+            #include <{header}>\n\
+            \n\
+            int main(void) {{\n    \
+                return strcmp({ns}_hello(), \"Hello from {lib}!\") == 0 ? 0 : 1;\n\
+            }}\n",
+            header = header,
+            ns = namespace(lib),
+            lib = lib,
+        );
+    }
+
+    format!(
+        "#include <coppo/test.hpp>\n\
+        #include <{header}>\n\
+        \n\
+        COPPO_TEST(hello) {{\n    \
+            COPPO_ASSERT_EQ({ns}::hello(), \"Hello from {lib}!\");\n\
+        }}\n",
+        header = header,
+        ns = namespace(lib),
+        lib = lib,
+    )
+}
+
+/// The source of the binary, it calls the library if there is one.
+fn bin_source(bin: &str, lib: Option<(&str, String)>, language: Language) -> String {
+    if language == Language::C {
+        return match lib {
+            Some((lib, header)) => format!(
+                "#include <stdio.h>\n\
+                #include <{header}>\n\
+                \n\
+                int main(void) {{\n    \
+                    printf(\"{bin}: %s\\n\", {ns}_hello());\n    \
+                    return 0;\n\
+                }}\n",
+                header = header,
+                bin = bin,
+                ns = namespace(lib),
+            ),
+            None => format!(
+                "#include <stdio.h>\n\
+                \n\
+                int main(void) {{\n    \
+                    printf(\"Hello from {}!\\n\");\n    \
+                    return 0;\n\
+                }}\n",
+                bin
+            ),
+        };
+    }
+
+    match lib {
+        Some((lib, header)) => format!(
+            "#include <iostream>\n\
+            #include <{header}>\n\
+            \n\
+            int main() {{\n    \
+                std::cout << \"{bin}: \" << {ns}::hello() << std::endl;\n    \
+                return 0;\n\
+            }}\n",
+            header = header,
+            bin = bin,
+            ns = namespace(lib),
+        ),
+        None => format!(
+            "#include <iostream>\n\
+            \n\
+            int main() {{\n    \
+                std::cout << \"Hello from {}!\" << std::endl;\n    \
+                return 0;\n\
+            }}\n",
+            bin
+        ),
+    }
+}
@@ -0,0 +1,508 @@
+//! User-defined project templates.
+//!
+//! A template is a file tree placed in `~/.coppo/templates/<name>/`, or in any directory
+//! given by its path, like `./templates/service`, with a small manifest file `template.toml` in its root directory:
+//!
+//! ```toml
+//! [template]
+//! description = "A project with a custom layout."
+//! exclude = ["build"]
+//! hooks = ["chmod +x scripts/*"]
+//! ```
+//!
+//! All files of the template (except the manifest and the excluded paths)
+//! will be copied into the new project.
+//! The variables in the file names and contents will be replaced:
+//! - `{{name}}`: the name of the new project.
+//! - `{{version}}`: the version of the new project.
+//! - `{{author}}`: the authors of the new project without their emails.
+//! - `{{year}}`: the current year.
+//!
+//! The `hooks` are the commands which will run in the new project directory
+//! after it is created, see the `hooks` module.
+//!
+//! The `.gitignore` of the template is a fragment,
+//! it will be merged with the base ignore of Coppo instead of being copied.
+//!
+//! A template can also be a git repository, like `https://github.com/org/tpl.git`.
+//! It will be cloned into `~/.coppo/templates-cache`, and updated on the next use.
+//! The VCS metadata of the repository will not be copied into the new project.
+//!
+//! A template which is not in `~/.coppo/templates` is looked up in the catalogs of the registries,
+//! the `[registries.<name>]` of the global configuration, so a team can publish its starters,
+//! like `qt-app` or `header-only-lib`.
+//! It is downloaded into `~/.coppo/templates/<name>` and updated when a new version is published.
+//! `coppo new --list-templates` lists the user-defined templates and the published ones.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use coppo_config::prelude::*;
+use coppo_logger::prelude::*;
+use coppo_registry::{Index, TemplateRelease, TEMPLATE_CHECKSUM_FILE};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::hooks;
+use crate::scaffold::Scaffold;
+use crate::vcs::git;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The manifest file name of a template.
+pub const TEMPLATE_MANIFEST: &str = "template.toml";
+
+/// The directory where the user-defined templates are stored,
+/// it is relative to the Coppo home directory.
+pub const TEMPLATES_DIR: &str = "templates";
+
+/// The directory where the remote templates are cached,
+/// it is relative to the Coppo home directory.
+pub const TEMPLATES_CACHE_DIR: &str = "templates-cache";
+
+/// The `.gitignore` fragment of a template.
+const GITIGNORE: &str = ".gitignore";
+
+/// The VCS metadata which will never be copied into the new project.
+const VCS_METADATA: &[&str] = &[".git", ".gitmodules", ".hg", ".svn"];
+
+/// The manifest of a template.
+#[derive(Debug, Default, Deserialize)]
+pub struct TemplateManifest {
+    #[serde(default)]
+    pub template: TemplateInfo,
+}
+
+/// The `[template]` section of the template manifest.
+#[derive(Debug, Default, Deserialize)]
+pub struct TemplateInfo {
+    /// The description of the template.
+    pub description: Option<String>,
+    /// The paths which will not be copied into the new project.
+    /// They are relative to the root directory of the template.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// The commands to run in the new project directory after it is created.
+    #[serde(default)]
+    pub hooks: Vec<String>,
+}
+
+/// A template listed by `coppo new --list-templates`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogEntry {
+    /// The name of the template.
+    pub name: String,
+    /// The description of the template.
+    pub description: Option<String>,
+    /// Where the template is from, `local` or the name of the registry.
+    pub source: String,
+}
+
+/// The variables which can be used in a template, like `{{name}}`.
+pub type Variables = BTreeMap<&'static str, String>;
+
+/// A project template.
+#[derive(Debug)]
+pub struct Template {
+    /// The root directory of the template.
+    pub root: PathBuf,
+    /// The manifest of the template.
+    pub manifest: TemplateManifest,
+}
+
+impl Template {
+    /// Resolve a template by the name, the path of its directory or the git repository url.
+    pub fn resolve(spec: &str) -> Result<Template> {
+        if is_remote(spec) {
+            Template::fetch(spec)
+        } else if is_local(spec) {
+            let root = Path::new(spec);
+            if !root.is_dir() {
+                return Err(format!("The template directory `{}` does not exist.", spec).into());
+            }
+            Template::load(root)
+        } else {
+            Template::find(spec)
+        }
+    }
+
+    /// Find a template by name in `~/.coppo/templates`,
+    /// or download it from the registries if it is not a user-defined one.
+    pub fn find(name: &str) -> Result<Template> {
+        let root = templates_dir()?.join(name);
+        if root.is_dir() && !is_downloaded(&root) {
+            return Template::load(&root);
+        }
+
+        match Template::download(name, &root) {
+            Ok(true) => Template::load(&root),
+            Ok(false) if root.is_dir() => {
+                warn!(
+                    "The template `{}` is no longer published, using the downloaded one.",
+                    name
+                );
+                Template::load(&root)
+            }
+            Ok(false) => Err(format!(
+                "The template `{}` does not exist, it should be placed in `{}`, \
+                or published by a registry.\n\
+                help: run `coppo new --list-templates` to see the available templates",
+                name,
+                root.display()
+            )
+            .into()),
+            Err(e) if root.is_dir() => {
+                warn!(
+                    "Failed to update the template `{}`, using the downloaded one: {}",
+                    name, e
+                );
+                Template::load(&root)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Download the template from the first registry publishing it into the directory.
+    /// Return `false` if no registry publishes it.
+    fn download(name: &str, root: &Path) -> Result<bool> {
+        let global = GlobalConfig::from_file()?;
+        for url in global.registries.values().map(|registry| &registry.index) {
+            let index = Index::configured(&global, url);
+            let templates = index.templates()?;
+            if let Some(template) = newest_template(&templates, name) {
+                coppo_registry::fetch_template(&index, template, root)?;
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// List the user-defined templates in `~/.coppo/templates`,
+    /// and the templates published by the registries.
+    /// A registry which fails to be listed is skipped with a warning.
+    pub fn catalog() -> Result<Vec<CatalogEntry>> {
+        let mut entries = vec![];
+        let dir = templates_dir()?;
+        if dir.is_dir() {
+            let mut roots = fs::read_dir(&dir)?
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|root| root.is_dir() && !is_downloaded(root))
+                .collect::<Vec<_>>();
+            roots.sort();
+            for root in roots {
+                let template = Template::load(&root)?;
+                entries.push(CatalogEntry {
+                    name: root
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .into_owned(),
+                    description: template.manifest.template.description,
+                    source: "local".to_owned(),
+                });
+            }
+        }
+
+        let global = GlobalConfig::from_file()?;
+        for (registry, settings) in &global.registries {
+            let templates = match Index::configured(&global, &settings.index).templates() {
+                Ok(templates) => templates,
+                Err(e) => {
+                    warn!(
+                        "Failed to list the templates of the registry `{}`: {}",
+                        registry, e
+                    );
+                    continue;
+                }
+            };
+            for template in &templates {
+                let listed = entries.iter().any(|entry| entry.name == template.name);
+                if !listed && newest_template(&templates, &template.name) == Some(template) {
+                    entries.push(CatalogEntry {
+                        name: template.name.clone(),
+                        description: template.description.clone(),
+                        source: registry.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Clone the template from a git repository into `~/.coppo/templates-cache`.
+    /// If the template has been cached, it will be updated instead.
+    pub fn fetch(url: &str) -> Result<Template> {
+        // An url starting with `-` would be an option of git.
+        if url.starts_with('-') {
+            return Err(format!("Invalid template url `{}`.", url).into());
+        }
+        let cache_dir = coppo_home()
+            .ok_or("Failed to get the Coppo home directory.")?
+            .join(TEMPLATES_CACHE_DIR);
+        let root = cache_dir.join(cache_name(url));
+
+        if root.join(".git").is_dir() {
+            info!("Updating the template from {}", url);
+            let updated = git(&root, &["fetch", "--quiet", "--depth", "1", "origin"])
+                .and_then(|_| git(&root, &["reset", "--quiet", "--hard", "FETCH_HEAD"]));
+            if let Err(e) = updated {
+                warn!("Failed to update the template, using the cached one: {}", e);
+            }
+        } else {
+            info!("Cloning the template from {}", url);
+            fs::create_dir_all(&cache_dir)?;
+            // Remove the broken cache if there is.
+            if root.exists() {
+                fs::remove_dir_all(&root)?;
+            }
+            git(
+                &cache_dir,
+                &[
+                    "clone",
+                    "--quiet",
+                    "--depth",
+                    "1",
+                    "--",
+                    url,
+                    &root.to_string_lossy(),
+                ],
+            )?;
+        }
+
+        Template::load(&root)
+    }
+
+    /// Load the template in the specified directory.
+    pub fn load(root: &Path) -> Result<Template> {
+        let manifest_file = root.join(TEMPLATE_MANIFEST);
+        let manifest = if manifest_file.exists() {
+            toml::from_str(&fs::read_to_string(&manifest_file)?)
+                .map_err(|e| format!("Failed to parse `{}`: {}", manifest_file.display(), e))?
+        } else {
+            TemplateManifest::default()
+        };
+
+        Ok(Template {
+            root: root.to_owned(),
+            manifest,
+        })
+    }
+
+    /// Copy the template into the project, and replace the variables.
+    /// Return the paths of the created files, relative to the project root.
+    pub fn instantiate(&self, scaffold: &mut Scaffold, vars: &Variables) -> Result<Vec<PathBuf>> {
+        let mut created = vec![];
+
+        for file in self.files()? {
+            let target = PathBuf::from(render(&file.to_string_lossy(), vars));
+
+            let content = fs::read(self.root.join(&file))?;
+            let written = match String::from_utf8(content) {
+                Ok(text) => scaffold.write(&target, render(&text, vars))?,
+                // Binary files are copied as they are.
+                Err(e) => scaffold.write(&target, e.into_bytes())?,
+            };
+
+            if written {
+                created.push(target);
+            }
+        }
+
+        Ok(created)
+    }
+
+    /// The `.gitignore` fragment of the template, with the variables replaced.
+    pub fn gitignore(&self, vars: &Variables) -> Result<Option<String>> {
+        let file = self.root.join(GITIGNORE);
+        if !file.is_file() {
+            return Ok(None);
+        }
+
+        Ok(Some(render(&fs::read_to_string(file)?, vars)))
+    }
+
+    /// The post-generation hooks of the template,
+    /// with the variables replaced by their environment variables, see [`hooks::render`].
+    pub fn hooks(&self, vars: &Variables) -> Vec<String> {
+        self.manifest
+            .template
+            .hooks
+            .iter()
+            .map(|hook| hooks::render(hook, vars))
+            .collect()
+    }
+
+    /// Collect the files of the template, relative to the root directory.
+    fn files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = vec![];
+        let mut dirs = vec![PathBuf::new()];
+
+        while let Some(dir) = dirs.pop() {
+            let mut entries =
+                fs::read_dir(self.root.join(&dir))?.collect::<std::io::Result<Vec<_>>>()?;
+            entries.sort_by_key(|entry| entry.file_name());
+
+            for entry in entries {
+                let path = dir.join(entry.file_name());
+                if self.is_excluded(&path) {
+                    continue;
+                }
+
+                if entry.file_type()?.is_dir() {
+                    dirs.push(path);
+                } else {
+                    files.push(path);
+                }
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Check if the path should not be copied into the new project.
+    fn is_excluded(&self, path: &Path) -> bool {
+        path == Path::new(TEMPLATE_MANIFEST)
+            || path == Path::new(GITIGNORE)
+            || path == Path::new(TEMPLATE_CHECKSUM_FILE)
+            || VCS_METADATA.iter().any(|vcs| path.starts_with(vcs))
+            || self
+                .manifest
+                .template
+                .exclude
+                .iter()
+                .any(|exclude| path.starts_with(exclude))
+    }
+}
+
+/// The directory of the templates, `~/.coppo/templates`.
+fn templates_dir() -> Result<PathBuf> {
+    Ok(coppo_home()
+        .ok_or("Failed to get the Coppo home directory.")?
+        .join(TEMPLATES_DIR))
+}
+
+/// Check if the template is downloaded from a registry instead of user-defined.
+fn is_downloaded(root: &Path) -> bool {
+    root.join(TEMPLATE_CHECKSUM_FILE).is_file()
+}
+
+/// The newest version of the template in the catalog.
+fn newest_template<'a>(
+    templates: &'a [TemplateRelease],
+    name: &str,
+) -> Option<&'a TemplateRelease> {
+    templates
+        .iter()
+        .filter(|template| template.name == name)
+        .max_by(|a, b| a.version.cmp(&b.version))
+}
+
+/// Check if the template is a git repository url.
+/// A spec starting with `-` is never a url, it would be an option of git.
+pub fn is_remote(spec: &str) -> bool {
+    !spec.starts_with('-')
+        && (["https://", "http://", "git://", "ssh://", "git@"]
+            .iter()
+            .any(|prefix| spec.starts_with(prefix))
+            || spec.ends_with(".git"))
+}
+
+/// Check if the template is the path of a directory, like `./tpl` or `/opt/templates/tpl`,
+/// instead of the name of a template in `~/.coppo/templates`.
+pub fn is_local(spec: &str) -> bool {
+    spec.contains('/') || spec.contains('\\') || spec.starts_with('.')
+}
+
+/// Get the cache directory name of a remote template,
+/// like `github.com-org-tpl-<hash>` for `https://github.com/org/tpl.git`.
+/// The short hash of the whole url keeps the urls with the same readable part apart.
+fn cache_name(url: &str) -> String {
+    let hash = format!("{:x}", Sha256::digest(url.as_bytes()));
+    let readable = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let readable = readable.strip_prefix("git@").unwrap_or(readable);
+    let readable = readable.trim_end_matches('/').trim_end_matches(".git");
+
+    let readable = readable
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+    format!("{}-{}", readable, &hash[..8])
+}
+
+/// Replace the `{{variable}}` placeholders in the text.
+/// Unknown variables are kept as they are.
+pub fn render(text: &str, vars: &Variables) -> String {
+    vars.iter().fold(text.to_owned(), |text, (key, value)| {
+        text.replace(&format!("{{{{{}}}}}", key), value)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let vars = Variables::from([("name", "demo".to_owned()), ("version", "0.1.0".to_owned())]);
+
+        assert_eq!(render("{{name}}-{{version}}", &vars), "demo-0.1.0");
+        assert_eq!(
+            render("{{ name }} {{unknown}}", &vars),
+            "{{ name }} {{unknown}}"
+        );
+    }
+
+    #[test]
+    fn test_remote() {
+        assert!(is_remote("https://github.com/org/tpl.git"));
+        assert!(is_remote("git@github.com:org/tpl.git"));
+        assert!(!is_remote("my-template"));
+        assert!(is_local("./my-template"));
+        assert!(is_local("/opt/templates/tpl"));
+        assert!(!is_local("my-template"));
+
+        assert!(!is_remote("--upload-pack=touch pwned.git"));
+
+        assert!(cache_name("https://github.com/org/tpl.git").starts_with("github.com-org-tpl-"));
+        assert_ne!(
+            cache_name("https://github.com/org/tpl.git"),
+            cache_name("git@github.com:org/tpl.git")
+        );
+        assert_ne!(
+            cache_name("https://host/a-b/c.git"),
+            cache_name("https://host/a/b-c.git")
+        );
+    }
+
+    #[test]
+    fn test_newest_template() {
+        let templates = coppo_registry::parse_templates(
+            r#"
+            [[templates]]
+            name = "qt-app"
+            version = "1.0.0"
+            checksum = "sha256:a"
+
+            [[templates]]
+            name = "qt-app"
+            version = "1.2.0"
+            checksum = "sha256:b"
+            "#,
+        )
+        .unwrap();
+
+        let template = newest_template(&templates, "qt-app").unwrap();
+        assert_eq!(template.checksum, "sha256:b");
+        assert!(newest_template(&templates, "header-only-lib").is_none());
+    }
+}
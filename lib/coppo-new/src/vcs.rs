@@ -0,0 +1,115 @@
+//! Version control initialization for the new project.
+
+use std::fmt;
+use std::path::Path;
+use std::process;
+use std::str::FromStr;
+
+use coppo_config::prelude::*;
+use coppo_logger::prelude::*;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The version control system of the new project.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Vcs {
+    /// Initialize a git repository, and create an initial commit.
+    #[default]
+    Git,
+    /// Do not initialize any version control system.
+    None,
+}
+
+impl Vcs {
+    /// All the names of the supported version control systems.
+    pub const NAMES: [&'static str; 2] = ["git", "none"];
+
+    /// The default version control system, `new.vcs` in the global configuration,
+    /// or git if it is not specified.
+    pub fn configured(global: &GlobalConfig) -> Result<Vcs> {
+        match &global.new.vcs {
+            Some(vcs) => vcs
+                .parse()
+                .map_err(|e| format!("{} in `new.vcs` of the global configuration.", e).into()),
+            None => Ok(Vcs::default()),
+        }
+    }
+
+    /// Initialize the version control system in the project directory.
+    /// It will be skipped if the directory is already inside a repository,
+    /// or with a warning if git is not installed or fails to initialize it,
+    /// the project is still usable without a repository.
+    pub fn init(&self, dir: &Path) -> Result<()> {
+        match self {
+            Vcs::Git => {
+                if git(dir, &["rev-parse", "--is-inside-work-tree"]).is_ok() {
+                    info!("Skipped git initialization, the project is already inside a git repository");
+                    return Ok(());
+                }
+
+                let initialized =
+                    git(dir, &["init", "--quiet"]).and_then(|_| git(dir, &["add", "--all"]));
+                if let Err(e) = initialized {
+                    let e = e.to_string();
+                    warn!(
+                        "Skipped git initialization: {}",
+                        e.lines().last().unwrap_or_default()
+                    );
+                    return Ok(());
+                }
+
+                // The commit may fail if the user identity is not configured,
+                // but the repository is still usable.
+                if let Err(e) = git(dir, &["commit", "--quiet", "-m", "Initial commit"]) {
+                    let e = e.to_string();
+                    warn!(
+                        "Failed to create the initial commit: {}",
+                        e.lines().last().unwrap_or_default()
+                    );
+                }
+
+                Ok(())
+            }
+            Vcs::None => Ok(()),
+        }
+    }
+}
+
+impl FromStr for Vcs {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "git" => Ok(Vcs::Git),
+            "none" => Ok(Vcs::None),
+            _ => Err(format!("Unknown version control system `{}`", s)),
+        }
+    }
+}
+
+impl fmt::Display for Vcs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Vcs::Git => write!(f, "git"),
+            Vcs::None => write!(f, "none"),
+        }
+    }
+}
+
+/// Run a git command in the specified directory, and return its stdout.
+pub(crate) fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = process::Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr)
+            .trim()
+            .to_owned()
+            .into())
+    }
+}
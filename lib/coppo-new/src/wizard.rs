@@ -0,0 +1,223 @@
+//! The interactive project creation wizard.
+//! It will be started by `coppo new --interactive`, or `coppo new` without a path.
+//!
+//! The options specified on the command line will be the defaults of the questions.
+
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+use coppo_config::{name, standard};
+
+use crate::license;
+use crate::style::Style;
+use crate::vcs::Vcs;
+use crate::{parse_std, CoppoNew};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The wizard which asks the user questions through the reader and the writer.
+pub struct Wizard<R, W> {
+    input: R,
+    output: W,
+}
+
+impl Wizard<io::StdinLock<'static>, io::Stdout> {
+    /// Create a wizard on the standard input and output.
+    pub fn stdio() -> Self {
+        Wizard::new(io::stdin().lock(), io::stdout())
+    }
+}
+
+impl<R: BufRead, W: Write> Wizard<R, W> {
+    /// Create a wizard on the specified reader and writer.
+    pub fn new(input: R, output: W) -> Self {
+        Self { input, output }
+    }
+
+    /// Ask all the questions, and fill the answers into the options.
+    pub fn run(&mut self, new: &mut CoppoNew) -> Result<()> {
+        let path = self.ask("Project path", &new.path.to_string_lossy(), |path| {
+            if path.is_empty() {
+                Err("The project path can not be empty".to_owned())
+            } else {
+                Ok(PathBuf::from(path))
+            }
+        })?;
+        if path != new.path {
+            // The default name follows the new path.
+            new.name = CoppoNew::default_name(&path).unwrap_or_default();
+            new.path = path;
+        }
+
+        // Suggest a valid name if the default one is invalid.
+        let default_name = match name::validate(&new.name) {
+            Ok(()) => new.name.clone(),
+            Err(_) => name::normalize(&new.name),
+        };
+        new.name = self.ask("Project name", &default_name, |name| {
+            name::validate(name).map(|_| name.to_owned())
+        })?;
+
+        new.lib = self.ask(
+            "Library target (empty for none)",
+            &new.lib.clone().unwrap_or_default(),
+            |lib| match lib {
+                "" => Ok(None),
+                lib => name::validate(lib).map(|_| Some(lib.to_owned())),
+            },
+        )?;
+
+        new.bins = self.ask(
+            "Binary targets (comma separated, empty for src/main.cpp)",
+            &new.bins.join(","),
+            |bins| {
+                bins.split(',')
+                    .map(str::trim)
+                    .filter(|bin| !bin.is_empty())
+                    .map(|bin| name::validate(bin).map(|_| bin.to_owned()))
+                    .collect()
+            },
+        )?;
+
+        let language = new.language;
+        let default_std = new
+            .std
+            .clone()
+            .unwrap_or_else(|| standard::default_std(language).to_owned());
+        new.std = self.ask(&format!("{} standard", language), &default_std, |std| {
+            parse_std(std, language)
+        })?;
+
+        let default_license = new.license.clone().unwrap_or_default();
+        new.license = self.ask(
+            "License (SPDX expression, empty for none)",
+            &default_license,
+            |expr| match expr {
+                "" => Ok(None),
+                expr => license::parse(expr)
+                    .map(|licenses| Some(license::expression(&licenses)))
+                    .map_err(|e| e.to_string()),
+            },
+        )?;
+
+        new.vcs = self.ask(
+            &format!("Version control ({})", Vcs::NAMES.join("/")),
+            &new.vcs.to_string(),
+            |vcs| vcs.parse(),
+        )?;
+
+        let default_style = Style::NAMES
+            .iter()
+            .find(|name| name.parse::<Style>().ok() == new.style)
+            .copied()
+            .unwrap_or("none");
+        new.style = self.ask(
+            &format!("Code style (none/{})", Style::NAMES.join("/")),
+            default_style,
+            |style| match style {
+                "none" => Ok(None),
+                style => style.parse().map(Some),
+            },
+        )?;
+
+        new.cmake_compat = self.ask(
+            "Create a CMakeLists.txt (y/n)",
+            if new.cmake_compat { "y" } else { "n" },
+            yes_or_no,
+        )?;
+
+        new.readme = self.ask(
+            "Create a README.md (y/n)",
+            if new.readme { "y" } else { "n" },
+            yes_or_no,
+        )?;
+
+        new.with_tests = self.ask(
+            "Create a basic test in tests/ (y/n)",
+            if new.with_tests { "y" } else { "n" },
+            yes_or_no,
+        )?;
+
+        new.with_examples = self.ask(
+            "Create an example in examples/ (y/n)",
+            if new.with_examples { "y" } else { "n" },
+            yes_or_no,
+        )?;
+
+        Ok(())
+    }
+
+    /// Ask a question until the answer is accepted by the parser.
+    /// An empty answer means the default value.
+    pub fn ask<T>(
+        &mut self,
+        question: &str,
+        default: &str,
+        parse: impl Fn(&str) -> std::result::Result<T, String>,
+    ) -> Result<T> {
+        loop {
+            if default.is_empty() {
+                write!(self.output, "{}: ", question)?;
+            } else {
+                write!(self.output, "{} [{}]: ", question, default)?;
+            }
+            self.output.flush()?;
+
+            let mut answer = String::new();
+            if self.input.read_line(&mut answer)? == 0 {
+                return Err("The interactive input is closed".into());
+            }
+
+            let answer = match answer.trim() {
+                "" => default,
+                answer => answer,
+            };
+
+            match parse(answer) {
+                Ok(value) => return Ok(value),
+                Err(e) => writeln!(self.output, "{}", e)?,
+            }
+        }
+    }
+}
+
+/// Parse a `y/n` answer.
+pub(crate) fn yes_or_no(answer: &str) -> std::result::Result<bool, String> {
+    match answer.to_ascii_lowercase().as_str() {
+        "y" | "yes" => Ok(true),
+        "n" | "no" => Ok(false),
+        _ => Err(format!("Please answer `y` or `n`, not `{}`", answer)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_wizard() -> Result<()> {
+        let input = "demo\n\ncore\nserver, client\n2x\n20\nmit\nnone\nwhatever\nllvm\n\ny\ny\n\n";
+        let mut output = vec![];
+        let mut new = CoppoNew::default();
+
+        Wizard::new(input.as_bytes(), &mut output).run(&mut new)?;
+
+        assert_eq!(new.path, PathBuf::from("demo"));
+        assert_eq!(new.name, "demo");
+        assert_eq!(new.lib.as_deref(), Some("core"));
+        assert_eq!(new.bins, vec!["server", "client"]);
+        assert_eq!(new.std.as_deref(), Some("c++20"));
+        assert_eq!(new.license.as_deref(), Some("MIT"));
+        assert_eq!(new.vcs, Vcs::None);
+        assert_eq!(new.style, Some(Style::Llvm));
+        assert!(!new.cmake_compat);
+        assert!(new.readme);
+        assert!(new.with_tests);
+        assert!(!new.with_examples);
+        let output = String::from_utf8(output)?;
+        assert!(output.contains("The C++ standard `2x` is invalid"));
+        assert!(output.contains("Unknown code style `whatever`"));
+
+        Ok(())
+    }
+}
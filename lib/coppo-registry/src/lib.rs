@@ -0,0 +1,611 @@
+//! The `coppo-registry` crate downloads the packages of the registries,
+//! the `[registries.<name>]` tables of the global configuration.
+//!
+//! A dependency is downloaded from a registry with `registry = "<name>"`:
+//!
+//! ```toml
+//! [dependencies]
+//! fmt = { version = "10.2", registry = "company" }
+//! ```
+//!
+//! A registry is served over HTTP, or from a directory with a `file://` URL, and is laid out like this:
+//! - `<index>/<name>.toml`, the published versions of the package, see [`Release`].
+//! - `<index>/<name>/<name>-<version>.tar.gz`, the archive of a version, a Coppo project.
+//!
+//! ```toml
+//! [[versions]]
+//! version = "10.2.1"
+//! checksum = "sha256:2f3c..."
+//! dependencies = { zlib = "1.3" }
+//! ```
+//!
+//! The dependencies of a version are packages of the same registry.
+//! The files are downloaded with `curl`, and an archive is extracted
+//! into `~/.coppo/cache/downloads/<name>/<version>` once its checksum is verified,
+//! so it is only downloaded once.
+//! The mirrors of a registry are tried before its index, and the downloads use
+//! the proxy, the timeout and the retries of `[http]`, see [`HttpSettings`].
+//! The packages copied into `vendor/<name>-<version>` by `coppo vendor` are used instead,
+//! see [`locate`].
+//!
+//! A registry can also publish project templates for `coppo new --template <name>`,
+//! its catalog is `<index>/templates.toml`, see [`TemplateRelease`],
+//! and the archive of a template is `<index>/templates/<name>-<version>.tar.gz`.
+//!
+//! ```toml
+//! [[templates]]
+//! name = "qt-app"
+//! description = "A Qt widgets application."
+//! version = "1.0.0"
+//! checksum = "sha256:9a1b..."
+//! ```
+//!
+//! With `coppo --offline`, nothing is downloaded, see [`set_offline`]:
+//! the packages must be locked, and downloaded or vendored already.
+
+#![forbid(unsafe_code)]
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use coppo_config::download;
+use coppo_config::global::{GlobalConfig, HttpSettings};
+use coppo_config::lock::LockedPackage;
+use coppo_config::{download_cache, name, CONFIG_FILE};
+use coppo_logger::prelude::*;
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The prefix of the source of the packages of a registry in the lockfile,
+/// followed by the URL of the index, like `registry+https://example.com/index`.
+pub const SOURCE_PREFIX: &str = "registry+";
+
+/// The directory of the vendored packages in the project.
+pub const VENDOR_DIR: &str = "vendor";
+
+/// The file of a vendored package with the checksum of its archive.
+pub const VENDOR_CHECKSUM_FILE: &str = ".coppo-checksum";
+
+/// The catalog of the templates of a registry, relative to its index.
+pub const TEMPLATES_FILE: &str = "templates.toml";
+
+/// The file of a downloaded template with the checksum of its archive.
+pub const TEMPLATE_CHECKSUM_FILE: &str = ".coppo-checksum";
+
+/// Whether nothing is downloaded, see [`set_offline`].
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable the offline mode, it is disabled by default.
+/// In the offline mode, the indexes and the archives are never downloaded.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// Check if the offline mode is enabled.
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// The error of a download in the offline mode.
+fn offline_error(what: String) -> Box<dyn std::error::Error> {
+    format!(
+        "{} needs the network, but `--offline` is given.\n\
+        help: build once without `--offline`, or run `coppo vendor`",
+        what
+    )
+    .into()
+}
+
+/// The index of a registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Index {
+    /// The URL of the index, without the trailing slash.
+    pub url: String,
+    /// The URLs of the mirrors of the index, without the trailing slash.
+    pub mirrors: Vec<String>,
+    /// The settings of the downloads.
+    pub http: HttpSettings,
+}
+
+/// A published version of a package, a `[[versions]]` of its index file.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Release {
+    /// The version.
+    pub version: Version,
+    /// The checksum of the archive, like `sha256:<hex>`.
+    pub checksum: String,
+    /// The packages of the registry it depends on, with their version requirements.
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, VersionReq>,
+    /// Whether the version is withdrawn, it is never selected.
+    #[serde(default)]
+    pub yanked: bool,
+}
+
+/// The index file of a package.
+#[derive(Debug, Deserialize)]
+struct IndexFile {
+    #[serde(default)]
+    versions: Vec<Release>,
+}
+
+/// A published project template, a `[[templates]]` of the catalog of the registry.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TemplateRelease {
+    /// The name of the template, like `qt-app`.
+    pub name: String,
+    /// What the template creates.
+    pub description: Option<String>,
+    /// The version of the template.
+    pub version: Version,
+    /// The checksum of the archive, like `sha256:<hex>`.
+    pub checksum: String,
+}
+
+/// The catalog of the templates of a registry.
+#[derive(Debug, Deserialize)]
+struct TemplatesFile {
+    #[serde(default)]
+    templates: Vec<TemplateRelease>,
+}
+
+impl Index {
+    /// The index of the URL.
+    pub fn new(url: &str) -> Self {
+        Index {
+            url: url.trim_end_matches('/').to_owned(),
+            mirrors: vec![],
+            http: HttpSettings::default(),
+        }
+    }
+
+    /// The index of the URL, with the mirrors of its registry
+    /// and the settings of the downloads in the global configuration.
+    pub fn configured(global: &GlobalConfig, url: &str) -> Self {
+        let mut index = Index::new(url);
+        index.mirrors = global
+            .registries
+            .values()
+            .filter(|registry| registry.index.trim_end_matches('/') == index.url)
+            .flat_map(|registry| &registry.mirrors)
+            .map(|mirror| mirror.trim_end_matches('/').to_owned())
+            .collect();
+        index.http = global.http.clone();
+        index
+    }
+
+    /// The index of the registry named in the global configuration.
+    pub fn of(global: &GlobalConfig, registry: &str) -> Result<Self> {
+        match global.registries.get(registry) {
+            Some(registry) => Ok(Index::configured(global, &registry.index)),
+            None => Err(format!(
+                "The registry `{0}` is not found.\n\
+                help: add it to `~/.coppo/config.toml` like `[registries.{0}]` with `index = \"<URL>\"`",
+                registry
+            )
+            .into()),
+        }
+    }
+
+    /// The index of the source of a locked package, like `registry+https://example.com/index`.
+    pub fn from_source(source: &str, global: &GlobalConfig) -> Option<Self> {
+        source
+            .strip_prefix(SOURCE_PREFIX)
+            .map(|url| Index::configured(global, url))
+    }
+
+    /// The source of the packages of the index in the lockfile.
+    pub fn source(&self) -> String {
+        format!("{}{}", SOURCE_PREFIX, self.url)
+    }
+
+    /// The URL of the index file of the package.
+    pub fn file_url(&self, name: &str) -> String {
+        format!("{}/{}.toml", self.url, name)
+    }
+
+    /// The URL of the archive of the version of the package.
+    pub fn archive_url(&self, name: &str, version: &Version) -> String {
+        format!("{0}/{1}/{1}-{2}.tar.gz", self.url, name, version)
+    }
+
+    /// The URL of the catalog of the templates.
+    pub fn templates_url(&self) -> String {
+        format!("{}/{}", self.url, TEMPLATES_FILE)
+    }
+
+    /// The URL of the archive of the template.
+    pub fn template_archive_url(&self, template: &TemplateRelease) -> String {
+        format!(
+            "{}/templates/{}-{}.tar.gz",
+            self.url, template.name, template.version
+        )
+    }
+
+    /// The URL of the index and the ones of its mirrors, the mirrors first.
+    ///
+    /// ```rust
+    /// use coppo_registry::Index;
+    ///
+    /// let mut index = Index::new("https://example.com/index");
+    /// index.mirrors.push("https://mirror.example.com".to_string());
+    /// assert_eq!(
+    ///     index.mirrored("https://example.com/index/fmt.toml"),
+    ///     ["https://mirror.example.com/fmt.toml", "https://example.com/index/fmt.toml"]
+    /// );
+    /// ```
+    pub fn mirrored(&self, url: &str) -> Vec<String> {
+        let Some(path) = url.strip_prefix(&self.url) else {
+            return vec![url.to_owned()];
+        };
+        self.mirrors
+            .iter()
+            .map(|mirror| format!("{}{}", mirror, path))
+            .chain([url.to_owned()])
+            .collect()
+    }
+
+    /// Download the file of the index with `curl`, from the mirrors first,
+    /// and return the content, or write it into `output`.
+    /// The error is the one of the last URL.
+    fn download(&self, url: &str, output: Option<&Path>) -> std::result::Result<Vec<u8>, String> {
+        let mut error = String::new();
+        for url in self.mirrored(url) {
+            if !error.is_empty() {
+                warn!("{}, trying `{}`...", error, url);
+            }
+            match download::download(&url, output, &self.http, false) {
+                Ok(content) => return Ok(content),
+                Err(e) => error = e,
+            }
+        }
+
+        Err(error)
+    }
+
+    /// Download the published versions of the package.
+    pub fn releases(&self, name: &str) -> Result<Vec<Release>> {
+        name::validate(name)?;
+        if is_offline() {
+            return Err(offline_error(format!("Resolving `{}`", name)));
+        }
+        let url = self.file_url(name);
+        let content = self.download(&url, None).map_err(|e| {
+            format!(
+                "The package `{}` is not found in the registry `{}`.\n{}",
+                name, self.url, e
+            )
+        })?;
+
+        parse_releases(&String::from_utf8_lossy(&content))
+            .map_err(|e| format!("Failed to parse `{}`: {}", url, e).into())
+    }
+
+    /// Download the catalog of the templates, it is empty if the registry does not publish any.
+    pub fn templates(&self) -> Result<Vec<TemplateRelease>> {
+        if is_offline() {
+            return Err(offline_error(format!(
+                "Listing the templates of `{}`",
+                self.url
+            )));
+        }
+        let url = self.templates_url();
+        // A registry without templates does not have the catalog.
+        let Ok(content) = self.download(&url, None) else {
+            return Ok(vec![]);
+        };
+
+        parse_templates(&String::from_utf8_lossy(&content))
+            .map_err(|e| format!("Failed to parse `{}`: {}", url, e).into())
+    }
+
+    /// Select the newest version of the package matching the requirement.
+    pub fn select(&self, name: &str, requirement: &VersionReq) -> Result<Release> {
+        let releases = self.releases(name)?;
+        newest(&releases, requirement).cloned().ok_or_else(|| {
+            format!(
+                "The dependency `{}` requires `{}`, but the registry `{}` does not have such a version.",
+                name, requirement, self.url
+            )
+            .into()
+        })
+    }
+
+    /// The published version of the package, even if it is yanked.
+    pub fn release(&self, name: &str, version: &Version) -> Result<Release> {
+        let releases = self.releases(name)?;
+        match releases
+            .into_iter()
+            .find(|release| release.version == *version)
+        {
+            Some(release) => Ok(release),
+            None => Err(format!(
+                "The version `{}` of `{}` is not found in the registry `{}`.",
+                version, name, self.url
+            )
+            .into()),
+        }
+    }
+}
+
+/// Parse the index file of a package.
+///
+/// # Example
+/// ```rust
+/// use coppo_registry::parse_releases;
+///
+/// let releases = parse_releases(r#"
+///     [[versions]]
+///     version = "10.2.1"
+///     checksum = "sha256:2f3c"
+/// "#).unwrap();
+/// assert_eq!(releases[0].version.to_string(), "10.2.1");
+/// ```
+pub fn parse_releases(content: &str) -> std::result::Result<Vec<Release>, toml::de::Error> {
+    toml::from_str::<IndexFile>(content).map(|file| file.versions)
+}
+
+/// Parse the catalog of the templates of a registry.
+///
+/// # Example
+/// ```rust
+/// use coppo_registry::parse_templates;
+///
+/// let templates = parse_templates(r#"
+///     [[templates]]
+///     name = "qt-app"
+///     version = "1.0.0"
+///     checksum = "sha256:9a1b"
+/// "#).unwrap();
+/// assert_eq!(templates[0].name, "qt-app");
+/// ```
+pub fn parse_templates(
+    content: &str,
+) -> std::result::Result<Vec<TemplateRelease>, toml::de::Error> {
+    toml::from_str::<TemplatesFile>(content).map(|file| file.templates)
+}
+
+/// The newest version matching the requirement, the yanked ones are skipped.
+pub fn newest<'a>(releases: &'a [Release], requirement: &VersionReq) -> Option<&'a Release> {
+    releases
+        .iter()
+        .filter(|release| !release.yanked && requirement.matches(&release.version))
+        .max_by(|a, b| a.version.cmp(&b.version))
+}
+
+/// The checksum of the file, like `sha256:<hex>`.
+pub fn checksum(path: &Path) -> Result<String> {
+    Ok(download::checksum(path)?)
+}
+
+/// The directory of the downloaded version of the package,
+/// like `~/.coppo/cache/downloads/<name>/<version>`.
+pub fn package_dir(name: &str, version: &Version) -> Result<PathBuf> {
+    let cache = download_cache().ok_or("Could not find the Coppo home directory.")?;
+    Ok(cache.join(name).join(version.to_string()))
+}
+
+/// The directory of the vendored copy of the package, like `vendor/fmt-10.2.1`.
+pub fn vendor_dir(vendor: &Path, package: &LockedPackage) -> PathBuf {
+    vendor.join(format!("{}-{}", package.name, package.version))
+}
+
+/// The vendored copy of the locked package, if there is one.
+/// It must have the locked checksum, otherwise it is vendored for another lockfile.
+pub fn vendored(vendor: &Path, package: &LockedPackage) -> Result<Option<PathBuf>> {
+    let dir = vendor_dir(vendor, package);
+    let Ok(checksum) = fs::read_to_string(dir.join(VENDOR_CHECKSUM_FILE)) else {
+        return Ok(None);
+    };
+    if Some(checksum.trim()) != package.checksum.as_deref() {
+        return Err(format!(
+            "The vendored `{}` {} does not match `Coppo.lock`.\n\
+            help: run `coppo vendor` to vendor the locked packages again",
+            package.name, package.version
+        )
+        .into());
+    }
+
+    Ok(Some(dir))
+}
+
+/// The directory of the locked package of a registry,
+/// the vendored copy in `vendor` if there is one, otherwise the downloaded one.
+pub fn locate(package: &LockedPackage, vendor: &Path, global: &GlobalConfig) -> Result<PathBuf> {
+    match vendored(vendor, package)? {
+        Some(dir) => Ok(dir),
+        None => fetch_locked(package, global),
+    }
+}
+
+/// Whether the locked package of a registry is vendored or downloaded already,
+/// so it can be used offline.
+pub fn is_available(package: &LockedPackage, vendor: &Path) -> bool {
+    if vendored(vendor, package).is_ok_and(|dir| dir.is_some()) {
+        return true;
+    }
+    Version::parse(&package.version)
+        .ok()
+        .and_then(|version| package_dir(&package.name, &version).ok())
+        .is_some_and(|dir| dir.join(CONFIG_FILE).is_file())
+}
+
+/// Download the locked package of a registry, and return its directory.
+pub fn fetch_locked(package: &LockedPackage, global: &GlobalConfig) -> Result<PathBuf> {
+    let index = Index::from_source(&package.source, global)
+        .ok_or_else(|| format!("The package `{}` is not from a registry.", package.name))?;
+    let version = Version::parse(&package.version)?;
+    let checksum = package
+        .checksum
+        .as_deref()
+        .ok_or_else(|| format!("The package `{}` does not have a checksum.", package.name))?;
+
+    fetch(&index, &package.name, &version, checksum)
+}
+
+/// Download and extract the version of the package, and return its directory.
+/// The archive must have the checksum, and it is not downloaded again once extracted.
+pub fn fetch(index: &Index, name: &str, version: &Version, checksum: &str) -> Result<PathBuf> {
+    name::validate(name)?;
+    let dir = package_dir(name, version)?;
+    if dir.join(CONFIG_FILE).is_file() {
+        return Ok(dir);
+    }
+    if is_offline() {
+        return Err(offline_error(format!("Downloading `{}` {}", name, version)));
+    }
+
+    let url = index.archive_url(name, version);
+    let label = format!("`{}` {}", name, version);
+    unpack(index, &url, checksum, &label, &dir, |root| {
+        if root.join(CONFIG_FILE).is_file() {
+            return Ok(());
+        }
+        Err(format!("The package {} does not have a `{}`.", label, CONFIG_FILE).into())
+    })?;
+
+    Ok(dir)
+}
+
+/// Download and extract the template of the registry into the directory,
+/// it replaces the previous version of the template.
+/// The checksum of the archive is written into [`TEMPLATE_CHECKSUM_FILE`] of the directory,
+/// so the template is not downloaded again until a new version is published.
+pub fn fetch_template(index: &Index, template: &TemplateRelease, dir: &Path) -> Result<()> {
+    name::validate(&template.name)?;
+    let marker = dir.join(TEMPLATE_CHECKSUM_FILE);
+    if fs::read_to_string(&marker).is_ok_and(|checksum| checksum.trim() == template.checksum) {
+        return Ok(());
+    }
+    if is_offline() {
+        return Err(offline_error(format!(
+            "Downloading the template `{}`",
+            template.name
+        )));
+    }
+
+    let url = index.template_archive_url(template);
+    let label = format!("the template `{}` {}", template.name, template.version);
+    unpack(index, &url, &template.checksum, &label, dir, |_| Ok(()))?;
+    fs::write(marker, &template.checksum)?;
+
+    Ok(())
+}
+
+/// Download the archive of the index, verify its checksum and extract it into the directory,
+/// see [`download::unpack`].
+fn unpack(
+    index: &Index,
+    url: &str,
+    checksum: &str,
+    label: &str,
+    dir: &Path,
+    check: impl FnOnce(&Path) -> Result<()>,
+) -> Result<()> {
+    let archive = url.rsplit('/').next().unwrap_or_default();
+    download::unpack(
+        dir,
+        archive,
+        checksum,
+        label,
+        |archive| {
+            info!("Downloading {}...", label);
+            index.download(url, Some(archive))?;
+            Ok(())
+        },
+        check,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_select() {
+        let releases = parse_releases(
+            r#"
+            [[versions]]
+            version = "1.2.0"
+            checksum = "sha256:a"
+
+            [[versions]]
+            version = "1.3.0"
+            checksum = "sha256:b"
+            dependencies = { zlib = "1.3" }
+
+            [[versions]]
+            version = "1.4.0"
+            checksum = "sha256:c"
+            yanked = true
+
+            [[versions]]
+            version = "2.0.0"
+            checksum = "sha256:d"
+            "#,
+        )
+        .unwrap();
+
+        let release = newest(&releases, &VersionReq::parse("1.2").unwrap()).unwrap();
+        assert_eq!(release.version, Version::new(1, 3, 0));
+        assert_eq!(release.dependencies["zlib"].to_string(), "^1.3");
+        assert!(newest(&releases, &VersionReq::parse("^3").unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_index() {
+        let index = Index::new("https://example.com/index/");
+        assert_eq!(index.file_url("fmt"), "https://example.com/index/fmt.toml");
+        assert_eq!(
+            index.archive_url("fmt", &Version::new(10, 2, 1)),
+            "https://example.com/index/fmt/fmt-10.2.1.tar.gz"
+        );
+        assert_eq!(
+            index.templates_url(),
+            "https://example.com/index/templates.toml"
+        );
+        let template = TemplateRelease {
+            name: "qt-app".to_string(),
+            description: None,
+            version: Version::new(1, 0, 0),
+            checksum: "sha256:a".to_string(),
+        };
+        assert_eq!(
+            index.template_archive_url(&template),
+            "https://example.com/index/templates/qt-app-1.0.0.tar.gz"
+        );
+        let global = GlobalConfig::default();
+        assert_eq!(Index::from_source(&index.source(), &global), Some(index));
+        assert_eq!(Index::from_source("system", &global), None);
+
+        let error = Index::of(&GlobalConfig::default(), "company")
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("The registry `company` is not found."));
+
+        let global = toml::from_str::<GlobalConfig>(
+            r#"
+            [registries.company]
+            index = "https://example.com/index"
+            mirrors = ["https://mirror.example.com/index/"]
+            "#,
+        )
+        .unwrap();
+        let index = Index::of(&global, "company").unwrap();
+        assert_eq!(index.mirrors, ["https://mirror.example.com/index"]);
+        assert_eq!(
+            Index::from_source(&index.source(), &global)
+                .unwrap()
+                .mirrors,
+            index.mirrors
+        );
+        assert_eq!(
+            index.mirrored("https://example.com/index/fmt.toml")[0],
+            "https://mirror.example.com/index/fmt.toml"
+        );
+    }
+}
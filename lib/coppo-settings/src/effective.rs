@@ -0,0 +1,165 @@
+//! The effective configuration, printed by `coppo config show`.
+//!
+//! It merges the manifest of the project, the global configuration and the environment,
+//! and tells where the compiler and the directories come from:
+//!
+//! ```toml
+//! compiler = "g++"
+//! wrapper = "ccache"
+//! target-dir = "/home/me/app/target"
+//! coppo-home = "/home/me/.coppo"
+//! flags = ["-Wall", "-std=c++20", "-Iinclude"]
+//! ldflags = []
+//! features = ["default", "logging"]
+//!
+//! [sources]
+//! compiler = "global"
+//! target-dir = "default"
+//! coppo-home = "default"
+//!
+//! [global]
+//! # The content of `~/.coppo/config.toml`.
+//!
+//! [manifest]
+//! # The content of `Coppo.toml`.
+//! ```
+
+use std::env;
+
+use coppo_build::{compiler_source, flags, target_dir, wrapper, TARGET_DIR_ENV};
+use coppo_config::prelude::*;
+use serde::Serialize;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The formats of `coppo config show`.
+pub const FORMATS: &[&str] = &["toml", "json"];
+
+/// The effective configuration.
+/// The values come first, as TOML can not have a value after a table.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Effective {
+    /// The compiler, see [`coppo_build::compiler`].
+    pub compiler: String,
+    /// The compiler wrapper, see [`coppo_build::wrapper`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wrapper: Option<String>,
+    /// The output directory.
+    pub target_dir: String,
+    /// The Coppo home directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coppo_home: Option<String>,
+    /// The compile flags shared by the sources, without the ones of the profile.
+    pub flags: Vec<String>,
+    /// The link flags.
+    pub ldflags: Vec<String>,
+    /// The enabled features.
+    pub features: Vec<String>,
+    /// Where the values come from.
+    pub sources: Sources,
+    /// The global configuration.
+    pub global: GlobalConfig,
+    /// The manifest of the project, it is `None` outside a project.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<toml::Value>,
+}
+
+/// Where the values of the effective configuration come from.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Sources {
+    /// `toolchain`, `project`, `global`, `detected` or `default`.
+    pub compiler: &'static str,
+    /// `environment` or `default`.
+    pub target_dir: &'static str,
+    /// `environment` or `default`.
+    pub coppo_home: &'static str,
+}
+
+impl Effective {
+    /// Merge the configuration of the project with the global one and the environment.
+    pub fn resolve(config: &Config) -> Result<Self> {
+        let (compiler, compiler_from) = compiler_source(config);
+        let from_env = |key: &str| match env::var_os(key) {
+            Some(value) if !value.is_empty() => "environment",
+            _ => "default",
+        };
+        let target = target_dir();
+        let target = match target.is_absolute() {
+            true => target,
+            false => env::current_dir()?.join(target),
+        };
+        let manifest = match config.is_empty() && config.workspace.is_none() {
+            true => None,
+            false => Some(toml::Value::try_from(config)?),
+        };
+
+        Ok(Effective {
+            compiler,
+            wrapper: wrapper(config),
+            target_dir: target.display().to_string(),
+            coppo_home: coppo_home().map(|home| home.display().to_string()),
+            flags: flags(config),
+            ldflags: config.build.ldflags.clone(),
+            features: config.enabled_features()?.into_iter().collect(),
+            sources: Sources {
+                compiler: compiler_from,
+                target_dir: from_env(TARGET_DIR_ENV),
+                coppo_home: from_env("COPPO_HOME"),
+            },
+            global: config.global.clone(),
+            manifest,
+        })
+    }
+
+    /// Render the configuration in the format, `toml` or `json`.
+    pub fn render(&self, format: &str) -> Result<String> {
+        match format {
+            "toml" => Ok(toml::to_string(self)?),
+            "json" => Ok(serde_json::to_string_pretty(self)?),
+            _ => Err(format!(
+                "The format `{}` is not supported, use one of {}.",
+                format,
+                FORMATS.join(", ")
+            )
+            .into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() -> Result<()> {
+        let mut config = Config::from_str(
+            r#"
+            [project]
+            name = "app"
+            version = "0.1.0"
+            authors = []
+
+            [build]
+            compiler = "g++"
+            std = "20"
+            "#,
+        )?;
+        config.global.build.flags = vec!["-Wall".to_string()];
+
+        let effective = Effective::resolve(&config)?;
+        let text = effective.render("toml")?;
+        if effective.sources.compiler == "project" {
+            assert!(text.starts_with("compiler = \"g++\"\n"));
+        }
+        assert!(text.contains("flags = [\"-Wall\", \"-std=c++20\"]"));
+        assert!(text.contains("[manifest.project]\nauthors = []\nname = \"app\""));
+
+        let json = serde_json::from_str::<serde_json::Value>(&effective.render("json")?)?;
+        assert_eq!(json["manifest"]["build"]["std"], "20");
+        assert!(effective.render("yaml").is_err());
+
+        Ok(())
+    }
+}
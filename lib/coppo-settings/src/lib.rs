@@ -0,0 +1,95 @@
+//! The `coppo-settings` crate is a Coppo addon that reads and writes
+//! the global configuration `~/.coppo/config.toml`.
+//!
+//! Only the known keys can be set, and the formatting and the comments of the file are kept.
+//!
+//! # Usage
+//! ```sh
+//! coppo config get <KEY>
+//! coppo config get --list
+//! coppo config set <KEY> <VALUE>
+//! coppo config unset <KEY>
+//! coppo config show [--format <toml|json>]
+//! ```
+//!
+//! `coppo config show` prints the effective configuration of the project, see [`effective`].
+
+#![forbid(unsafe_code)]
+
+pub mod effective;
+
+use coppo_addons::prelude::*;
+use coppo_config::global::KEYS;
+use coppo_config::prelude::*;
+use coppo_logger::prelude::*;
+
+use effective::{Effective, FORMATS};
+
+/// The `Coppo config` add-on.
+/// Get or set the keys of the global configuration, like `coppo config set build.compiler g++`,
+/// or show the effective configuration of the project with `coppo config show`.
+pub struct CoppoConfigAddon;
+
+impl_addon! {
+    CoppoConfigAddon,
+    name => "config",
+    description => "Get or set the global configuration",
+    args => [
+        arg!(<ACTION> "The action to do")
+            .value_parser(["get", "set", "unset", "show"]),
+        arg!([KEY] "The key, like `build.compiler`")
+            .value_parser(value_parser!(String)),
+        arg!([VALUE] "The value to set, a list is given as `a, b` or as a TOML array")
+            .value_parser(value_parser!(String)),
+        arg!(--list "List all the keys which are set")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("KEY"),
+        arg!(--format <FORMAT> "The format of `show`")
+            .value_parser(FORMATS.to_vec())
+            .default_value("toml"),
+    ],
+    run => |config, matches| {
+        let key = matches.get_one::<String>("KEY");
+        let value = matches.get_one::<String>("VALUE");
+
+        match (matches.get_one::<String>("ACTION").map(String::as_str), key, value) {
+            (Some("get"), None, None) if matches.get_flag("list") => {
+                for (key, value) in GlobalConfig::list()? {
+                    println!("{} = {}", key, value);
+                }
+            }
+            (Some("get"), Some(key), None) => match GlobalConfig::get(key)? {
+                Some(value) => println!("{}", value),
+                None => return Err(format!("The key `{}` is not set.", key).into()),
+            },
+            (Some("set"), Some(key), Some(value)) => {
+                GlobalConfig::set(key, value)?;
+                success!("`{}` is set to `{}`.", key, value);
+            }
+            (Some("unset"), Some(key), None) => {
+                GlobalConfig::unset(key)?;
+                success!("`{}` is unset.", key);
+            }
+            (Some("show"), None, None) => {
+                let format = matches.get_one::<String>("format").map_or("toml", String::as_str);
+                print!("{}", Effective::resolve(config)?.render(format)?.trim_end());
+                println!();
+            }
+            _ => return Err(usage().into()),
+        }
+    }
+}
+
+/// The usage of the add-on, with the available keys.
+fn usage() -> String {
+    let mut usage =
+        "Usage: coppo config get <KEY> | get --list | set <KEY> <VALUE> | unset <KEY> | show\n\
+        \n\
+        The available keys are:\n"
+            .to_owned();
+    for key in KEYS {
+        usage += &format!("  {:<16}{}\n", key.name, key.description);
+    }
+
+    usage.trim_end().to_owned()
+}
@@ -0,0 +1,302 @@
+//! The `coppo bloat` add-on, it lists the largest functions of a binary.
+//!
+//! The function sizes are read from the symbol table of the binary,
+//! and the C++ names are demangled, so the functions can be grouped by their top namespace,
+//! like `std`, or by their source file.
+//! The source file is only known for the local functions of the ELF binaries,
+//! which follow the `FILE` symbol of their translation unit.
+//!
+//! # Usage
+//! ```sh
+//! coppo bloat [--bin <NAME>] [-n <N>] [--group-by <symbol|namespace|file>]
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use coppo_addons::prelude::*;
+use coppo_build::{bin_path, build, profile_arg, release_arg, select, selected_profile, Runnable};
+use coppo_logger::prelude::*;
+use object::{Object, ObjectSection, ObjectSymbol, SymbolKind};
+
+use crate::{format_size, Result};
+
+/// The group of the functions without namespace or source file.
+const UNKNOWN: &str = "[unknown]";
+
+/// The `Coppo bloat` add-on.
+/// List the largest functions of a binary of the current project.
+pub struct CoppoBloatAddon;
+
+impl_addon! {
+    CoppoBloatAddon,
+    name => "bloat",
+    description => "List the largest functions of the binary",
+    args => [
+        arg!(--bin <NAME> "The binary target to analyze")
+            .value_parser(value_parser!(String)),
+        arg!(-n <N> "The number of the items to list")
+            .value_parser(value_parser!(usize))
+            .default_value("20"),
+        arg!(--"group-by" <GROUP> "Group the functions by symbol, namespace or file")
+            .value_parser(GroupBy::NAMES)
+            .default_value("symbol"),
+        release_arg(),
+        profile_arg(),
+        arg!(--"no-build" "Analyze the existing binary without building it")
+            .action(ArgAction::SetTrue),
+    ],
+    run => |config, matches| {
+        let Runnable::Bin(bin) = select(config, matches.get_one::<String>("bin").map(String::as_str), None)? else {
+            unreachable!("only binaries are selected");
+        };
+        let profile = selected_profile(matches);
+        if !matches.get_flag("no-build") {
+            build(config, profile)?;
+        }
+
+        let group_by = matches.get_one::<String>("group-by").unwrap().parse::<GroupBy>()?;
+        let path = bin_path(config, &bin, profile);
+        info!("Analyzing `{}`...", path);
+        let bloat = Bloat::new(Path::new(&path))?;
+        print!("{}", bloat.render(group_by, *matches.get_one::<usize>("N").unwrap()));
+    }
+}
+
+/// How the functions are grouped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    /// Every function is listed alone.
+    Symbol,
+    /// The functions are grouped by their top namespace, like `std`.
+    Namespace,
+    /// The functions are grouped by their source file.
+    File,
+}
+
+impl GroupBy {
+    /// All the names of the groupings.
+    pub const NAMES: [&'static str; 3] = ["symbol", "namespace", "file"];
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "symbol" => Ok(GroupBy::Symbol),
+            "namespace" => Ok(GroupBy::Namespace),
+            "file" => Ok(GroupBy::File),
+            _ => Err(format!("Unknown grouping `{}`", s)),
+        }
+    }
+}
+
+/// A function of the binary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function {
+    /// The demangled name.
+    pub name: String,
+    /// The size of the machine code.
+    pub size: u64,
+    /// The source file, if it is known.
+    pub file: Option<String>,
+}
+
+/// The functions of a binary.
+#[derive(Debug, Clone)]
+pub struct Bloat {
+    /// The size of the binary file.
+    pub file_size: u64,
+    /// The size of the code section.
+    pub text_size: u64,
+    /// The functions.
+    pub functions: Vec<Function>,
+}
+
+impl Bloat {
+    /// Read the functions from the symbol table of the binary.
+    pub fn new(path: &Path) -> Result<Self> {
+        let data = fs::read(path)
+            .map_err(|e| format!("Failed to read the binary `{}`: {}", path.display(), e))?;
+        let file = object::File::parse(&*data)?;
+
+        let text_size = file
+            .sections()
+            .filter(|section| section.kind() == object::SectionKind::Text)
+            .map(|section| section.size())
+            .sum();
+
+        let mut functions = vec![];
+        let mut source = None;
+        for symbol in file.symbols() {
+            match symbol.kind() {
+                SymbolKind::File => source = symbol.name().ok().map(str::to_owned),
+                SymbolKind::Text if symbol.size() > 0 => {
+                    let name = symbol.name().unwrap_or_default();
+                    functions.push(Function {
+                        name: demangle(name),
+                        size: symbol.size(),
+                        file: source.clone().filter(|_| symbol.is_local()),
+                    });
+                }
+                _ => {}
+            }
+        }
+        if functions.is_empty() {
+            return Err(format!(
+                "The binary `{}` does not have a symbol table, is it stripped?",
+                path.display()
+            )
+            .into());
+        }
+
+        Ok(Self {
+            file_size: data.len() as u64,
+            text_size,
+            functions,
+        })
+    }
+
+    /// Group the functions, and sum their sizes.
+    /// The groups are sorted by size, the largest first.
+    pub fn group(&self, group_by: GroupBy) -> Vec<(String, u64)> {
+        let mut groups: HashMap<String, u64> = HashMap::new();
+        for function in &self.functions {
+            let key = match group_by {
+                GroupBy::Symbol => function.name.clone(),
+                GroupBy::Namespace => namespace(&function.name).unwrap_or(UNKNOWN).to_owned(),
+                GroupBy::File => function.file.as_deref().unwrap_or(UNKNOWN).to_owned(),
+            };
+            *groups.entry(key).or_default() += function.size;
+        }
+
+        let mut groups = groups.into_iter().collect::<Vec<_>>();
+        groups.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        groups
+    }
+
+    /// Render the largest `n` groups as a table, like `cargo bloat`.
+    pub fn render(&self, group_by: GroupBy, n: usize) -> String {
+        let percent = |size: u64, total: u64| match total {
+            0 => 0.0,
+            total => size as f64 * 100.0 / total as f64,
+        };
+        let row = |file: f64, text: f64, size: String, name: &str| {
+            format!("{:>6.1}% {:>6.1}% {:>10}  {}\n", file, text, size, name)
+        };
+
+        let groups = self.group(group_by);
+        let mut table = format!("{:>7} {:>7} {:>10}  {}\n", "File", ".text", "Size", "Name");
+        for (name, size) in groups.iter().take(n) {
+            table += &row(
+                percent(*size, self.file_size),
+                percent(*size, self.text_size),
+                format_size(*size),
+                name,
+            );
+        }
+
+        let rest = groups.iter().skip(n).map(|(_, size)| size).sum::<u64>();
+        if groups.len() > n {
+            table += &row(
+                percent(rest, self.file_size),
+                percent(rest, self.text_size),
+                format_size(rest),
+                &format!("And {} smaller items", groups.len() - n),
+            );
+        }
+        table += &row(
+            percent(self.text_size, self.file_size),
+            100.0,
+            format_size(self.text_size),
+            &format!(
+                ".text section size, the file size is {}",
+                format_size(self.file_size)
+            ),
+        );
+
+        table
+    }
+}
+
+/// Demangle the C++ symbol name, other names are returned as they are.
+fn demangle(name: &str) -> String {
+    cpp_demangle::Symbol::new(name)
+        .ok()
+        .and_then(|symbol| symbol.demangle(&Default::default()).ok())
+        .unwrap_or_else(|| name.to_owned())
+}
+
+/// The top namespace of the demangled name, like `std` of `std::vector<int>::push_back(int)`.
+/// The `::` in the template arguments and the parameters is ignored,
+/// and so is the return type of the template functions, like `void` of `void std::swap<int>(int&, int&)`.
+fn namespace(name: &str) -> Option<&str> {
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in name.char_indices() {
+        match c {
+            '<' | '(' => depth += 1,
+            '>' | ')' => depth -= 1,
+            ' ' if depth == 0 => start = i + 1,
+            ':' if depth == 0 && name[i..].starts_with("::") => return Some(&name[start..i]),
+            _ => {}
+        }
+        if c == '(' && depth == 1 {
+            break;
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_namespace() {
+        assert_eq!(
+            namespace("std::vector<int, std::allocator<int> >::push_back(int const&)"),
+            Some("std")
+        );
+        assert_eq!(namespace("core::hello[abi:cxx11]()"), Some("core"));
+        assert_eq!(namespace("void std::swap<int>(int&, int&)"), Some("std"));
+        assert_eq!(namespace("main"), None);
+        assert_eq!(namespace("hello(std::string)"), None);
+        assert_eq!(namespace("_start"), None);
+    }
+
+    #[test]
+    fn test_group() {
+        let function = |name: &str, size| Function {
+            name: demangle(name),
+            size,
+            file: None,
+        };
+        let bloat = Bloat {
+            file_size: 1000,
+            text_size: 100,
+            functions: vec![
+                function("_ZN4core5helloEv", 30),
+                function("_ZN4core3byeEv", 20),
+                function("main", 40),
+            ],
+        };
+
+        assert_eq!(bloat.functions[0].name, "core::hello()");
+        assert_eq!(
+            bloat.group(GroupBy::Namespace),
+            vec![("core".to_string(), 50), (UNKNOWN.to_string(), 40)]
+        );
+        assert_eq!(
+            bloat.render(GroupBy::Symbol, 1),
+            "   File   .text       Size  Name\n   \
+               4.0%   40.0%       40 B  main\n   \
+               5.0%   50.0%       50 B  And 2 smaller items\n  \
+              10.0%  100.0%      100 B  .text section size, the file size is 1000 B\n"
+        );
+    }
+}
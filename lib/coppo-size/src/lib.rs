@@ -0,0 +1,226 @@
+//! The `coppo-size` crate is a Coppo addon that reports the size of the binaries of the current project.
+//!
+//! The total size and the size of every section are compared with the previous report,
+//! which is stored in `target/.size-history`, so the size regressions are caught early.
+//! It also provides the `bloat` add-on, which lists the largest functions of the binary.
+//!
+//! # Usage
+//! ```sh
+//! coppo size [--bin <NAME>] [--no-build]
+//! coppo bloat [--bin <NAME>] [-n <N>] [--group-by <symbol|namespace|file>]
+//! ```
+
+#![forbid(unsafe_code)]
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use coppo_addons::prelude::*;
+use coppo_build::{
+    bin_path, build, profile_arg, release_arg, select, selected_profile, target_dir, Runnable,
+};
+use coppo_logger::prelude::*;
+use object::{Object, ObjectSection};
+use serde::{Deserialize, Serialize};
+
+pub mod bloat;
+
+pub use bloat::CoppoBloatAddon;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The size history of the binaries, one JSON report per line.
+pub const SIZE_HISTORY: &str = ".size-history";
+
+/// The `Coppo size` add-on.
+/// Report the total size and the section sizes of a binary of the current project.
+pub struct CoppoSizeAddon;
+
+impl_addon! {
+    CoppoSizeAddon,
+    name => "size",
+    description => "Report the size of the binary and its sections",
+    args => [
+        arg!(--bin <NAME> "The binary target to report")
+            .value_parser(value_parser!(String)),
+        release_arg(),
+        profile_arg(),
+        arg!(--"no-build" "Report the existing binary without building it")
+            .action(ArgAction::SetTrue),
+    ],
+    run => |config, matches| {
+        let Runnable::Bin(bin) = select(config, matches.get_one::<String>("bin").map(String::as_str), None)? else {
+            unreachable!("only binaries are selected");
+        };
+        let profile = selected_profile(matches);
+        if !matches.get_flag("no-build") {
+            build(config, profile)?;
+        }
+
+        let path = bin_path(config, &bin, profile);
+        info!("Size of `{}`:", path);
+        let report = Report::new(&bin, Path::new(&path))?;
+        let history = target_dir().join(SIZE_HISTORY);
+        let previous = Report::last(&history, &bin)?;
+        print!("{}", report.render(previous.as_ref()));
+        report.append(&history)?;
+    }
+}
+
+/// The size report of a binary.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Report {
+    /// The name of the binary target.
+    pub bin: String,
+    /// The time of the report, in seconds since the Unix epoch.
+    pub time: u64,
+    /// The size of the binary file.
+    pub total: u64,
+    /// The sizes of the sections.
+    pub sections: BTreeMap<String, u64>,
+}
+
+impl Report {
+    /// Read the sizes of the binary.
+    pub fn new(bin: &str, path: &Path) -> Result<Self> {
+        let data = fs::read(path)
+            .map_err(|e| format!("Failed to read the binary `{}`: {}", path.display(), e))?;
+        let file = object::File::parse(&*data)?;
+
+        let mut sections = BTreeMap::new();
+        for section in file.sections() {
+            let name = section.name().unwrap_or_default();
+            if !name.is_empty() && section.size() > 0 {
+                *sections.entry(name.to_owned()).or_default() += section.size();
+            }
+        }
+
+        Ok(Self {
+            bin: bin.to_owned(),
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_secs()),
+            total: data.len() as u64,
+            sections,
+        })
+    }
+
+    /// The last report of the binary in the history.
+    pub fn last(history: &Path, bin: &str) -> Result<Option<Self>> {
+        if !history.exists() {
+            return Ok(None);
+        }
+
+        Ok(fs::read_to_string(history)?
+            .lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str::<Report>(line).ok())
+            .find(|report| report.bin == bin))
+    }
+
+    /// Append the report to the history.
+    pub fn append(&self, history: &Path) -> Result<()> {
+        if let Some(dir) = history.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(history)?;
+        writeln!(file, "{}", serde_json::to_string(self)?)?;
+
+        Ok(())
+    }
+
+    /// Render the report as a table, with the differences from the previous report.
+    /// The sections are sorted by size, the largest first.
+    pub fn render(&self, previous: Option<&Report>) -> String {
+        let diff = |name: Option<&str>, size: u64| {
+            let Some(previous) = previous else {
+                return String::new();
+            };
+            let old = match name {
+                Some(name) => previous.sections.get(name).copied().unwrap_or(0),
+                None => previous.total,
+            };
+            match size as i64 - old as i64 {
+                0 => "=".to_owned(),
+                diff if diff > 0 => format!("+{}", format_size(diff as u64)),
+                diff => format!("-{}", format_size(diff.unsigned_abs())),
+            }
+        };
+
+        let mut sections = self.sections.iter().collect::<Vec<_>>();
+        sections.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        let width = sections
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0)
+            .max("section".len());
+
+        let row = |name: &str, size: String, diff: String| {
+            format!(
+                "{:<width$}  {:>10}  {:>10}",
+                name,
+                size,
+                diff,
+                width = width
+            )
+            .trim_end()
+            .to_owned()
+                + "\n"
+        };
+
+        let header = if previous.is_some() { "diff" } else { "" };
+        let mut table = row("section", "size".to_owned(), header.to_owned());
+        for (name, size) in sections {
+            table += &row(name, format_size(*size), diff(Some(name), *size));
+        }
+        table += &row("total", format_size(self.total), diff(None, self.total));
+
+        table
+    }
+}
+
+/// Format the size, like `512 B` or `1.5 KiB`.
+pub fn format_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if size < 1024 {
+        return format!("{} B", size);
+    }
+    let mut size = size as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let report = |total, text| Report {
+            bin: "app".to_string(),
+            time: 0,
+            total,
+            sections: BTreeMap::from([(".text".to_string(), text), (".data".to_string(), 16)]),
+        };
+
+        assert_eq!(
+            report(4096, 2048).render(Some(&report(4000, 2048 + 100))),
+            "section        size        diff\n\
+            .text       2.0 KiB      -100 B\n\
+            .data          16 B           =\n\
+            total       4.0 KiB       +96 B\n"
+        );
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(3 * 1024 * 1024), "3.0 MiB");
+    }
+}
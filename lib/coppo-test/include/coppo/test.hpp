@@ -0,0 +1,188 @@
+// The built-in test framework of `coppo test`.
+//
+// Include this header in a test under `tests/`, and define the tests with `COPPO_TEST`:
+//
+//     #include <coppo/test.hpp>
+//
+//     COPPO_TEST(addition) {
+//         COPPO_ASSERT_EQ(1 + 1, 2);
+//     }
+//
+// The header defines `main`, unless `COPPO_TEST_NO_MAIN` is defined.
+// The test binary accepts the following arguments:
+// - `--list`: print the names of the tests, one per line.
+// - `<name>...`: only run the tests with the given names.
+
+#pragma once
+
+#include <chrono>
+#include <cstring>
+#include <exception>
+#include <iostream>
+#include <sstream>
+#include <string>
+#include <type_traits>
+#include <utility>
+#include <vector>
+
+namespace coppo {
+namespace test {
+
+// A failed assertion, it stops the current test.
+struct Failure {
+    std::string message;
+};
+
+// A test registered by `COPPO_TEST`.
+struct Case {
+    const char *name;
+    void (*run)();
+};
+
+inline std::vector<Case> &registry() {
+    static std::vector<Case> cases;
+    return cases;
+}
+
+struct Registrar {
+    Registrar(const char *name, void (*run)()) { registry().push_back(Case{name, run}); }
+};
+
+[[noreturn]] inline void fail(const char *file, int line, const std::string &message) {
+    std::ostringstream out;
+    out << file << ":" << line << ": " << message;
+    throw Failure{out.str()};
+}
+
+template <typename T, typename = void>
+struct printable : std::false_type {};
+
+template <typename T>
+struct printable<T, decltype(void(std::declval<std::ostream &>() << std::declval<const T &>()))>
+    : std::true_type {};
+
+template <typename T>
+void show(std::ostream &out, const T &value, std::true_type) {
+    out << value;
+}
+
+template <typename T>
+void show(std::ostream &out, const T &, std::false_type) {
+    out << "<unprintable>";
+}
+
+template <typename A, typename B>
+void compare(bool passed, const A &left, const B &right, const char *expression, const char *file,
+             int line) {
+    if (passed) {
+        return;
+    }
+    std::ostringstream out;
+    out << "assertion `" << expression << "` failed\n  left: ";
+    show(out, left, printable<A>{});
+    out << "\n right: ";
+    show(out, right, printable<B>{});
+    fail(file, line, out.str());
+}
+
+// Run the tests, and print a result line for each of them:
+// `coppo-test: <ok|failed> <microseconds> <name>`.
+inline int run(int argc, char **argv) {
+    if (argc == 2 && std::strcmp(argv[1], "--list") == 0) {
+        for (const Case &test : registry()) {
+            std::cout << test.name << "\n";
+        }
+        return 0;
+    }
+
+    int failed = 0;
+    for (const Case &test : registry()) {
+        bool selected = argc < 2;
+        for (int i = 1; i < argc; ++i) {
+            selected = selected || std::strcmp(argv[i], test.name) == 0;
+        }
+        if (!selected) {
+            continue;
+        }
+
+        std::string error;
+        auto start = std::chrono::steady_clock::now();
+        try {
+            test.run();
+        } catch (const Failure &failure) {
+            error = failure.message;
+        } catch (const std::exception &e) {
+            error = std::string("uncaught exception: ") + e.what();
+        } catch (...) {
+            error = "uncaught exception";
+        }
+        auto micros = std::chrono::duration_cast<std::chrono::microseconds>(
+                          std::chrono::steady_clock::now() - start)
+                          .count();
+
+        if (!error.empty()) {
+            ++failed;
+            std::cerr << error << "\n";
+        }
+        std::cout << "coppo-test: " << (error.empty() ? "ok" : "failed") << " " << micros << " "
+                  << test.name << std::endl;
+    }
+
+    return failed == 0 ? 0 : 1;
+}
+
+} // namespace test
+} // namespace coppo
+
+// Define a test.
+#define COPPO_TEST(name)                                                                           \
+    static void coppo_test_##name();                                                               \
+    static ::coppo::test::Registrar coppo_test_registrar_##name(#name, coppo_test_##name);         \
+    static void coppo_test_##name()
+
+// Fail the test with the message.
+#define COPPO_FAIL(message) ::coppo::test::fail(__FILE__, __LINE__, (message))
+
+// Assert the condition is true.
+#define COPPO_ASSERT(condition)                                                                    \
+    do {                                                                                           \
+        if (!(condition)) {                                                                        \
+            COPPO_FAIL("assertion `" #condition "` failed");                                       \
+        }                                                                                          \
+    } while (0)
+
+// Assert the two values are equal, they are printed if the assertion fails.
+#define COPPO_ASSERT_EQ(left, right)                                                               \
+    do {                                                                                           \
+        const auto &coppo_left = (left);                                                           \
+        const auto &coppo_right = (right);                                                         \
+        ::coppo::test::compare(coppo_left == coppo_right, coppo_left, coppo_right,                 \
+                               #left " == " #right, __FILE__, __LINE__);                           \
+    } while (0)
+
+// Assert the two values are not equal, they are printed if the assertion fails.
+#define COPPO_ASSERT_NE(left, right)                                                               \
+    do {                                                                                           \
+        const auto &coppo_left = (left);                                                           \
+        const auto &coppo_right = (right);                                                         \
+        ::coppo::test::compare(coppo_left != coppo_right, coppo_left, coppo_right,                 \
+                               #left " != " #right, __FILE__, __LINE__);                           \
+    } while (0)
+
+// Assert the expression throws an exception of the type.
+#define COPPO_ASSERT_THROWS(expression, type)                                                      \
+    do {                                                                                           \
+        bool coppo_thrown = false;                                                                 \
+        try {                                                                                      \
+            (void)(expression);                                                                    \
+        } catch (const type &) {                                                                   \
+            coppo_thrown = true;                                                                   \
+        }                                                                                          \
+        if (!coppo_thrown) {                                                                       \
+            COPPO_FAIL("`" #expression "` did not throw `" #type "`");                             \
+        }                                                                                          \
+    } while (0)
+
+#ifndef COPPO_TEST_NO_MAIN
+int main(int argc, char **argv) { return ::coppo::test::run(argc, argv); }
+#endif
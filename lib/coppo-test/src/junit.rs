@@ -0,0 +1,116 @@
+//! The JUnit XML report of the tests, like `coppo test --junit report.xml`.
+//! It is understood by most CI systems, like GitLab and Jenkins.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::{Outcome, Status};
+
+/// Escape the text for XML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Render the outcomes into the JUnit XML report,
+/// the tests are grouped into a test suite for every test binary.
+pub fn report(outcomes: &[Outcome]) -> String {
+    let mut suites: BTreeMap<&str, Vec<&Outcome>> = BTreeMap::new();
+    for outcome in outcomes {
+        suites.entry(&outcome.binary).or_default().push(outcome);
+    }
+
+    let failures = |outcomes: &[&Outcome]| {
+        outcomes
+            .iter()
+            .filter(|outcome| outcome.status != Status::Passed)
+            .count()
+    };
+    let time = |outcomes: &[&Outcome]| {
+        outcomes
+            .iter()
+            .map(|outcome| outcome.duration)
+            .sum::<Duration>()
+            .as_secs_f64()
+    };
+
+    let all = outcomes.iter().collect::<Vec<_>>();
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+        <testsuites tests=\"{}\" failures=\"{}\" time=\"{:.6}\">\n",
+        all.len(),
+        failures(&all),
+        time(&all)
+    );
+    for (binary, outcomes) in suites {
+        xml += &format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{:.6}\">\n",
+            escape(binary),
+            outcomes.len(),
+            failures(&outcomes),
+            time(&outcomes)
+        );
+        for outcome in outcomes {
+            let case = format!(
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.6}\"",
+                escape(outcome.name.as_deref().unwrap_or(binary)),
+                escape(binary),
+                outcome.duration.as_secs_f64()
+            );
+            let message = match outcome.status {
+                Status::Passed => {
+                    xml += &format!("{}/>\n", case);
+                    continue;
+                }
+                Status::Failed => "failed".to_owned(),
+                Status::TimedOut => {
+                    format!("timed out after {:.2}s", outcome.duration.as_secs_f64())
+                }
+            };
+            xml += &format!(
+                "{}>\n      <failure message=\"{}\">{}</failure>\n    </testcase>\n",
+                case,
+                escape(&message),
+                escape(outcome.output.trim())
+            );
+        }
+        xml += "  </testsuite>\n";
+    }
+
+    xml + "</testsuites>\n"
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let outcome = |name: &str, status| Outcome {
+            binary: "math".to_string(),
+            name: Some(name.to_string()),
+            status,
+            duration: Duration::from_millis(2),
+            output: "left: 1 < 2".to_string(),
+        };
+        let xml = report(&[
+            outcome("addition", Status::Passed),
+            outcome("division", Status::Failed),
+        ]);
+
+        assert_eq!(
+            xml,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+            <testsuites tests=\"2\" failures=\"1\" time=\"0.004000\">\n  \
+              <testsuite name=\"math\" tests=\"2\" failures=\"1\" time=\"0.004000\">\n    \
+                <testcase name=\"addition\" classname=\"math\" time=\"0.002000\"/>\n    \
+                <testcase name=\"division\" classname=\"math\" time=\"0.002000\">\n      \
+                  <failure message=\"failed\">left: 1 &lt; 2</failure>\n    \
+                </testcase>\n  \
+              </testsuite>\n\
+            </testsuites>\n"
+        );
+    }
+}
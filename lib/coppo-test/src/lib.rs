@@ -0,0 +1,528 @@
+//! The `coppo-test` crate is a Coppo addon that compiles and runs the tests of the current project.
+//!
+//! Every source file in `tests/` is compiled into its own test binary,
+//! linked with the library target of the project, built once like `coppo build` does,
+//! and the `[dev-dependencies]`.
+//! A test binary can use the built-in test framework `coppo/test.hpp`,
+//! then every `COPPO_TEST` in it is discovered and reported with its timing.
+//! Otherwise the whole binary is a single test, and it passes if it exits with zero.
+//!
+//! Every test runs in its own process, the processes run in parallel.
+//!
+//! # Usage
+//! ```sh
+//! coppo test [FILTER] [-j <N>] [--shard <K/N>] [--timeout <SECS>] [--junit <PATH>]
+//! ```
+
+#![forbid(unsafe_code)]
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use coppo_addons::prelude::*;
+use coppo_build::parallel::{default_jobs, run_parallel};
+use coppo_build::{
+    build_library, find_compiler, ldflags, link_command, lock, log_command, profile_flags,
+    run_path_flags, target_dir, verbose_arg, workspace, wrapper, DEFAULT_PROFILE,
+};
+use coppo_config::files::SOURCE_EXTENSIONS;
+use coppo_logger::prelude::*;
+
+pub mod junit;
+pub mod runner;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The tests are placed in the `tests` directory.
+pub const TESTS_DIR: &str = "tests";
+
+/// The built-in test framework header, it is included as `<coppo/test.hpp>`.
+pub const TEST_HEADER: &str = include_str!("../include/coppo/test.hpp");
+
+/// The include directory of the built-in headers, relative to the `target` directory.
+const INCLUDE_OUTPUT: &str = "coppo-include";
+
+/// The test binaries are stored in the `target/tests` directory.
+const TESTS_OUTPUT: &str = "tests";
+
+/// The prefix of the result lines printed by the built-in test framework.
+const RESULT_PREFIX: &str = "coppo-test: ";
+
+/// The `Coppo test` add-on.
+/// Compile and run the tests of the current project,
+/// it fails if any test fails.
+pub struct CoppoTestAddon;
+
+impl_addon! {
+    CoppoTestAddon,
+    name => "test",
+    description => "Compile and run the tests of the current project",
+    args => [
+        arg!([FILTER] "Only run the tests whose names contain the filter")
+            .value_parser(value_parser!(String)),
+        arg!(-j --jobs <N> "The number of tests run in parallel, defaults to the number of CPUs")
+            .value_parser(value_parser!(u64).range(1..)),
+        arg!(--shard <SHARD> "Only run the K-th of N shards of the tests, like `2/5`")
+            .value_parser(value_parser!(Shard)),
+        arg!(--timeout <SECS> "Fail the tests running longer than the seconds")
+            .value_parser(value_parser!(f64)),
+        arg!(--junit <PATH> "Write a JUnit XML report of the tests")
+            .value_parser(value_parser!(PathBuf)),
+        verbose_arg(),
+        workspace::package_arg(),
+        workspace::workspace_arg(),
+    ],
+    run => |config, matches| {
+        set_verbose(matches.get_flag("verbose"));
+        let options = Options {
+            filter: matches.get_one::<String>("FILTER").cloned(),
+            jobs: matches
+                .get_one::<u64>("jobs")
+                .map_or_else(default_jobs, |jobs| *jobs as usize),
+            shard: matches.get_one::<Shard>("shard").copied(),
+            timeout: matches
+                .get_one::<f64>("timeout")
+                .map(|secs| Duration::from_secs_f64(secs.max(0.0))),
+            junit: matches.get_one::<PathBuf>("junit").cloned(),
+        };
+        workspace::for_each(config, matches, "Testing", |config| test(config, &options))?;
+    }
+}
+
+/// The options of `coppo test`.
+#[derive(Debug, Clone, Default)]
+struct Options {
+    filter: Option<String>,
+    jobs: usize,
+    shard: Option<Shard>,
+    timeout: Option<Duration>,
+    junit: Option<PathBuf>,
+}
+
+/// A shard of the tests, like `2/5`, used to split the tests across CI jobs.
+/// The tests are assigned to the shards in a round-robin fashion by their sorted names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    /// The index of the shard, starting from `1`.
+    pub index: usize,
+    /// The number of the shards.
+    pub count: usize,
+}
+
+impl Shard {
+    /// Check if the `n`-th test belongs to the shard.
+    pub fn contains(&self, n: usize) -> bool {
+        n % self.count + 1 == self.index
+    }
+}
+
+impl FromStr for Shard {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || format!("Invalid shard `{}`, expected `K/N` with 1 <= K <= N", s);
+        let (index, count) = s.split_once('/').ok_or_else(invalid)?;
+        let index = index.trim().parse().map_err(|_| invalid())?;
+        let count = count.trim().parse().map_err(|_| invalid())?;
+        if index == 0 || index > count {
+            return Err(invalid());
+        }
+
+        Ok(Shard { index, count })
+    }
+}
+
+impl fmt::Display for Shard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+/// A test binary, compiled from a source file in `tests/`.
+#[derive(Debug, Clone)]
+struct TestTarget {
+    /// The name of the binary, the file stem of the source.
+    name: String,
+    /// The source file.
+    source: PathBuf,
+    /// Whether the test uses the built-in test framework.
+    framework: bool,
+}
+
+impl TestTarget {
+    /// The output path of the test binary.
+    fn binary(&self) -> PathBuf {
+        let binary = target_dir().join(TESTS_OUTPUT).join(&self.name);
+        if cfg!(windows) {
+            binary.with_extension("exe")
+        } else {
+            binary
+        }
+    }
+}
+
+/// A test to run, a `COPPO_TEST` or a whole test binary.
+#[derive(Debug, Clone)]
+struct Job<'a> {
+    target: &'a TestTarget,
+    /// The name of the test in the binary, `None` without the test framework.
+    name: Option<String>,
+}
+
+impl Job<'_> {
+    /// The full name of the test, like `binary::test`, or just `binary` without the test framework.
+    fn full_name(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{}::{}", self.target.name, name),
+            None => self.target.name.clone(),
+        }
+    }
+}
+
+/// The status of a finished test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// The test passed.
+    Passed,
+    /// The test failed or crashed.
+    Failed,
+    /// The test was killed for the timeout.
+    TimedOut,
+}
+
+/// The outcome of a single test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The name of the test binary.
+    pub binary: String,
+    /// The name of the test in the binary, `None` without the test framework.
+    pub name: Option<String>,
+    /// The status of the test.
+    pub status: Status,
+    /// The time spent by the test.
+    pub duration: Duration,
+    /// The output of the test, without the result lines.
+    pub output: String,
+}
+
+fn test(config: &Config, options: &Options) -> Result<()> {
+    if !Config::exists() {
+        return Err("The project does not have a `Coppo.toml` file.".into());
+    }
+    if config.is_empty() {
+        return Err("The project name and version is needed".into());
+    }
+
+    let targets = discover()?;
+    if targets.is_empty() {
+        warn!("There is no test in `{}`.", TESTS_DIR);
+        return Ok(());
+    }
+
+    // The tests are built with the debug profile, and also use the dev-dependencies.
+    lock::ensure(config)?;
+    let mut flags = profile_flags(&config.profile(DEFAULT_PROFILE)?);
+    let artifacts = workspace::dev_artifacts(config, &flags)?;
+
+    // The tests are linked with the library target, built once for all of them.
+    let library = build_library(config, DEFAULT_PROFILE, &flags)?;
+    flags.extend(lock::system_flags(config, true)?);
+
+    info!("Compiling {} test binaries...", targets.len());
+    let include = write_header()?;
+    for target in &targets {
+        compile(
+            config,
+            target,
+            &include,
+            library.as_deref(),
+            &artifacts,
+            &flags,
+        )?;
+    }
+
+    let mut jobs = vec![];
+    for target in &targets {
+        jobs.extend(list(target)?);
+    }
+    let total = jobs.len();
+    jobs.retain(|job| {
+        options
+            .filter
+            .as_deref()
+            .is_none_or(|filter| job.full_name().contains(filter))
+    });
+    if let Some(shard) = options.shard {
+        let mut n = 0;
+        jobs.retain(|_| {
+            n += 1;
+            shard.contains(n - 1)
+        });
+        info!("Running the shard {} with {} tests...", shard, jobs.len());
+    }
+
+    let start = Instant::now();
+    let outcomes = run_parallel(&jobs, options.jobs, |job| run(job, options.timeout))
+        .into_iter()
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    for (job, outcome) in jobs.iter().zip(&outcomes) {
+        let duration = format_duration(outcome.duration);
+        match outcome.status {
+            Status::Passed => info!("test {} ... ok ({})", job.full_name(), duration),
+            Status::Failed => error!("test {} ... FAILED ({})", job.full_name(), duration),
+            Status::TimedOut => error!("test {} ... TIMEOUT ({})", job.full_name(), duration),
+        }
+    }
+
+    if let Some(path) = &options.junit {
+        fs::write(path, junit::report(&outcomes))?;
+        info!("The JUnit report has been written to `{}`.", path.display());
+    }
+
+    let failures = jobs
+        .iter()
+        .zip(&outcomes)
+        .filter(|(_, outcome)| outcome.status != Status::Passed)
+        .collect::<Vec<_>>();
+    let summary = format!(
+        "{} passed; {} failed; {} filtered out; finished in {}",
+        outcomes.len() - failures.len(),
+        failures.len(),
+        total - jobs.len(),
+        format_duration(start.elapsed())
+    );
+    if !failures.is_empty() {
+        for (job, outcome) in failures
+            .iter()
+            .filter(|(_, outcome)| !outcome.output.is_empty())
+        {
+            eprintln!("---- {} ----\n{}\n", job.full_name(), outcome.output.trim());
+        }
+        error!("failures:");
+        for (job, _) in &failures {
+            error!("    {}", job.full_name());
+        }
+        return Err(format!("test result: FAILED. {}", summary).into());
+    }
+    success!("test result: ok. {}", summary);
+
+    Ok(())
+}
+
+/// Discover the test binaries, every source file in `tests/` is a test binary.
+fn discover() -> Result<Vec<TestTarget>> {
+    let dir = Path::new(TESTS_DIR);
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let mut targets = vec![];
+    for entry in fs::read_dir(dir)? {
+        let source = entry?.path();
+        let is_source = source
+            .extension()
+            .is_some_and(|ext| SOURCE_EXTENSIONS.iter().any(|source| ext == *source));
+        if let (true, Some(name)) = (is_source, source.file_stem()) {
+            targets.push(TestTarget {
+                name: name.to_string_lossy().into_owned(),
+                framework: fs::read_to_string(&source)?.contains("<coppo/test.hpp>"),
+                source,
+            });
+        }
+    }
+    targets.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(targets)
+}
+
+/// Write the built-in headers into the `target` directory,
+/// and return the include directory.
+fn write_header() -> Result<PathBuf> {
+    let include = target_dir().join(INCLUDE_OUTPUT);
+    fs::create_dir_all(include.join("coppo"))?;
+    fs::write(include.join("coppo").join("test.hpp"), TEST_HEADER)?;
+
+    Ok(include)
+}
+
+/// Compile the test binary linked with the library of the project,
+/// the libraries of the workspace members it depends on and the flags of the system packages.
+fn compile(
+    config: &Config,
+    target: &TestTarget,
+    include: &Path,
+    library: Option<&Path>,
+    artifacts: &[workspace::Artifact],
+    flags: &[String],
+) -> Result<()> {
+    let binary = target.binary();
+    if let Some(dir) = binary.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let compiler = find_compiler(config)?;
+    let mut inputs = vec![target.source.clone()];
+    inputs.extend(library.map(Path::to_owned));
+    let mut args = vec![format!("-I{}", include.display())];
+    args.extend(coppo_build::flags(config));
+    args.extend(workspace::link_flags(artifacts));
+    args.extend(flags.iter().cloned());
+    args.extend(ldflags(config));
+    if let Some(library) = library {
+        args.extend(run_path_flags(&compiler, &binary, library));
+    }
+    let mut command = link_command(
+        wrapper(config).as_deref(),
+        &compiler,
+        &inputs,
+        &args,
+        &binary,
+        None,
+    );
+    log_command(&command);
+    let output = command.output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let logger = LOGGER.get_or_init(|| Logger::new(false));
+        let diagnostics = Diagnostic::parse_clang(&stderr);
+        if diagnostics.is_empty() {
+            error!("{}", stderr.trim());
+        }
+        for diagnostic in &diagnostics {
+            logger.diagnostic(diagnostic);
+        }
+        return Err(format!("The test `{}` failed to compile.", target.name).into());
+    }
+
+    Ok(())
+}
+
+/// List the tests in the test binary.
+fn list(target: &TestTarget) -> Result<Vec<Job<'_>>> {
+    if !target.framework {
+        return Ok(vec![Job { target, name: None }]);
+    }
+
+    let output = process::Command::new(target.binary())
+        .arg("--list")
+        .output()?;
+    if !output.status.success() {
+        return Err(format!("Failed to list the tests of `{}`.", target.name).into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|name| !name.is_empty())
+        .map(|name| Job {
+            target,
+            name: Some(name.to_owned()),
+        })
+        .collect())
+}
+
+/// Run the test in its own process.
+/// The error is a `String`, so it can be sent across the threads.
+fn run(job: &Job, timeout: Option<Duration>) -> std::result::Result<Outcome, String> {
+    let mut command = process::Command::new(job.target.binary());
+    command.args(&job.name);
+    let execution = runner::execute(&mut command, timeout)?;
+
+    // The built-in test framework reports the time of the test itself.
+    let reported = execution
+        .stdout
+        .lines()
+        .find_map(|line| parse_result(line).filter(|(name, ..)| Some(name) == job.name.as_ref()));
+    let (status, duration) = match (execution.status, reported) {
+        (None, _) => (Status::TimedOut, execution.duration),
+        (Some(_), Some((_, true, duration))) => (Status::Passed, duration),
+        (Some(status), None) if job.name.is_none() && status.success() => {
+            (Status::Passed, execution.duration)
+        }
+        (Some(_), Some((_, false, duration))) => (Status::Failed, duration),
+        (Some(_), None) => (Status::Failed, execution.duration),
+    };
+
+    let stdout = execution
+        .stdout
+        .lines()
+        .filter(|line| !line.starts_with(RESULT_PREFIX))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut output = format!("{}\n{}", stdout.trim(), execution.stderr.trim());
+    if status == Status::TimedOut {
+        output += &format!("\nThe test timed out after {}.", format_duration(duration));
+    }
+
+    Ok(Outcome {
+        binary: job.target.name.clone(),
+        name: job.name.clone(),
+        status,
+        duration,
+        output: output.trim().to_owned(),
+    })
+}
+
+/// Parse a result line of the built-in test framework,
+/// like `coppo-test: ok 42 addition`, into the name, whether it passed and its time.
+fn parse_result(line: &str) -> Option<(String, bool, Duration)> {
+    let mut parts = line.strip_prefix(RESULT_PREFIX)?.splitn(3, ' ');
+    let passed = match parts.next()? {
+        "ok" => true,
+        "failed" => false,
+        _ => return None,
+    };
+    let micros = parts.next()?.parse().ok()?;
+    let name = parts.next()?;
+
+    Some((name.to_owned(), passed, Duration::from_micros(micros)))
+}
+
+/// Format the duration, like `1.25ms` or `3.10s`.
+fn format_duration(duration: Duration) -> String {
+    if duration < Duration::from_secs(1) {
+        format!("{:.2}ms", duration.as_secs_f64() * 1000.0)
+    } else {
+        format!("{:.2}s", duration.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_result() {
+        assert_eq!(
+            parse_result("coppo-test: ok 1500 addition"),
+            Some(("addition".to_string(), true, Duration::from_micros(1500)))
+        );
+        assert_eq!(
+            parse_result("coppo-test: failed 0 division").map(|(_, passed, _)| passed),
+            Some(false)
+        );
+        assert_eq!(parse_result("addition passed"), None);
+        assert_eq!(format_duration(Duration::from_micros(1500)), "1.50ms");
+        assert_eq!(format_duration(Duration::from_millis(3100)), "3.10s");
+    }
+
+    #[test]
+    fn test_shard() {
+        let shard = "2/3".parse::<Shard>().unwrap();
+        assert_eq!(shard, Shard { index: 2, count: 3 });
+        assert_eq!(
+            (0..7).filter(|n| shard.contains(*n)).collect::<Vec<_>>(),
+            vec![1, 4]
+        );
+
+        assert!("0/3".parse::<Shard>().is_err());
+        assert!("4/3".parse::<Shard>().is_err());
+        assert!("2".parse::<Shard>().is_err());
+    }
+}
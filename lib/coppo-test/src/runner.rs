@@ -0,0 +1,68 @@
+//! Run a test process with an optional timeout, and capture its output.
+
+use std::io::Read;
+use std::process::{Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+type Result<T> = std::result::Result<T, String>;
+
+/// The interval to check if the process has exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// The captured result of a process.
+#[derive(Debug)]
+pub struct Execution {
+    /// The exit status, it is `None` if the process was killed for the timeout.
+    pub status: Option<ExitStatus>,
+    /// The standard output.
+    pub stdout: String,
+    /// The standard error.
+    pub stderr: String,
+    /// The time spent by the process.
+    pub duration: Duration,
+}
+
+/// Run the command, and kill it if it does not exit within the timeout.
+pub fn execute(command: &mut Command, timeout: Option<Duration>) -> Result<Execution> {
+    let start = Instant::now();
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run the test: {}", e))?;
+
+    // Read the pipes in the background, or the child may block on a full pipe.
+    let capture = |pipe: Option<Box<dyn Read + Send>>| {
+        thread::spawn(move || {
+            let mut output = vec![];
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut output);
+            }
+            String::from_utf8_lossy(&output).into_owned()
+        })
+    };
+    let stdout = capture(child.stdout.take().map(|pipe| Box::new(pipe) as _));
+    let stderr = capture(child.stderr.take().map(|pipe| Box::new(pipe) as _));
+
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            break Some(status);
+        }
+        if timeout.is_some_and(|timeout| start.elapsed() >= timeout) {
+            let _ = child.kill();
+            let _ = child.wait();
+            break None;
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+    let duration = start.elapsed();
+
+    Ok(Execution {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+        duration,
+    })
+}
@@ -0,0 +1,454 @@
+//! The `coppo-toolchain` crate is a Coppo addon that manages prebuilt toolchains,
+//! so a project does not depend on the compiler on the `PATH`.
+//!
+//! The toolchains are downloaded into `~/.coppo/toolchains` with `curl`, with the `[http]` settings
+//! of the global configuration, see [`coppo_config::download`],
+//! and verified against the checksum published with the release before they are extracted:
+//! - `llvm-<version>`, the LLVM releases, like `llvm-18.1.8`.
+//! - `mingw-<version>`, the llvm-mingw releases, like `mingw-20240619`.
+//!
+//! If a release does not publish the checksum of its archive,
+//! it is given with `--checksum`, like the one on the release page.
+//!
+//! A project uses a toolchain after `coppo toolchain override <TOOLCHAIN>` in its directory.
+//!
+//! # Usage
+//! ```sh
+//! coppo toolchain install <TOOLCHAIN> [--checksum <SHA256>]
+//! coppo toolchain uninstall <TOOLCHAIN>
+//! coppo toolchain list
+//! coppo toolchain override <TOOLCHAIN>
+//! coppo toolchain override --unset
+//! ```
+
+#![forbid(unsafe_code)]
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use coppo_addons::prelude::*;
+use coppo_config::download;
+use coppo_config::global::HttpSettings;
+use coppo_config::toolchain::{self, toolchains_dir, Overrides};
+use coppo_logger::prelude::*;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The `Coppo toolchain` add-on.
+/// Install the toolchains, and select the toolchain of the current project.
+pub struct CoppoToolchainAddon;
+
+impl_addon! {
+    CoppoToolchainAddon,
+    name => "toolchain",
+    description => "Manage the toolchains of Coppo",
+    project => false,
+    args => [
+        arg!(<ACTION> "The action to do")
+            .value_parser(["install", "uninstall", "list", "override"]),
+        arg!([TOOLCHAIN] "The toolchain, like `llvm-18.1.8` or `mingw-20240619`")
+            .value_parser(value_parser!(Toolchain)),
+        arg!(--unset "Remove the override of the current directory")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("TOOLCHAIN"),
+        arg!(--checksum <SHA256> "The checksum of the archive to install, if the release does not publish it")
+            .value_parser(parse_checksum),
+    ],
+    run => |config, matches| {
+        let toolchain = matches.get_one::<Toolchain>("TOOLCHAIN");
+        let required = || {
+            toolchain.ok_or_else(|| {
+                format!(
+                    "A toolchain is needed, like `llvm-18.1.8`, the kinds are: {}.",
+                    Kind::NAMES.join(", ")
+                )
+            })
+        };
+
+        match matches.get_one::<String>("ACTION").map(String::as_str) {
+            Some("install") => install(
+                required()?,
+                matches.get_one::<String>("checksum").map(String::as_str),
+                &config.global.http,
+            )?,
+            Some("uninstall") => uninstall(required()?)?,
+            Some("list") => list()?,
+            Some("override") if matches.get_flag("unset") => {
+                let dir = env::current_dir()?;
+                let mut overrides = Overrides::from_file()?;
+                match overrides.unset(&dir) {
+                    Some(toolchain) => {
+                        overrides.to_file()?;
+                        success!("The override `{}` is removed.", toolchain);
+                    }
+                    None => warn!("The directory `{}` does not have an override.", dir.display()),
+                }
+            }
+            Some("override") => {
+                let toolchain = required()?;
+                if !toolchain.path()?.is_dir() {
+                    return Err(format!(
+                        "The toolchain `{0}` is not installed, run `coppo toolchain install {0}` first.",
+                        toolchain
+                    )
+                    .into());
+                }
+
+                let dir = env::current_dir()?;
+                let mut overrides = Overrides::from_file()?;
+                overrides.set(&dir, &toolchain.to_string());
+                overrides.to_file()?;
+                success!("The toolchain of `{}` is `{}`.", dir.display(), toolchain);
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// The kinds of the toolchains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// The LLVM releases, <https://github.com/llvm/llvm-project/releases>.
+    Llvm,
+    /// The llvm-mingw releases, <https://github.com/mstorsjo/llvm-mingw/releases>.
+    Mingw,
+}
+
+impl Kind {
+    /// All the names of the kinds.
+    pub const NAMES: [&'static str; 2] = ["llvm", "mingw"];
+}
+
+impl FromStr for Kind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "llvm" => Ok(Kind::Llvm),
+            "mingw" => Ok(Kind::Mingw),
+            _ => Err(format!(
+                "Unknown toolchain kind `{}`, the kinds are: {}",
+                s,
+                Kind::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Kind::Llvm => write!(f, "llvm"),
+            Kind::Mingw => write!(f, "mingw"),
+        }
+    }
+}
+
+/// A toolchain, like `llvm-18.1.8`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Toolchain {
+    /// The kind of the toolchain.
+    pub kind: Kind,
+    /// The release version, it is the release date for llvm-mingw.
+    pub version: String,
+}
+
+impl FromStr for Toolchain {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (kind, version) = s
+            .split_once('-')
+            .ok_or_else(|| format!("The toolchain `{}` must be like `llvm-18.1.8`", s))?;
+        if version.is_empty()
+            || !version
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.')
+        {
+            return Err(format!("Invalid version `{}` of the toolchain", version));
+        }
+
+        Ok(Toolchain {
+            kind: kind.parse()?,
+            version: version.to_owned(),
+        })
+    }
+}
+
+impl fmt::Display for Toolchain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.kind, self.version)
+    }
+}
+
+impl Toolchain {
+    /// The directory of the toolchain.
+    pub fn path(&self) -> Result<PathBuf> {
+        let dir = toolchains_dir().ok_or("Could not find the Coppo home directory.")?;
+        Ok(dir.join(self.to_string()))
+    }
+
+    /// The URL of the prebuilt toolchain for the platform.
+    pub fn url(&self, os: &str, arch: &str) -> std::result::Result<String, String> {
+        let v = &self.version;
+        let archive = match (self.kind, os, arch) {
+            (Kind::Llvm, "linux", "x86_64") => {
+                format!("clang+llvm-{}-x86_64-linux-gnu-ubuntu-18.04.tar.xz", v)
+            }
+            (Kind::Llvm, "linux", "aarch64") => {
+                format!("clang+llvm-{}-aarch64-linux-gnu.tar.xz", v)
+            }
+            (Kind::Llvm, "macos", "aarch64") => {
+                format!("clang+llvm-{}-arm64-apple-macos11.tar.xz", v)
+            }
+            (Kind::Llvm, "windows", "x86_64") => {
+                format!("clang+llvm-{}-x86_64-pc-windows-msvc.tar.xz", v)
+            }
+            (Kind::Mingw, "windows", "x86_64" | "aarch64") => {
+                format!("llvm-mingw-{}-ucrt-{}.zip", v, arch)
+            }
+            (Kind::Mingw, "linux", "x86_64" | "aarch64") => {
+                format!("llvm-mingw-{}-ucrt-ubuntu-20.04-{}.tar.xz", v, arch)
+            }
+            (Kind::Mingw, "macos", _) => format!("llvm-mingw-{}-ucrt-macos-universal.tar.xz", v),
+            _ => {
+                return Err(format!(
+                    "There is no prebuilt `{}` toolchain for {}-{}",
+                    self.kind, os, arch
+                ))
+            }
+        };
+
+        Ok(match self.kind {
+            Kind::Llvm => format!(
+                "https://github.com/llvm/llvm-project/releases/download/llvmorg-{}/{}",
+                v, archive
+            ),
+            Kind::Mingw => format!(
+                "https://github.com/mstorsjo/llvm-mingw/releases/download/{}/{}",
+                v, archive
+            ),
+        })
+    }
+
+    /// The URL of the release in the GitHub API, it has the checksums of the archives.
+    pub fn release_url(&self) -> String {
+        match self.kind {
+            Kind::Llvm => format!(
+                "https://api.github.com/repos/llvm/llvm-project/releases/tags/llvmorg-{}",
+                self.version
+            ),
+            Kind::Mingw => format!(
+                "https://api.github.com/repos/mstorsjo/llvm-mingw/releases/tags/{}",
+                self.version
+            ),
+        }
+    }
+}
+
+/// Parse the checksum of `--checksum`, like `sha256:<hex>` or the hexadecimal digest only.
+pub fn parse_checksum(checksum: &str) -> std::result::Result<String, String> {
+    let digest = checksum.strip_prefix("sha256:").unwrap_or(checksum);
+    if digest.len() != 64 || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!(
+            "Invalid checksum `{}`, it must be a SHA-256 digest like `sha256:<hex>`",
+            checksum
+        ));
+    }
+
+    Ok(format!("sha256:{}", digest.to_ascii_lowercase()))
+}
+
+/// The checksum of the archive in the release of the GitHub API, like `sha256:<hex>`.
+fn release_checksum(release: &str, archive: &str) -> Option<String> {
+    let release = serde_json::from_str::<serde_json::Value>(release).ok()?;
+    release["assets"]
+        .as_array()?
+        .iter()
+        .find(|asset| asset["name"] == archive)
+        .and_then(|asset| asset["digest"].as_str())
+        .and_then(|digest| parse_checksum(digest).ok())
+}
+
+/// Download and extract the toolchain, with the proxy, the timeout and the retries of `http`.
+/// The archive must have the checksum, or the one published with the release.
+fn install(toolchain: &Toolchain, checksum: Option<&str>, http: &HttpSettings) -> Result<()> {
+    let path = toolchain.path()?;
+    if path.is_dir() {
+        success!("The toolchain `{}` is already installed.", toolchain);
+        return Ok(());
+    }
+
+    let url = toolchain.url(env::consts::OS, env::consts::ARCH)?;
+    let archive = url.rsplit('/').next().unwrap_or_default();
+    let checksum = match checksum {
+        Some(checksum) => checksum.to_owned(),
+        None => {
+            let release = download::download(&toolchain.release_url(), None, http, false)?;
+            release_checksum(&String::from_utf8_lossy(&release), archive).ok_or_else(|| {
+                format!(
+                    "The release of `{}` does not publish the checksum of `{}`.\n\
+                    help: verify the archive, and pass its checksum with `--checksum <SHA256>`",
+                    toolchain, archive
+                )
+            })?
+        }
+    };
+
+    unpack(toolchain, &path, &url, &checksum, http)?;
+    success!("The toolchain `{}` is installed.", toolchain);
+    Ok(())
+}
+
+/// Download the archive of the toolchain from the URL, and extract it into the directory
+/// once its checksum is verified.
+fn unpack(
+    toolchain: &Toolchain,
+    path: &Path,
+    url: &str,
+    checksum: &str,
+    http: &HttpSettings,
+) -> Result<()> {
+    download::unpack(
+        path,
+        url.rsplit('/').next().unwrap_or_default(),
+        checksum,
+        &format!("the toolchain `{}`", toolchain),
+        |archive| {
+            info!("Downloading `{}`...", url);
+            download::download(url, Some(archive), http, true)?;
+            info!("Extracting the toolchain...");
+            Ok(())
+        },
+        |root| match toolchain::compiler(root).is_file() {
+            true => Ok(()),
+            false => Err(format!(
+                "The toolchain does not have `{}`.",
+                toolchain::compiler(Path::new("")).display()
+            )
+            .into()),
+        },
+    )
+}
+
+/// Remove the toolchain.
+fn uninstall(toolchain: &Toolchain) -> Result<()> {
+    let path = toolchain.path()?;
+    if !path.is_dir() {
+        return Err(format!("The toolchain `{}` is not installed.", toolchain).into());
+    }
+
+    fs::remove_dir_all(path)?;
+    success!("The toolchain `{}` is uninstalled.", toolchain);
+    Ok(())
+}
+
+/// List the installed toolchains, the one of the current directory is marked.
+fn list() -> Result<()> {
+    let installed = toolchain::installed();
+    let dir = env::current_dir()?;
+    let overrides = Overrides::from_file()?;
+    let active = overrides.get(&dir);
+
+    if installed.is_empty() {
+        info!("No toolchain is installed.");
+    }
+    for name in installed {
+        match active == Some(name.as_str()) {
+            true => println!("{} (override)", name),
+            false => println!("{}", name),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_toolchain() {
+        let llvm = "llvm-18.1.8".parse::<Toolchain>().unwrap();
+        assert_eq!(llvm.kind, Kind::Llvm);
+        assert_eq!(llvm.to_string(), "llvm-18.1.8");
+        assert_eq!(
+            llvm.url("linux", "x86_64").unwrap(),
+            "https://github.com/llvm/llvm-project/releases/download/llvmorg-18.1.8/\
+            clang+llvm-18.1.8-x86_64-linux-gnu-ubuntu-18.04.tar.xz"
+        );
+        assert!(llvm.url("freebsd", "x86_64").is_err());
+
+        let mingw = "mingw-20240619".parse::<Toolchain>().unwrap();
+        assert_eq!(
+            mingw.url("windows", "x86_64").unwrap(),
+            "https://github.com/mstorsjo/llvm-mingw/releases/download/20240619/\
+            llvm-mingw-20240619-ucrt-x86_64.zip"
+        );
+
+        assert!("gcc-13".parse::<Toolchain>().is_err());
+        assert!("llvm".parse::<Toolchain>().is_err());
+        assert!("llvm-../x".parse::<Toolchain>().is_err());
+    }
+
+    #[test]
+    fn test_checksum() {
+        let digest = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+        let release = format!(
+            r#"{{"assets": [
+                {{"name": "llvm-mingw-1-ucrt-x86_64.zip", "digest": null}},
+                {{"name": "clang+llvm-18.1.8-x86_64-linux-gnu-ubuntu-18.04.tar.xz", "digest": "sha256:{}"}}
+            ]}}"#,
+            digest
+        );
+        assert_eq!(
+            release_checksum(
+                &release,
+                "clang+llvm-18.1.8-x86_64-linux-gnu-ubuntu-18.04.tar.xz"
+            ),
+            Some(format!("sha256:{}", digest))
+        );
+        assert_eq!(
+            release_checksum(&release, "llvm-mingw-1-ucrt-x86_64.zip"),
+            None
+        );
+        assert_eq!(release_checksum(&release, "unknown.tar.xz"), None);
+
+        assert_eq!(
+            parse_checksum(&digest.to_ascii_uppercase()),
+            Ok(format!("sha256:{}", digest))
+        );
+        assert!(parse_checksum("sha256:1234").is_err());
+    }
+
+    #[test]
+    fn test_unpack_mismatch() {
+        let root = std::env::temp_dir().join(format!("coppo-toolchain-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        // The archive is not extracted, or it would fail to be.
+        let archive = root.join("clang+llvm-1.0.0.tar.gz");
+        fs::write(&archive, "not an archive").unwrap();
+
+        let toolchain = "llvm-1.0.0".parse::<Toolchain>().unwrap();
+        let path = root.join("llvm-1.0.0");
+        let url = format!("file://{}", archive.display());
+        let error = unpack(
+            &toolchain,
+            &path,
+            &url,
+            &parse_checksum(&"0".repeat(64)).unwrap(),
+            &HttpSettings::default(),
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("The checksum of the toolchain `llvm-1.0.0` is `sha256:"));
+        assert!(!path.exists());
+        assert!(!root.join(".download-llvm-1.0.0").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
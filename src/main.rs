@@ -5,12 +5,63 @@
 #![forbid(unsafe_code)]
 #![allow(unused_imports)]
 
-use coppo_build::{CoppoBuildAddon, CoppoRunAddon};
+use coppo_build::{
+    CoppoAddAddon, CoppoBuildAddon, CoppoCheckAddon, CoppoCleanAddon, CoppoEnvAddon,
+    CoppoExpandAddon, CoppoLocateProjectAddon, CoppoMetadataAddon, CoppoPkgidAddon,
+    CoppoRemoveAddon, CoppoRunAddon, CoppoUpdateAddon, CoppoVendorAddon, CoppoVerifyProjectAddon,
+    CoppoWatchAddon,
+};
+use coppo_cache::CoppoCacheAddon;
 use coppo_cli::{addons, command, CoppoCli};
-use coppo_new::CoppoNewAddon;
+use coppo_dist::CoppoDistAddon;
+use coppo_doc::CoppoDocAddon;
+use coppo_export::CoppoExportAddon;
+use coppo_fmt::CoppoFmtAddon;
+use coppo_graph::{CoppoGraphAddon, CoppoTreeAddon};
+use coppo_ide::CoppoIdeAddon;
+use coppo_lint::{CoppoFixAddon, CoppoIncludesAddon, CoppoLintAddon};
+use coppo_new::{CoppoInitAddon, CoppoNewAddon};
+use coppo_settings::CoppoConfigAddon;
+use coppo_size::{CoppoBloatAddon, CoppoSizeAddon};
+use coppo_test::CoppoTestAddon;
+use coppo_toolchain::CoppoToolchainAddon;
 
 fn main() {
     CoppoCli::new(command!())
-        .add_addons(addons![CoppoNewAddon, CoppoBuildAddon, CoppoRunAddon])
+        .add_addons(addons![
+            CoppoNewAddon,
+            CoppoInitAddon,
+            CoppoBuildAddon,
+            CoppoRunAddon,
+            CoppoCheckAddon,
+            CoppoWatchAddon,
+            CoppoTestAddon,
+            CoppoCleanAddon,
+            CoppoAddAddon,
+            CoppoRemoveAddon,
+            CoppoUpdateAddon,
+            CoppoVendorAddon,
+            CoppoEnvAddon,
+            CoppoConfigAddon,
+            CoppoToolchainAddon,
+            CoppoCacheAddon,
+            CoppoExpandAddon,
+            CoppoVerifyProjectAddon,
+            CoppoLocateProjectAddon,
+            CoppoPkgidAddon,
+            CoppoMetadataAddon,
+            CoppoFmtAddon,
+            CoppoLintAddon,
+            CoppoFixAddon,
+            CoppoIncludesAddon,
+            CoppoDocAddon,
+            CoppoSizeAddon,
+            CoppoBloatAddon,
+            CoppoGraphAddon,
+            CoppoTreeAddon,
+            CoppoIdeAddon,
+            CoppoExportAddon,
+            CoppoDistAddon
+        ])
         .run()
 }